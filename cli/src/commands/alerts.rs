@@ -0,0 +1,226 @@
+//! Price alert command handlers
+
+use anyhow::Result;
+use std::collections::HashMap;
+use std::io::Write;
+use std::time::Duration;
+use zerodha_cli_core::{
+    alerts::{Alert, AlertBook, AlertMonitor, Trigger},
+    api::KiteConnectClient,
+    config::Config,
+    error::{exit_code, exit_code_for},
+    output::{self, format_inr},
+};
+
+use super::{AlertsCommands, AlertsSubcommands};
+
+pub async fn run_alerts(
+    cmd: AlertsCommands,
+    config: &Config,
+    api_client: &KiteConnectClient,
+    output_format: &str,
+) -> Result<()> {
+    match cmd.command {
+        AlertsSubcommands::List => run_alerts_list(config, output_format),
+        AlertsSubcommands::Add {
+            symbol,
+            above,
+            below,
+            exec,
+        } => run_alerts_add(config, &symbol, above, below, exec, output_format),
+        AlertsSubcommands::Rm { id } => run_alerts_rm(config, id, output_format),
+        AlertsSubcommands::Watch {
+            symbol,
+            above,
+            below,
+            exec,
+            interval,
+            once,
+        } => {
+            let alerts = match symbol {
+                Some(symbol) => vec![Alert::new(0, &symbol, above, below, exec)?],
+                None => AlertBook::load(config)?.alerts,
+            };
+            run_alerts_watch(alerts, interval, once, api_client).await
+        }
+    }
+}
+
+pub fn run_alerts_list(config: &Config, output_format: &str) -> Result<()> {
+    let book = AlertBook::load(config)?;
+
+    if output_format == "json" {
+        output::emit(output::to_json(&book.alerts)?)?;
+    } else if output_format == "ndjson" {
+        output::write_ndjson(&book.alerts, output::writer())?;
+    } else if output_format == "yaml" {
+        output::write_yaml(&book.alerts, output::writer())?;
+    } else if book.alerts.is_empty() {
+        output::notice("No alerts. Add one with 'kite alerts add --symbol NSE:INFY --above 1600'.");
+    } else {
+        print_alerts_table(&book.alerts)?;
+    }
+
+    Ok(())
+}
+
+pub fn run_alerts_add(
+    config: &Config,
+    symbol: &str,
+    above: Option<f64>,
+    below: Option<f64>,
+    exec: Option<String>,
+    output_format: &str,
+) -> Result<()> {
+    let mut book = AlertBook::load(config)?;
+    let alert = book.add(symbol, above, below, exec)?.clone();
+    book.save(config)?;
+    output::notice(format_args!("✓ Added alert #{} on {}", alert.id, alert.symbol));
+    emit_alert(&alert, output_format)
+}
+
+pub fn run_alerts_rm(config: &Config, id: u32, output_format: &str) -> Result<()> {
+    let mut book = AlertBook::load(config)?;
+    let alert = book.remove(id)?;
+    book.save(config)?;
+    output::notice(format_args!("✓ Removed alert #{} on {}", alert.id, alert.symbol));
+    emit_alert(&alert, output_format)
+}
+
+/// Write the alert an add/rm touched, for scripts; text output only gets the notice
+fn emit_alert(alert: &Alert, output_format: &str) -> Result<()> {
+    match output_format {
+        "json" => output::emit(output::to_json(alert)?)?,
+        "ndjson" => output::emit(serde_json::to_string(alert)?)?,
+        "yaml" => output::write_yaml(alert, output::writer())?,
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Poll LTPs until interrupted (or the first alert fires with `once`)
+pub async fn run_alerts_watch(
+    alerts: Vec<Alert>,
+    interval_secs: u64,
+    once: bool,
+    api_client: &KiteConnectClient,
+) -> Result<()> {
+    if alerts.is_empty() {
+        anyhow::bail!("No alerts to watch. Pass --symbol or add alerts with 'kite alerts add'.");
+    }
+
+    let mut monitor = AlertMonitor::new(alerts);
+    let symbols = monitor.symbols();
+    let refs: Vec<&str> = symbols.iter().map(String::as_str).collect();
+
+    println!(
+        "Watching {} symbol(s) every {}s. Press Ctrl+C to stop.",
+        symbols.len(),
+        interval_secs
+    );
+
+    let mut ticker = tokio::time::interval(Duration::from_secs(interval_secs.max(1)));
+    ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+    loop {
+        tokio::select! {
+            _ = ticker.tick() => {}
+            _ = tokio::signal::ctrl_c() => break,
+        }
+
+        // Requests go through the client's rate limiter
+        let response = match api_client.get_ltp(&refs).await {
+            Ok(response) => response,
+            Err(e) if exit_code_for(&e) == exit_code::AUTH => return Err(e),
+            Err(e) => {
+                eprintln!("Warning: failed to fetch prices: {:#}", e);
+                continue;
+            }
+        };
+        let prices: HashMap<String, f64> = response
+            .data
+            .into_iter()
+            .map(|(symbol, ltp)| (symbol, ltp.last_price))
+            .collect();
+
+        let triggers = monitor.check(&prices);
+        for trigger in &triggers {
+            println!(
+                "[{}] {} crossed {} {} (LTP {})",
+                chrono::Local::now().format("%H:%M:%S"),
+                trigger.symbol,
+                trigger.direction,
+                format_inr(trigger.threshold),
+                format_inr(trigger.price)
+            );
+            if let Some(ref command) = trigger.command {
+                spawn_alert_command(command, trigger);
+            }
+        }
+
+        if once && !triggers.is_empty() {
+            break;
+        }
+    }
+
+    Ok(())
+}
+
+/// Run an alert's command in the background with the trigger in its environment
+fn spawn_alert_command(command: &str, trigger: &Trigger) {
+    let mut cmd = if cfg!(windows) {
+        let mut cmd = tokio::process::Command::new("cmd");
+        cmd.arg("/C");
+        cmd
+    } else {
+        let mut cmd = tokio::process::Command::new("sh");
+        cmd.arg("-c");
+        cmd
+    };
+    cmd.arg(command)
+        .env("KITE_ALERT_SYMBOL", &trigger.symbol)
+        .env("KITE_ALERT_DIRECTION", trigger.direction.to_string())
+        .env("KITE_ALERT_THRESHOLD", trigger.threshold.to_string())
+        .env("KITE_ALERT_PRICE", trigger.price.to_string());
+
+    match cmd.spawn() {
+        Ok(mut child) => {
+            let command = command.to_string();
+            tokio::spawn(async move {
+                match child.wait().await {
+                    Ok(status) if !status.success() => {
+                        eprintln!("Warning: alert command '{}' exited with {}", command, status)
+                    }
+                    Err(e) => eprintln!("Warning: alert command '{}' failed: {}", command, e),
+                    Ok(_) => {}
+                }
+            });
+        }
+        Err(e) => eprintln!("Warning: failed to run alert command '{}': {}", command, e),
+    }
+}
+
+fn print_alerts_table(alerts: &[Alert]) -> Result<()> {
+    use comfy_table::{Cell, ContentArrangement, Table};
+
+    let mut out = output::writer();
+    let price = |p: Option<f64>| p.map(format_inr).unwrap_or_else(|| "-".to_string());
+
+    let mut table = Table::new();
+    table.set_header(output::header(vec!["ID", "Symbol", "Above", "Below", "Command"]));
+
+    for alert in alerts {
+        table.add_row(vec![
+            Cell::new(alert.id.to_string()),
+            Cell::new(&alert.symbol),
+            Cell::new(price(alert.above)),
+            Cell::new(price(alert.below)),
+            Cell::new(alert.command.as_deref().unwrap_or("-")),
+        ]);
+    }
+
+    table.set_content_arrangement(ContentArrangement::Dynamic);
+    writeln!(out, "{table}")?;
+
+    Ok(())
+}
@@ -1,9 +1,11 @@
 //! Authentication command handlers
 
 use anyhow::{Context, Result};
+use std::io::Write;
 use zerodha_cli_core::{
     auth::{self, AuthStatus},
     config::Config,
+    output,
 };
 
 use super::AuthCommands;
@@ -14,41 +16,49 @@ pub async fn run_auth(
     api_client: &zerodha_cli_core::api::KiteConnectClient,
 ) -> Result<()> {
     match cmd.command {
-        super::AuthSubcommands::Login => run_auth_login(config, api_client).await?,
+        super::AuthSubcommands::Login { port } => run_auth_login(port, config, api_client).await?,
         super::AuthSubcommands::Status => run_auth_status(config)?,
         super::AuthSubcommands::Logout => run_auth_logout(config)?,
         super::AuthSubcommands::Setup {
             api_key,
             api_secret,
         } => run_auth_setup(api_key, api_secret, config)?,
+        super::AuthSubcommands::Profiles => run_auth_profiles(config),
+        super::AuthSubcommands::Use { name } => run_auth_use(&name, config)?,
+        super::AuthSubcommands::VerifyPostback { payload } => {
+            run_auth_verify_postback(&payload, config)?
+        }
     }
     Ok(())
 }
 
 pub async fn run_auth_login(
+    port: Option<u16>,
     config: &mut Config,
     api_client: &zerodha_cli_core::api::KiteConnectClient,
 ) -> Result<()> {
     // Check if already authenticated
-    match auth::status(config) {
-        AuthStatus::Authenticated { expiry } => {
-            println!("Already authenticated!");
-            if let Some(expiry_str) = expiry {
-                if let Ok(expiry) = chrono::DateTime::parse_from_rfc3339(&expiry_str) {
-                    println!("Token expires: {}", expiry.format("%Y-%m-%d %H:%M:%S UTC"));
-                }
+    if let AuthStatus::Authenticated { expiry } = auth::status(config) {
+        output::notice("Already authenticated!");
+        if let Some(expiry_str) = expiry {
+            if let Ok(expiry) = chrono::DateTime::parse_from_rfc3339(&expiry_str) {
+                output::notice(format_args!(
+                    "Token expires: {}",
+                    expiry.format("%Y-%m-%d %H:%M:%S UTC")
+                ));
             }
-            println!("\nRun 'kite auth logout' first if you want to login with different credentials.");
-            return Ok(());
         }
-        _ => {}
+        output::notice(
+            "\nRun 'kite auth logout' first if you want to login with different credentials.",
+        );
+        return Ok(());
     }
 
-    println!("Initiating OAuth login flow...");
-    let token = auth::login(api_client, config)
+    output::progress("Initiating OAuth login flow...");
+    let token = auth::login(api_client, config, port)
         .await
         .context("Failed to complete login")?;
-    println!("✓ Logged in successfully!");
+    output::notice("✓ Logged in successfully!");
     println!("Access token: {}", token);
     Ok(())
 }
@@ -77,7 +87,8 @@ pub fn run_auth_status(config: &Config) -> Result<()> {
 }
 
 pub fn run_auth_logout(config: &mut Config) -> Result<()> {
-    print!("Are you sure you want to logout? [y/N]: ");
+    eprint!("Are you sure you want to logout? [y/N]: ");
+    std::io::stderr().flush()?;
     let mut input = String::new();
     std::io::stdin().read_line(&mut input)?;
     let input = input.trim().to_lowercase();
@@ -95,7 +106,71 @@ pub fn run_auth_setup(api_key: String, api_secret: String, config: &mut Config)
     config.api.api_key = api_key;
     config.api.api_secret = api_secret;
     config.save().context("Failed to save config")?;
-    println!("✓ API credentials configured successfully!");
-    println!("Config file: {}", Config::config_path()?.display());
+    println!(
+        "✓ API credentials configured successfully for profile '{}'!",
+        config.profile
+    );
+    println!("Config file: {}", config.path.display());
     Ok(())
 }
+
+pub fn run_auth_profiles(config: &Config) {
+    let names = config.profile_names();
+    if names.is_empty() {
+        println!("No profiles configured. Run 'kite auth setup' to add one.");
+        return;
+    }
+
+    let active = config
+        .active_profile
+        .as_deref()
+        .unwrap_or(zerodha_cli_core::config::DEFAULT_PROFILE);
+    for name in names {
+        let marker = if name == active { "*" } else { " " };
+        println!("{} {}", marker, name);
+    }
+}
+
+pub fn run_auth_use(name: &str, config: &mut Config) -> Result<()> {
+    config.set_active_profile(name)?;
+    config.save().context("Failed to save config")?;
+    println!("✓ Active profile set to '{}'", name);
+    Ok(())
+}
+
+/// Report whether a postback payload was signed with this profile's API secret
+pub fn run_auth_verify_postback(payload: &str, config: &Config) -> Result<()> {
+    use std::io::Read;
+    use zerodha_cli_core::auth::postback::verify_postback;
+
+    if config.api.api_secret.is_empty() {
+        anyhow::bail!("No API secret configured. Run 'kite auth setup' first.");
+    }
+    let content = if payload == "-" {
+        let mut content = String::new();
+        std::io::stdin()
+            .read_to_string(&mut content)
+            .context("Failed to read postback from stdin")?;
+        content
+    } else {
+        std::fs::read_to_string(payload)
+            .with_context(|| format!("Failed to read postback from {}", payload))?
+    };
+    let payload: serde_json::Value =
+        serde_json::from_str(&content).context("Postback payload is not valid JSON")?;
+
+    let order_id = payload
+        .get("order_id")
+        .and_then(|v| v.as_str())
+        .unwrap_or_default()
+        .to_string();
+    if verify_postback(&payload, &config.api.api_secret)? {
+        println!("✓ Checksum matches for order {}", order_id);
+        Ok(())
+    } else {
+        anyhow::bail!(
+            "Checksum mismatch for order {}; the postback wasn't signed with this profile's API secret",
+            order_id
+        )
+    }
+}
@@ -0,0 +1,327 @@
+//! Run a JSON array of commands in one process (`kite batch`)
+//!
+//! Request: `[{"id": 1, "command": ["quotes", "ltp", "NSE:INFY"]}, {"command": "orders list"}]`
+//! where `command` is the arguments after `kite`, as an array or a
+//! whitespace-separated string, and `id` is optional and echoed back.
+//!
+//! Response: one `{"id", "command", "ok", "data", "error"}` object per request,
+//! in order. `data` is the command's JSON output; `error` is set when `ok` is false.
+
+use anyhow::{Context, Result};
+use clap::Parser;
+use serde::{Deserialize, Serialize};
+use std::io::Read;
+use zerodha_cli_core::{api::KiteConnectClient, config::Config, error::ZerodhaError, output};
+
+use super::{
+    alerts, cache, gtt, instruments, margins, orders, portfolio, quotes, schema, status, watch,
+    AlertsCommands, AlertsSubcommands, Cli, Commands, OrdersCommands, OrdersSubcommands,
+    StatusSubcommands,
+};
+
+/// One command in a batch request
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BatchRequest {
+    /// Caller-chosen identifier copied to the result
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<serde_json::Value>,
+    pub command: BatchArgs,
+}
+
+/// Command arguments, as a list or a single whitespace-separated line
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum BatchArgs {
+    Args(Vec<String>),
+    Line(String),
+}
+
+impl BatchArgs {
+    fn args(&self) -> Vec<String> {
+        match self {
+            BatchArgs::Args(args) => args.clone(),
+            BatchArgs::Line(line) => line.split_whitespace().map(str::to_string).collect(),
+        }
+    }
+}
+
+/// Outcome of one batch command
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BatchResult {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<serde_json::Value>,
+    /// The command as it was run, arguments joined with spaces
+    pub command: String,
+    pub ok: bool,
+    pub data: serde_json::Value,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Read a batch from `input` (`-` for stdin), run it and print the results
+pub async fn run_batch(
+    input: &str,
+    config: &Config,
+    api_client: &KiteConnectClient,
+    dry_run: bool,
+    assume_yes: bool,
+) -> Result<()> {
+    let content = if input == "-" {
+        let mut content = String::new();
+        std::io::stdin()
+            .read_to_string(&mut content)
+            .context("Failed to read batch from stdin")?;
+        content
+    } else {
+        std::fs::read_to_string(input)
+            .with_context(|| format!("Failed to read batch file {}", input))?
+    };
+    let requests: Vec<BatchRequest> =
+        serde_json::from_str(&content).context("Batch input must be a JSON array of commands")?;
+
+    let mut results = Vec::with_capacity(requests.len());
+    for request in requests {
+        results.push(run_request(request, config, api_client, dry_run, assume_yes).await);
+    }
+
+    output::emit(output::to_json(&results)?)
+}
+
+async fn run_request(
+    request: BatchRequest,
+    config: &Config,
+    api_client: &KiteConnectClient,
+    dry_run: bool,
+    assume_yes: bool,
+) -> BatchResult {
+    let args = request.command.args();
+    let command = args.join(" ");
+
+    output::start_capture();
+    let result = match parse_command(&args) {
+        Ok(cli) => {
+            let dry_run = dry_run || cli.dry_run;
+            let assume_yes = assume_yes || cli.yes;
+            if needs_confirmation(&cli.command) && !dry_run && !assume_yes {
+                Err(ZerodhaError::Validation(
+                    "This command changes orders, GTTs or positions; pass --yes to run it in a \
+                     batch"
+                        .to_string(),
+                )
+                .into())
+            } else {
+                dispatch(cli.command, config, api_client, dry_run, assume_yes).await
+            }
+        }
+        Err(e) => Err(e),
+    };
+    let captured = output::take_capture();
+
+    match result {
+        Ok(()) => BatchResult {
+            id: request.id,
+            command,
+            ok: true,
+            data: captured_data(&captured),
+            error: None,
+        },
+        Err(e) => BatchResult {
+            id: request.id,
+            command,
+            ok: false,
+            data: serde_json::Value::Null,
+            error: Some(format!("{:#}", e)),
+        },
+    }
+}
+
+/// Parse batch arguments as if they followed `kite` on the command line
+fn parse_command(args: &[String]) -> Result<Cli> {
+    let cli = Cli::try_parse_from(std::iter::once("kite").chain(args.iter().map(String::as_str)))
+        .map_err(|e| anyhow::anyhow!("{}", e.render().to_string().trim()))?;
+    Ok(cli)
+}
+
+/// Whether a command may change the account and so can't run without `--yes`
+///
+/// Stdin carries the batch, so a confirmation prompt can't be answered.
+/// Anything not known to be read-only or local counts as a change.
+fn needs_confirmation(command: &Commands) -> bool {
+    !(watch::is_read_only(command)
+        || matches!(
+            command,
+            Commands::Instruments(_)
+                | Commands::Cache(_)
+                | Commands::Alerts(_)
+                | Commands::Schema { .. }
+        ))
+}
+
+/// Run a command with JSON output; interactive commands are refused
+async fn dispatch(
+    command: Commands,
+    config: &Config,
+    api_client: &KiteConnectClient,
+    dry_run: bool,
+    assume_yes: bool,
+) -> Result<()> {
+    let json = "json";
+    match command {
+        Commands::Instruments(cmd) => instruments::run_instruments(cmd, config, api_client, json).await,
+        Commands::Quotes(cmd) => quotes::run_quotes(cmd, api_client, json).await,
+        Commands::Orders(OrdersCommands {
+            command: OrdersSubcommands::New { .. },
+        }) => anyhow::bail!("orders new is interactive and can't run in a batch"),
+        Commands::Orders(cmd) => {
+            orders::run_orders(cmd, config, api_client, json, dry_run, assume_yes).await
+        }
+        Commands::Portfolio(cmd) => {
+            portfolio::run_portfolio(cmd, config, api_client, json, dry_run, assume_yes).await
+        }
+        Commands::Margins(cmd) => margins::run_margins(cmd, api_client, json).await,
+        Commands::Gtt(cmd) => gtt::run_gtt(cmd, api_client, json, dry_run, assume_yes).await,
+        Commands::Cache(cmd) => cache::run_cache(cmd, config),
+        Commands::Alerts(AlertsCommands {
+            command: AlertsSubcommands::Watch { .. },
+        }) => anyhow::bail!("alerts watch runs until interrupted and can't run in a batch"),
+        Commands::Alerts(cmd) => alerts::run_alerts(cmd, config, api_client, json).await,
+        Commands::Schema { model } => schema::run_schema(model.as_deref(), json),
+        Commands::Status { command: None } => {
+            anyhow::bail!("status only prints text; use 'status market' in a batch")
+        }
+        Commands::Status {
+            command: Some(StatusSubcommands::Market),
+        } => status::run_market_status(json),
+        Commands::Auth(_)
+        | Commands::Shell { .. }
+        | Commands::Batch { .. }
+        | Commands::Completions { .. } => {
+            anyhow::bail!("This command can't run in a batch")
+        }
+    }
+}
+
+/// JSON a command wrote: one document, one per line (NDJSON), or plain text
+fn captured_data(captured: &str) -> serde_json::Value {
+    let captured = captured.trim();
+    if captured.is_empty() {
+        return serde_json::Value::Null;
+    }
+    if let Ok(value) = serde_json::from_str(captured) {
+        return value;
+    }
+    captured
+        .lines()
+        .map(serde_json::from_str)
+        .collect::<Result<Vec<serde_json::Value>, _>>()
+        .map(serde_json::Value::Array)
+        .unwrap_or_else(|_| serde_json::Value::String(captured.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_request_and_result_round_trip() {
+        let input = r#"[
+            {"id": 1, "command": ["quotes", "ltp", "NSE:INFY"]},
+            {"command": "orders list --status OPEN"}
+        ]"#;
+        let requests: Vec<BatchRequest> = serde_json::from_str(input).unwrap();
+        assert_eq!(requests[0].id, Some(json!(1)));
+        assert_eq!(requests[1].command.args(), ["orders", "list", "--status", "OPEN"]);
+        let again: Vec<BatchRequest> =
+            serde_json::from_str(&serde_json::to_string(&requests).unwrap()).unwrap();
+        assert_eq!(again, requests);
+
+        let result = BatchResult {
+            id: Some(json!(1)),
+            command: "quotes ltp NSE:INFY".to_string(),
+            ok: true,
+            data: json!({"NSE:INFY": {"last_price": 1500.0}}),
+            error: None,
+        };
+        let value = serde_json::to_value(&result).unwrap();
+        assert!(value.get("error").is_none());
+        assert_eq!(serde_json::from_value::<BatchResult>(value).unwrap(), result);
+    }
+
+    #[test]
+    fn test_commands_map_onto_cli() {
+        let args = BatchArgs::Line("--dry-run gtt delete 5".to_string()).args();
+        let cli = parse_command(&args).unwrap();
+        assert!(cli.dry_run);
+        assert!(matches!(cli.command, Commands::Gtt(_)));
+        assert!(parse_command(&["nope".to_string()]).is_err());
+    }
+
+    #[test]
+    fn test_account_changes_need_confirmation() {
+        let parse = |line: &str| parse_command(&BatchArgs::Line(line.to_string()).args()).unwrap();
+
+        for line in ["orders cancel 1", "gtt delete 5", "portfolio squareoff --all"] {
+            assert!(needs_confirmation(&parse(line).command), "{}", line);
+        }
+        for line in ["orders list", "quotes ltp NSE:INFY", "instruments search INFY"] {
+            assert!(!needs_confirmation(&parse(line).command), "{}", line);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_refuses_account_changes_without_yes() {
+        let config = Config::default();
+        let client = KiteConnectClient::new("key".into(), "secret".into());
+        let request = BatchRequest {
+            id: None,
+            command: BatchArgs::Line("orders cancel 1".to_string()),
+        };
+
+        let result = run_request(request, &config, &client, false, false).await;
+        assert!(!result.ok);
+        assert!(result.error.unwrap().contains("--yes"));
+    }
+
+    #[tokio::test]
+    async fn test_batch_output_is_json_only() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut config = Config::default();
+        config.path = dir.path().join("config.toml");
+        let client = KiteConnectClient::new("key".into(), "secret".into());
+        let run = |line: &str| {
+            let request = BatchRequest {
+                id: None,
+                command: BatchArgs::Line(line.to_string()),
+            };
+            run_request(request, &config, &client, false, false)
+        };
+
+        let result = run("alerts add --symbol NSE:INFY --above 1600").await;
+        assert!(result.ok, "{:?}", result.error);
+        assert_eq!(result.data["symbol"], json!("NSE:INFY"));
+
+        let result = run("alerts rm 1").await;
+        assert!(result.ok, "{:?}", result.error);
+        assert_eq!(result.data["id"], json!(1));
+
+        // An empty list is a notice on stderr, not text in the data
+        let result = run("alerts list").await;
+        assert!(result.ok, "{:?}", result.error);
+        assert_eq!(result.data, json!([]));
+
+        for line in ["status", "alerts watch"] {
+            let result = run(line).await;
+            assert!(!result.ok, "{}", line);
+            assert!(result.error.unwrap().contains("batch"), "{}", line);
+        }
+    }
+
+    #[test]
+    fn test_captured_data() {
+        assert_eq!(captured_data(""), serde_json::Value::Null);
+        assert_eq!(captured_data("[1, 2]\n"), json!([1, 2]));
+        assert_eq!(captured_data("{\"a\":1}\n{\"a\":2}\n"), json!([{"a": 1}, {"a": 2}]));
+        assert_eq!(captured_data("No trades found."), json!("No trades found."));
+    }
+}
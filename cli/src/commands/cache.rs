@@ -0,0 +1,101 @@
+//! Cache command handlers
+
+use anyhow::Result;
+use std::io::Write;
+use zerodha_cli_core::{
+    cache::{CacheInfo, InstrumentCache},
+    config::Config,
+    output,
+};
+
+use super::CacheCommands;
+
+pub fn run_cache(cmd: CacheCommands, config: &Config) -> Result<()> {
+    match cmd.command {
+        super::CacheSubcommands::Clear => InstrumentCache::clear_all(),
+        super::CacheSubcommands::Info => {
+            let info = InstrumentCache::info()?;
+            print_cache_info(&info, config.cache.instrument_ttl_hours)
+        }
+        super::CacheSubcommands::Path => {
+            output::emit(InstrumentCache::cache_dir()?.display())?;
+            Ok(())
+        }
+    }
+}
+
+fn print_cache_info(info: &CacheInfo, ttl_hours: u32) -> Result<()> {
+    use comfy_table::{Cell, CellAlignment, ContentArrangement, Table};
+
+    let mut out = output::writer();
+
+    writeln!(out, "Cache directory: {}", info.cache_dir.display())?;
+
+    if info.files.is_empty() {
+        writeln!(out, "No cached instruments")?;
+        return Ok(());
+    }
+
+    let mut table = Table::new();
+    table.set_header(output::header(vec!["Exchange", "Size", "Modified", "Status"]));
+
+    for file in &info.files {
+        let status = if InstrumentCache::is_valid_with_ttl(&file.exchange, ttl_hours)? {
+            "✓ Fresh"
+        } else {
+            "○ Expired"
+        };
+        table.add_row(vec![
+            Cell::new(file.exchange.to_uppercase()),
+            Cell::new(format_size(file.size)).set_alignment(CellAlignment::Right),
+            Cell::new(
+                file.modified
+                    .with_timezone(&chrono::Local)
+                    .format("%Y-%m-%d %H:%M"),
+            ),
+            Cell::new(status),
+        ]);
+    }
+
+    table.set_content_arrangement(ContentArrangement::Dynamic);
+    writeln!(out, "{table}")?;
+    writeln!(
+        out,
+        "Total: {} in {} file(s) (TTL {}h)",
+        format_size(info.total_size),
+        info.files.len(),
+        ttl_hours
+    )?;
+
+    Ok(())
+}
+
+/// Human-readable byte count (KiB-based)
+fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KB", "MB", "GB"];
+
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+
+    if unit == 0 {
+        format!("{} {}", bytes, UNITS[0])
+    } else {
+        format!("{:.1} {}", size, UNITS[unit])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_size() {
+        assert_eq!(format_size(512), "512 B");
+        assert_eq!(format_size(2048), "2.0 KB");
+        assert_eq!(format_size(5 * 1024 * 1024 + 512 * 1024), "5.5 MB");
+    }
+}
@@ -0,0 +1,45 @@
+//! Shell completion script generation
+
+use anyhow::Result;
+use clap::CommandFactory;
+use clap_complete::Shell;
+use std::io::Write;
+
+use super::Cli;
+
+/// Write the completion script for `shell` to `out`
+pub fn write_completions(shell: Shell, out: &mut dyn Write) -> Result<()> {
+    let mut cmd = Cli::command();
+    let name = cmd.get_name().to_string();
+    clap_complete::generate(shell, &mut cmd, name, out);
+    Ok(())
+}
+
+/// Print the completion script for `shell` to stdout
+///
+/// The script is buffered first because `generate` panics on write errors,
+/// such as a closed pipe in `kite completions zsh | head`.
+pub fn run_completions(shell: Shell) -> Result<()> {
+    let mut script = Vec::new();
+    write_completions(shell, &mut script)?;
+    std::io::stdout().lock().write_all(&script)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_completions_cover_subcommands_and_global_flags() {
+        for shell in [Shell::Bash, Shell::Zsh, Shell::Fish, Shell::PowerShell] {
+            let mut script = Vec::new();
+            write_completions(shell, &mut script).unwrap();
+            let script = String::from_utf8(script).unwrap();
+
+            for word in ["instruments", "cancel-all", "completions", "access-token", "no-color"] {
+                assert!(script.contains(word), "{:?} script is missing {}", shell, word);
+            }
+        }
+    }
+}
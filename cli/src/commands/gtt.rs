@@ -2,73 +2,120 @@
 
 use anyhow::Result;
 use serde_json;
-use zerodha_cli_core::api::KiteConnectClient;
+use chrono::{Duration, NaiveDateTime};
+use std::io::Write;
+use zerodha_cli_core::{
+    api::KiteConnectClient,
+    market,
+    models::{GTTTrigger, ModifyGTT, OrderType},
+    output,
+    validation::parse_symbol,
+};
 
-use super::{GttCommands, GttSubcommands};
+use super::orders::parse_order_type;
+use super::{confirm_or_prompt, print_request_body, GttCommands, GttModifyArgs, GttSubcommands};
+
+/// GTT statuses accepted by `gtt list --status`
+const GTT_STATUSES: &[&str] = &["active", "triggered", "disabled", "expired"];
+
+/// GTTs expiring within this many days are highlighted
+const EXPIRY_WARNING_DAYS: i64 = 7;
 
 /// Parameters for creating a GTT order
 pub(crate) struct GTTCreateParams {
     symbol: String,
-    order_type: String,
+    transaction_type: String,
     quantity: i32,
     price: f64,
     trigger_price: f64,
-    order_type_enum: Option<String>,
+    trigger_type: String,
+    target_trigger_price: Option<f64>,
+    target_price: Option<f64>,
+    order_type: Option<String>,
     product: Option<String>,
+    dry_run: bool,
+    show_payload: bool,
 }
 
 pub async fn run_gtt(
     cmd: GttCommands,
     api_client: &KiteConnectClient,
     output_format: &str,
+    dry_run: bool,
+    assume_yes: bool,
 ) -> Result<()> {
     match cmd.command {
-        GttSubcommands::List => run_gtt_list(output_format, api_client).await,
+        GttSubcommands::List { status } => {
+            run_gtt_list(status.as_deref(), output_format, api_client).await
+        }
         GttSubcommands::Get { trigger_id } => {
             run_gtt_get(trigger_id, output_format, api_client).await
         }
         GttSubcommands::Create {
             symbol,
-            order_type,
+            side,
             quantity,
             price,
             trigger_price,
-            trigger_type: _,
-            order_type_enum,
+            trigger_type,
+            target_trigger_price,
+            target_price,
+            order_type,
             product,
+            show_payload,
         } => {
             let params = GTTCreateParams {
                 symbol,
-                order_type,
+                transaction_type: side,
                 quantity,
                 price,
                 trigger_price,
-                order_type_enum,
+                trigger_type,
+                target_trigger_price,
+                target_price,
+                order_type,
                 product,
+                dry_run,
+                show_payload,
             };
             run_gtt_create(params, api_client).await
         }
         GttSubcommands::Modify {
             trigger_id,
-            price,
-            trigger_price,
-        } => run_gtt_modify(trigger_id, price, trigger_price, api_client).await,
-        GttSubcommands::Delete { trigger_id } => run_gtt_delete(trigger_id, api_client).await,
+            changes,
+        } => run_gtt_modify(trigger_id, &changes, dry_run, api_client).await,
+        GttSubcommands::Delete { trigger_id } => {
+            run_gtt_delete(trigger_id, dry_run, assume_yes, api_client).await
+        }
+        GttSubcommands::DeleteAll { status } => {
+            run_gtt_delete_all(status.as_deref(), dry_run, assume_yes, api_client).await
+        }
     }
 }
 
-pub async fn run_gtt_list(output_format: &str, api_client: &KiteConnectClient) -> Result<()> {
-    let gtt_list = api_client.list_gtt().await?;
+pub async fn run_gtt_list(
+    status: Option<&str>,
+    output_format: &str,
+    api_client: &KiteConnectClient,
+) -> Result<()> {
+    let status = parse_status(status)?;
+    let mut gtt_list = api_client.list_gtt().await?;
+    gtt_list.retain(|gtt| has_status(gtt, status.as_deref()));
+    sort_by_expiry(&mut gtt_list);
 
     if gtt_list.is_empty() {
-        println!("No GTT orders found.");
+        output::notice("No GTT orders found.");
         return Ok(());
     }
 
     if output_format == "json" {
-        println!("{}", serde_json::to_string_pretty(&gtt_list)?);
+        output::emit(output::to_json(&gtt_list)?)?;
+    } else if output_format == "ndjson" {
+        output::write_ndjson(&gtt_list, output::writer())?;
+    } else if output_format == "yaml" {
+        output::write_yaml(&gtt_list, output::writer())?;
     } else {
-        print_gtt_table(&gtt_list);
+        print_gtt_table(&gtt_list)?;
     }
 
     Ok(())
@@ -86,38 +133,42 @@ pub async fn run_gtt_get(
     let gtt = api_client.get_gtt(id).await?;
 
     if output_format == "json" {
-        println!("{}", serde_json::to_string_pretty(&gtt)?);
+        output::emit(output::to_json(&gtt)?)?;
+    } else if output_format == "ndjson" {
+        output::emit(serde_json::to_string(&gtt)?)?;
+    } else if output_format == "yaml" {
+        output::write_yaml(&gtt, output::writer())?;
     } else {
-        print_gtt_details(&gtt);
+        print_gtt_details(&gtt)?;
     }
 
     Ok(())
 }
 
 pub async fn run_gtt_create(params: GTTCreateParams, api_client: &KiteConnectClient) -> Result<()> {
-    use zerodha_cli_core::models::{OrderType, Product, TransactionType};
+    use zerodha_cli_core::models::{
+        GTTCondition, GTTOrder, GTTTriggerType, Product, TransactionType,
+    };
 
     let symbol = params.symbol;
-    let order_type = params.order_type;
+    let transaction_type = params.transaction_type;
     let quantity = params.quantity;
     let price = params.price;
     let trigger_price = params.trigger_price;
-    let order_type_enum = params.order_type_enum;
+    let target_trigger_price = params.target_trigger_price;
+    let target_price = params.target_price;
+    let order_type = params.order_type;
     let product = params.product;
 
-    // Validate symbol
-    let parts: Vec<&str> = symbol.split(':').collect();
-    if parts.len() != 2 {
-        anyhow::bail!("Invalid symbol format. Expected: EXCHANGE:SYMBOL (e.g., NSE:INFY)");
-    }
+    let (exchange, tradingsymbol) = parse_symbol(&symbol)?;
 
-    let tx_type = match order_type.to_uppercase().as_str() {
+    let tx_type = match transaction_type.to_uppercase().as_str() {
         "BUY" => TransactionType::Buy,
         "SELL" => TransactionType::Sell,
         _ => anyhow::bail!("Invalid transaction type. Use BUY or SELL"),
     };
 
-    let ord_type = match order_type_enum
+    let ord_type = match order_type
         .as_deref()
         .unwrap_or("LIMIT")
         .to_uppercase()
@@ -135,9 +186,66 @@ pub async fn run_gtt_create(params: GTTCreateParams, api_client: &KiteConnectCli
         _ => anyhow::bail!("Invalid product. Use CNC, MIS, or NRML"),
     };
 
+    let trigger_type = match params.trigger_type.to_lowercase().as_str() {
+        "single" => GTTTriggerType::Single,
+        "two-leg" | "oco" => GTTTriggerType::TwoLeg,
+        _ => anyhow::bail!("Invalid trigger type. Use single or two-leg"),
+    };
+
+    // Kite needs the current price to decide which way each trigger fires
+    let ltp_key = format!("{}:{}", exchange, tradingsymbol);
+    let last_price = api_client
+        .get_ltp(&[ltp_key.as_str()])
+        .await?
+        .data
+        .get(&ltp_key)
+        .map(|ltp| ltp.last_price)
+        .ok_or_else(|| anyhow::anyhow!("Could not fetch last price for {}", ltp_key))?;
+
+    let leg = |leg_price: f64| GTTOrder {
+        exchange: exchange.clone(),
+        tradingsymbol: tradingsymbol.clone(),
+        transaction_type: tx_type.clone(),
+        quantity: quantity as u32,
+        order_type: ord_type.clone(),
+        product: prod.clone(),
+        price: leg_price,
+    };
+
+    let (trigger_values, orders) = match trigger_type {
+        GTTTriggerType::Single => {
+            if target_trigger_price.is_some() || target_price.is_some() {
+                anyhow::bail!("--target-trigger-price and --target-price need --trigger-type two-leg");
+            }
+            (vec![trigger_price], vec![leg(price)])
+        }
+        GTTTriggerType::TwoLeg => {
+            let (target_trigger, target) = match (target_trigger_price, target_price) {
+                (Some(t), Some(p)) => (t, p),
+                _ => anyhow::bail!(
+                    "Two-leg GTT requires --target-trigger-price and --target-price"
+                ),
+            };
+            zerodha_cli_core::validation::validate_two_leg_gtt(
+                &tx_type,
+                trigger_price,
+                target_trigger,
+                last_price,
+            )?;
+
+            // Kite expects trigger values in ascending order with matching legs
+            let mut legs = [(trigger_price, price), (target_trigger, target)];
+            legs.sort_by(|a, b| a.0.total_cmp(&b.0));
+            (
+                legs.iter().map(|(t, _)| *t).collect(),
+                legs.iter().map(|(_, p)| leg(*p)).collect(),
+            )
+        }
+    };
+
     let request = zerodha_cli_core::models::PlaceGTT {
-        tradingsymbol: parts[1].to_string(),
-        exchange: parts[0].to_string(),
+        tradingsymbol: tradingsymbol.clone(),
+        exchange: exchange.clone(),
         transaction_type: tx_type,
         product: prod,
         order_type: ord_type,
@@ -147,85 +255,270 @@ pub async fn run_gtt_create(params: GTTCreateParams, api_client: &KiteConnectCli
         trailing_stoploss: None,
         stoploss: None,
         squareoff: None,
+        trigger_type,
+        condition: GTTCondition {
+            exchange,
+            tradingsymbol,
+            trigger_values,
+            last_price,
+        },
+        orders,
     };
 
+    if params.dry_run {
+        eprintln!(
+            "[DRY RUN] Would create {} GTT on {}:{}",
+            request.trigger_type, request.exchange, request.tradingsymbol
+        );
+        if params.show_payload {
+            print_request_body(&request)?;
+        }
+        return Ok(());
+    }
+
     let response = api_client.create_gtt(&request).await?;
-    println!("✓ GTT order created successfully!");
-    println!("  Trigger ID: {}", response.trigger_id);
-    println!("  Status: {}", response.status);
+    eprintln!("✓ GTT order created successfully!");
+    eprintln!("  Trigger ID: {}", response.trigger_id);
+    eprintln!("  Status: {}", response.status);
 
     Ok(())
 }
 
+/// Build a modify request from the fields passed to `gtt modify`
+fn modify_request(changes: &GttModifyArgs) -> Result<ModifyGTT> {
+    let order_type = match changes.order_type.as_deref().map(parse_order_type).transpose()? {
+        Some(OrderType::SL | OrderType::SLM) => {
+            anyhow::bail!("Invalid order type for a GTT. Use MARKET or LIMIT")
+        }
+        order_type => order_type,
+    };
+    if changes.quantity == Some(0) {
+        anyhow::bail!("Quantity must be greater than 0");
+    }
+
+    let request = ModifyGTT {
+        order_type,
+        quantity: changes.quantity,
+        price: changes.price,
+        trigger_price: changes.trigger_price,
+        trailing_stoploss: changes.trailing_stoploss,
+        stoploss: changes.stoploss,
+        squareoff: changes.squareoff,
+    };
+    let unchanged = request.order_type.is_none()
+        && request.quantity.is_none()
+        && request.price.is_none()
+        && request.trigger_price.is_none()
+        && request.trailing_stoploss.is_none()
+        && request.stoploss.is_none()
+        && request.squareoff.is_none();
+    if unchanged {
+        anyhow::bail!(
+            "Nothing to modify. Pass at least one of --price, --trigger-price, --quantity, \
+             --order-type, --trailing-stoploss, --stoploss or --squareoff"
+        );
+    }
+    Ok(request)
+}
+
 pub async fn run_gtt_modify(
     trigger_id: String,
-    price: Option<f64>,
-    trigger_price: Option<f64>,
+    changes: &GttModifyArgs,
+    dry_run: bool,
     api_client: &KiteConnectClient,
 ) -> Result<()> {
     let id: u64 = trigger_id
         .parse()
         .map_err(|_| anyhow::anyhow!("Invalid trigger ID. Must be a number"))?;
 
-    let request = zerodha_cli_core::models::ModifyGTT {
-        order_type: None,
-        quantity: None,
-        price,
-        trigger_price,
-        trailing_stoploss: None,
-        stoploss: None,
-        squareoff: None,
-    };
+    let request = modify_request(changes)?;
+
+    if dry_run {
+        eprintln!("[DRY RUN] Would modify GTT {}", id);
+        print_request_body(&request)?;
+        return Ok(());
+    }
 
     let response = api_client.modify_gtt(id, &request).await?;
-    println!("✓ GTT order modified successfully!");
-    println!("  Trigger ID: {}", response.trigger_id);
-    println!("  Status: {}", response.status);
+    eprintln!("✓ GTT order modified successfully!");
+    eprintln!("  Trigger ID: {}", response.trigger_id);
+    eprintln!("  Status: {}", response.status);
 
     Ok(())
 }
 
-pub async fn run_gtt_delete(trigger_id: String, api_client: &KiteConnectClient) -> Result<()> {
+pub async fn run_gtt_delete(
+    trigger_id: String,
+    dry_run: bool,
+    assume_yes: bool,
+    api_client: &KiteConnectClient,
+) -> Result<()> {
     let id: u64 = trigger_id
         .parse()
         .map_err(|_| anyhow::anyhow!("Invalid trigger ID. Must be a number"))?;
 
+    if dry_run {
+        eprintln!("[DRY RUN] Would delete GTT {}", id);
+        return Ok(());
+    }
+
     // Confirm
-    print!("Delete GTT order {}? [y/N]: ", trigger_id);
-    let mut input = String::new();
-    std::io::stdin().read_line(&mut input)?;
-    if !input.trim().to_lowercase().starts_with('y') {
-        println!("Deletion cancelled.");
+    if !confirm_or_prompt(&format!("Delete GTT order {}?", trigger_id), assume_yes)? {
+        eprintln!("Deletion cancelled.");
         return Ok(());
     }
 
     api_client.delete_gtt(id).await?;
-    println!("✓ GTT order deleted successfully!");
+    eprintln!("✓ GTT order deleted successfully!");
 
     Ok(())
 }
 
-fn print_gtt_table(gtt_list: &[zerodha_cli_core::models::GTTTrigger]) {
-    use comfy_table::{Cell, Color, ContentArrangement, Table};
+pub async fn run_gtt_delete_all(
+    status: Option<&str>,
+    dry_run: bool,
+    assume_yes: bool,
+    api_client: &KiteConnectClient,
+) -> Result<()> {
+    let status = parse_status(status)?;
+    let mut gtt_list = api_client.list_gtt().await?;
+    gtt_list.retain(|gtt| has_status(gtt, status.as_deref()));
+    sort_by_expiry(&mut gtt_list);
+
+    if gtt_list.is_empty() {
+        eprintln!("No GTT orders to delete.");
+        return Ok(());
+    }
+
+    eprintln!("Found {} GTT orders:", gtt_list.len());
+    for gtt in &gtt_list {
+        eprintln!(
+            "  {} - {} {} {} @ ₹{:.2} ({})",
+            gtt.id, gtt.transaction_type, gtt.quantity, gtt.tradingsymbol, gtt.trigger_price, gtt.status
+        );
+    }
+
+    if dry_run {
+        eprintln!("\n[DRY RUN] Would delete {} GTT order(s).", gtt_list.len());
+        return Ok(());
+    }
+
+    if !confirm_or_prompt(&format!("\nDelete {} GTT orders?", gtt_list.len()), assume_yes)? {
+        eprintln!("Deletion cancelled.");
+        return Ok(());
+    }
+
+    let mut outcome = DeleteOutcome::default();
+    for gtt in &gtt_list {
+        let result = api_client.delete_gtt(gtt.id).await;
+        match &result {
+            Ok(_) => eprintln!("✓ Deleted GTT {}", gtt.id),
+            Err(e) => eprintln!("✗ Failed to delete GTT {}: {:#}", gtt.id, e),
+        }
+        outcome.record(gtt.id, result.map(|_| ()));
+    }
+    outcome.into_result()
+}
+
+/// Per-trigger results of `gtt delete-all`
+#[derive(Debug, Default)]
+struct DeleteOutcome {
+    deleted: Vec<u64>,
+    failed: Vec<u64>,
+}
+
+impl DeleteOutcome {
+    fn record(&mut self, id: u64, result: Result<()>) {
+        match result {
+            Ok(()) => self.deleted.push(id),
+            Err(_) => self.failed.push(id),
+        }
+    }
+
+    /// Fail when any deletion failed, naming the triggers left behind
+    fn into_result(self) -> Result<()> {
+        if self.failed.is_empty() {
+            return Ok(());
+        }
+        let ids: Vec<String> = self.failed.iter().map(u64::to_string).collect();
+        anyhow::bail!(
+            "{} of {} GTT deletions failed: {}",
+            self.failed.len(),
+            self.failed.len() + self.deleted.len(),
+            ids.join(", ")
+        )
+    }
+}
+
+/// Validate a `--status` filter, lowercased
+fn parse_status(status: Option<&str>) -> Result<Option<String>> {
+    let status = status.map(str::to_lowercase);
+    if let Some(ref status) = status {
+        if !GTT_STATUSES.contains(&status.as_str()) {
+            anyhow::bail!(
+                "Invalid GTT status '{}'. Use one of: {}",
+                status,
+                GTT_STATUSES.join(", ")
+            );
+        }
+    }
+    Ok(status)
+}
+
+/// Whether `gtt` passes a `--status` filter; no filter matches everything
+fn has_status(gtt: &GTTTrigger, status: Option<&str>) -> bool {
+    status.is_none_or(|status| gtt.status.eq_ignore_ascii_case(status))
+}
+
+/// Order GTTs by expiry, soonest first; those without an expiry go last
+fn sort_by_expiry(gtt_list: &mut [GTTTrigger]) {
+    gtt_list.sort_by_key(|gtt| (gtt.expires_at.is_none(), gtt.expires_at));
+}
+
+/// Whether an active GTT expires within [`EXPIRY_WARNING_DAYS`] of `now`
+fn expires_soon(gtt: &GTTTrigger, now: NaiveDateTime) -> bool {
+    gtt.status.eq_ignore_ascii_case("active")
+        && gtt.expires_at.is_some_and(|expires_at| {
+            expires_at >= now && expires_at - now <= Duration::days(EXPIRY_WARNING_DAYS)
+        })
+}
+
+fn print_gtt_table(gtt_list: &[GTTTrigger]) -> Result<()> {
+    use comfy_table::{Cell, ContentArrangement, Table};
+    use zerodha_cli_core::output::CellColorExt;
+
+    let mut out = output::writer();
 
     let mut table = Table::new();
-    table.set_header(vec![
+    table.set_header(output::header(vec![
         "ID",
         "Symbol",
         "Type",
         "Trigger Price",
         "Status",
         "Generated",
-    ]);
+        "Expires",
+    ]));
 
+    let now = market::now_ist();
     for gtt in gtt_list {
         let status_cell = match gtt.status.to_lowercase().as_str() {
-            "active" => Cell::new("ACTIVE").fg(Color::Green),
-            "triggered" => Cell::new("TRIGGERED").fg(Color::Yellow),
-            "disabled" => Cell::new("DISABLED").fg(Color::Red),
-            "expired" => Cell::new("EXPIRED").fg(Color::Red),
+            "active" => Cell::new("ACTIVE").color(output::theme().positive),
+            "triggered" => Cell::new("TRIGGERED").color(output::theme().pending),
+            "disabled" => Cell::new("DISABLED").color(output::theme().negative),
+            "expired" => Cell::new("EXPIRED").color(output::theme().negative),
             _ => Cell::new(&gtt.status),
         };
+        let expires = gtt
+            .expires_at
+            .as_ref()
+            .map(output::format_time)
+            .unwrap_or_else(|| "-".to_string());
+        let expires_cell = if expires_soon(gtt, now) {
+            Cell::new(expires).color(output::theme().pending)
+        } else {
+            Cell::new(expires)
+        };
 
         table.add_row(vec![
             Cell::new(gtt.id.to_string()),
@@ -233,33 +526,154 @@ fn print_gtt_table(gtt_list: &[zerodha_cli_core::models::GTTTrigger]) {
             Cell::new(format!("{:?}", gtt.transaction_type)),
             Cell::new(format!("₹{:.2}", gtt.trigger_price)),
             status_cell,
-            Cell::new(&gtt.generated_at),
+            Cell::new(output::format_time(&gtt.generated_at)),
+            expires_cell,
         ]);
     }
 
     table.set_content_arrangement(ContentArrangement::Dynamic);
-    println!("{table}");
+    writeln!(out, "{table}")?;
+
+    Ok(())
 }
 
-fn print_gtt_details(gtt: &zerodha_cli_core::models::GTTTrigger) {
-    println!("GTT Order: {}", gtt.id);
-    println!();
-    println!("Symbol: {} ({})", gtt.tradingsymbol, gtt.exchange);
-    println!("Status: {}", gtt.status);
-    println!("Type: {:?}", gtt.transaction_type);
-    println!("Order Type: {:?}", gtt.order_type);
-    println!("Product: {:?}", gtt.product);
-    println!("Quantity: {}", gtt.quantity);
-    println!("Price: ₹{:.2}", gtt.price);
-    println!("Trigger Price: ₹{:.2}", gtt.trigger_price);
-    println!("Last Price: ₹{:.2}", gtt.last_price);
-
-    println!();
-    println!("Generated: {}", gtt.generated_at);
+fn print_gtt_details(gtt: &zerodha_cli_core::models::GTTTrigger) -> Result<()> {
+    let mut out = output::writer();
+
+    writeln!(out, "GTT Order: {}", gtt.id)?;
+    writeln!(out)?;
+    writeln!(out, "Symbol: {} ({})", gtt.tradingsymbol, gtt.exchange)?;
+    writeln!(out, "Status: {}", gtt.status)?;
+    writeln!(out, "Type: {:?}", gtt.transaction_type)?;
+    writeln!(out, "Order Type: {:?}", gtt.order_type)?;
+    writeln!(out, "Product: {:?}", gtt.product)?;
+    writeln!(out, "Quantity: {}", gtt.quantity)?;
+    writeln!(out, "Price: ₹{:.2}", gtt.price)?;
+    writeln!(out, "Trigger Price: ₹{:.2}", gtt.trigger_price)?;
+    writeln!(out, "Last Price: ₹{:.2}", gtt.last_price)?;
+
+    writeln!(out)?;
+    writeln!(out, "Generated: {}", output::format_time(&gtt.generated_at))?;
     if let Some(updated) = &gtt.updated_at {
-        println!("Updated: {}", updated);
+        writeln!(out, "Updated: {}", output::format_time(updated))?;
     }
     if let Some(expires) = &gtt.expires_at {
-        println!("Expires: {}", expires);
+        writeln!(out, "Expires: {}", output::format_time(expires))?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+    use zerodha_cli_core::models::{Exchange, OrderType, Product, TransactionType};
+
+    fn at(day: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2024, 6, day)
+            .unwrap()
+            .and_hms_opt(9, 15, 0)
+            .unwrap()
+    }
+
+    fn gtt(id: u64, status: &str, expires_at: Option<NaiveDateTime>) -> GTTTrigger {
+        GTTTrigger {
+            id,
+            user_id: "AB1234".to_string(),
+            tradingsymbol: "INFY".to_string(),
+            exchange: Exchange::NSE,
+            transaction_type: TransactionType::Buy,
+            product: Product::CNC,
+            order_type: OrderType::Limit,
+            quantity: 1,
+            price: 1500.0,
+            trigger_price: 1500.0,
+            last_price: 1550.0,
+            trailing_stoploss: None,
+            stoploss: None,
+            squareoff: None,
+            generated_at: at(1),
+            updated_at: None,
+            expires_at,
+            status: status.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_sort_by_expiry_puts_missing_last() {
+        let mut list = vec![
+            gtt(1, "active", None),
+            gtt(2, "active", Some(at(20))),
+            gtt(3, "active", Some(at(5))),
+        ];
+        sort_by_expiry(&mut list);
+        let ids: Vec<u64> = list.iter().map(|g| g.id).collect();
+        assert_eq!(ids, vec![3, 2, 1]);
+    }
+
+    #[test]
+    fn test_status_filter() {
+        let active = gtt(1, "active", None);
+        let expired = gtt(2, "EXPIRED", None);
+        assert!(has_status(&active, None));
+        assert!(has_status(&expired, Some("expired")));
+        assert!(!has_status(&active, Some("expired")));
+
+        assert_eq!(parse_status(Some("Active")).unwrap().as_deref(), Some("active"));
+        assert_eq!(parse_status(None).unwrap(), None);
+        assert!(parse_status(Some("pending")).is_err());
+    }
+
+    #[test]
+    fn test_delete_outcome_reports_failures() {
+        let mut outcome = DeleteOutcome::default();
+        outcome.record(1, Ok(()));
+        outcome.record(2, Ok(()));
+        assert!(outcome.into_result().is_ok());
+
+        let mut outcome = DeleteOutcome::default();
+        outcome.record(1, Ok(()));
+        outcome.record(2, Err(anyhow::anyhow!("Trigger not found")));
+        outcome.record(3, Err(anyhow::anyhow!("Network error")));
+        assert_eq!(outcome.deleted, [1]);
+        let err = outcome.into_result().unwrap_err();
+        assert_eq!(err.to_string(), "2 of 3 GTT deletions failed: 2, 3");
+    }
+
+    #[test]
+    fn test_expires_soon() {
+        let now = at(10);
+        assert!(expires_soon(&gtt(1, "active", Some(at(17))), now));
+        assert!(!expires_soon(&gtt(1, "active", Some(at(18))), now));
+        assert!(!expires_soon(&gtt(1, "active", Some(at(9))), now));
+        assert!(!expires_soon(&gtt(1, "triggered", Some(at(12))), now));
+        assert!(!expires_soon(&gtt(1, "active", None), now));
+    }
+
+    #[test]
+    fn test_modify_request_from_partial_flags() {
+        let changes = GttModifyArgs {
+            quantity: Some(5),
+            order_type: Some("market".to_string()),
+            stoploss: Some(2.5),
+            ..Default::default()
+        };
+        let request = modify_request(&changes).unwrap();
+        assert_eq!(request.quantity, Some(5));
+        assert!(matches!(request.order_type, Some(OrderType::Market)));
+        assert_eq!(request.stoploss, Some(2.5));
+        assert_eq!(request.price, None);
+        assert_eq!(
+            serde_json::to_value(&request).unwrap(),
+            serde_json::json!({"order_type": "MARKET", "quantity": 5, "stoploss": 2.5})
+        );
+
+        assert!(modify_request(&GttModifyArgs::default()).is_err());
+        let sl = GttModifyArgs {
+            order_type: Some("SL".to_string()),
+            ..Default::default()
+        };
+        assert!(modify_request(&sl).is_err());
     }
 }
@@ -2,103 +2,268 @@
 
 use anyhow::Result;
 use serde_json;
+use std::io::Write;
+use std::time::Instant;
 use zerodha_cli_core::{
-    api::KiteConnectClient, cache::InstrumentCache, models::Instrument, output::OutputFormatter,
+    api::KiteConnectClient,
+    cache::{search_instruments, InstrumentCache, RefreshResult, EXCHANGES},
+    chain::{self, ChainRow},
+    config::Config,
+    error::ZerodhaError,
+    instruments::{find_by_token, suggest_symbol, InstrumentFilter},
+    models::Instrument,
+    output::{self, OutputFormatter},
+    validation::parse_symbol,
 };
 
-use super::InstrumentsCommands;
+use super::{InstrumentsCommands, PageArgs};
 
 pub async fn run_instruments(
     cmd: InstrumentsCommands,
+    config: &Config,
     api_client: &KiteConnectClient,
     output_format: &str,
 ) -> Result<()> {
+    let ttl_hours = config.cache.instrument_ttl_hours;
     match cmd.command {
-        super::InstrumentsSubcommands::List { exchange, refresh } => {
-            run_instruments_list(exchange, refresh, output_format, api_client).await?
+        super::InstrumentsSubcommands::List {
+            exchange,
+            refresh,
+            columns,
+            limit,
+            all,
+            instrument_type,
+            expiry,
+            strike,
+            page,
+        } => {
+            let columns = parse_columns(columns.as_deref())?;
+            let max_rows = (!all).then_some(limit);
+            let filter = InstrumentFilter::parse(instrument_type.as_deref(), expiry.as_deref(), strike)?;
+            run_instruments_list(
+                exchange,
+                refresh,
+                &columns,
+                max_rows,
+                &filter,
+                page,
+                ttl_hours,
+                output_format,
+                api_client,
+            )
+            .await?
         }
-        super::InstrumentsSubcommands::Search { query, exchange } => {
-            run_instruments_search(query, exchange, output_format, api_client).await?
+        super::InstrumentsSubcommands::Search {
+            query,
+            exchange,
+            all_exchanges,
+            limit,
+            exact,
+            columns,
+        } => {
+            let columns = parse_columns(columns.as_deref())?;
+            run_instruments_search(
+                query,
+                exchange,
+                all_exchanges,
+                limit,
+                exact,
+                &columns,
+                ttl_hours,
+                output_format,
+                api_client,
+            )
+            .await?
         }
         super::InstrumentsSubcommands::Get { symbol } => {
-            run_instruments_get(symbol, output_format, api_client).await?
+            run_instruments_get(symbol, ttl_hours, output_format, api_client).await?
+        }
+        super::InstrumentsSubcommands::Token { token } => {
+            run_instruments_token(token, output_format)?
+        }
+        super::InstrumentsSubcommands::Refresh { exchange, all } => {
+            run_instruments_refresh(exchange, all, api_client).await?
+        }
+        super::InstrumentsSubcommands::Chain { symbol, expiry } => {
+            run_instruments_chain(symbol, expiry, ttl_hours, output_format, api_client).await?
         }
     }
     Ok(())
 }
 
+/// Load an exchange's instruments from cache, downloading them if stale
+async fn load_instruments(
+    exchange: &str,
+    ttl_hours: u32,
+    api_client: &KiteConnectClient,
+) -> Result<Vec<Instrument>> {
+    if InstrumentCache::is_valid_with_ttl(exchange, ttl_hours)? {
+        return InstrumentCache::load(exchange);
+    }
+
+    output::progress("Downloading instruments from exchange...");
+    let instruments = api_client.list_instruments(Some(exchange)).await?;
+    InstrumentCache::save(exchange, &instruments)?;
+    Ok(instruments)
+}
+
+/// Instruments of every cached exchange, warning about the ones not cached
+fn load_all_cached() -> Result<Vec<Instrument>> {
+    let (instruments, missing) = InstrumentCache::load_all();
+    if instruments.is_empty() {
+        anyhow::bail!("No exchanges are cached. Run `kite instruments refresh --all` first");
+    }
+    if !missing.is_empty() {
+        eprintln!(
+            "Warning: skipping exchanges that aren't cached: {} (run `kite instruments refresh --all`)",
+            missing.join(", ")
+        );
+    }
+    Ok(instruments)
+}
+
+#[allow(clippy::too_many_arguments)]
 pub async fn run_instruments_list(
     exchange: Option<String>,
     refresh: bool,
+    columns: &[InstrumentColumn],
+    max_rows: Option<usize>,
+    filter: &InstrumentFilter,
+    page: PageArgs,
+    ttl_hours: u32,
     output_format: &str,
     api_client: &KiteConnectClient,
 ) -> Result<()> {
     let exchange = exchange.unwrap_or_else(|| "NSE".to_string());
 
     // Check if cache is valid
-    let instruments = if !refresh && InstrumentCache::is_valid(&exchange)? {
-        println!("Loading instruments from cache...");
+    let instruments = if !refresh && InstrumentCache::is_valid_with_ttl(&exchange, ttl_hours)? {
+        output::progress("Loading instruments from cache...");
         InstrumentCache::load(&exchange)?
     } else {
-        println!("Downloading instruments from exchange...");
+        output::progress("Downloading instruments from exchange...");
         let instruments = api_client.list_instruments(Some(exchange.as_str())).await?;
         InstrumentCache::save(&exchange, &instruments)?;
-        println!("✓ Downloaded {} instruments", instruments.len());
+        output::progress(format_args!("✓ Downloaded {} instruments", instruments.len()));
         instruments
     };
+    let instruments: Vec<Instrument> = if filter.is_empty() {
+        instruments
+    } else {
+        instruments.into_iter().filter(|i| filter.matches(i)).collect()
+    };
+    if instruments.is_empty() {
+        output::notice("No instruments match the filters.");
+        return Ok(());
+    }
+    let (instruments, footer) = page.apply(instruments)?;
 
     // Display
-    if output_format == "json" {
-        instruments.print_json()?;
+    if let Some(template) = output::template() {
+        instruments.print_template(&template, &mut output::writer())?;
+    } else if output_format == "json" {
+        instruments.print_json(&mut output::writer())?;
+    } else if output_format == "ndjson" {
+        instruments.print_ndjson(&mut output::writer())?;
+    } else if output_format == "csv" {
+        instruments.print_csv(&mut output::writer())?;
+    } else if let Some(footer) = footer {
+        // A page is shown whole, regardless of --limit
+        print_instruments_table(&instruments, columns, None)?;
+        writeln!(output::writer(), "{}", footer)?;
     } else {
-        print_instruments_table(&instruments);
+        print_instruments_table(&instruments, columns, max_rows)?;
     }
 
     Ok(())
 }
 
+pub async fn run_instruments_refresh(
+    exchange: Option<String>,
+    all: bool,
+    api_client: &KiteConnectClient,
+) -> Result<()> {
+    let started = Instant::now();
+
+    if !all {
+        let exchange = exchange.unwrap_or_else(|| "NSE".to_string()).to_uppercase();
+        output::progress(format_args!("Downloading {} instruments...", exchange));
+        let instruments = api_client.list_instruments(Some(exchange.as_str())).await?;
+        InstrumentCache::save(&exchange, &instruments)?;
+        output::notice(format_args!(
+            "✓ Downloaded {} instruments in {:.1}s",
+            instruments.len(),
+            started.elapsed().as_secs_f64()
+        ));
+        return Ok(());
+    }
+
+    output::progress(format_args!("Downloading instruments for {} exchanges...", EXCHANGES.len()));
+    let results = InstrumentCache::refresh_all(api_client).await;
+
+    let mut failed = 0;
+    for RefreshResult { exchange, result } in &results {
+        match result {
+            Ok(count) => output::notice(format_args!("  ✓ {}: {} instruments", exchange, count)),
+            Err(e) => {
+                failed += 1;
+                output::notice(format_args!("  ✗ {}: {:#}", exchange, e));
+            }
+        }
+    }
+    output::notice(format_args!("Finished in {:.1}s", started.elapsed().as_secs_f64()));
+
+    if failed > 0 {
+        anyhow::bail!("{} of {} exchanges failed to refresh", failed, results.len());
+    }
+
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
 pub async fn run_instruments_search(
     query: String,
     exchange_filter: Option<String>,
+    all_exchanges: bool,
+    limit: usize,
+    exact: bool,
+    columns: &[InstrumentColumn],
+    ttl_hours: u32,
     output_format: &str,
     api_client: &KiteConnectClient,
 ) -> Result<()> {
-    let query_lower = query.to_lowercase();
-
     // Get instruments from cache or API
-    let exchange = exchange_filter.as_deref().unwrap_or("NSE");
-    let instruments = if InstrumentCache::is_valid(exchange)? {
-        InstrumentCache::load(exchange)?
+    let instruments = if all_exchanges {
+        load_all_cached()?
     } else {
-        println!("Downloading instruments from exchange...");
-        let instruments = api_client.list_instruments(Some(exchange)).await?;
-        InstrumentCache::save(exchange, &instruments)?;
-        instruments
+        let exchange = exchange_filter.as_deref().unwrap_or("NSE");
+        load_instruments(exchange, ttl_hours, api_client).await?
     };
 
-    // Filter by query
-    let filtered: Vec<Instrument> = instruments
-        .into_iter()
-        .filter(|inst| {
-            let matches_symbol = inst.tradingsymbol.to_lowercase().contains(&query_lower);
-            let matches_name = inst.name.to_lowercase().contains(&query_lower);
-            let matches_exchange = exchange_filter.is_none()
-                || format!("{:?}", inst.exchange).to_lowercase() == exchange;
-            (matches_symbol || matches_name) && matches_exchange
-        })
-        .collect();
+    // Rank by query
+    let filtered = search_instruments(instruments, &query, exact, limit);
 
     if filtered.is_empty() {
-        println!("No instruments found matching '{}'", query);
+        output::notice(format_args!("No instruments found matching '{}'", query));
         return Ok(());
     }
 
-    println!("Found {} instruments matching '{}':", filtered.len(), query);
+    output::notice(format_args!(
+        "Top {} instruments matching '{}':",
+        filtered.len(),
+        query
+    ));
 
-    if output_format == "json" {
-        filtered.print_json()?;
+    if let Some(template) = output::template() {
+        filtered.print_template(&template, &mut output::writer())?;
+    } else if output_format == "json" {
+        filtered.print_json(&mut output::writer())?;
+    } else if output_format == "ndjson" {
+        filtered.print_ndjson(&mut output::writer())?;
+    } else if output_format == "csv" {
+        filtered.print_csv(&mut output::writer())?;
     } else {
-        print_instruments_table(&filtered);
+        print_instruments_table(&filtered, columns, None)?;
     }
 
     Ok(())
@@ -106,80 +271,361 @@ pub async fn run_instruments_search(
 
 pub async fn run_instruments_get(
     symbol: String,
+    ttl_hours: u32,
     output_format: &str,
     api_client: &KiteConnectClient,
 ) -> Result<()> {
-    let parts: Vec<&str> = symbol.split(':').collect();
-    if parts.len() != 2 {
-        anyhow::bail!("Invalid symbol format. Expected: EXCHANGE:SYMBOL (e.g., NSE:INFY)");
+    let (exchange, tradingsymbol) = parse_symbol(&symbol)?;
+    let (exchange, tradingsymbol) = (exchange.as_str(), tradingsymbol.as_str());
+
+    // Prefer the cached index; fall back to the API when the cache is cold
+    let cached = if InstrumentCache::is_valid_with_ttl(exchange, ttl_hours)? {
+        InstrumentCache::lookup(exchange, tradingsymbol)?
+    } else {
+        None
+    };
+
+    let instrument = match cached {
+        Some(instrument) => instrument,
+        None => api_client
+            .get_instrument(exchange, tradingsymbol)
+            .await
+            .map_err(|e| {
+                // Only a missing symbol gets a hint, not a network or auth failure
+                let not_found = matches!(
+                    e.downcast_ref::<ZerodhaError>(),
+                    Some(ZerodhaError::InstrumentNotFound(_))
+                );
+                match suggest_symbol(exchange, tradingsymbol).filter(|_| not_found) {
+                    Some(suggestion) => e.context(format!("Did you mean {}?", suggestion)),
+                    None => e,
+                }
+            })?,
+    };
+
+    if output_format == "json" {
+        output::emit(output::to_json(&instrument)?)?;
+    } else if output_format == "ndjson" {
+        output::emit(serde_json::to_string(&instrument)?)?;
+    } else {
+        print_instrument_details(&instrument)?;
     }
 
-    let exchange = parts[0];
-    let tradingsymbol = parts[1];
+    Ok(())
+}
 
-    let instrument = api_client.get_instrument(exchange, tradingsymbol).await?;
+pub fn run_instruments_token(token: u64, output_format: &str) -> Result<()> {
+    let Some(instrument) = find_by_token(token) else {
+        anyhow::bail!(
+            "Instrument token {} not found in any cached exchange. \
+             Run `kite instruments refresh --all` to update the cache",
+            token
+        );
+    };
 
     if output_format == "json" {
-        println!("{}", serde_json::to_string_pretty(&instrument)?);
+        output::emit(output::to_json(&instrument)?)?;
+    } else if output_format == "ndjson" {
+        output::emit(serde_json::to_string(&instrument)?)?;
     } else {
-        print_instrument_details(&instrument);
+        print_instrument_details(&instrument)?;
     }
 
     Ok(())
 }
 
-fn print_instruments_table(instruments: &[Instrument]) {
-    use comfy_table::{Cell, ContentArrangement, Table};
+pub async fn run_instruments_chain(
+    symbol: String,
+    expiry: Option<String>,
+    ttl_hours: u32,
+    output_format: &str,
+    api_client: &KiteConnectClient,
+) -> Result<()> {
+    let (exchange, underlying) = parse_symbol(&symbol)?;
+    let underlying = underlying.as_str();
+    let exchange = chain::options_exchange(&exchange).to_uppercase();
+
+    let instruments = load_instruments(&exchange, ttl_hours, api_client).await?;
+
+    let expiry = match expiry {
+        Some(expiry) => expiry,
+        None => {
+            let today = chrono::Local::now().format("%Y-%m-%d").to_string();
+            chain::nearest_expiry(&instruments, underlying, &today).ok_or_else(|| {
+                anyhow::anyhow!("No upcoming option expiries found for {}", underlying)
+            })?
+        }
+    };
+
+    let rows =
+        chain::option_chain(api_client, &instruments, &exchange, underlying, &expiry).await?;
+    if rows.is_empty() {
+        output::notice(format_args!(
+            "No options found for {} expiring {} on {}",
+            underlying, expiry, exchange
+        ));
+        return Ok(());
+    }
+
+    if output_format == "json" {
+        output::emit(output::to_json(&rows)?)?;
+    } else if output_format == "ndjson" {
+        output::write_ndjson(&rows, output::writer())?;
+    } else if output_format == "yaml" {
+        output::write_yaml(&rows, output::writer())?;
+    } else {
+        print_chain_table(underlying, &expiry, &rows)?;
+    }
+
+    Ok(())
+}
+
+fn print_chain_table(underlying: &str, expiry: &str, rows: &[ChainRow]) -> Result<()> {
+    use comfy_table::{Cell, CellAlignment, ContentArrangement, Table};
+
+    let mut out = output::writer();
+
+    let ltp = |leg: &Option<chain::ChainLeg>| match leg.as_ref().and_then(|l| l.last_price) {
+        Some(price) => format!("{:.2}", price),
+        None => "-".to_string(),
+    };
 
     let mut table = Table::new();
-    table.set_header(vec![
-        "Symbol",
-        "Name",
-        "Exchange",
-        "Type",
-        "Lot Size",
-        "Tick Size",
-    ]);
-
-    for inst in instruments.iter().take(50) {
-        // Limit to first 50 for display
+    table.set_header(output::header(vec!["Call LTP", "Strike", "Put LTP"]));
+
+    for row in rows {
         table.add_row(vec![
-            Cell::new(&inst.tradingsymbol),
-            Cell::new(&inst.name),
-            Cell::new(format!("{:?}", inst.exchange)),
-            Cell::new(format!("{:?}", inst.instrument_type)),
-            Cell::new(inst.lot_size.to_string()),
-            Cell::new(inst.tick_size.to_string()),
+            Cell::new(ltp(&row.call)).set_alignment(CellAlignment::Right),
+            Cell::new(format!("{:.2}", row.strike)).set_alignment(CellAlignment::Center),
+            Cell::new(ltp(&row.put)).set_alignment(CellAlignment::Right),
         ]);
     }
 
-    if instruments.len() > 50 {
-        println!("Showing 50 of {} instruments", instruments.len());
+    writeln!(out, "Option chain: {} (expiry {})", underlying.to_uppercase(), expiry)?;
+    table.set_content_arrangement(ContentArrangement::Dynamic);
+    writeln!(out, "{table}")?;
+
+    Ok(())
+}
+
+/// A selectable column of the instruments table
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InstrumentColumn {
+    Symbol,
+    Name,
+    Exchange,
+    Segment,
+    Type,
+    Token,
+    ExchangeToken,
+    Expiry,
+    Strike,
+    LotSize,
+    TickSize,
+    LastPrice,
+}
+
+impl InstrumentColumn {
+    const ALL: [InstrumentColumn; 12] = [
+        InstrumentColumn::Symbol,
+        InstrumentColumn::Name,
+        InstrumentColumn::Exchange,
+        InstrumentColumn::Segment,
+        InstrumentColumn::Type,
+        InstrumentColumn::Token,
+        InstrumentColumn::ExchangeToken,
+        InstrumentColumn::Expiry,
+        InstrumentColumn::Strike,
+        InstrumentColumn::LotSize,
+        InstrumentColumn::TickSize,
+        InstrumentColumn::LastPrice,
+    ];
+
+    /// Columns shown when --columns is not given
+    const DEFAULT: [InstrumentColumn; 6] = [
+        InstrumentColumn::Symbol,
+        InstrumentColumn::Name,
+        InstrumentColumn::Exchange,
+        InstrumentColumn::Type,
+        InstrumentColumn::LotSize,
+        InstrumentColumn::TickSize,
+    ];
+
+    /// Name accepted by --columns
+    fn key(self) -> &'static str {
+        match self {
+            InstrumentColumn::Symbol => "symbol",
+            InstrumentColumn::Name => "name",
+            InstrumentColumn::Exchange => "exchange",
+            InstrumentColumn::Segment => "segment",
+            InstrumentColumn::Type => "type",
+            InstrumentColumn::Token => "token",
+            InstrumentColumn::ExchangeToken => "exchange_token",
+            InstrumentColumn::Expiry => "expiry",
+            InstrumentColumn::Strike => "strike",
+            InstrumentColumn::LotSize => "lot_size",
+            InstrumentColumn::TickSize => "tick_size",
+            InstrumentColumn::LastPrice => "last_price",
+        }
+    }
+
+    fn header(self) -> &'static str {
+        match self {
+            InstrumentColumn::Symbol => "Symbol",
+            InstrumentColumn::Name => "Name",
+            InstrumentColumn::Exchange => "Exchange",
+            InstrumentColumn::Segment => "Segment",
+            InstrumentColumn::Type => "Type",
+            InstrumentColumn::Token => "Token",
+            InstrumentColumn::ExchangeToken => "Exch Token",
+            InstrumentColumn::Expiry => "Expiry",
+            InstrumentColumn::Strike => "Strike",
+            InstrumentColumn::LotSize => "Lot Size",
+            InstrumentColumn::TickSize => "Tick Size",
+            InstrumentColumn::LastPrice => "Last Price",
+        }
+    }
+
+    fn value(self, inst: &Instrument) -> String {
+        match self {
+            InstrumentColumn::Symbol => inst.tradingsymbol.clone(),
+            InstrumentColumn::Name => inst.name.clone(),
+            InstrumentColumn::Exchange => format!("{:?}", inst.exchange),
+            InstrumentColumn::Segment => format!("{:?}", inst.segment),
+            InstrumentColumn::Type => format!("{:?}", inst.instrument_type),
+            InstrumentColumn::Token => inst.instrument_token.to_string(),
+            InstrumentColumn::ExchangeToken => inst.exchange_token.to_string(),
+            InstrumentColumn::Expiry => inst.expiry.clone().unwrap_or_default(),
+            InstrumentColumn::Strike => inst.strike.map(|s| s.to_string()).unwrap_or_default(),
+            InstrumentColumn::LotSize => inst.lot_size.to_string(),
+            InstrumentColumn::TickSize => inst.tick_size.to_string(),
+            InstrumentColumn::LastPrice => inst
+                .last_price
+                .map(|p| format!("{:.2}", p))
+                .unwrap_or_default(),
+        }
+    }
+}
+
+impl std::str::FromStr for InstrumentColumn {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let key = s.trim().to_lowercase().replace('-', "_");
+        let key = match key.as_str() {
+            "tradingsymbol" => "symbol",
+            "instrument_type" => "type",
+            "instrument_token" => "token",
+            other => other,
+        };
+        Self::ALL
+            .into_iter()
+            .find(|column| column.key() == key)
+            .ok_or_else(|| {
+                let valid: Vec<&str> = Self::ALL.iter().map(|c| c.key()).collect();
+                anyhow::anyhow!(
+                    "Unknown column '{}'. Valid columns: {}",
+                    s.trim(),
+                    valid.join(", ")
+                )
+            })
+    }
+}
+
+/// Parse a --columns list, falling back to the default columns
+pub fn parse_columns(spec: Option<&str>) -> Result<Vec<InstrumentColumn>> {
+    let Some(spec) = spec.filter(|s| !s.trim().is_empty()) else {
+        return Ok(InstrumentColumn::DEFAULT.to_vec());
+    };
+    spec.split(',')
+        .filter(|name| !name.trim().is_empty())
+        .map(str::parse)
+        .collect()
+}
+
+fn print_instruments_table(
+    instruments: &[Instrument],
+    columns: &[InstrumentColumn],
+    max_rows: Option<usize>,
+) -> Result<()> {
+    use comfy_table::{Cell, ContentArrangement, Table};
+
+    let mut out = output::writer();
+
+    let mut table = Table::new();
+    table.set_header(output::header(columns.iter().map(|c| c.header()).collect::<Vec<_>>()));
+
+    let shown = max_rows.unwrap_or(instruments.len()).min(instruments.len());
+    for inst in &instruments[..shown] {
+        table.add_row(
+            columns
+                .iter()
+                .map(|c| Cell::new(c.value(inst)))
+                .collect::<Vec<_>>(),
+        );
+    }
+
+    if shown < instruments.len() {
+        writeln!(
+            out,
+            "Showing {} of {} instruments (use --limit or --all for more)",
+            shown,
+            instruments.len()
+        )?;
     }
 
     table.set_content_arrangement(ContentArrangement::Dynamic);
-    println!("{table}");
+    writeln!(out, "{table}")?;
+
+    Ok(())
 }
 
-fn print_instrument_details(inst: &Instrument) {
-    println!("Instrument: {}", inst.tradingsymbol);
-    println!();
-    println!("Name: {}", inst.name);
-    println!("Exchange: {:?}", inst.exchange);
-    println!("Segment: {:?}", inst.segment);
-    println!("Type: {:?}", inst.instrument_type);
-    println!("Lot Size: {}", inst.lot_size);
-    println!("Tick Size: {}", inst.tick_size);
+fn print_instrument_details(inst: &Instrument) -> Result<()> {
+    let mut out = output::writer();
+
+    writeln!(out, "Instrument: {}", inst.tradingsymbol)?;
+    writeln!(out)?;
+    writeln!(out, "Name: {}", inst.name)?;
+    writeln!(out, "Exchange: {:?}", inst.exchange)?;
+    writeln!(out, "Segment: {:?}", inst.segment)?;
+    writeln!(out, "Type: {:?}", inst.instrument_type)?;
+    writeln!(out, "Lot Size: {}", inst.lot_size)?;
+    writeln!(out, "Tick Size: {}", inst.tick_size)?;
 
     if let Some(expiry) = &inst.expiry {
-        println!("Expiry: {}", expiry);
+        writeln!(out, "Expiry: {}", expiry)?;
     }
 
     if let Some(strike) = inst.strike {
-        println!("Strike Price: {}", strike);
+        writeln!(out, "Strike Price: {}", strike)?;
     }
 
     if let Some(last_price) = inst.last_price {
-        println!("Last Price: ₹{:.2}", last_price);
+        writeln!(out, "Last Price: ₹{:.2}", last_price)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_columns() {
+        assert_eq!(parse_columns(None).unwrap(), InstrumentColumn::DEFAULT.to_vec());
+        assert_eq!(
+            parse_columns(Some("symbol, Lot_Size,tick-size,instrument_token")).unwrap(),
+            vec![
+                InstrumentColumn::Symbol,
+                InstrumentColumn::LotSize,
+                InstrumentColumn::TickSize,
+                InstrumentColumn::Token,
+            ]
+        );
+
+        let err = parse_columns(Some("symbol,isin")).unwrap_err().to_string();
+        assert!(err.contains("Unknown column 'isin'"), "{}", err);
+        assert!(err.contains("lot_size"));
     }
 }
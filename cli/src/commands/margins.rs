@@ -1,8 +1,15 @@
 //! Margins command handlers
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 use serde_json;
-use zerodha_cli_core::api::KiteConnectClient;
+use std::io::Write;
+use zerodha_cli_core::{
+    api::KiteConnectClient,
+    margins::BuyingPower,
+    models::{BasketMargins, PlaceOrder},
+    output::{self, format_inr},
+    validation::parse_symbol,
+};
 
 use super::MarginsCommands;
 
@@ -17,6 +24,12 @@ pub async fn run_margins(
         super::MarginsSubcommands::Commodity => {
             run_margins_commodity(output_format, api_client).await
         }
+        super::MarginsSubcommands::Basket { file } => {
+            run_margins_basket(&file, output_format, api_client).await
+        }
+        super::MarginsSubcommands::BuyingPower { symbol, product } => {
+            run_margins_buying_power(&symbol, &product, output_format, api_client).await
+        }
     }
 }
 
@@ -24,9 +37,13 @@ pub async fn run_margins_list(output_format: &str, api_client: &KiteConnectClien
     let margins = api_client.get_margins().await?;
 
     if output_format == "json" {
-        println!("{}", serde_json::to_string_pretty(&margins)?);
+        output::emit(output::to_json(&margins)?)?;
+    } else if output_format == "ndjson" {
+        output::emit(serde_json::to_string(&margins)?)?;
+    } else if output_format == "yaml" {
+        output::write_yaml(&margins, output::writer())?;
     } else {
-        print_margins(&margins);
+        print_margins(&margins)?;
     }
 
     Ok(())
@@ -36,11 +53,15 @@ pub async fn run_margins_equity(output_format: &str, api_client: &KiteConnectCli
     let equity = api_client.get_equity_margins().await?;
 
     if output_format == "json" {
-        println!("{}", serde_json::to_string_pretty(&equity)?);
+        output::emit(output::to_json(&equity)?)?;
+    } else if output_format == "ndjson" {
+        output::emit(serde_json::to_string(&equity)?)?;
+    } else if output_format == "yaml" {
+        output::write_yaml(&equity, output::writer())?;
     } else {
         match &equity.equity {
-            Some(margin) => print_equity_margins(margin),
-            None => println!("No equity margin data available"),
+            Some(margin) => print_equity_margins(margin)?,
+            None => output::notice("No equity margin data available"),
         }
     }
 
@@ -54,22 +75,170 @@ pub async fn run_margins_commodity(
     let commodity = api_client.get_commodity_margins().await?;
 
     if output_format == "json" {
-        println!("{}", serde_json::to_string_pretty(&commodity)?);
+        output::emit(output::to_json(&commodity)?)?;
+    } else if output_format == "ndjson" {
+        output::emit(serde_json::to_string(&commodity)?)?;
+    } else if output_format == "yaml" {
+        output::write_yaml(&commodity, output::writer())?;
     } else {
         match &commodity.commodity {
-            Some(margin) => print_commodity_margins(margin),
-            None => println!("No commodity margin data available"),
+            Some(margin) => print_commodity_margins(margin)?,
+            None => output::notice("No commodity margin data available"),
         }
     }
 
     Ok(())
 }
 
-fn print_margins(margins: &zerodha_cli_core::models::MarginResponse) {
+pub async fn run_margins_basket(
+    file: &str,
+    output_format: &str,
+    api_client: &KiteConnectClient,
+) -> Result<()> {
+    let content = std::fs::read_to_string(file)
+        .with_context(|| format!("Failed to read basket file {}", file))?;
+    let orders = PlaceOrder::parse_basket(&content)
+        .with_context(|| format!("Failed to parse basket file {}", file))?;
+
+    let margins = api_client.get_basket_margins(&orders).await?;
+
+    if matches!(output_format, "json" | "ndjson" | "yaml") {
+        let mut value = serde_json::to_value(&margins)?;
+        value["benefit"] = serde_json::json!(margins.benefit());
+        match output_format {
+            "json" => output::emit(output::to_json(&value)?)?,
+            "ndjson" => output::emit(serde_json::to_string(&value)?)?,
+            _ => output::write_yaml(&value, output::writer())?,
+        }
+    } else {
+        print_basket_margins(&margins)?;
+    }
+
+    Ok(())
+}
+
+pub async fn run_margins_buying_power(
+    symbol: &str,
+    product: &str,
+    output_format: &str,
+    api_client: &KiteConnectClient,
+) -> Result<()> {
+    let (exchange, tradingsymbol) = parse_symbol(symbol)?;
+    let product = super::orders::parse_product(product)?;
+    let key = format!("{}:{}", exchange, tradingsymbol);
+
+    let equity = api_client.get_equity_margins().await?;
+    let available = equity
+        .equity
+        .map(|margin| margin.net)
+        .context("No equity margin data available")?;
+    let last_price = api_client
+        .get_ltp(&[key.as_str()])
+        .await?
+        .data
+        .get(&key)
+        .map(|ltp| ltp.last_price)
+        .with_context(|| format!("No last price for {}", key))?;
+
+    let estimate = BuyingPower::estimate(&key, &product, available, last_price);
+
+    match output_format {
+        "json" => output::emit(output::to_json(&estimate)?)?,
+        "ndjson" => output::emit(serde_json::to_string(&estimate)?)?,
+        "yaml" => output::write_yaml(&estimate, output::writer())?,
+        _ => print_buying_power(&estimate)?,
+    }
+
+    Ok(())
+}
+
+fn print_buying_power(estimate: &BuyingPower) -> Result<()> {
     use comfy_table::{Cell, ContentArrangement, Table};
 
+    let mut out = output::writer();
+
     let mut table = Table::new();
-    table.set_header(vec!["Segment", "Net", "Available", "Used"]);
+    table.set_header(output::header(vec!["Field", "Value"]));
+    table.add_row(vec![Cell::new("Symbol"), Cell::new(&estimate.symbol)]);
+    table.add_row(vec![Cell::new("Product"), Cell::new(&estimate.product)]);
+    table.add_row(vec![
+        Cell::new("Available"),
+        Cell::new(format_inr(estimate.available)),
+    ]);
+    table.add_row(vec![
+        Cell::new("Leverage"),
+        Cell::new(format!("{}x", estimate.leverage)),
+    ]);
+    table.add_row(vec![
+        Cell::new("Buying Power"),
+        Cell::new(format_inr(estimate.buying_power)),
+    ]);
+    table.add_row(vec![
+        Cell::new("Last Price"),
+        Cell::new(format_inr(estimate.last_price)),
+    ]);
+    table.add_row(vec![
+        Cell::new("Max Quantity"),
+        Cell::new(format!("~{}", estimate.max_quantity)),
+    ]);
+
+    table.set_content_arrangement(ContentArrangement::Dynamic);
+    writeln!(out, "{table}")?;
+    writeln!(
+        out,
+        "Estimate only: Kite's margins vary by stock and change with volatility."
+    )?;
+
+    Ok(())
+}
+
+fn print_basket_margins(margins: &BasketMargins) -> Result<()> {
+    use comfy_table::{Cell, CellAlignment, ContentArrangement, Table};
+
+    let mut out = output::writer();
+    let amount = |value: f64| Cell::new(format_inr(value)).set_alignment(CellAlignment::Right);
+
+    let mut table = Table::new();
+    table.set_header(output::header(vec!["Symbol", "SPAN", "Exposure", "Premium", "Total"]));
+
+    for order in &margins.orders {
+        table.add_row(vec![
+            Cell::new(order.tradingsymbol.as_deref().unwrap_or("-")),
+            amount(order.span),
+            amount(order.exposure),
+            amount(order.option_premium),
+            amount(order.total),
+        ]);
+    }
+
+    table.set_content_arrangement(ContentArrangement::Dynamic);
+    writeln!(out, "{table}")?;
+    writeln!(out)?;
+    writeln!(out, "Margin without hedging: {}", format_inr(margins.initial.total))?;
+    writeln!(out, "Total required:         {}", format_inr(margins.final_margin.total))?;
+
+    let benefit = margins.benefit();
+    if margins.initial.total > 0.0 {
+        writeln!(
+            out,
+            "Hedge benefit:          {} ({:.1}%)",
+            format_inr(benefit),
+            benefit / margins.initial.total * 100.0
+        )?;
+    } else {
+        writeln!(out, "Hedge benefit:          {}", format_inr(benefit))?;
+    }
+
+    Ok(())
+}
+
+fn print_margins(margins: &zerodha_cli_core::models::MarginResponse) -> Result<()> {
+    use comfy_table::{Cell, ContentArrangement, Table};
+
+    let mut out = output::writer();
+
+    let mut table = Table::new();
+    table.set_header(output::header(vec!["Segment", "Net", "Available", "Used"]));
 
     if let Some(ref equity) = margins.equity {
         let equity_avail = equity.available.cash
@@ -81,9 +250,9 @@ fn print_margins(margins: &zerodha_cli_core::models::MarginResponse) {
 
         table.add_row(vec![
             Cell::new("Equity"),
-            Cell::new(format!("₹{:.2}", equity.net)),
-            Cell::new(format!("₹{:.2}", equity_avail)),
-            Cell::new(format!("₹{:.2}", equity_used)),
+            Cell::new(format_inr(equity.net)),
+            Cell::new(format_inr(equity_avail)),
+            Cell::new(format_inr(equity_used)),
         ]);
     }
 
@@ -97,130 +266,140 @@ fn print_margins(margins: &zerodha_cli_core::models::MarginResponse) {
 
         table.add_row(vec![
             Cell::new("Commodity"),
-            Cell::new(format!("₹{:.2}", commodity.net)),
-            Cell::new(format!("₹{:.2}", commodity_avail)),
-            Cell::new(format!("₹{:.2}", commodity_used)),
+            Cell::new(format_inr(commodity.net)),
+            Cell::new(format_inr(commodity_avail)),
+            Cell::new(format_inr(commodity_used)),
         ]);
     }
 
     table.set_content_arrangement(ContentArrangement::Dynamic);
-    println!("{table}");
+    writeln!(out, "{table}")?;
+
+    Ok(())
 }
 
-fn print_equity_margins(margin: &zerodha_cli_core::models::Margin) {
+fn print_equity_margins(margin: &zerodha_cli_core::models::Margin) -> Result<()> {
     use comfy_table::{Cell, ContentArrangement, Table};
 
+    let mut out = output::writer();
+
     let available =
         margin.available.cash + margin.available.collateral + margin.available.live_balance;
     let used = margin.utilised.debits + margin.utilised.exposure + margin.utilised.options_premium;
 
     let mut table = Table::new();
-    table.set_header(vec!["Field", "Amount"]);
+    table.set_header(output::header(vec!["Field", "Amount"]));
 
     table.add_row(vec![
         Cell::new("Net"),
-        Cell::new(format!("₹{:.2}", margin.net)),
+        Cell::new(format_inr(margin.net)),
     ]);
     table.add_row(vec![
         Cell::new("Available"),
-        Cell::new(format!("₹{:.2}", available)),
+        Cell::new(format_inr(available)),
     ]);
-    table.add_row(vec![Cell::new("Used"), Cell::new(format!("₹{:.2}", used))]);
+    table.add_row(vec![Cell::new("Used"), Cell::new(format_inr(used))]);
 
     table.add_row(vec![Cell::new(""), Cell::new("".to_string())]);
     table.add_row(vec![
         Cell::new("Cash"),
-        Cell::new(format!("₹{:.2}", margin.available.cash)),
+        Cell::new(format_inr(margin.available.cash)),
     ]);
     table.add_row(vec![
         Cell::new("Opening Balance"),
-        Cell::new(format!("₹{:.2}", margin.available.opening_balance)),
+        Cell::new(format_inr(margin.available.opening_balance)),
     ]);
     table.add_row(vec![
         Cell::new("Live Balance"),
-        Cell::new(format!("₹{:.2}", margin.available.live_balance)),
+        Cell::new(format_inr(margin.available.live_balance)),
     ]);
     table.add_row(vec![
         Cell::new("Collateral"),
-        Cell::new(format!("₹{:.2}", margin.available.collateral)),
+        Cell::new(format_inr(margin.available.collateral)),
     ]);
 
     table.add_row(vec![Cell::new(""), Cell::new("".to_string())]);
     table.add_row(vec![
         Cell::new("Debits"),
-        Cell::new(format!("₹{:.2}", margin.utilised.debits)),
+        Cell::new(format_inr(margin.utilised.debits)),
     ]);
     table.add_row(vec![
         Cell::new("Exposure"),
-        Cell::new(format!("₹{:.2}", margin.utilised.exposure)),
+        Cell::new(format_inr(margin.utilised.exposure)),
     ]);
     table.add_row(vec![
         Cell::new("Options Premium"),
-        Cell::new(format!("₹{:.2}", margin.utilised.options_premium)),
+        Cell::new(format_inr(margin.utilised.options_premium)),
     ]);
 
     table.set_content_arrangement(ContentArrangement::Dynamic);
-    println!("Equity Margins");
-    println!("{table}");
+    writeln!(out, "Equity Margins")?;
+    writeln!(out, "{table}")?;
+
+    Ok(())
 }
 
-fn print_commodity_margins(margin: &zerodha_cli_core::models::Margin) {
+fn print_commodity_margins(margin: &zerodha_cli_core::models::Margin) -> Result<()> {
     use comfy_table::{Cell, ContentArrangement, Table};
 
+    let mut out = output::writer();
+
     let available =
         margin.available.cash + margin.available.collateral + margin.available.live_balance;
     let used = margin.utilised.debits + margin.utilised.exposure + margin.utilised.options_premium;
 
     let mut table = Table::new();
-    table.set_header(vec!["Field", "Amount"]);
+    table.set_header(output::header(vec!["Field", "Amount"]));
 
     table.add_row(vec![
         Cell::new("Net"),
-        Cell::new(format!("₹{:.2}", margin.net)),
+        Cell::new(format_inr(margin.net)),
     ]);
     table.add_row(vec![
         Cell::new("Available"),
-        Cell::new(format!("₹{:.2}", available)),
+        Cell::new(format_inr(available)),
     ]);
-    table.add_row(vec![Cell::new("Used"), Cell::new(format!("₹{:.2}", used))]);
+    table.add_row(vec![Cell::new("Used"), Cell::new(format_inr(used))]);
 
     table.add_row(vec![Cell::new(""), Cell::new("".to_string())]);
     table.add_row(vec![
         Cell::new("Cash"),
-        Cell::new(format!("₹{:.2}", margin.available.cash)),
+        Cell::new(format_inr(margin.available.cash)),
     ]);
     table.add_row(vec![
         Cell::new("Opening Balance"),
-        Cell::new(format!("₹{:.2}", margin.available.opening_balance)),
+        Cell::new(format_inr(margin.available.opening_balance)),
     ]);
     table.add_row(vec![
         Cell::new("Live Balance"),
-        Cell::new(format!("₹{:.2}", margin.available.live_balance)),
+        Cell::new(format_inr(margin.available.live_balance)),
     ]);
     table.add_row(vec![
         Cell::new("Collateral"),
-        Cell::new(format!("₹{:.2}", margin.available.collateral)),
+        Cell::new(format_inr(margin.available.collateral)),
     ]);
 
     table.add_row(vec![Cell::new(""), Cell::new("".to_string())]);
     table.add_row(vec![
         Cell::new("Debits"),
-        Cell::new(format!("₹{:.2}", margin.utilised.debits)),
+        Cell::new(format_inr(margin.utilised.debits)),
     ]);
     table.add_row(vec![
         Cell::new("Exposure"),
-        Cell::new(format!("₹{:.2}", margin.utilised.exposure)),
+        Cell::new(format_inr(margin.utilised.exposure)),
     ]);
     table.add_row(vec![
         Cell::new("Options Premium"),
-        Cell::new(format!("₹{:.2}", margin.utilised.options_premium)),
+        Cell::new(format_inr(margin.utilised.options_premium)),
     ]);
     table.add_row(vec![
         Cell::new("Span"),
-        Cell::new(format!("₹{:.2}", margin.utilised.span)),
+        Cell::new(format_inr(margin.utilised.span)),
     ]);
 
     table.set_content_arrangement(ContentArrangement::Dynamic);
-    println!("Commodity Margins");
-    println!("{table}");
+    writeln!(out, "Commodity Margins")?;
+    writeln!(out, "{table}")?;
+
+    Ok(())
 }
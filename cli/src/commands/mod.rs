@@ -1,46 +1,133 @@
 //! CLI command definitions and routing
 
+mod alerts;
 mod auth;
+mod batch;
+mod cache;
+mod completions;
 mod gtt;
 mod instruments;
 mod margins;
 mod orders;
 mod portfolio;
 mod quotes;
+mod schema;
 mod shell;
+mod shell_helper;
 mod status;
+mod watch;
 
 use anyhow::{Context, Result};
 use clap::{Parser, Subcommand};
 use std::sync::Arc;
-use zerodha_cli_core::{api::KiteConnectClient, config::Config};
+use std::time::Duration;
+use zerodha_cli_core::{
+    api::{CircuitBreakerPolicy, HttpOptions, KiteConnectClient, RecordingMode, RetryPolicy},
+    config::{load_env_file, ApiConfig, Config},
+    error::{exit_code, ZerodhaError},
+    output::{self, OutputFormat},
+};
 
 #[derive(Parser)]
 #[command(name = "kite")]
 #[command(
     about = "Zerodha Kite Connect CLI",
-    long_about = "A terminal-based trading tool for Zerodha's Kite Connect API"
+    long_about = "A terminal-based trading tool for Zerodha's Kite Connect API",
+    after_long_help = "Exit codes:\n  \
+        0  success\n  \
+        1  generic failure\n  \
+        2  authentication (missing credentials, expired token, no permission)\n  \
+        3  validation (invalid arguments or input)\n  \
+        4  rate limit exceeded\n  \
+        5  network or exchange connectivity failure\n  \
+        6  order, margin or holding rejected by the broker"
 )]
 #[command(version = "1.0.0")]
 #[command(author = "Zerodha CLI Team")]
 pub struct Cli {
-    /// Output format (table, json)
+    /// Output format (table, json, csv, yaml, ndjson)
     #[arg(short, long, global = true, default_value = "table")]
     pub output: String,
 
-    /// Config file path
-    #[arg(short, long, global = true)]
-    pub config: Option<String>,
+    /// Write formatted output to a file instead of stdout
+    #[arg(long, global = true, value_name = "PATH")]
+    pub output_file: Option<std::path::PathBuf>,
 
-    /// Verbose output
-    #[arg(short, long, global = true)]
-    pub verbose: bool,
+    /// Config file path (defaults to ZERODHA_CONFIG, then the platform config dir)
+    #[arg(short, long, global = true, value_name = "PATH")]
+    pub config: Option<std::path::PathBuf>,
+
+    /// Read ZERODHA_* variables from this file instead of the nearest .env
+    #[arg(long, global = true, value_name = "PATH")]
+    pub env_file: Option<std::path::PathBuf>,
+
+    /// Account profile to use (defaults to the active profile)
+    #[arg(long, global = true)]
+    pub profile: Option<String>,
+
+    /// API key, overriding ZERODHA_API_KEY and the config file
+    #[arg(long, global = true, value_name = "KEY")]
+    pub api_key: Option<String>,
+
+    /// API secret, overriding ZERODHA_API_SECRET and the config file
+    #[arg(long, global = true, value_name = "SECRET")]
+    pub api_secret: Option<String>,
+
+    /// Access token, overriding ZERODHA_ACCESS_TOKEN and the config file
+    #[arg(long, global = true, value_name = "TOKEN")]
+    pub access_token: Option<String>,
+
+    /// Verbose output (-v for diagnostics, -vv for trace)
+    #[arg(short, long, global = true, action = clap::ArgAction::Count)]
+    pub verbose: u8,
+
+    /// Suppress progress messages so only results are printed
+    #[arg(long, global = true)]
+    pub quiet: bool,
+
+    /// Disable colored output (also honors NO_COLOR)
+    #[arg(long, global = true)]
+    pub no_color: bool,
+
+    /// Abbreviate large rupee amounts (e.g. ₹12.3L, ₹1.2Cr)
+    #[arg(long, global = true)]
+    pub human: bool,
+
+    /// Indent JSON output even when piped (default on a terminal)
+    #[arg(long, global = true, conflicts_with = "compact")]
+    pub pretty: bool,
+
+    /// Print JSON on a single line even on a terminal (default when piped)
+    #[arg(long, global = true)]
+    pub compact: bool,
+
+    /// Print one line per record with {field} filled from its JSON fields,
+    /// e.g. "{tradingsymbol} {last_price} {pnl}" (holdings, positions, orders,
+    /// trades and instruments)
+    #[arg(long, global = true, alias = "output-template", value_name = "TEMPLATE")]
+    pub template: Option<String>,
+
+    /// Show what order, GTT and position changes would do without sending them
+    #[arg(long, global = true)]
+    pub dry_run: bool,
+
+    /// Answer yes to every confirmation prompt
+    #[arg(short, long, global = true, visible_alias = "force")]
+    pub yes: bool,
+
+    /// Clear the screen and re-run a read-only command until Ctrl+C
+    #[arg(long, global = true, conflicts_with = "output_file")]
+    pub watch: bool,
+
+    /// Seconds between runs with --watch
+    #[arg(long, global = true, value_name = "SECS", default_value_t = 5, requires = "watch")]
+    pub interval: u64,
 
     #[command(subcommand)]
     pub command: Commands,
 }
 
-#[derive(Subcommand)]
+#[derive(Subcommand, Clone)]
 pub enum Commands {
     /// Authentication management
     Auth(AuthCommands),
@@ -63,24 +150,69 @@ pub enum Commands {
     /// Good Till Triggered orders
     Gtt(GttCommands),
 
+    /// Manage the local instrument cache
+    Cache(CacheCommands),
+
+    /// Price alerts on last traded prices
+    Alerts(AlertsCommands),
+
     /// Show system status
-    Status,
+    Status {
+        #[command(subcommand)]
+        command: Option<StatusSubcommands>,
+    },
 
-    /// Interactive REPL mode
-    Shell,
+    /// Interactive REPL mode, or run a script of shell commands
+    Shell {
+        /// Run commands from this file instead of prompting
+        #[arg(long, value_name = "PATH")]
+        file: Option<std::path::PathBuf>,
+
+        /// Keep running the script after a command fails
+        #[arg(long, requires = "file")]
+        continue_on_error: bool,
+
+        /// Track per-command usage and add it to the saved totals on exit
+        #[arg(long, conflicts_with = "file")]
+        stats: bool,
+    },
+
+    /// Run a JSON array of commands and print a JSON array of results
+    Batch {
+        /// File with the commands, or `-` for stdin
+        #[arg(default_value = "-")]
+        input: String,
+    },
+
+    /// Print the JSON Schema of a model in `-o json` output (lists models when omitted)
+    Schema {
+        /// Model name, e.g. Order, Holding, Position, Quote, GTTTrigger
+        model: Option<String>,
+    },
+
+    /// Print a shell completion script (e.g. `kite completions zsh > ~/.zfunc/_kite`)
+    Completions {
+        /// Shell to generate completions for
+        #[arg(value_enum)]
+        shell: clap_complete::Shell,
+    },
 }
 
-#[derive(clap::Args, Debug)]
+#[derive(clap::Args, Debug, Clone)]
 pub struct AuthCommands {
     /// Authenticate with Zerodha (OAuth flow)
     #[command(subcommand)]
     pub command: AuthSubcommands,
 }
 
-#[derive(Subcommand, Debug)]
+#[derive(Subcommand, Debug, Clone)]
 pub enum AuthSubcommands {
     /// Authenticate with Zerodha (OAuth flow)
-    Login,
+    Login {
+        /// Capture the request token from a redirect to http://127.0.0.1:<PORT>/
+        #[arg(long)]
+        port: Option<u16>,
+    },
 
     /// Show authentication status
     Status,
@@ -98,15 +230,31 @@ pub enum AuthSubcommands {
         #[arg(long)]
         api_secret: String,
     },
+
+    /// List configured profiles
+    Profiles,
+
+    /// Set the active profile
+    Use {
+        /// Profile name
+        name: String,
+    },
+
+    /// Check an order postback's checksum against the configured API secret
+    VerifyPostback {
+        /// Postback JSON body (`-` for stdin)
+        #[arg(long)]
+        payload: String,
+    },
 }
 
-#[derive(clap::Args, Debug)]
+#[derive(clap::Args, Debug, Clone)]
 pub struct InstrumentsCommands {
     #[command(subcommand)]
     pub command: InstrumentsSubcommands,
 }
 
-#[derive(Subcommand, Debug)]
+#[derive(Subcommand, Debug, Clone)]
 pub enum InstrumentsSubcommands {
     /// List all instruments from exchange
     List {
@@ -117,6 +265,33 @@ pub enum InstrumentsSubcommands {
         /// Refresh cache (re-download instruments)
         #[arg(short, long)]
         refresh: bool,
+
+        /// Table columns, comma-separated (e.g. symbol,name,lot_size,tick_size)
+        #[arg(long, value_name = "COLUMNS")]
+        columns: Option<String>,
+
+        /// Maximum rows shown in the table
+        #[arg(long, default_value = "50", conflicts_with = "all")]
+        limit: usize,
+
+        /// Show every row in the table
+        #[arg(long)]
+        all: bool,
+
+        /// Only this instrument type (CE, PE, FUT, EQ)
+        #[arg(long = "type", value_name = "TYPE")]
+        instrument_type: Option<String>,
+
+        /// Only contracts expiring on this date (YYYY-MM-DD)
+        #[arg(long)]
+        expiry: Option<String>,
+
+        /// Only options with this strike price
+        #[arg(long)]
+        strike: Option<f64>,
+
+        #[command(flatten)]
+        page: PageArgs,
     },
 
     /// Search for instrument by symbol or name
@@ -127,6 +302,22 @@ pub enum InstrumentsSubcommands {
         /// Exchange filter
         #[arg(short, long)]
         exchange: Option<String>,
+
+        /// Search every cached exchange (e.g. both NFO and BFO)
+        #[arg(long, alias = "exchange-all", conflicts_with = "exchange")]
+        all_exchanges: bool,
+
+        /// Maximum number of results
+        #[arg(long, default_value = "20")]
+        limit: usize,
+
+        /// Plain substring matching instead of fuzzy search
+        #[arg(long)]
+        exact: bool,
+
+        /// Table columns, comma-separated (e.g. symbol,name,lot_size,tick_size)
+        #[arg(long, value_name = "COLUMNS")]
+        columns: Option<String>,
     },
 
     /// Get detailed info for specific instrument
@@ -134,20 +325,57 @@ pub enum InstrumentsSubcommands {
         /// Instrument symbol (e.g., NSE:INFY)
         symbol: String,
     },
+
+    /// Find a cached instrument by its numeric instrument token
+    Token {
+        /// Instrument token (e.g., 408065)
+        token: u64,
+    },
+
+    /// Re-download instruments into the cache
+    Refresh {
+        /// Exchange to refresh (defaults to NSE)
+        #[arg(short, long, conflicts_with = "all")]
+        exchange: Option<String>,
+
+        /// Refresh every exchange in parallel
+        #[arg(long)]
+        all: bool,
+    },
+
+    /// Show the option chain for an underlying
+    Chain {
+        /// Underlying symbol (e.g., NSE:NIFTY)
+        symbol: String,
+
+        /// Expiry date (YYYY-MM-DD); defaults to the nearest expiry
+        #[arg(long)]
+        expiry: Option<String>,
+    },
 }
 
-#[derive(clap::Args, Debug)]
+#[derive(clap::Args, Debug, Clone)]
 pub struct QuotesCommands {
     #[command(subcommand)]
     pub command: QuotesSubcommands,
 }
 
-#[derive(Subcommand, Debug)]
+#[derive(Subcommand, Debug, Clone)]
 pub enum QuotesSubcommands {
     /// Get full quote for one or more instruments
     Get {
         /// Instrument symbols (e.g., NSE:INFY NSE:TCS)
         symbols: Vec<String>,
+
+        /// Market depth levels to show per side (0 hides depth)
+        #[arg(long, default_value_t = 5)]
+        depth_levels: usize,
+    },
+
+    /// One compact line per symbol: LTP, day change, high/low and volume
+    Snap {
+        /// Instrument symbols
+        symbols: Vec<String>,
     },
 
     /// Get OHLC data only
@@ -163,19 +391,34 @@ pub enum QuotesSubcommands {
     },
 }
 
-#[derive(clap::Args, Debug)]
+#[derive(clap::Args, Debug, Clone)]
 pub struct OrdersCommands {
     #[command(subcommand)]
     pub command: OrdersSubcommands,
 }
 
-#[derive(Subcommand, Debug)]
+#[derive(Subcommand, Debug, Clone)]
 pub enum OrdersSubcommands {
     /// List all orders
     List {
         /// Filter by status
         #[arg(short, long)]
         status: Option<String>,
+
+        /// Filter by order tag
+        #[arg(long)]
+        tag: Option<String>,
+
+        /// Only orders placed at or after this time (HH:MM or YYYY-MM-DD HH:MM, IST)
+        #[arg(long)]
+        since: Option<String>,
+
+        /// Only orders placed at or before this time (HH:MM or YYYY-MM-DD HH:MM, IST)
+        #[arg(long)]
+        until: Option<String>,
+
+        #[command(flatten)]
+        page: PageArgs,
     },
 
     /// Get details for specific order
@@ -184,19 +427,27 @@ pub enum OrdersSubcommands {
         order_id: String,
     },
 
+    /// Place an order interactively, prompting for each field
+    New {
+        /// Place the order even if it exceeds [risk] max_order_value
+        #[arg(long)]
+        override_risk: bool,
+    },
+
     /// Place a limit order
     Place {
         /// Instrument symbol (e.g., NSE:INFY)
         #[arg(short, long)]
         symbol: String,
 
-        /// Transaction type (BUY, SELL)
-        #[arg(short, long)]
-        order_type: String,
+        /// Side: BUY or SELL (--order-type is a deprecated alias)
+        #[arg(long, alias = "order-type")]
+        side: String,
 
-        /// Order type (MARKET, LIMIT, SL, SL-M)
-        #[arg(long)]
-        order_type_enum: Option<String>,
+        /// Order type: MARKET, LIMIT, SL or SL-M (default: [defaults] order_type;
+        /// --order-type-enum is a deprecated alias)
+        #[arg(long = "type", alias = "order-type-enum")]
+        order_type: Option<String>,
 
         /// Quantity
         #[arg(short, long)]
@@ -206,21 +457,57 @@ pub enum OrdersSubcommands {
         #[arg(short, long)]
         price: f64,
 
-        /// Product type (CNC, MIS, NRML)
-        #[arg(short, long)]
+        /// Trigger price (for SL and SL-M orders)
+        #[arg(long)]
+        trigger_price: Option<f64>,
+
+        /// Quantity shown in the market depth (10%-100% of --quantity)
+        #[arg(long)]
+        disclosed_quantity: Option<i32>,
+
+        /// Product type (CNC, MIS, NRML, MTF; default: [defaults] product)
+        #[arg(long)]
         product: Option<String>,
 
-        /// Validity (DAY, IOC)
-        #[arg(short, long)]
+        /// Validity (DAY, IOC, TTL; default: [defaults] validity)
+        #[arg(long)]
         validity: Option<String>,
 
-        /// Dry-run mode (don't actually place order)
+        /// Order lifetime in minutes (1-365, requires --validity TTL)
         #[arg(long)]
-        dry_run: bool,
+        ttl_minutes: Option<u32>,
+
+        /// With --dry-run, print the JSON request body that would be sent
+        #[arg(long, requires = "dry_run")]
+        show_payload: bool,
 
         /// Variety (regular, amo, co, iceberg)
         #[arg(long, default_value = "regular")]
         variety: String,
+
+        /// Order tag (alphanumeric, max 20 chars)
+        #[arg(long)]
+        tag: Option<String>,
+
+        /// Wait until the order is complete, rejected or cancelled
+        #[arg(long)]
+        wait: bool,
+
+        /// Maximum seconds to wait with --wait
+        #[arg(long, default_value_t = 60)]
+        wait_timeout: u64,
+
+        /// Round the price to the instrument's tick size instead of rejecting it
+        #[arg(long)]
+        round_tick: bool,
+
+        /// Refuse the order if it's worth more than this (overrides [risk] max_order_value)
+        #[arg(long)]
+        max_loss: Option<f64>,
+
+        /// Place the order even if it exceeds the risk limit
+        #[arg(long)]
+        override_risk: bool,
     },
 
     /// Place a market order
@@ -229,21 +516,64 @@ pub enum OrdersSubcommands {
         #[arg(short, long)]
         symbol: String,
 
-        /// Transaction type (BUY, SELL)
-        #[arg(short, long)]
-        order_type: String,
+        /// Side: BUY or SELL (--order-type is a deprecated alias)
+        #[arg(long, alias = "order-type")]
+        side: String,
 
         /// Quantity
         #[arg(short, long)]
         quantity: i32,
 
-        /// Product type
+        /// Product type (default: [defaults] product)
         #[arg(short, long)]
         product: Option<String>,
 
-        /// Dry-run mode
+        /// With --dry-run, print the JSON request body that would be sent
+        #[arg(long, requires = "dry_run")]
+        show_payload: bool,
+
+        /// Order tag (alphanumeric, max 20 chars)
+        #[arg(long)]
+        tag: Option<String>,
+
+        /// Wait until the order is complete, rejected or cancelled
+        #[arg(long)]
+        wait: bool,
+
+        /// Maximum seconds to wait with --wait
+        #[arg(long, default_value_t = 60)]
+        wait_timeout: u64,
+
+        /// Refuse the order if it's worth more than this at the last price
+        /// (overrides [risk] max_order_value)
+        #[arg(long)]
+        max_loss: Option<f64>,
+
+        /// Place the order even if it exceeds the risk limit
+        #[arg(long)]
+        override_risk: bool,
+    },
+
+    /// Place a basket of orders from a JSON file
+    Basket {
+        /// Path to a JSON array of order specs
+        file: String,
+
+        /// Stop at the first failed order
+        #[arg(long)]
+        atomic: bool,
+
+        /// Preview the combined basket margin before placing
         #[arg(long)]
-        dry_run: bool,
+        margins: bool,
+
+        /// With --dry-run, print the JSON request body that would be sent
+        #[arg(long, requires = "dry_run")]
+        show_payload: bool,
+
+        /// Place orders even if they exceed [risk] max_order_value
+        #[arg(long)]
+        override_risk: bool,
     },
 
     /// Modify an existing order
@@ -264,12 +594,16 @@ pub enum OrdersSubcommands {
         trigger_price: Option<f64>,
 
         /// New validity
-        #[arg(short, long)]
+        #[arg(long)]
         validity: Option<String>,
 
         /// New disclosed quantity
         #[arg(long)]
         disclosed_quantity: Option<i32>,
+
+        /// With --dry-run, print the JSON request body that would be sent
+        #[arg(long, requires = "dry_run")]
+        show_payload: bool,
     },
 
     /// Cancel an order
@@ -283,25 +617,131 @@ pub enum OrdersSubcommands {
     },
 
     /// Cancel all open orders
-    CancelAll,
+    CancelAll {
+        /// Only cancel orders with this product (CNC, MIS, NRML)
+        #[arg(long)]
+        product: Option<String>,
+
+        /// Only cancel orders for this symbol (e.g., NSE:INFY)
+        #[arg(long)]
+        symbol: Option<String>,
+
+        /// Only cancel orders with this transaction type (BUY, SELL)
+        #[arg(long)]
+        transaction_type: Option<String>,
+    },
 
     /// View trade history
     Trades {
         /// Order ID (optional)
         order_id: Option<String>,
+
+        /// Only trades filled at or after this time (HH:MM or YYYY-MM-DD HH:MM, IST)
+        #[arg(long)]
+        since: Option<String>,
+
+        /// Only trades filled at or before this time (HH:MM or YYYY-MM-DD HH:MM, IST)
+        #[arg(long)]
+        until: Option<String>,
+
+        #[command(flatten)]
+        page: PageArgs,
     },
+
+    /// Summarize today's orders by status and trades by symbol
+    Stats,
+}
+
+/// Fields `gtt modify` can change; at least one is required
+#[derive(clap::Args, Debug, Default, Clone)]
+pub struct GttModifyArgs {
+    /// New order price
+    #[arg(short, long)]
+    pub price: Option<f64>,
+
+    /// New trigger price
+    #[arg(short, long)]
+    pub trigger_price: Option<f64>,
+
+    /// New quantity
+    #[arg(short, long)]
+    pub quantity: Option<u32>,
+
+    /// New order type (MARKET, LIMIT)
+    #[arg(long = "type", aliases = ["order-type", "order-type-enum"])]
+    pub order_type: Option<String>,
+
+    /// New trailing stoploss
+    #[arg(long)]
+    pub trailing_stoploss: Option<f64>,
+
+    /// New stoploss
+    #[arg(long)]
+    pub stoploss: Option<f64>,
+
+    /// New squareoff (target)
+    #[arg(long)]
+    pub squareoff: Option<f64>,
+}
+
+/// `--page`/`--page-size` options for long listings
+#[derive(clap::Args, Debug, Default, Clone, Copy)]
+pub struct PageArgs {
+    /// Show only this page of results (1-based)
+    #[arg(long, value_name = "N")]
+    pub page: Option<usize>,
+
+    /// Rows per page (default 50)
+    #[arg(long, value_name = "M")]
+    pub page_size: Option<usize>,
+}
+
+impl PageArgs {
+    const DEFAULT_PAGE_SIZE: usize = 50;
+
+    /// Keep only the requested page of `rows`, returning its footer
+    ///
+    /// Without --page or --page-size all rows are kept and there is no footer.
+    pub fn apply<T: Clone>(&self, rows: Vec<T>) -> Result<(Vec<T>, Option<String>)> {
+        if self.page.is_none() && self.page_size.is_none() {
+            return Ok((rows, None));
+        }
+        let page = output::paginate(
+            &rows,
+            self.page.unwrap_or(1),
+            self.page_size.unwrap_or(Self::DEFAULT_PAGE_SIZE),
+        )?;
+        Ok((page.items.to_vec(), Some(page.footer())))
+    }
 }
 
-#[derive(clap::Args, Debug)]
+#[derive(clap::Args, Debug, Clone)]
 pub struct PortfolioCommands {
     #[command(subcommand)]
     pub command: PortfolioSubcommands,
 }
 
-#[derive(Subcommand, Debug)]
+#[derive(Subcommand, Debug, Clone)]
 pub enum PortfolioSubcommands {
     /// View holdings (long-term equity)
-    Holdings,
+    Holdings {
+        /// Realized gains CSV for a financial year (e.g. fy2023 = Apr 2023-Mar 2024)
+        #[arg(long, value_name = "FY")]
+        report: Option<String>,
+
+        /// Console tradebook CSV export covering the trades behind --report
+        #[arg(long, value_name = "FILE", requires = "report")]
+        tradebook: Option<std::path::PathBuf>,
+    },
+
+    /// Account-wide value and P&L across holdings and positions
+    Summary,
+
+    /// P&L per symbol with holdings and positions merged
+    Pnl,
+
+    /// Holdings eligible for the auction market
+    Auctions,
 
     /// View positions (intraday/F&O)
     Positions {
@@ -312,6 +752,29 @@ pub enum PortfolioSubcommands {
         /// Show day positions only
         #[arg(long)]
         day: bool,
+
+        /// Only positions with this product (MIS, NRML, CNC, MTF)
+        #[arg(long)]
+        product: Option<String>,
+    },
+
+    /// Holdings and positions side by side, grouped by product
+    All,
+
+    /// Close open positions with opposing market orders
+    #[command(visible_alias = "square-off")]
+    Squareoff {
+        /// Instrument symbol (e.g. NSE:INFY)
+        #[arg(short, long, required_unless_present = "all", conflicts_with = "all")]
+        symbol: Option<String>,
+
+        /// Square off every open position
+        #[arg(long)]
+        all: bool,
+
+        /// Place orders even if they exceed [risk] max_order_value
+        #[arg(long)]
+        override_risk: bool,
     },
 
     /// Convert position type
@@ -321,7 +784,7 @@ pub enum PortfolioSubcommands {
         symbol: String,
 
         /// Transaction type (BUY, SELL)
-        #[arg(short, long)]
+        #[arg(long)]
         order_type: String,
 
         /// Quantity
@@ -335,16 +798,20 @@ pub enum PortfolioSubcommands {
         /// To product type
         #[arg(long)]
         to: String,
+
+        /// Whether the position was opened today or carried over (day, overnight)
+        #[arg(long, default_value = "day")]
+        position_type: String,
     },
 }
 
-#[derive(clap::Args, Debug)]
+#[derive(clap::Args, Debug, Clone)]
 pub struct MarginsCommands {
     #[command(subcommand)]
     pub command: MarginsSubcommands,
 }
 
-#[derive(Subcommand, Debug)]
+#[derive(Subcommand, Debug, Clone)]
 pub enum MarginsSubcommands {
     /// View all margin segments
     List,
@@ -354,18 +821,39 @@ pub enum MarginsSubcommands {
 
     /// View commodity margins
     Commodity,
+
+    /// Combined margin for a basket of orders, including the hedge benefit
+    Basket {
+        /// JSON file with an array of orders
+        file: String,
+    },
+
+    /// Estimate how much of a symbol the available funds can buy
+    BuyingPower {
+        /// Trading symbol (e.g. NSE:INFY)
+        #[arg(short, long)]
+        symbol: String,
+
+        /// Product (CNC, MIS, NRML, MTF); MIS and MTF apply leverage
+        #[arg(long, default_value = "CNC")]
+        product: String,
+    },
 }
 
-#[derive(clap::Args, Debug)]
+#[derive(clap::Args, Debug, Clone)]
 pub struct GttCommands {
     #[command(subcommand)]
     pub command: GttSubcommands,
 }
 
-#[derive(Subcommand, Debug)]
+#[derive(Subcommand, Debug, Clone)]
 pub enum GttSubcommands {
-    /// List all GTT orders
-    List,
+    /// List GTT orders, soonest to expire first
+    List {
+        /// Filter by status (active, triggered, disabled, expired)
+        #[arg(short, long)]
+        status: Option<String>,
+    },
 
     /// Get details for specific GTT
     Get {
@@ -379,33 +867,46 @@ pub enum GttSubcommands {
         #[arg(short, long)]
         symbol: String,
 
-        /// Transaction type (BUY, SELL)
-        #[arg(short, long)]
-        order_type: String,
+        /// Side: BUY or SELL (--order-type is a deprecated alias)
+        #[arg(long, alias = "order-type")]
+        side: String,
 
         /// Quantity
         #[arg(short, long)]
         quantity: i32,
 
-        /// Order price
+        /// Order price (stoploss leg price for two-leg)
         #[arg(short, long)]
         price: f64,
 
-        /// Trigger price
+        /// Trigger price (stoploss trigger for two-leg)
         #[arg(short, long)]
         trigger_price: f64,
 
         /// Trigger type (single, two-leg)
-        #[arg(long)]
+        #[arg(long, default_value = "single")]
         trigger_type: String,
 
-        /// Order type (MARKET, LIMIT)
+        /// Target leg trigger price (two-leg only)
+        #[arg(long)]
+        target_trigger_price: Option<f64>,
+
+        /// Target leg order price (two-leg only)
         #[arg(long)]
-        order_type_enum: Option<String>,
+        target_price: Option<f64>,
+
+        /// Order type: MARKET or LIMIT (default: LIMIT; --order-type-enum is a
+        /// deprecated alias)
+        #[arg(long = "type", alias = "order-type-enum")]
+        order_type: Option<String>,
 
         /// Product type
-        #[arg(short, long)]
+        #[arg(long)]
         product: Option<String>,
+
+        /// With --dry-run, print the JSON request body that would be sent
+        #[arg(long, requires = "dry_run")]
+        show_payload: bool,
     },
 
     /// Modify an existing GTT
@@ -413,13 +914,8 @@ pub enum GttSubcommands {
         /// Trigger ID
         trigger_id: String,
 
-        /// New order price
-        #[arg(short, long)]
-        price: Option<f64>,
-
-        /// New trigger price
-        #[arg(short, long)]
-        trigger_price: Option<f64>,
+        #[command(flatten)]
+        changes: GttModifyArgs,
     },
 
     /// Delete a GTT order
@@ -427,62 +923,554 @@ pub enum GttSubcommands {
         /// Trigger ID
         trigger_id: String,
     },
+
+    /// Delete every GTT, or only those with a status, after one confirmation
+    DeleteAll {
+        /// Only delete GTTs with this status (active, triggered, disabled, expired)
+        #[arg(short, long)]
+        status: Option<String>,
+    },
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum StatusSubcommands {
+    /// Show whether equity and F&O markets are open (IST)
+    Market,
+}
+
+#[derive(clap::Args, Debug, Clone)]
+pub struct CacheCommands {
+    #[command(subcommand)]
+    pub command: CacheSubcommands,
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum CacheSubcommands {
+    /// Delete all cached instrument files
+    Clear,
+
+    /// Show cached exchanges, sizes and freshness
+    Info,
+
+    /// Print the cache directory
+    Path,
+}
+
+#[derive(clap::Args, Debug, Clone)]
+pub struct AlertsCommands {
+    #[command(subcommand)]
+    pub command: AlertsSubcommands,
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum AlertsSubcommands {
+    /// List saved alerts
+    List,
+
+    /// Save an alert to alerts.toml
+    Add {
+        /// Instrument symbol (e.g., NSE:INFY)
+        #[arg(short, long)]
+        symbol: String,
+
+        /// Fire when the LTP reaches or rises above this price
+        #[arg(long)]
+        above: Option<f64>,
+
+        /// Fire when the LTP reaches or falls below this price
+        #[arg(long)]
+        below: Option<f64>,
+
+        /// Shell command to run when the alert fires
+        #[arg(long, value_name = "CMD")]
+        exec: Option<String>,
+    },
+
+    /// Remove a saved alert
+    Rm {
+        /// Alert ID (see 'kite alerts list')
+        id: u32,
+    },
+
+    /// Poll prices and report threshold crossings (saved alerts unless --symbol is given)
+    Watch {
+        /// Watch a single symbol instead of the saved alerts
+        #[arg(short, long)]
+        symbol: Option<String>,
+
+        /// Fire when the LTP reaches or rises above this price
+        #[arg(long, requires = "symbol")]
+        above: Option<f64>,
+
+        /// Fire when the LTP reaches or falls below this price
+        #[arg(long, requires = "symbol")]
+        below: Option<f64>,
+
+        /// Shell command to run when the alert fires
+        #[arg(long, value_name = "CMD", requires = "symbol")]
+        exec: Option<String>,
+
+        /// Seconds between price checks
+        #[arg(long, default_value_t = 5)]
+        interval: u64,
+
+        /// Exit after the first alert fires
+        #[arg(long)]
+        once: bool,
+    },
+}
+
+/// Send our own logs to stderr at the level chosen by `-v`; other crates stay at warn
+fn init_logging(verbose: u8, no_color: bool) {
+    use std::io::IsTerminal;
+    use tracing_subscriber::filter::{LevelFilter, Targets};
+    use tracing_subscriber::prelude::*;
+
+    let level = match verbose {
+        0 => LevelFilter::WARN,
+        1 => LevelFilter::DEBUG,
+        _ => LevelFilter::TRACE,
+    };
+    let filter = Targets::new()
+        .with_target("zerodha_cli_core", level)
+        .with_target("zerodha_cli", level)
+        .with_default(LevelFilter::WARN);
+
+    tracing_subscriber::registry()
+        .with(
+            tracing_subscriber::fmt::layer()
+                .with_writer(std::io::stderr)
+                .with_ansi(!no_color && std::io::stderr().is_terminal()),
+        )
+        .with(filter)
+        .init();
+}
+
+/// Ask a yes/no question on stderr and read the answer from stdin;
+/// `assume_yes` answers yes without asking
+///
+/// Fails when stdin is already at its end, so a closed or consumed stdin is
+/// never taken as "no".
+pub(crate) fn confirm_or_prompt(message: &str, assume_yes: bool) -> Result<bool> {
+    use std::io::Write;
+
+    if assume_yes {
+        return Ok(true);
+    }
+    eprint!("{} [y/N]: ", message);
+    std::io::stderr().flush()?;
+    let mut input = String::new();
+    if std::io::stdin().read_line(&mut input)? == 0 {
+        eprintln!();
+        return Err(ZerodhaError::Validation(
+            "No answer to the confirmation prompt; pass --yes to confirm".to_string(),
+        )
+        .into());
+    }
+    Ok(is_yes(&input))
+}
+
+fn is_yes(input: &str) -> bool {
+    matches!(input.trim().to_lowercase().as_str(), "y" | "yes")
+}
+
+/// Print the JSON body a request would be sent with
+///
+/// Request structs never carry credentials; those travel in the auth header.
+pub(crate) fn print_request_body<T: serde::Serialize>(request: &T) -> Result<()> {
+    eprintln!("Request body:");
+    eprintln!("{}", serde_json::to_string_pretty(request)?);
+    Ok(())
+}
+
+/// Transport settings from `[api]`, falling back to the client defaults
+fn http_options(api: &ApiConfig) -> HttpOptions {
+    let defaults = HttpOptions::default();
+    HttpOptions {
+        timeout: api.timeout_secs.map_or(defaults.timeout, Duration::from_secs),
+        connect_timeout: api
+            .connect_timeout_secs
+            .map_or(defaults.connect_timeout, Duration::from_secs),
+        proxy: api.proxy.clone().filter(|url| !url.is_empty()),
+        proxy_auth: api.proxy_username.clone().map(|username| {
+            (username, api.proxy_password.clone().unwrap_or_default())
+        }),
+        ca_cert: api
+            .ca_cert
+            .as_deref()
+            .filter(|path| !path.is_empty())
+            .map(std::path::PathBuf::from),
+        accept_invalid_certs: api.danger_accept_invalid_certs.unwrap_or(false),
+    }
+}
+
+/// Circuit breaker thresholds from `[api]`, falling back to the client defaults
+fn circuit_breaker_policy(api: &ApiConfig) -> CircuitBreakerPolicy {
+    let defaults = CircuitBreakerPolicy::default();
+    CircuitBreakerPolicy {
+        failure_threshold: api
+            .circuit_breaker_threshold
+            .unwrap_or(defaults.failure_threshold),
+        window: api
+            .circuit_breaker_window_secs
+            .map_or(defaults.window, Duration::from_secs),
+        cooldown: api
+            .circuit_breaker_cooldown_secs
+            .map_or(defaults.cooldown, Duration::from_secs),
+    }
+}
+
+/// Old names of `--side` and `--type`, still accepted as aliases
+pub(crate) const RENAMED_ORDER_FLAGS: [(&str, &str); 2] =
+    [("--order-type", "--side"), ("--order-type-enum", "--type")];
+
+/// Old names of `gtt modify --type`, which never took a side
+pub(crate) const RENAMED_GTT_MODIFY_FLAGS: [(&str, &str); 2] =
+    [("--order-type", "--type"), ("--order-type-enum", "--type")];
+
+/// Warn on stderr for each `(old, new)` flag in `renamed` given under its old name
+pub(crate) fn warn_renamed_flags(args: &[String], renamed: &[(&str, &str)]) {
+    for warning in renamed_flag_warnings(args, renamed) {
+        output::notice(warning);
+    }
+}
+
+fn renamed_flag_warnings(args: &[String], renamed: &[(&str, &str)]) -> Vec<String> {
+    renamed
+        .iter()
+        .filter(|(old, _)| {
+            args.iter().any(|arg| {
+                arg == old || arg.strip_prefix(old).is_some_and(|rest| rest.starts_with('='))
+            })
+        })
+        .map(|(old, new)| format!("Warning: {} is deprecated; use {} instead", old, new))
+        .collect()
 }
 
 /// Run the CLI
 pub async fn run() -> Result<()> {
-    let cli = Cli::parse();
-
-    // Load config
-    let mut config = if let Some(ref path) = cli.config {
-        let content = std::fs::read_to_string(path)
-            .with_context(|| format!("Failed to read config from {}", path))?;
-        toml::from_str(&content).with_context(|| "Failed to parse config file")?
-    } else {
-        Config::load().with_context(|| {
-            "Failed to load config. Run 'kite auth setup' to configure API credentials."
-        })?
+    // clap exits with 2 on usage errors, which would read as an auth failure
+    let cli = Cli::try_parse().unwrap_or_else(|e| {
+        let _ = e.print();
+        let code = if e.use_stderr() {
+            exit_code::VALIDATION
+        } else {
+            0
+        };
+        std::process::exit(code)
+    });
+
+    // Completions need neither config nor credentials
+    if let Commands::Completions { shell } = cli.command {
+        return completions::run_completions(shell);
+    }
+
+    // Real environment variables win over the file; flags win over both
+    load_env_file(cli.env_file.as_deref())?;
+
+    init_logging(cli.verbose, cli.no_color);
+
+    // Reject unknown output formats up front
+    cli.output.parse::<OutputFormat>()?;
+    if cli.watch {
+        watch::ensure_watchable(&cli.command)?;
+    }
+    let renamed: &[(&str, &str)] = match &cli.command {
+        Commands::Orders(OrdersCommands {
+            command: OrdersSubcommands::Place { .. } | OrdersSubcommands::Market { .. },
+        })
+        | Commands::Gtt(GttCommands {
+            command: GttSubcommands::Create { .. },
+        }) => &RENAMED_ORDER_FLAGS,
+        Commands::Gtt(GttCommands {
+            command: GttSubcommands::Modify { .. },
+        }) => &RENAMED_GTT_MODIFY_FLAGS,
+        _ => &[],
+    };
+    warn_renamed_flags(&std::env::args().collect::<Vec<_>>(), renamed);
+
+    // Color and JSON style depend on where output goes
+    if let Some(ref path) = cli.output_file {
+        output::set_output_file(path)?;
+    }
+
+    output::init_color(cli.no_color);
+    output::set_human_amounts(cli.human);
+    output::init_json_style(cli.pretty, cli.compact);
+    output::set_quiet(cli.quiet);
+    output::set_template(cli.template.clone());
+
+    // Schemas are static and need no config
+    if let Commands::Schema { ref model } = cli.command {
+        return schema::run_schema(model.as_deref(), &cli.output);
+    }
+
+    // Load config; every command reads and saves this one file
+    let config_path = match cli.config {
+        Some(ref path) => path.clone(),
+        None => Config::config_path().context("Failed to get config path")?,
     };
+    let mut config = Config::load_from(&config_path, cli.profile.as_deref()).with_context(|| {
+        "Failed to load config. Run 'kite auth setup' to configure API credentials."
+    })?;
+
+    // Bare symbols like INFY are looked up on the configured exchange
+    zerodha_cli_core::validation::set_default_exchange(&config.defaults.exchange);
+
+    // --no-color ignores the theme entirely, including invalid entries
+    if !cli.no_color {
+        output::set_theme(
+            output::Theme::from_config(&config.output.theme)
+                .context("Invalid [output.theme] in config")?,
+        );
+    }
+
+    // Flags take precedence over env vars, which take precedence over the file
+    config.override_credentials(
+        cli.api_key.as_deref(),
+        cli.api_secret.as_deref(),
+        cli.access_token.as_deref(),
+    );
+    if matches!(
+        cli.command,
+        Commands::Instruments(_)
+            | Commands::Quotes(_)
+            | Commands::Orders(_)
+            | Commands::Portfolio(_)
+            | Commands::Margins(_)
+            | Commands::Gtt(_)
+            | Commands::Alerts(AlertsCommands {
+                command: AlertsSubcommands::Watch { .. }
+            })
+    ) {
+        config.require_credentials()?;
+    }
 
     // Create API client
-    let api_client =
+    let mut api_client =
         KiteConnectClient::new(config.api.api_key.clone(), config.api.api_secret.clone());
+    if let Some(per_second) = config.api.rate_limit {
+        api_client = api_client
+            .with_rate_limit(per_second)
+            .context("Invalid rate_limit in config")?;
+    }
+    let http = http_options(&config.api);
+    if http.accept_invalid_certs {
+        output::notice(
+            "WARNING: TLS certificate verification is disabled ([api] danger_accept_invalid_certs).\n\
+             Anyone between you and Kite can read and alter this session, including your \
+             access token and orders. Prefer [api] ca_cert with your proxy's CA.",
+        );
+    }
+    api_client = api_client
+        .with_http_options(http)
+        .context("Invalid HTTP settings in config")?;
+    match api_client.recording_mode() {
+        Some(RecordingMode::Record(dir)) => output::notice(format_args!(
+            "NOTE: recording API traffic to {} (ZERODHA_RECORD is set)",
+            dir.display()
+        )),
+        Some(RecordingMode::Replay(dir)) => output::notice(format_args!(
+            "NOTE: replaying API responses from {} (ZERODHA_REPLAY is set); \
+             nothing is sent to Kite",
+            dir.display()
+        )),
+        None => {}
+    }
+    if let Some(max_retries) = config.api.max_retries {
+        api_client = api_client.with_retry_policy(RetryPolicy {
+            max_retries,
+            ..RetryPolicy::default()
+        });
+    }
+    api_client = api_client.with_circuit_breaker(circuit_breaker_policy(&config.api));
+    if let Some(state_file) = zerodha_cli_core::api::circuit_breaker::default_state_file() {
+        api_client = api_client.with_circuit_breaker_state(state_file);
+    }
+
+    // Persist tokens renewed after a 401 to the same profile
+    let profile = Some(config.profile.clone());
+    api_client.on_token_refresh(move |access_token, refresh_token| {
+        if let Err(e) = zerodha_cli_core::auth::save_refreshed_session(
+            &config_path,
+            profile.as_deref(),
+            access_token,
+            refresh_token,
+        ) {
+            eprintln!("Warning: failed to save refreshed token: {}", e);
+        }
+    });
+    if let Some(ref token) = config.api.refresh_token {
+        api_client.set_refresh_token(token.clone()).await?;
+    }
+
+    if cli.verbose > 0 {
+        // Never print any part of the access token
+        eprintln!(
+            "Profile: {}, API key: {}, access token: {}",
+            config.profile,
+            status::mask_key(&config.api.api_key),
+            if config.api.access_token.is_some() {
+                "set"
+            } else {
+                "not set"
+            }
+        );
+    }
 
     // Set access token if available
     if let Some(ref token) = config.api.access_token {
-        eprintln!("Debug: API key from config ({} chars): {}", config.api.api_key.len(), &config.api.api_key[..8.min(config.api.api_key.len())]);
-        eprintln!("Debug: Access token from config ({} chars): {}...", token.len(), &token[..16.min(token.len())]);
         api_client.set_access_token(token.clone()).await?;
-    } else {
-        eprintln!("Debug: No access token found in config");
+    }
+
+    if cli.watch {
+        return watch::run_watch(cli.command, cli.interval, &config, &api_client, &cli.output).await;
     }
 
     // Execute command
     match cli.command {
         Commands::Auth(auth_cmd) => auth::run_auth(auth_cmd, &mut config, &api_client).await?,
         Commands::Instruments(instruments_cmd) => {
-            instruments::run_instruments(instruments_cmd, &api_client, &cli.output).await?
+            instruments::run_instruments(instruments_cmd, &config, &api_client, &cli.output).await?
         }
         Commands::Quotes(quotes_cmd) => {
             quotes::run_quotes(quotes_cmd, &api_client, &cli.output).await?
         }
         Commands::Orders(orders_cmd) => {
-            orders::run_orders(orders_cmd, &config, &api_client, &cli.output).await?
+            orders::run_orders(orders_cmd, &config, &api_client, &cli.output, cli.dry_run, cli.yes).await?
         }
         Commands::Portfolio(portfolio_cmd) => {
-            portfolio::run_portfolio(portfolio_cmd, &api_client, &cli.output).await?
+            portfolio::run_portfolio(portfolio_cmd, &config, &api_client, &cli.output, cli.dry_run, cli.yes).await?
         }
         Commands::Margins(margins_cmd) => {
             margins::run_margins(margins_cmd, &api_client, &cli.output).await?
         }
-        Commands::Gtt(gtt_cmd) => gtt::run_gtt(gtt_cmd, &api_client, &cli.output).await?,
-        Commands::Status => status::run_status(&config, &api_client).await?,
-        Commands::Shell => {
+        Commands::Gtt(gtt_cmd) => {
+            gtt::run_gtt(gtt_cmd, &api_client, &cli.output, cli.dry_run, cli.yes).await?
+        }
+        Commands::Cache(cache_cmd) => cache::run_cache(cache_cmd, &config)?,
+        Commands::Alerts(alerts_cmd) => {
+            alerts::run_alerts(alerts_cmd, &config, &api_client, &cli.output).await?
+        }
+        Commands::Status { command: None } => status::run_status(&config, &api_client).await?,
+        Commands::Status {
+            command: Some(StatusSubcommands::Market),
+        } => status::run_market_status(&cli.output)?,
+        Commands::Batch { input } => {
+            batch::run_batch(&input, &config, &api_client, cli.dry_run, cli.yes).await?
+        }
+        Commands::Shell {
+            file,
+            continue_on_error,
+            stats,
+        } => {
             let config_arc = Arc::new(tokio::sync::Mutex::new(config));
             let api_client_arc = Arc::new(api_client);
-            shell::run_shell(config_arc, api_client_arc, &cli.output).await?
+            match file {
+                Some(path) => {
+                    shell::run_script(
+                        &path,
+                        config_arc,
+                        api_client_arc,
+                        &cli.output,
+                        continue_on_error,
+                        cli.dry_run,
+                        cli.yes,
+                    )
+                    .await?
+                }
+                None => {
+                    shell::run_shell(
+                        config_arc,
+                        api_client_arc,
+                        &cli.output,
+                        stats,
+                        cli.dry_run,
+                        cli.yes,
+                    )
+                    .await?
+                }
+            }
+        }
+        Commands::Completions { .. } | Commands::Schema { .. } => {
+            unreachable!("handled before loading config")
         }
     }
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::CommandFactory;
+
+    #[test]
+    fn test_cli_definition_is_valid() {
+        Cli::command().debug_assert();
+    }
+
+    #[test]
+    fn test_confirmation_answers() {
+        assert!(is_yes("y\n"));
+        assert!(is_yes(" YES "));
+        assert!(!is_yes("\n"));
+        assert!(!is_yes("nope"));
+        assert!(confirm_or_prompt("Delete everything?", true).unwrap());
+    }
+
+    #[test]
+    fn test_force_is_an_alias_for_yes() {
+        assert!(Cli::parse_from(["kite", "orders", "cancel", "1", "--force"]).yes);
+        assert!(Cli::parse_from(["kite", "-y", "gtt", "delete", "1"]).yes);
+    }
+
+    #[test]
+    fn test_place_side_and_type_with_deprecated_aliases() {
+        let place = |args: &[&str]| {
+            let base = ["kite", "orders", "place", "-s", "NSE:INFY", "-q", "1", "-p", "1500"];
+            match Cli::parse_from(base.iter().chain(args)).command {
+                Commands::Orders(OrdersCommands {
+                    command: OrdersSubcommands::Place { side, order_type, .. },
+                }) => (side, order_type),
+                _ => panic!("expected orders place"),
+            }
+        };
+        let expected = ("BUY".to_string(), Some("LIMIT".to_string()));
+        assert_eq!(place(&["--side", "BUY", "--type", "LIMIT"]), expected);
+        assert_eq!(place(&["--order-type", "BUY", "--order-type-enum", "LIMIT"]), expected);
+    }
+
+    #[test]
+    fn test_gtt_create_side_and_type_with_deprecated_aliases() {
+        let create = |args: &[&str]| {
+            let base = ["kite", "gtt", "create", "-s", "NSE:INFY", "-q", "1", "-p", "1500", "-t", "1490"];
+            match Cli::parse_from(base.iter().chain(args)).command {
+                Commands::Gtt(GttCommands {
+                    command: GttSubcommands::Create { side, order_type, .. },
+                }) => (side, order_type),
+                _ => panic!("expected gtt create"),
+            }
+        };
+        let expected = ("SELL".to_string(), Some("LIMIT".to_string()));
+        assert_eq!(create(&["--side", "SELL", "--type", "LIMIT"]), expected);
+        assert_eq!(create(&["--order-type", "SELL", "--order-type-enum", "LIMIT"]), expected);
+    }
+
+    #[test]
+    fn test_renamed_flag_warnings() {
+        let args = |args: &[&str]| args.iter().map(|a| a.to_string()).collect::<Vec<_>>();
+        let current = args(&["--side", "BUY", "--type", "LIMIT"]);
+        assert!(renamed_flag_warnings(&current, &RENAMED_ORDER_FLAGS).is_empty());
+
+        let old = args(&["--order-type", "BUY", "--order-type-enum=SL"]);
+        let warnings = renamed_flag_warnings(&old, &RENAMED_ORDER_FLAGS);
+        assert_eq!(warnings.len(), 2);
+        assert!(warnings[0].contains("--order-type is deprecated; use --side"));
+        assert!(warnings[1].contains("--order-type-enum is deprecated; use --type"));
+
+        let modify = args(&["5", "--order-type", "LIMIT"]);
+        let warnings = renamed_flag_warnings(&modify, &RENAMED_GTT_MODIFY_FLAGS);
+        assert_eq!(warnings, ["Warning: --order-type is deprecated; use --type instead"]);
+    }
+}
@@ -1,25 +1,93 @@
 //! Orders command handlers
 
 use anyhow::{Context, Result};
+use std::io::Write;
+use std::time::{Duration, Instant};
 use zerodha_cli_core::{
     api::KiteConnectClient,
-    config::Config,
-    models::{Order, OrderType, Product, TransactionType, Validity},
+    cache::InstrumentCache,
+    config::{Config, DefaultsConfig},
+    error::ZerodhaError,
+    instruments::{resolve_symbol, suggest_symbol, SymbolIndex},
+    market,
+    models::{
+        timestamp::TimeRange, Instrument, ModifyOrder, Order, OrderType, PlaceOrder, Product,
+        TransactionType, Validity,
+    },
+    orders::DayStats,
+    output::{self, format_inr, OutputFormatter},
+    validation::{parse_symbol, validate_symbol},
 };
 
-use super::OrdersCommands;
+use super::{confirm_or_prompt, print_request_body, OrdersCommands, PageArgs};
+
+/// Most cached symbols listed in an unknown-instrument error
+const MAX_SYMBOL_SUGGESTIONS: usize = 3;
 
 /// Parameters for placing an order
 pub(crate) struct OrderParams {
     symbol: String,
     transaction_type: String,
-    order_type_enum: Option<String>,
+    order_type: Option<String>,
     quantity: i32,
     price: f64,
+    trigger_price: Option<f64>,
+    disclosed_quantity: Option<i32>,
     product: Option<String>,
     validity: Option<String>,
+    ttl_minutes: Option<u32>,
     dry_run: bool,
+    show_payload: bool,
     variety: String,
+    tag: Option<String>,
+    wait: Option<Duration>,
+    round_tick: bool,
+    max_loss: Option<f64>,
+    override_risk: bool,
+    assume_yes: bool,
+}
+
+/// Parameters for placing a market order
+pub(crate) struct MarketOrderParams {
+    symbol: String,
+    transaction_type: String,
+    quantity: i32,
+    product: Option<String>,
+    dry_run: bool,
+    show_payload: bool,
+    tag: Option<String>,
+    wait: Option<Duration>,
+    max_loss: Option<f64>,
+    override_risk: bool,
+    assume_yes: bool,
+}
+
+/// Filters narrowing which open orders cancel-all touches
+#[derive(Default)]
+pub(crate) struct CancelFilter {
+    product: Option<Product>,
+    symbol: Option<(String, String)>,
+    transaction_type: Option<TransactionType>,
+}
+
+impl CancelFilter {
+    /// Whether an order passes every filter that is set
+    fn matches(&self, order: &Order) -> bool {
+        let product_ok = self
+            .product
+            .as_ref()
+            .is_none_or(|p| p.to_string() == order.product.to_string());
+        let symbol_ok = self.symbol.as_ref().is_none_or(|(exchange, symbol)| {
+            order.exchange.to_string().eq_ignore_ascii_case(exchange)
+                && order.tradingsymbol.eq_ignore_ascii_case(symbol)
+        });
+        let tx_ok = self
+            .transaction_type
+            .as_ref()
+            .is_none_or(|t| t.to_string() == order.transaction_type.to_string());
+
+        product_ok && symbol_ok && tx_ok
+    }
 }
 
 pub async fn run_orders(
@@ -27,49 +95,112 @@ pub async fn run_orders(
     config: &Config,
     api_client: &KiteConnectClient,
     output_format: &str,
+    dry_run: bool,
+    assume_yes: bool,
 ) -> Result<()> {
     match cmd.command {
-        super::OrdersSubcommands::List { status } => {
-            run_orders_list(status, output_format, api_client).await?
+        super::OrdersSubcommands::List {
+            status,
+            tag,
+            since,
+            until,
+            page,
+        } => {
+            let range = time_range(since.as_deref(), until.as_deref())?;
+            run_orders_list(status, tag, range, page, output_format, api_client).await?
         }
         super::OrdersSubcommands::Get { order_id } => {
             run_orders_get(order_id, output_format, api_client).await?
         }
+        super::OrdersSubcommands::New { override_risk } => {
+            run_orders_wizard(config, dry_run, override_risk, assume_yes, api_client).await?
+        }
         super::OrdersSubcommands::Place {
             symbol,
+            side,
             order_type,
-            order_type_enum,
             quantity,
             price,
+            trigger_price,
+            disclosed_quantity,
             product,
             validity,
-            dry_run,
+            ttl_minutes,
+            show_payload,
             variety,
+            tag,
+            wait,
+            wait_timeout,
+            round_tick,
+            max_loss,
+            override_risk,
         } => {
             let params = OrderParams {
                 symbol,
-                transaction_type: order_type,
-                order_type_enum,
+                transaction_type: side,
+                order_type,
                 quantity,
                 price,
+                trigger_price,
+                disclosed_quantity,
                 product,
                 validity,
+                ttl_minutes,
                 dry_run,
+                show_payload,
                 variety,
+                tag,
+                wait: wait.then(|| Duration::from_secs(wait_timeout)),
+                round_tick,
+                max_loss,
+                override_risk,
+                assume_yes,
             };
             run_orders_place(params, config, api_client).await?
         }
         super::OrdersSubcommands::Market {
             symbol,
-            order_type,
+            side,
             quantity,
             product,
-            dry_run,
+            show_payload,
+            tag,
+            wait,
+            wait_timeout,
+            max_loss,
+            override_risk,
         } => {
-            run_orders_market(
-                symbol, order_type, quantity, product, dry_run, config, api_client,
-            )
-            .await?
+            let params = MarketOrderParams {
+                symbol,
+                transaction_type: side,
+                quantity,
+                product,
+                dry_run,
+                show_payload,
+                tag,
+                wait: wait.then(|| Duration::from_secs(wait_timeout)),
+                max_loss,
+                override_risk,
+                assume_yes,
+            };
+            run_orders_market(params, config, api_client).await?
+        }
+        super::OrdersSubcommands::Basket {
+            file,
+            atomic,
+            margins,
+            show_payload,
+            override_risk,
+        } => {
+            let options = BasketOptions {
+                atomic,
+                preview_margins: margins,
+                dry_run,
+                show_payload,
+                override_risk,
+                assume_yes,
+            };
+            run_orders_basket(file, options, config, api_client).await?
         }
         super::OrdersSubcommands::Modify {
             order_id,
@@ -78,54 +209,102 @@ pub async fn run_orders(
             trigger_price,
             validity,
             disclosed_quantity,
+            show_payload,
         } => {
-            run_orders_modify(
-                order_id,
+            let request = ModifyOrder {
+                quantity: quantity.map(|q| q as u32),
                 price,
-                quantity,
                 trigger_price,
-                validity,
-                disclosed_quantity,
-                api_client,
-            )
-            .await?
+                validity: validity.map(|v| parse_validity(&v)).transpose()?,
+                disclosed_quantity: disclosed_quantity.map(|q| q as u32),
+            };
+            run_orders_modify(order_id, request, dry_run, show_payload, api_client).await?
         }
         super::OrdersSubcommands::Cancel { order_id, variety } => {
-            run_orders_cancel(order_id, variety, api_client).await?
+            run_orders_cancel(order_id, variety, dry_run, assume_yes, api_client).await?
         }
-        super::OrdersSubcommands::CancelAll => run_orders_cancel_all(api_client).await?,
-        super::OrdersSubcommands::Trades { order_id } => {
-            run_orders_trades(order_id, output_format, api_client).await?
+        super::OrdersSubcommands::CancelAll {
+            product,
+            symbol,
+            transaction_type,
+        } => {
+            let filter = CancelFilter {
+                product: product.as_deref().map(parse_product).transpose()?,
+                symbol: symbol.as_deref().map(parse_symbol).transpose()?,
+                transaction_type: transaction_type
+                    .as_deref()
+                    .map(parse_transaction_type)
+                    .transpose()?,
+            };
+            run_orders_cancel_all(filter, dry_run, assume_yes, api_client).await?
         }
+        super::OrdersSubcommands::Trades {
+            order_id,
+            since,
+            until,
+            page,
+        } => {
+            let range = time_range(since.as_deref(), until.as_deref())?;
+            run_orders_trades(order_id, range, page, output_format, api_client).await?
+        }
+        super::OrdersSubcommands::Stats => run_orders_stats(output_format, api_client).await?,
     }
     Ok(())
 }
 
+/// `--since`/`--until` window, with bare times taken as today in IST
+fn time_range(since: Option<&str>, until: Option<&str>) -> Result<TimeRange> {
+    TimeRange::parse(since, until, market::now_ist().date())
+}
+
 pub async fn run_orders_list(
     status_filter: Option<String>,
+    tag_filter: Option<String>,
+    range: TimeRange,
+    page: PageArgs,
     output_format: &str,
     api_client: &KiteConnectClient,
 ) -> Result<()> {
     let orders = api_client.list_orders().await?;
 
-    let filtered = if let Some(status) = status_filter {
-        orders
-            .into_iter()
-            .filter(|o| format!("{:?}", o.status).to_lowercase() == status.to_lowercase())
-            .collect()
-    } else {
-        orders
-    };
+    let filtered: Vec<Order> = orders
+        .into_iter()
+        .filter(|o| {
+            status_filter.as_ref().is_none_or(|status| {
+                format!("{:?}", o.status).to_lowercase() == status.to_lowercase()
+            })
+        })
+        .filter(|o| {
+            tag_filter.as_ref().is_none_or(|tag| {
+                o.tag
+                    .as_deref()
+                    .is_some_and(|t| t.eq_ignore_ascii_case(tag))
+            })
+        })
+        .filter(|o| range.contains(&o.order_timestamp))
+        .collect();
 
     if filtered.is_empty() {
-        println!("No orders found.");
+        output::notice("No orders found.");
         return Ok(());
     }
-
-    if output_format == "json" {
-        println!("{}", serde_json::to_string_pretty(&filtered)?);
+    let (filtered, footer) = page.apply(filtered)?;
+
+    if let Some(template) = output::template() {
+        filtered.print_template(&template, &mut output::writer())?;
+    } else if output_format == "json" {
+        output::emit(output::to_json(&filtered)?)?;
+    } else if output_format == "ndjson" {
+        filtered.print_ndjson(&mut output::writer())?;
+    } else if output_format == "yaml" {
+        filtered.print_yaml(&mut output::writer())?;
+    } else if output_format == "csv" {
+        filtered.print_csv(&mut output::writer())?;
     } else {
-        print_orders_table(&filtered);
+        print_orders_table(&filtered)?;
+        if let Some(footer) = footer {
+            writeln!(output::writer(), "{}", footer)?;
+        }
     }
 
     Ok(())
@@ -139,9 +318,13 @@ pub async fn run_orders_get(
     let order = api_client.get_order(&order_id).await?;
 
     if output_format == "json" {
-        println!("{}", serde_json::to_string_pretty(&order)?);
+        output::emit(output::to_json(&order)?)?;
+    } else if output_format == "ndjson" {
+        output::emit(serde_json::to_string(&order)?)?;
+    } else if output_format == "yaml" {
+        output::write_yaml(&order, output::writer())?;
     } else {
-        print_order_details(&order);
+        print_order_details(&order)?;
     }
 
     Ok(())
@@ -154,32 +337,62 @@ pub async fn run_orders_place(
 ) -> Result<()> {
     let symbol = params.symbol;
     let transaction_type = params.transaction_type;
-    let order_type_enum = params.order_type_enum;
     let quantity = params.quantity;
-    let price = params.price;
+    let mut price = params.price;
     let product = params.product;
     let validity = params.validity;
+    let ttl_minutes = params.ttl_minutes;
     let dry_run = params.dry_run;
     let _variety = params.variety;
+    let tag = params.tag;
+    let wait = params.wait;
+
+    if let Some(ref tag) = tag {
+        zerodha_cli_core::validation::validate_tag(tag).context("Invalid order tag")?;
+    }
 
     // Validate symbol
-    let (exchange, tradingsymbol) = validate_symbol(&symbol)?;
+    let (exchange, tradingsymbol) = parse_symbol(&symbol)?;
 
     // Parse enums
     let tx_type = parse_transaction_type(&transaction_type)?;
-    let order_type = parse_order_type(order_type_enum.as_deref().unwrap_or("LIMIT"))?;
-    let prod = parse_product(product.as_deref().unwrap_or(&config.defaults.product))?;
-    let val = parse_validity(validity.as_deref().unwrap_or("DAY"))?;
+    let (order_type, prod, val) = order_fields(
+        params.order_type.as_deref(),
+        product.as_deref(),
+        validity.as_deref(),
+        &config.defaults,
+    )?;
+    zerodha_cli_core::validation::validate_validity_ttl(&val, ttl_minutes)
+        .context("Invalid order validity")?;
+
+    check_symbol_listed(&exchange, &tradingsymbol)?;
+    let instrument = cached_instrument(&exchange, &tradingsymbol);
+    if let Some(ref instrument) = instrument {
+        if !matches!(order_type, OrderType::Market) {
+            price = check_tick_size(price, instrument.tick_size, params.round_tick)?;
+        }
+    }
+
+    // The last price lets validate_order check which side a stop-loss trigger
+    // is on, and values orders without a limit price for the risk check
+    let ltp = match order_type {
+        OrderType::Limit => None,
+        _ => fetch_ltp(&exchange, &tradingsymbol, api_client).await,
+    };
 
     // Validate order (clone values for validation since they get moved)
     zerodha_cli_core::validation::validate_order(
         order_type.clone(),
+        tx_type.clone(),
         quantity,
         price,
-        None,
+        params.trigger_price,
+        params.disclosed_quantity,
         prod.clone(),
+        ltp,
     )
     .context("Invalid order parameters")?;
+    check_lot_size(instrument.as_ref(), quantity)?;
 
     // Build request
     let request = zerodha_cli_core::models::PlaceOrder {
@@ -190,59 +403,239 @@ pub async fn run_orders_place(
         order_type,
         product: prod,
         price: Some(price),
-        trigger_price: None,
+        trigger_price: params.trigger_price,
         validity: Some(val),
-        disclosed_quantity: None,
+        validity_ttl: ttl_minutes,
+        disclosed_quantity: params.disclosed_quantity.map(|q| q as u32),
         variety: Some(_variety.to_string()),
+        tag,
+    };
+
+    // Check the risk limit before anything else, so dry runs show a refusal too
+    let order_price = match request.order_type {
+        OrderType::Market | OrderType::SLM => OrderPrice::Market(ltp),
+        OrderType::Limit | OrderType::SL => OrderPrice::Limit(price),
     };
+    check_order_value(
+        &request,
+        &order_price,
+        params.max_loss.or(config.risk.max_order_value),
+        params.override_risk,
+    )?;
 
     if dry_run {
-        println!("[DRY RUN] Would place order:");
-        println!("  Symbol: {}", symbol);
-        println!("  Type: {}", transaction_type);
-        println!(
-            "  Order Type: {}",
-            order_type_enum.unwrap_or_else(|| "LIMIT".to_string())
-        );
-        println!("  Quantity: {}", quantity);
-        println!("  Price: ₹{:.2}", price);
+        eprintln!("[DRY RUN] Would place order:");
+        eprintln!("  Symbol: {}", symbol);
+        eprintln!("  Side: {}", transaction_type);
+        eprintln!("  Order Type: {}", request.order_type);
+        eprintln!("  Quantity: {}", quantity);
+        eprintln!("  Price: ₹{:.2}", price);
+        if params.show_payload {
+            print_request_body(&request)?;
+        }
         return Ok(());
     }
 
-    // Confirm
-    print!("Confirm order? [y/N]: ");
-    let mut input = String::new();
-    std::io::stdin().read_line(&mut input)?;
-    if !input.trim().to_lowercase().starts_with('y') {
-        println!("Order cancelled.");
+    eprintln!("{}", order_summary(&request, order_price));
+    if !confirm_or_prompt("Confirm order?", params.assume_yes)? {
+        eprintln!("Order cancelled.");
         return Ok(());
     }
 
     let response = api_client.place_order(&request).await?;
-    println!("✓ Order placed successfully!");
-    println!("  Order ID: {}", response.order_id);
-    println!("  Status: {:?}", response.status);
+    eprintln!("✓ Order placed successfully!");
+    eprintln!("  Order ID: {}", response.order_id);
+    eprintln!("  Status: {:?}", response.status);
+
+    if let Some(timeout) = wait {
+        wait_and_report(&response.order_id, timeout, api_client).await?;
+    }
 
     Ok(())
 }
 
+/// Order type, product and validity from the flags, falling back to `[defaults]`
+fn order_fields(
+    order_type: Option<&str>,
+    product: Option<&str>,
+    validity: Option<&str>,
+    defaults: &DefaultsConfig,
+) -> Result<(OrderType, Product, Validity)> {
+    Ok((
+        parse_order_type(order_type.unwrap_or(&defaults.order_type))?,
+        parse_product(product.unwrap_or(&defaults.product))?,
+        parse_validity(validity.unwrap_or(&defaults.validity))?,
+    ))
+}
+
+/// Price an order confirmation is based on
+pub(crate) enum OrderPrice {
+    Limit(f64),
+    /// Last traded price, when it could be fetched
+    Market(Option<f64>),
+}
+
+impl OrderPrice {
+    /// Order value at this price; `None` for a market order without a last price
+    fn notional(&self, quantity: u32) -> Option<f64> {
+        match *self {
+            OrderPrice::Limit(price) | OrderPrice::Market(Some(price)) => {
+                Some(price * quantity as f64)
+            }
+            OrderPrice::Market(None) => None,
+        }
+    }
+}
+
+/// Refuse an order worth more than `limit` unless the risk check is overridden
+///
+/// A market order whose last price couldn't be fetched is refused too, since
+/// its value can't be estimated.
+pub(crate) fn check_order_value(
+    request: &PlaceOrder,
+    price: &OrderPrice,
+    limit: Option<f64>,
+    override_risk: bool,
+) -> Result<()> {
+    let Some(limit) = limit else {
+        return Ok(());
+    };
+    if !limit.is_finite() || limit <= 0.0 {
+        anyhow::bail!("Risk limit must be greater than 0, got {}", limit);
+    }
+    if override_risk {
+        return Ok(());
+    }
+    match price.notional(request.quantity) {
+        Some(value) if value > limit => anyhow::bail!(
+            "Order value {} exceeds the risk limit of {}; pass --override-risk to place it anyway",
+            format_inr(value),
+            format_inr(limit)
+        ),
+        Some(_) => Ok(()),
+        None => anyhow::bail!(
+            "Can't check the market order against the risk limit of {} without a last price; \
+             pass --override-risk to place it anyway",
+            format_inr(limit)
+        ),
+    }
+}
+
+/// Order details and notional value shown before asking for confirmation
+fn order_summary(request: &PlaceOrder, price: OrderPrice) -> String {
+    let (price, notional) = match price {
+        OrderPrice::Limit(p) => (format_inr(p), format_inr(p * request.quantity as f64)),
+        OrderPrice::Market(Some(ltp)) => (
+            format!("MARKET (LTP {})", format_inr(ltp)),
+            format!("~{}", format_inr(ltp * request.quantity as f64)),
+        ),
+        OrderPrice::Market(None) => ("MARKET".to_string(), "unknown".to_string()),
+    };
+
+    let mut summary = format!(
+        "  Symbol:   {}:{}\n  Side:     {}\n  Quantity: {}\n  Price:    {}\n  Product:  {}\n  Notional: {}",
+        request.exchange,
+        request.tradingsymbol,
+        request.transaction_type,
+        request.quantity,
+        price,
+        request.product,
+        notional
+    );
+    if let Some(trigger) = request.trigger_price {
+        summary.push_str(&format!("\n  Trigger:  {}", format_inr(trigger)));
+    }
+    summary
+}
+
+/// Instrument details from the cache; `None` when the exchange isn't cached
+fn cached_instrument(exchange: &str, tradingsymbol: &str) -> Option<Instrument> {
+    InstrumentCache::lookup(exchange, tradingsymbol).ok().flatten()
+}
+
+/// Refuse a symbol missing from the exchange's cached instrument list
+///
+/// Skipped when the exchange isn't cached. Suggests cached symbols starting
+/// with the given one, or else the closest match.
+fn check_symbol_listed(exchange: &str, tradingsymbol: &str) -> Result<()> {
+    let index = SymbolIndex::load(exchange);
+    if index.is_empty() || index.contains(tradingsymbol) {
+        return Ok(());
+    }
+
+    let mut candidates = resolve_symbol(exchange, tradingsymbol);
+    candidates.truncate(MAX_SYMBOL_SUGGESTIONS);
+    if candidates.is_empty() {
+        candidates.extend(suggest_symbol(exchange, tradingsymbol));
+    }
+    let err = anyhow::Error::new(ZerodhaError::InstrumentNotFound(format!(
+        "{}:{}",
+        exchange, tradingsymbol
+    )));
+    if candidates.is_empty() {
+        return Err(err);
+    }
+    Err(err.context(format!("Did you mean {}?", candidates.join(", "))))
+}
+
+/// Reject quantities that aren't whole lots of a cached F&O instrument
+///
+/// Skipped when the instrument isn't cached.
+fn check_lot_size(instrument: Option<&Instrument>, quantity: i32) -> Result<()> {
+    let Some(instrument) = instrument else {
+        return Ok(());
+    };
+    zerodha_cli_core::validation::validate_lot_size(quantity, instrument.lot_size).with_context(
+        || format!("Invalid quantity for {}:{}", instrument.exchange, instrument.tradingsymbol),
+    )
+}
+
+/// Check a limit price against the tick size, rounding it instead with `round`
+fn check_tick_size(price: f64, tick: f64, round: bool) -> Result<f64> {
+    use zerodha_cli_core::validation::{round_to_tick, validate_tick_size};
+
+    if !round {
+        validate_tick_size(price, tick)?;
+        return Ok(price);
+    }
+    let rounded = round_to_tick(price, tick);
+    if rounded != price {
+        eprintln!("Rounded price {} to {} (tick size {})", price, rounded, tick);
+    }
+    Ok(rounded)
+}
+
 pub async fn run_orders_market(
-    symbol: String,
-    transaction_type: String,
-    quantity: i32,
-    product: Option<String>,
-    dry_run: bool,
+    params: MarketOrderParams,
     config: &Config,
     api_client: &KiteConnectClient,
 ) -> Result<()> {
-    use zerodha_cli_core::models::Validity;
+    let symbol = params.symbol;
+    let transaction_type = params.transaction_type;
+    let quantity = params.quantity;
+    let product = params.product;
+    let dry_run = params.dry_run;
+    let tag = params.tag;
+    let wait = params.wait;
+
+    // Caught here since the request takes the quantity as unsigned
+    if quantity <= 0 {
+        return Err(ZerodhaError::Validation("Quantity must be greater than 0".to_string()).into());
+    }
+    if let Some(ref tag) = tag {
+        zerodha_cli_core::validation::validate_tag(tag).context("Invalid order tag")?;
+    }
 
     // Validate symbol
-    let (exchange, tradingsymbol) = validate_symbol(&symbol)?;
+    let (exchange, tradingsymbol) = parse_symbol(&symbol)?;
 
     // Parse enums
     let tx_type = parse_transaction_type(&transaction_type)?;
-    let prod = parse_product(product.as_deref().unwrap_or(&config.defaults.product))?;
+    let (_, prod, val) = order_fields(Some("MARKET"), product.as_deref(), None, &config.defaults)?;
+    zerodha_cli_core::validation::validate_validity_ttl(&val, None)
+        .context("Invalid order validity")?;
+    check_symbol_listed(&exchange, &tradingsymbol)?;
+    check_lot_size(cached_instrument(&exchange, &tradingsymbol).as_ref(), quantity)?;
 
     // Build request
     let request = zerodha_cli_core::models::PlaceOrder {
@@ -254,59 +647,501 @@ pub async fn run_orders_market(
         product: prod,
         price: None,
         trigger_price: None,
-        validity: Some(Validity::Day),
+        validity: Some(val),
+        validity_ttl: None,
         disclosed_quantity: None,
         variety: Some("regular".to_string()),
+        tag,
     };
 
+    // Estimate the notional from the last price and check the risk limit,
+    // so dry runs show a refusal too
+    let ltp = fetch_ltp(&request.exchange, &request.tradingsymbol, api_client).await;
+    let order_price = OrderPrice::Market(ltp);
+    check_order_value(
+        &request,
+        &order_price,
+        params.max_loss.or(config.risk.max_order_value),
+        params.override_risk,
+    )?;
+
     if dry_run {
-        println!("[DRY RUN] Would place market order:");
-        println!("  Symbol: {}", symbol);
-        println!("  Type: {}", transaction_type);
-        println!("  Quantity: {}", quantity);
+        eprintln!("[DRY RUN] Would place market order:");
+        eprintln!("  Symbol: {}", symbol);
+        eprintln!("  Side: {}", transaction_type);
+        eprintln!("  Quantity: {}", quantity);
+        if params.show_payload {
+            print_request_body(&request)?;
+        }
         return Ok(());
     }
 
-    // Confirm
-    print!("Confirm market order? [y/N]: ");
-    let mut input = String::new();
-    std::io::stdin().read_line(&mut input)?;
-    if !input.trim().to_lowercase().starts_with('y') {
-        println!("Order cancelled.");
+    eprintln!("{}", order_summary(&request, order_price));
+    if !confirm_or_prompt("Confirm market order?", params.assume_yes)? {
+        eprintln!("Order cancelled.");
         return Ok(());
     }
 
     let response = api_client.place_order(&request).await?;
-    println!("✓ Market order placed successfully!");
-    println!("  Order ID: {}", response.order_id);
-    println!("  Status: {:?}", response.status);
+    eprintln!("✓ Market order placed successfully!");
+    eprintln!("  Order ID: {}", response.order_id);
+    eprintln!("  Status: {:?}", response.status);
+
+    if let Some(timeout) = wait {
+        wait_and_report(&response.order_id, timeout, api_client).await?;
+    }
 
     Ok(())
 }
 
-pub async fn run_orders_modify(
-    order_id: String,
-    price: Option<f64>,
-    quantity: Option<i32>,
-    trigger_price: Option<f64>,
-    validity: Option<String>,
-    _disclosed_quantity: Option<i32>,
+/// Last traded price, warning instead of failing when it can't be fetched
+pub(crate) async fn fetch_ltp(exchange: &str, tradingsymbol: &str, api_client: &KiteConnectClient) -> Option<f64> {
+    let ltp_key = format!("{}:{}", exchange, tradingsymbol);
+    match api_client.get_ltp(&[ltp_key.as_str()]).await {
+        Ok(response) => response.data.get(&ltp_key).map(|ltp| ltp.last_price),
+        Err(e) => {
+            eprintln!("Warning: could not fetch last price for {}: {:#}", ltp_key, e);
+            None
+        }
+    }
+}
+
+/// Prompt until `parse` accepts the answer; an empty answer takes `default`
+fn ask<T>(
+    rl: &mut rustyline::DefaultEditor,
+    prompt: &str,
+    default: Option<&str>,
+    parse: impl Fn(&str) -> Result<T>,
+) -> Result<T> {
+    use rustyline::error::ReadlineError;
+
+    let prompt = match default {
+        Some(default) => format!("{} [{}]: ", prompt, default),
+        None => format!("{}: ", prompt),
+    };
+    loop {
+        let line = match rl.readline(&prompt) {
+            Ok(line) => line,
+            Err(ReadlineError::Interrupted | ReadlineError::Eof) => {
+                anyhow::bail!("Order entry cancelled")
+            }
+            Err(e) => return Err(e.into()),
+        };
+        let answer = match line.trim() {
+            "" => default.unwrap_or_default(),
+            answer => answer,
+        };
+        if answer.is_empty() {
+            eprintln!("  A value is required");
+            continue;
+        }
+        match parse(answer) {
+            Ok(value) => return Ok(value),
+            Err(e) => eprintln!("  {:#}", e),
+        }
+    }
+}
+
+/// Parse a positive whole-number quantity
+fn parse_quantity(s: &str) -> Result<i32> {
+    let quantity: i32 = s
+        .parse()
+        .map_err(|_| anyhow::anyhow!("Quantity must be a whole number"))?;
+    if quantity <= 0 {
+        anyhow::bail!("Quantity must be greater than 0");
+    }
+    Ok(quantity)
+}
+
+/// Parse a positive price
+fn parse_price(s: &str) -> Result<f64> {
+    let price: f64 = s
+        .trim_start_matches('₹')
+        .parse()
+        .map_err(|_| anyhow::anyhow!("Price must be a number"))?;
+    if !price.is_finite() || price <= 0.0 {
+        anyhow::bail!("Price must be greater than 0");
+    }
+    Ok(price)
+}
+
+/// Build an order by prompting for each field, then confirm and place it
+pub async fn run_orders_wizard(
+    config: &Config,
+    dry_run: bool,
+    override_risk: bool,
+    assume_yes: bool,
     api_client: &KiteConnectClient,
 ) -> Result<()> {
-    let val = validity.map(|v| parse_validity(&v)).transpose()?;
+    let mut rl = rustyline::DefaultEditor::new()?;
+    eprintln!("New order (Ctrl+C to cancel)");
+
+    let (exchange, tradingsymbol) = ask(&mut rl, "Symbol (EXCHANGE:SYMBOL)", None, parse_symbol)?;
+    let instrument = cached_instrument(&exchange, &tradingsymbol);
+    let tick = instrument.as_ref().map(|i| i.tick_size);
+
+    let tx_type = ask(&mut rl, "Side (BUY/SELL)", None, |s| {
+        parse_transaction_type(s).map_err(|_| anyhow::anyhow!("Invalid side. Use BUY or SELL"))
+    })?;
+    let order_type = ask(
+        &mut rl,
+        "Order type (MARKET/LIMIT/SL/SL-M)",
+        Some(&config.defaults.order_type),
+        parse_order_type,
+    )?;
+    let product = ask(
+        &mut rl,
+        "Product (CNC/MIS/NRML/MTF)",
+        Some(&config.defaults.product),
+        parse_product,
+    )?;
+    let validity = ask(
+        &mut rl,
+        "Validity (DAY/IOC/TTL)",
+        Some(&config.defaults.validity),
+        parse_validity,
+    )?;
+    let validity_ttl = match validity {
+        Validity::TTL => Some(ask(&mut rl, "Lifetime in minutes (1-365)", None, |s| {
+            let minutes = s.trim().parse::<u32>().context("Invalid number of minutes")?;
+            zerodha_cli_core::validation::validate_validity_ttl(&Validity::TTL, Some(minutes))?;
+            Ok(minutes)
+        })?),
+        Validity::Day | Validity::IOC => None,
+    };
+    let quantity = ask(&mut rl, "Quantity", None, |s| {
+        let quantity = parse_quantity(s)?;
+        check_lot_size(instrument.as_ref(), quantity)?;
+        Ok(quantity)
+    })?;
+
+    let price = match order_type {
+        OrderType::Limit | OrderType::SL => Some(ask(&mut rl, "Price", None, |s| {
+            let price = parse_price(s)?;
+            match tick {
+                Some(tick) => check_tick_size(price, tick, false),
+                None => Ok(price),
+            }
+        })?),
+        OrderType::Market | OrderType::SLM => None,
+    };
+
+    // The last price lets validate_order check which side the trigger is on,
+    // and estimates the notional for orders without a limit price
+    let needs_trigger = matches!(order_type, OrderType::SL | OrderType::SLM);
+    let ltp = if needs_trigger || price.is_none() {
+        fetch_ltp(&exchange, &tradingsymbol, api_client).await
+    } else {
+        None
+    };
+    let trigger_price = if needs_trigger {
+        Some(ask(&mut rl, "Trigger price", None, |s| {
+            let trigger = parse_price(s)?;
+            zerodha_cli_core::validation::validate_order(
+                order_type.clone(),
+                tx_type.clone(),
+                quantity,
+                price.unwrap_or(trigger),
+                Some(trigger),
+                None,
+                product.clone(),
+                ltp,
+            )?;
+            Ok(trigger)
+        })?)
+    } else {
+        None
+    };
 
-    let request = zerodha_cli_core::models::ModifyOrder {
-        quantity: quantity.map(|q| q as u32),
+    let request = PlaceOrder {
+        exchange,
+        tradingsymbol,
+        transaction_type: tx_type,
+        quantity: quantity as u32,
+        order_type,
+        product,
         price,
         trigger_price,
-        validity: val,
-        disclosed_quantity: _disclosed_quantity.map(|q| q as u32),
+        validity: Some(validity),
+        validity_ttl,
+        disclosed_quantity: None,
+        variety: Some("regular".to_string()),
+        tag: None,
+    };
+
+    let summary_price = match price {
+        Some(price) => OrderPrice::Limit(price),
+        None => OrderPrice::Market(ltp),
     };
+    check_order_value(&request, &summary_price, config.risk.max_order_value, override_risk)?;
+    eprintln!();
+    eprintln!("{}", order_summary(&request, summary_price));
+    if dry_run {
+        eprintln!("[DRY RUN] Order not placed.");
+        return Ok(());
+    }
+    if !confirm_or_prompt("Confirm order?", assume_yes)? {
+        eprintln!("Order cancelled.");
+        return Ok(());
+    }
+
+    let response = api_client.place_order(&request).await?;
+    eprintln!("✓ Order placed successfully!");
+    eprintln!("  Order ID: {}", response.order_id);
+    eprintln!("  Status: {:?}", response.status);
+    Ok(())
+}
+
+/// Poll an order until it reaches a terminal state or the timeout elapses
+///
+/// Each poll goes through the client's rate limiter.
+pub async fn wait_for_order(
+    order_id: &str,
+    timeout: Duration,
+    api_client: &KiteConnectClient,
+) -> Result<Order> {
+    let start = Instant::now();
+
+    loop {
+        let order = api_client.get_order(order_id).await?;
+        if order.status.is_terminal() {
+            return Ok(order);
+        }
+
+        if start.elapsed() >= timeout {
+            anyhow::bail!(
+                "Timed out after {}s waiting for order {} (last status: {})",
+                timeout.as_secs(),
+                order_id,
+                order.status
+            );
+        }
+
+        tokio::time::sleep(Duration::from_secs(1)).await;
+    }
+}
+
+/// Wait for an order and fail unless it completed
+async fn wait_and_report(
+    order_id: &str,
+    timeout: Duration,
+    api_client: &KiteConnectClient,
+) -> Result<()> {
+    output::progress(format_args!("Waiting for order {} to complete...", order_id));
+    let order = wait_for_order(order_id, timeout, api_client).await?;
+
+    println!();
+    print_order_details(&order)?;
+
+    if !matches!(order.status, zerodha_cli_core::models::OrderStatus::Complete) {
+        anyhow::bail!("Order {} finished with status {}", order_id, order.status);
+    }
+
+    Ok(())
+}
+
+/// How `orders basket` places its orders
+pub(crate) struct BasketOptions {
+    atomic: bool,
+    preview_margins: bool,
+    dry_run: bool,
+    show_payload: bool,
+    override_risk: bool,
+    assume_yes: bool,
+}
+
+pub async fn run_orders_basket(
+    file: String,
+    options: BasketOptions,
+    config: &Config,
+    api_client: &KiteConnectClient,
+) -> Result<()> {
+    let BasketOptions {
+        atomic,
+        preview_margins,
+        dry_run,
+        show_payload,
+        override_risk,
+        assume_yes,
+    } = options;
+
+    let content = std::fs::read_to_string(&file)
+        .with_context(|| format!("Failed to read basket file {}", file))?;
+    let orders = zerodha_cli_core::models::PlaceOrder::parse_basket(&content)
+        .with_context(|| format!("Failed to parse basket file {}", file))?;
+
+    // Validate every leg before placing anything
+    for (i, order) in orders.iter().enumerate() {
+        let ltp = match order.order_type {
+            OrderType::SL | OrderType::SLM => {
+                fetch_ltp(&order.exchange, &order.tradingsymbol, api_client).await
+            }
+            OrderType::Market | OrderType::Limit => None,
+        };
+        validate_basket_order(order, ltp).with_context(|| {
+            format!("Invalid order #{} ({}:{})", i + 1, order.exchange, order.tradingsymbol)
+        })?;
+    }
+
+    check_basket_value(&orders, config.risk.max_order_value, override_risk, api_client).await?;
+
+    eprintln!("Basket: {} order(s)", orders.len());
+    for (i, order) in orders.iter().enumerate() {
+        eprintln!(
+            "  {}. {} {} {}:{} {} @ {}",
+            i + 1,
+            order.transaction_type,
+            order.quantity,
+            order.exchange,
+            order.tradingsymbol,
+            order.order_type,
+            order
+                .price
+                .map(|p| format!("₹{:.2}", p))
+                .unwrap_or_else(|| "MARKET".to_string())
+        );
+    }
+
+    if preview_margins {
+        let margins = api_client.get_basket_margins(&orders).await?;
+        eprintln!();
+        eprintln!(
+            "Basket margin required: ₹{:.2} (hedge benefit ₹{:.2})",
+            margins.final_margin.total,
+            margins.benefit()
+        );
+    }
+
+    if dry_run {
+        if show_payload {
+            eprintln!();
+            print_request_body(&orders)?;
+        }
+        eprintln!();
+        eprintln!("[DRY RUN] Basket validated, no orders placed.");
+        return Ok(());
+    }
+
+    if !confirm_or_prompt(&format!("\nPlace all {} orders?", orders.len()), assume_yes)? {
+        eprintln!("Basket cancelled.");
+        return Ok(());
+    }
+
+    let mut placed = 0;
+    let mut failed = 0;
+
+    for (i, order) in orders.iter().enumerate() {
+        match api_client.place_order(order).await {
+            Ok(response) => {
+                placed += 1;
+                eprintln!(
+                    "✓ #{} {} placed (Order ID: {})",
+                    i + 1,
+                    order.tradingsymbol,
+                    response.order_id
+                );
+            }
+            Err(e) => {
+                failed += 1;
+                eprintln!("✗ #{} {} failed: {}", i + 1, order.tradingsymbol, e);
+                if atomic {
+                    eprintln!("Stopping basket (--atomic)");
+                    break;
+                }
+            }
+        }
+    }
+
+    let skipped = orders.len() - placed - failed;
+    eprintln!();
+    eprintln!(
+        "Summary: {} placed, {} failed, {} skipped",
+        placed, failed, skipped
+    );
+
+    if failed > 0 {
+        anyhow::bail!("{} basket order(s) failed", failed);
+    }
+
+    Ok(())
+}
+
+/// Check every leg against the risk limit, pricing legs without a limit
+/// price at the last traded price
+async fn check_basket_value(
+    orders: &[PlaceOrder],
+    limit: Option<f64>,
+    override_risk: bool,
+    api_client: &KiteConnectClient,
+) -> Result<()> {
+    if limit.is_none() {
+        return Ok(());
+    }
+    for (i, order) in orders.iter().enumerate() {
+        let price = match order.price {
+            Some(price) if price > 0.0 => OrderPrice::Limit(price),
+            _ => OrderPrice::Market(
+                fetch_ltp(&order.exchange, &order.tradingsymbol, api_client).await,
+            ),
+        };
+        check_order_value(order, &price, limit, override_risk).with_context(|| {
+            format!("Order #{} ({}:{})", i + 1, order.exchange, order.tradingsymbol)
+        })?;
+    }
+    Ok(())
+}
+
+/// Validate a single basket order; `last_price` checks stop-loss triggers
+fn validate_basket_order(
+    order: &zerodha_cli_core::models::PlaceOrder,
+    last_price: Option<f64>,
+) -> Result<()> {
+    validate_symbol(&format!(
+        "{}:{}",
+        order.exchange, order.tradingsymbol
+    ))?;
+
+    if let Some(ref tag) = order.tag {
+        zerodha_cli_core::validation::validate_tag(tag)?;
+    }
+
+    zerodha_cli_core::validation::validate_validity_ttl(
+        order.validity.as_ref().unwrap_or(&Validity::Day),
+        order.validity_ttl,
+    )?;
+
+    // Every leg, MARKET included, gets the same checks as a single order
+    zerodha_cli_core::validation::validate_order(
+        order.order_type.clone(),
+        order.transaction_type.clone(),
+        order.quantity as i32,
+        order.price.unwrap_or(0.0),
+        order.trigger_price,
+        order.disclosed_quantity.map(|q| q as i32),
+        order.product.clone(),
+        last_price,
+    )
+}
+
+pub async fn run_orders_modify(
+    order_id: String,
+    request: ModifyOrder,
+    dry_run: bool,
+    show_payload: bool,
+    api_client: &KiteConnectClient,
+) -> Result<()> {
+    if dry_run {
+        eprintln!("[DRY RUN] Would modify order {}", order_id);
+        if show_payload {
+            print_request_body(&request)?;
+        }
+        return Ok(());
+    }
 
     let response = api_client.modify_order(&order_id, &request).await?;
-    println!("✓ Order modified successfully!");
-    println!("  Order ID: {}", response.order_id);
-    println!("  Status: {:?}", response.status);
+    eprintln!("✓ Order modified successfully!");
+    eprintln!("  Order ID: {}", response.order_id);
+    eprintln!("  Status: {:?}", response.status);
 
     Ok(())
 }
@@ -314,101 +1149,206 @@ pub async fn run_orders_modify(
 pub async fn run_orders_cancel(
     order_id: String,
     _variety: String,
+    dry_run: bool,
+    assume_yes: bool,
     api_client: &KiteConnectClient,
 ) -> Result<()> {
+    if dry_run {
+        eprintln!("[DRY RUN] Would cancel order {}", order_id);
+        return Ok(());
+    }
+
     // Confirm
-    print!("Cancel order {}? [y/N]: ", order_id);
-    let mut input = String::new();
-    std::io::stdin().read_line(&mut input)?;
-    if !input.trim().to_lowercase().starts_with('y') {
-        println!("Cancellation aborted.");
+    if !confirm_or_prompt(&format!("Cancel order {}?", order_id), assume_yes)? {
+        eprintln!("Cancellation aborted.");
         return Ok(());
     }
 
     api_client.cancel_order(&order_id, &_variety).await?;
-    println!("✓ Order cancelled successfully!");
+    eprintln!("✓ Order cancelled successfully!");
 
     Ok(())
 }
 
-pub async fn run_orders_cancel_all(api_client: &KiteConnectClient) -> Result<()> {
+pub async fn run_orders_cancel_all(
+    filter: CancelFilter,
+    dry_run: bool,
+    assume_yes: bool,
+    api_client: &KiteConnectClient,
+) -> Result<()> {
     let orders = api_client.list_orders().await?;
     let open_orders: Vec<_> = orders
         .into_iter()
         .filter(|o| format!("{:?}", o.status) == "Open")
+        .filter(|o| filter.matches(o))
         .collect();
 
     if open_orders.is_empty() {
-        println!("No open orders to cancel.");
+        eprintln!("No open orders to cancel.");
         return Ok(());
     }
 
-    println!("Found {} open orders:", open_orders.len());
+    eprintln!("Found {} open orders:", open_orders.len());
     for order in &open_orders {
-        println!(
+        eprintln!(
             "  {} - {} {:?} @ ₹{:.2}",
             order.order_id, order.tradingsymbol, order.transaction_type, order.price
         );
     }
 
-    print!("\nCancel all open orders? [y/N]: ");
-    let mut input = String::new();
-    std::io::stdin().read_line(&mut input)?;
-    if !input.trim().to_lowercase().starts_with('y') {
-        println!("Cancellation aborted.");
+    if dry_run {
+        eprintln!("\n[DRY RUN] Would cancel {} order(s).", open_orders.len());
+        return Ok(());
+    }
+
+    let prompt = format!("\nCancel these {} order(s)?", open_orders.len());
+    if !confirm_or_prompt(&prompt, assume_yes)? {
+        eprintln!("Cancellation aborted.");
         return Ok(());
     }
 
     for order in open_orders {
         let variety_str = format!("{:?}", order.variety).to_lowercase();
         match api_client.cancel_order(&order.order_id, &variety_str).await {
-            Ok(_) => println!("✓ Cancelled {}", order.order_id),
-            Err(e) => println!("✗ Failed to cancel {}: {}", order.order_id, e),
+            Ok(_) => eprintln!("✓ Cancelled {}", order.order_id),
+            Err(e) => eprintln!("✗ Failed to cancel {}: {}", order.order_id, e),
         }
     }
 
     Ok(())
 }
 
+pub async fn run_orders_stats(output_format: &str, api_client: &KiteConnectClient) -> Result<()> {
+    let (orders, trades) =
+        tokio::try_join!(api_client.list_orders(), api_client.list_trades(None))?;
+    let stats = DayStats::new(&orders, &trades);
+
+    if output_format == "json" {
+        output::emit(output::to_json(&stats)?)?;
+    } else if output_format == "ndjson" {
+        output::emit(serde_json::to_string(&stats)?)?;
+    } else if output_format == "yaml" {
+        output::write_yaml(&stats, output::writer())?;
+    } else {
+        print_stats(&stats)?;
+    }
+
+    Ok(())
+}
+
+fn print_stats(stats: &DayStats) -> Result<()> {
+    use comfy_table::{Cell, CellAlignment, ContentArrangement, Table};
+
+    let mut out = output::writer();
+    let amount = |value: f64| Cell::new(format_inr(value)).set_alignment(CellAlignment::Right);
+
+    let statuses = stats
+        .orders_by_status
+        .iter()
+        .map(|(status, count)| format!("{} {}", count, status))
+        .collect::<Vec<_>>()
+        .join(", ");
+    if statuses.is_empty() {
+        writeln!(out, "Orders: {}", stats.orders)?;
+    } else {
+        writeln!(out, "Orders: {} ({})", stats.orders, statuses)?;
+    }
+    writeln!(out, "Trades: {}", stats.trades)?;
+    writeln!(
+        out,
+        "Bought: {} | Sold: {} | Turnover: {}",
+        format_inr(stats.buy_value),
+        format_inr(stats.sell_value),
+        format_inr(stats.turnover())
+    )?;
+
+    if stats.symbols.is_empty() {
+        return Ok(());
+    }
+    let mut table = Table::new();
+    table.set_header(output::header(vec![
+        "Symbol", "Trades", "Bought", "Sold", "Net Qty", "Buy Value", "Sell Value",
+    ]));
+    for activity in &stats.symbols {
+        table.add_row(vec![
+            Cell::new(&activity.symbol),
+            Cell::new(activity.trades),
+            Cell::new(activity.buy_quantity),
+            Cell::new(activity.sell_quantity),
+            Cell::new(activity.net_quantity),
+            amount(activity.buy_value),
+            amount(activity.sell_value),
+        ]);
+    }
+    table.set_content_arrangement(ContentArrangement::Dynamic);
+    writeln!(out)?;
+    writeln!(out, "{table}")?;
+    Ok(())
+}
+
 pub async fn run_orders_trades(
     order_id: Option<String>,
+    range: TimeRange,
+    page: PageArgs,
     output_format: &str,
     api_client: &KiteConnectClient,
 ) -> Result<()> {
-    let trades = api_client.list_trades(order_id.as_deref()).await?;
+    let trades: Vec<_> = api_client
+        .list_trades(order_id.as_deref())
+        .await?
+        .into_iter()
+        .filter(|t| range.contains(&t.fill_timestamp))
+        .collect();
 
     if trades.is_empty() {
-        println!("No trades found.");
+        output::notice("No trades found.");
         return Ok(());
     }
-
-    if output_format == "json" {
-        println!("{}", serde_json::to_string_pretty(&trades)?);
+    // Match over every trade so a page still shows P&L for positions opened earlier
+    let pnl = zerodha_cli_core::report::realized_pnl_by_trade(&trades);
+    let (trades, footer) = page.apply(trades)?;
+
+    if let Some(template) = output::template() {
+        trades.print_template(&template, &mut output::writer())?;
+    } else if output_format == "json" {
+        output::emit(output::to_json(&trades)?)?;
+    } else if output_format == "ndjson" {
+        trades.print_ndjson(&mut output::writer())?;
+    } else if output_format == "yaml" {
+        trades.print_yaml(&mut output::writer())?;
+    } else if output_format == "csv" {
+        trades.print_csv(&mut output::writer())?;
     } else {
-        print_trades_table(&trades);
+        print_trades_table(&trades, &pnl)?;
+        if let Some(footer) = footer {
+            writeln!(output::writer(), "{}", footer)?;
+        }
     }
 
     Ok(())
 }
 
-fn print_orders_table(orders: &[Order]) {
-    use comfy_table::{Cell, Color, ContentArrangement, Table};
+fn print_orders_table(orders: &[Order]) -> Result<()> {
+    use comfy_table::{Cell, ContentArrangement, Table};
+    use zerodha_cli_core::output::CellColorExt;
+
+    let mut out = output::writer();
 
     let mut table = Table::new();
-    table.set_header(vec![
+    table.set_header(output::header(vec![
         "Order ID", "Symbol", "Type", "Qty", "Price", "Status", "Time",
-    ]);
+    ]));
 
     for order in orders {
         let status_cell = match &order.status {
             zerodha_cli_core::models::OrderStatus::Complete => {
-                Cell::new("COMPLETE").fg(Color::Green)
+                Cell::new("COMPLETE").color(output::theme().positive)
             }
-            zerodha_cli_core::models::OrderStatus::Open => Cell::new("OPEN").fg(Color::Yellow),
+            zerodha_cli_core::models::OrderStatus::Open => Cell::new("OPEN").color(output::theme().pending),
             zerodha_cli_core::models::OrderStatus::Cancelled => {
-                Cell::new("CANCELLED").fg(Color::Red)
+                Cell::new("CANCELLED").color(output::theme().negative)
             }
-            zerodha_cli_core::models::OrderStatus::Rejected => Cell::new("REJECTED").fg(Color::Red),
+            zerodha_cli_core::models::OrderStatus::Rejected => Cell::new("REJECTED").color(output::theme().negative),
             _ => Cell::new(order.status.to_string()),
         };
 
@@ -419,51 +1359,72 @@ fn print_orders_table(orders: &[Order]) {
             Cell::new(order.quantity.to_string()),
             Cell::new(format!("₹{:.2}", order.price)),
             status_cell,
-            Cell::new(format!("{:?}", order.order_timestamp)),
+            Cell::new(output::format_time(&order.order_timestamp)),
         ]);
     }
 
     table.set_content_arrangement(ContentArrangement::Dynamic);
-    println!("{table}");
+    writeln!(out, "{table}")?;
+
+    Ok(())
 }
 
-fn print_order_details(order: &Order) {
-    println!("Order: {}", order.order_id);
-    println!();
-    println!("Symbol: {} ({})", order.tradingsymbol, order.exchange);
-    println!("Type: {}", order.transaction_type);
-    println!("Order Type: {}", order.order_type);
-    println!("Product: {}", order.product);
-    println!("Variety: {}", order.variety);
-    println!("Validity: {}", order.validity);
-    println!("Quantity: {}", order.quantity);
-    println!("Price: ₹{:.2}", order.price);
+fn print_order_details(order: &Order) -> Result<()> {
+    let mut out = output::writer();
+
+    writeln!(out, "Order: {}", order.order_id)?;
+    writeln!(out)?;
+    writeln!(out, "Symbol: {} ({})", order.tradingsymbol, order.exchange)?;
+    writeln!(out, "Type: {}", order.transaction_type)?;
+    writeln!(out, "Order Type: {}", order.order_type)?;
+    writeln!(out, "Product: {}", order.product)?;
+    writeln!(out, "Variety: {}", order.variety)?;
+    writeln!(out, "Validity: {}", order.validity)?;
+    writeln!(out, "Quantity: {}", order.quantity)?;
+    writeln!(out, "Price: ₹{:.2}", order.price)?;
 
     if let Some(trigger) = &order.trigger_price {
-        println!("Trigger Price: ₹{:.2}", trigger);
+        writeln!(out, "Trigger Price: ₹{:.2}", trigger)?;
     }
 
     if let Some(avg_price) = order.average_price {
-        println!("Average Price: ₹{:.2}", avg_price);
+        writeln!(out, "Average Price: ₹{:.2}", avg_price)?;
     }
 
-    println!();
-    println!("Status: {}", order.status);
+    writeln!(out)?;
+    writeln!(out, "Status: {}", order.status)?;
     if let Some(msg) = &order.status_message {
-        println!("Status Message: {}", msg);
+        writeln!(out, "Status Message: {}", msg)?;
     }
-    println!("Placed At: {:?}", order.order_timestamp);
+    writeln!(out, "Placed At: {}", output::format_time(&order.order_timestamp))?;
+    if let Some(updated) = &order.update_timestamp {
+        writeln!(out, "Updated At: {}", output::format_time(updated))?;
+    }
+
+    Ok(())
 }
 
-fn print_trades_table(trades: &[zerodha_cli_core::models::Trade]) {
+fn print_trades_table(
+    trades: &[zerodha_cli_core::models::Trade],
+    pnl: &std::collections::HashMap<String, f64>,
+) -> Result<()> {
     use comfy_table::{Cell, ContentArrangement, Table};
+    use zerodha_cli_core::output::CellColorExt;
+
+    let mut out = output::writer();
 
     let mut table = Table::new();
-    table.set_header(vec![
-        "Trade ID", "Order ID", "Symbol", "Type", "Quantity", "Price", "Time",
-    ]);
+    table.set_header(output::header(vec![
+        "Trade ID", "Order ID", "Symbol", "Type", "Quantity", "Price", "Realized P&L", "Time",
+    ]));
 
     for trade in trades {
+        let pnl_cell = match pnl.get(&trade.trade_id) {
+            Some(&pnl) if pnl > 0.0 => Cell::new(format_inr(pnl)).color(output::theme().positive),
+            Some(&pnl) if pnl < 0.0 => Cell::new(format_inr(pnl)).color(output::theme().negative),
+            Some(&pnl) => Cell::new(format_inr(pnl)),
+            None => Cell::new("-"),
+        };
         table.add_row(vec![
             Cell::new(&trade.trade_id),
             Cell::new(&trade.order_id),
@@ -471,27 +1432,22 @@ fn print_trades_table(trades: &[zerodha_cli_core::models::Trade]) {
             Cell::new(trade.transaction_type.to_string()),
             Cell::new(trade.quantity.to_string()),
             Cell::new(format!("₹{:.2}", trade.average_price)),
-            Cell::new(format!("{:?}", trade.trade_timestamp)),
+            pnl_cell,
+            Cell::new(output::format_time(&trade.fill_timestamp)),
         ]);
     }
 
     table.set_content_arrangement(ContentArrangement::Dynamic);
-    println!("{table}");
-}
+    writeln!(out, "{table}")?;
 
-fn validate_symbol(symbol: &str) -> Result<(String, String)> {
-    let parts: Vec<&str> = symbol.split(':').collect();
-    if parts.len() != 2 {
-        anyhow::bail!("Invalid symbol format. Expected: EXCHANGE:SYMBOL (e.g., NSE:INFY)");
-    }
-    Ok((parts[0].to_string(), parts[1].to_string()))
+    Ok(())
 }
 
-fn parse_transaction_type(s: &str) -> Result<TransactionType> {
+pub(crate) fn parse_transaction_type(s: &str) -> Result<TransactionType> {
     Ok(serde_json::from_str(&format!("\"{}\"", s.to_uppercase()))?)
 }
 
-fn parse_order_type(s: &str) -> Result<OrderType> {
+pub(crate) fn parse_order_type(s: &str) -> Result<OrderType> {
     let s_upper = s.to_uppercase();
     Ok(if s_upper == "MARKET" {
         OrderType::Market
@@ -506,7 +1462,7 @@ fn parse_order_type(s: &str) -> Result<OrderType> {
     })
 }
 
-fn parse_product(s: &str) -> Result<Product> {
+pub(crate) fn parse_product(s: &str) -> Result<Product> {
     let s_upper = s.to_uppercase();
     Ok(if s_upper == "CNC" {
         Product::CNC
@@ -514,10 +1470,12 @@ fn parse_product(s: &str) -> Result<Product> {
         Product::MIS
     } else if s_upper == "NRML" {
         Product::NRML
+    } else if s_upper == "MTF" {
+        Product::MTF
     } else if s_upper == "BO" {
         Product::BO
     } else {
-        anyhow::bail!("Invalid product. Use CNC, MIS, NRML, or BO")
+        anyhow::bail!("Invalid product. Use CNC, MIS, NRML, MTF, or BO")
     })
 }
 
@@ -527,7 +1485,266 @@ fn parse_validity(s: &str) -> Result<Validity> {
         Validity::Day
     } else if s_upper == "IOC" {
         Validity::IOC
+    } else if s_upper == "TTL" {
+        Validity::TTL
     } else {
-        anyhow::bail!("Invalid validity. Use DAY or IOC")
+        anyhow::bail!("Invalid validity. Use DAY, IOC, or TTL")
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use zerodha_cli_core::models::{Exchange, OrderStatus, OrderVariety};
+
+    fn sample_order(symbol: &str, product: Product, tx: TransactionType) -> Order {
+        Order {
+            order_id: "1".to_string(),
+            exchange_order_id: None,
+            parent_order_id: None,
+            status: OrderStatus::Open,
+            status_message: None,
+            tradingsymbol: symbol.to_string(),
+            exchange: Exchange::NSE,
+            variety: OrderVariety::Regular,
+            order_type: OrderType::Limit,
+            transaction_type: tx,
+            validity: Validity::Day,
+            product,
+            quantity: 1,
+            disclosed_quantity: None,
+            price: 100.0,
+            trigger_price: None,
+            average_price: None,
+            pending_quantity: 1,
+            filled_quantity: 0,
+            cancelled_quantity: 0,
+            placed_by: "AB1234".to_string(),
+            order_timestamp: zerodha_cli_core::models::timestamp::parse("2024-02-25 10:30:00")
+                .unwrap(),
+            update_timestamp: None,
+            tag: None,
+        }
+    }
+
+    #[test]
+    fn test_parse_product_mtf() {
+        assert!(matches!(parse_product("mtf").unwrap(), Product::MTF));
+        assert!(matches!(parse_product("MTF").unwrap(), Product::MTF));
+        assert!(parse_product("xyz").unwrap_err().to_string().contains("MTF"));
+    }
+
+    #[test]
+    fn test_cancel_filter_empty_matches_all() {
+        let filter = CancelFilter::default();
+        assert!(filter.matches(&sample_order("INFY", Product::CNC, TransactionType::Buy)));
+        assert!(filter.matches(&sample_order("TCS", Product::MIS, TransactionType::Sell)));
+    }
+
+    #[test]
+    fn test_cancel_filter_product() {
+        let filter = CancelFilter {
+            product: Some(Product::MIS),
+            ..Default::default()
+        };
+        assert!(filter.matches(&sample_order("INFY", Product::MIS, TransactionType::Buy)));
+        assert!(!filter.matches(&sample_order("INFY", Product::CNC, TransactionType::Buy)));
+    }
+
+    #[test]
+    fn test_cancel_filter_symbol() {
+        let filter = CancelFilter {
+            symbol: Some(validate_symbol("NSE:infy").unwrap()),
+            ..Default::default()
+        };
+        assert!(filter.matches(&sample_order("INFY", Product::CNC, TransactionType::Buy)));
+        assert!(!filter.matches(&sample_order("TCS", Product::CNC, TransactionType::Buy)));
+    }
+
+    #[test]
+    fn test_cancel_filter_combined() {
+        let filter = CancelFilter {
+            product: Some(Product::MIS),
+            symbol: None,
+            transaction_type: Some(TransactionType::Sell),
+        };
+        assert!(filter.matches(&sample_order("INFY", Product::MIS, TransactionType::Sell)));
+        assert!(!filter.matches(&sample_order("INFY", Product::MIS, TransactionType::Buy)));
+        assert!(!filter.matches(&sample_order("INFY", Product::NRML, TransactionType::Sell)));
+    }
+
+    #[test]
+    fn test_modify_payload_has_only_order_fields() {
+        let request = ModifyOrder {
+            quantity: Some(5),
+            price: Some(1500.5),
+            trigger_price: None,
+            validity: Some(Validity::Day),
+            disclosed_quantity: None,
+        };
+        let body = serde_json::to_value(&request).unwrap();
+        let mut keys: Vec<&str> = body.as_object().unwrap().keys().map(String::as_str).collect();
+        keys.sort();
+        assert_eq!(keys, vec!["price", "quantity", "validity"]);
+        assert_eq!(body["validity"], "DAY");
+    }
+
+    fn sample_place_order() -> PlaceOrder {
+        PlaceOrder {
+            exchange: "NSE".to_string(),
+            tradingsymbol: "INFY".to_string(),
+            transaction_type: TransactionType::Buy,
+            quantity: 10,
+            order_type: OrderType::Limit,
+            product: Product::CNC,
+            price: Some(1500.0),
+            trigger_price: None,
+            validity: Some(Validity::Day),
+            validity_ttl: None,
+            disclosed_quantity: None,
+            variety: None,
+            tag: None,
+        }
+    }
+
+    #[test]
+    fn test_order_summary_shows_notional() {
+        let request = sample_place_order();
+
+        let summary = order_summary(&request, OrderPrice::Limit(1500.0));
+        assert!(summary.contains("NSE:INFY"));
+        assert!(summary.contains("Side:     BUY"));
+        assert!(summary.contains("Product:  CNC"));
+        assert!(summary.contains(&format!("Notional: {}", format_inr(15000.0))));
+
+        let summary = order_summary(&request, OrderPrice::Market(Some(1510.0)));
+        assert!(summary.contains(&format!("Notional: ~{}", format_inr(15100.0))));
+        assert!(order_summary(&request, OrderPrice::Market(None)).contains("Notional: unknown"));
+    }
+
+    #[test]
+    fn test_check_order_value_against_risk_limit() {
+        let request = sample_place_order();
+
+        // 10 × 1500 = 15000
+        assert!(check_order_value(&request, &OrderPrice::Limit(1500.0), None, false).is_ok());
+        assert!(check_order_value(&request, &OrderPrice::Limit(1500.0), Some(15000.0), false).is_ok());
+        let err = check_order_value(&request, &OrderPrice::Limit(1500.0), Some(14999.0), false)
+            .unwrap_err();
+        assert!(err.to_string().contains("--override-risk"), "{}", err);
+        assert!(check_order_value(&request, &OrderPrice::Limit(1500.0), Some(14999.0), true).is_ok());
+
+        // Market orders are valued at the last price
+        assert!(check_order_value(&request, &OrderPrice::Market(Some(900.0)), Some(10000.0), false).is_ok());
+        assert!(check_order_value(&request, &OrderPrice::Market(Some(1100.0)), Some(10000.0), false).is_err());
+        assert!(check_order_value(&request, &OrderPrice::Market(None), Some(10000.0), false).is_err());
+        assert!(check_order_value(&request, &OrderPrice::Market(None), Some(10000.0), true).is_ok());
+
+        assert!(check_order_value(&request, &OrderPrice::Limit(1.0), Some(0.0), true).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_basket_legs_checked_against_risk_limit() {
+        let client = KiteConnectClient::new("key".into(), "secret".into());
+        let small = sample_place_order();
+        let mut large = sample_place_order();
+        large.quantity = 100;
+
+        let basket = [small.clone(), large];
+        assert!(check_basket_value(&basket, None, false, &client).await.is_ok());
+        let err = check_basket_value(&basket, Some(20000.0), false, &client)
+            .await
+            .unwrap_err();
+        assert!(format!("{:#}", err).contains("Order #2"), "{:#}", err);
+        assert!(check_basket_value(&basket, Some(20000.0), true, &client).await.is_ok());
+        assert!(check_basket_value(&[small], Some(20000.0), false, &client).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_market_order_rejects_non_positive_quantity() {
+        let client = KiteConnectClient::new("key".into(), "secret".into());
+        for quantity in [0, -5] {
+            let params = MarketOrderParams {
+                symbol: "NSE:INFY".to_string(),
+                transaction_type: "BUY".to_string(),
+                quantity,
+                product: None,
+                dry_run: true,
+                show_payload: false,
+                tag: None,
+                wait: None,
+                max_loss: None,
+                override_risk: false,
+                assume_yes: true,
+            };
+            let err = run_orders_market(params, &Config::default(), &client).await.unwrap_err();
+            assert!(err.to_string().contains("greater than 0"), "{:#}", err);
+        }
+    }
+
+    #[test]
+    fn test_basket_market_legs_are_validated() {
+        let mut order = sample_place_order();
+        order.order_type = OrderType::Market;
+        order.price = None;
+        assert!(validate_basket_order(&order, None).is_ok());
+
+        order.disclosed_quantity = Some(11);
+        let err = validate_basket_order(&order, None).unwrap_err();
+        assert!(err.to_string().contains("can't exceed"), "{}", err);
+
+        order.disclosed_quantity = None;
+        order.quantity = 0;
+        assert!(validate_basket_order(&order, None).is_err());
+    }
+
+    #[test]
+    fn test_basket_stop_loss_trigger_checked_against_last_price() {
+        let mut order = sample_place_order();
+        order.order_type = OrderType::SL;
+        order.trigger_price = Some(1490.0);
+
+        assert!(validate_basket_order(&order, None).is_ok());
+        assert!(validate_basket_order(&order, Some(1480.0)).is_ok());
+        let err = validate_basket_order(&order, Some(1495.0)).unwrap_err();
+        assert!(err.to_string().contains("above the last price"), "{}", err);
+    }
+
+    #[test]
+    fn test_wizard_quantity_and_price_parsing() {
+        assert_eq!(parse_quantity("25").unwrap(), 25);
+        assert!(parse_quantity("0").is_err());
+        assert!(parse_quantity("1.5").is_err());
+        assert_eq!(parse_price("₹101.5").unwrap(), 101.5);
+        assert!(parse_price("-1").is_err());
+        assert!(parse_price("abc").is_err());
+    }
+
+    #[test]
+    fn test_order_fields_fall_back_to_config_defaults() {
+        let defaults = DefaultsConfig {
+            product: "MIS".to_string(),
+            order_type: "MARKET".to_string(),
+            validity: "IOC".to_string(),
+            ..DefaultsConfig::default()
+        };
+
+        let (order_type, product, validity) = order_fields(None, None, None, &defaults).unwrap();
+        assert!(matches!(order_type, OrderType::Market));
+        assert_eq!(product, Product::MIS);
+        assert!(matches!(validity, Validity::IOC));
+
+        // Flags win over the configured defaults
+        let (order_type, product, validity) =
+            order_fields(Some("limit"), Some("cnc"), Some("day"), &defaults).unwrap();
+        assert!(matches!(order_type, OrderType::Limit));
+        assert_eq!(product, Product::CNC);
+        assert!(matches!(validity, Validity::Day));
+
+        let bad = DefaultsConfig {
+            validity: "GTC".to_string(),
+            ..DefaultsConfig::default()
+        };
+        assert!(order_fields(None, None, None, &bad).is_err());
+    }
+}
@@ -1,21 +1,60 @@
 //! Portfolio command handlers
 
-use anyhow::Result;
-use zerodha_cli_core::api::KiteConnectClient;
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::path::Path;
+use zerodha_cli_core::{
+    api::KiteConnectClient,
+    config::Config,
+    error::ZerodhaError,
+    models::{
+        AuctionHolding, ConvertPosition, Holding, OrderType, PlaceOrder, Position, PositionType,
+        Product, TransactionType, Validity,
+    },
+    output::{self, format_inr, OutputFormatter},
+    portfolio::{pnl_by_symbol, PortfolioSummary, SymbolPnl},
+    report::{read_tradebook, FinancialYear, TaxReport, Term},
+    validation::parse_symbol,
+};
 
-use super::PortfolioCommands;
+use super::orders::{check_order_value, parse_product, parse_transaction_type, OrderPrice};
+use super::{confirm_or_prompt, PortfolioCommands};
 
 pub async fn run_portfolio(
     cmd: PortfolioCommands,
+    config: &Config,
     api_client: &KiteConnectClient,
     output_format: &str,
+    dry_run: bool,
+    assume_yes: bool,
 ) -> Result<()> {
     match cmd.command {
-        super::PortfolioSubcommands::Holdings => {
+        super::PortfolioSubcommands::Holdings { report: None, .. } => {
             run_portfolio_holdings(output_format, api_client).await
         }
-        super::PortfolioSubcommands::Positions { net, day } => {
-            run_portfolio_positions(net, day, output_format, api_client).await
+        super::PortfolioSubcommands::Holdings {
+            report: Some(year),
+            tradebook,
+        } => run_portfolio_tax_report(&year, tradebook.as_deref(), output_format, api_client).await,
+        super::PortfolioSubcommands::Summary => run_portfolio_summary(output_format, api_client).await,
+        super::PortfolioSubcommands::Pnl => run_portfolio_pnl(output_format, api_client).await,
+        super::PortfolioSubcommands::Auctions => run_portfolio_auctions(output_format, api_client).await,
+        super::PortfolioSubcommands::Positions { net, day, product } => {
+            let product = product.as_deref().map(parse_product).transpose()?;
+            run_portfolio_positions(net, day, product, output_format, api_client).await
+        }
+        super::PortfolioSubcommands::All => run_portfolio_all(output_format, api_client).await,
+        super::PortfolioSubcommands::Squareoff {
+            symbol,
+            all: _,
+            override_risk,
+        } => {
+            let symbol = symbol.as_deref().map(parse_symbol).transpose()?;
+            let limit = config.risk.max_order_value;
+            run_portfolio_squareoff(symbol, limit, override_risk, dry_run, assume_yes, api_client)
+                .await
         }
         super::PortfolioSubcommands::Convert {
             symbol,
@@ -23,7 +62,12 @@ pub async fn run_portfolio(
             quantity,
             from,
             to,
-        } => run_portfolio_convert(symbol, order_type, quantity, from, to, api_client).await,
+            position_type,
+        } => {
+            let request =
+                conversion_request(&symbol, &order_type, quantity, &from, &to, &position_type)?;
+            run_portfolio_convert(request, dry_run, assume_yes, api_client).await
+        }
     }
 }
 
@@ -34,14 +78,145 @@ pub async fn run_portfolio_holdings(
     let holdings = api_client.get_holdings().await?;
 
     if holdings.is_empty() {
-        println!("No holdings found.");
+        output::notice("No holdings found.");
+        return Ok(());
+    }
+
+    if let Some(template) = output::template() {
+        holdings.print_template(&template, &mut output::writer())?;
+    } else if output_format == "json" {
+        output::emit(output::to_json(&holdings)?)?;
+    } else if output_format == "ndjson" {
+        holdings.print_ndjson(&mut output::writer())?;
+    } else if output_format == "yaml" {
+        holdings.print_yaml(&mut output::writer())?;
+    } else if output_format == "csv" {
+        holdings.print_csv(&mut output::writer())?;
+    } else {
+        print_holdings_table(&holdings)?;
+    }
+
+    Ok(())
+}
+
+pub async fn run_portfolio_tax_report(
+    year: &str,
+    tradebook: Option<&Path>,
+    output_format: &str,
+    api_client: &KiteConnectClient,
+) -> Result<()> {
+    let year: FinancialYear = year.parse()?;
+    // The API only has today's trades, far too few for a year's gains
+    let Some(tradebook) = tradebook else {
+        return Err(ZerodhaError::Validation(
+            "--report needs --tradebook <FILE>, a tradebook CSV exported from Console for the \
+             whole period the sold shares were held"
+                .to_string(),
+        )
+        .into());
+    };
+    let file = std::fs::File::open(tradebook)
+        .with_context(|| format!("Failed to open tradebook {}", tradebook.display()))?;
+    let trades = read_tradebook(file)?;
+    let holdings = api_client.get_holdings().await?;
+    let report = TaxReport::build(&trades, &holdings, year);
+
+    if output_format == "json" {
+        output::emit(output::to_json(&report.gains)?)?;
+    } else if output_format == "ndjson" {
+        output::write_ndjson(&report.gains, output::writer())?;
+    } else if output_format == "yaml" {
+        output::write_yaml(&report.gains, output::writer())?;
+    } else {
+        output::write_csv(&report.gains, output::writer())?;
+    }
+
+    // Totals go to stderr so the CSV stays clean
+    eprintln!(
+        "FY {}-{:02}: {} lot(s), short-term {}, long-term {}",
+        year.start_year,
+        (year.start_year + 1) % 100,
+        report.gains.len(),
+        format_inr(report.total_gain(Term::Short)),
+        format_inr(report.total_gain(Term::Long))
+    );
+    for sell in &report.unmatched {
+        eprintln!(
+            "Warning: no buy found for {} {} sold on {}",
+            sell.quantity, sell.symbol, sell.sell_date
+        );
+    }
+
+    Ok(())
+}
+
+pub async fn run_portfolio_summary(
+    output_format: &str,
+    api_client: &KiteConnectClient,
+) -> Result<()> {
+    let (holdings, positions) =
+        tokio::try_join!(api_client.get_holdings(), api_client.get_positions())?;
+    let summary = PortfolioSummary::new(&holdings, &positions);
+
+    if output_format == "json" {
+        output::emit(output::to_json(&summary)?)?;
+    } else if output_format == "ndjson" {
+        output::emit(serde_json::to_string(&summary)?)?;
+    } else if output_format == "yaml" {
+        output::write_yaml(&summary, output::writer())?;
+    } else {
+        print_summary(&summary)?;
+    }
+
+    Ok(())
+}
+
+pub async fn run_portfolio_pnl(output_format: &str, api_client: &KiteConnectClient) -> Result<()> {
+    let (holdings, positions) =
+        tokio::try_join!(api_client.get_holdings(), api_client.get_positions())?;
+    let rows = pnl_by_symbol(&holdings, &positions.net);
+
+    if rows.is_empty() {
+        output::notice("No holdings or positions found.");
+        return Ok(());
+    }
+
+    if output_format == "json" {
+        output::emit(output::to_json(&rows)?)?;
+    } else if output_format == "ndjson" {
+        output::write_ndjson(&rows, output::writer())?;
+    } else if output_format == "yaml" {
+        output::write_yaml(&rows, output::writer())?;
+    } else if output_format == "csv" {
+        output::write_csv(&rows, output::writer())?;
+    } else {
+        print_pnl_table(&rows)?;
+    }
+
+    Ok(())
+}
+
+pub async fn run_portfolio_auctions(
+    output_format: &str,
+    api_client: &KiteConnectClient,
+) -> Result<()> {
+    let auctions = api_client.get_auctions().await?;
+
+    if auctions.is_empty() {
+        output::notice("No holdings eligible for auction.");
         return Ok(());
     }
 
     if output_format == "json" {
-        println!("{}", serde_json::to_string_pretty(&holdings)?);
+        output::emit(output::to_json(&auctions)?)?;
+    } else if output_format == "ndjson" {
+        output::write_ndjson(&auctions, output::writer())?;
+    } else if output_format == "yaml" {
+        output::write_yaml(&auctions, output::writer())?;
+    } else if output_format == "csv" {
+        output::write_csv(&auctions, output::writer())?;
     } else {
-        print_holdings_table(&holdings);
+        print_auctions_table(&auctions)?;
     }
 
     Ok(())
@@ -50,43 +225,384 @@ pub async fn run_portfolio_holdings(
 pub async fn run_portfolio_positions(
     _net: bool,
     _day: bool,
+    product: Option<Product>,
     output_format: &str,
     api_client: &KiteConnectClient,
 ) -> Result<()> {
     let response = api_client.get_positions().await?;
-    let positions = response.net;
+    let positions = with_product(response.net, product.as_ref());
 
     if positions.is_empty() {
-        println!("No positions found.");
+        match product {
+            Some(product) => output::notice(format_args!("No {} positions found.", product)),
+            None => output::notice("No positions found."),
+        }
         return Ok(());
     }
 
+    if let Some(template) = output::template() {
+        positions.print_template(&template, &mut output::writer())?;
+    } else if output_format == "json" {
+        output::emit(output::to_json(&positions)?)?;
+    } else if output_format == "ndjson" {
+        positions.print_ndjson(&mut output::writer())?;
+    } else if output_format == "yaml" {
+        positions.print_yaml(&mut output::writer())?;
+    } else if output_format == "csv" {
+        positions.print_csv(&mut output::writer())?;
+    } else {
+        print_positions_table(&positions)?;
+    }
+
+    Ok(())
+}
+
+/// Positions with `product`, or all of them when no product is given
+fn with_product(positions: Vec<Position>, product: Option<&Product>) -> Vec<Position> {
+    match product {
+        Some(product) => positions.into_iter().filter(|p| p.product == *product).collect(),
+        None => positions,
+    }
+}
+
+/// Holdings plus net positions grouped by product, for `portfolio all`
+#[derive(Debug, Serialize)]
+struct PortfolioView {
+    holdings: Vec<Holding>,
+    positions: BTreeMap<String, Vec<Position>>,
+}
+
+impl PortfolioView {
+    fn new(holdings: Vec<Holding>, positions: Vec<Position>) -> Self {
+        let mut by_product: BTreeMap<String, Vec<Position>> = BTreeMap::new();
+        for position in positions {
+            by_product
+                .entry(position.product.to_string())
+                .or_default()
+                .push(position);
+        }
+        Self {
+            holdings,
+            positions: by_product,
+        }
+    }
+}
+
+pub async fn run_portfolio_all(output_format: &str, api_client: &KiteConnectClient) -> Result<()> {
+    // Holdings and positions have different columns, so there's no single CSV
+    if output_format == "csv" {
+        return Err(ZerodhaError::Validation(
+            "portfolio all has no CSV output; use -o json/ndjson/yaml, or \
+             portfolio holdings/positions -o csv"
+                .to_string(),
+        )
+        .into());
+    }
+
+    let (holdings, positions) =
+        tokio::try_join!(api_client.get_holdings(), api_client.get_positions())?;
+    let view = PortfolioView::new(holdings, positions.net);
+
     if output_format == "json" {
-        println!("{}", serde_json::to_string_pretty(&positions)?);
+        output::emit(output::to_json(&view)?)?;
+    } else if output_format == "ndjson" {
+        output::emit(serde_json::to_string(&view)?)?;
+    } else if output_format == "yaml" {
+        output::write_yaml(&view, output::writer())?;
     } else {
-        print_positions_table(&positions);
+        let mut out = output::writer();
+        writeln!(out, "Holdings (CNC)")?;
+        if view.holdings.is_empty() {
+            writeln!(out, "No holdings found.")?;
+        } else {
+            print_holdings_table(&view.holdings)?;
+        }
+        for (product, positions) in &view.positions {
+            writeln!(out)?;
+            writeln!(out, "Positions ({})", product)?;
+            print_positions_table(positions)?;
+        }
+        if view.positions.is_empty() {
+            writeln!(out)?;
+            writeln!(out, "No positions found.")?;
+        }
     }
 
     Ok(())
 }
 
+/// Build a position conversion request from command-line values
+fn conversion_request(
+    symbol: &str,
+    transaction_type: &str,
+    quantity: i32,
+    from: &str,
+    to: &str,
+    position_type: &str,
+) -> Result<ConvertPosition> {
+    let (exchange, tradingsymbol) = parse_symbol(symbol)?;
+    if quantity <= 0 {
+        anyhow::bail!("Quantity must be greater than 0");
+    }
+    let old_product = parse_product(from)?;
+    let new_product = parse_product(to)?;
+    if old_product == new_product {
+        anyhow::bail!("--from and --to must be different products");
+    }
+    let position_type = match position_type.to_lowercase().as_str() {
+        "day" => PositionType::Day,
+        "overnight" => PositionType::Overnight,
+        _ => anyhow::bail!("Invalid position type. Use day or overnight"),
+    };
+
+    Ok(ConvertPosition {
+        exchange,
+        tradingsymbol,
+        transaction_type: parse_transaction_type(transaction_type)?,
+        position_type,
+        quantity: quantity as u32,
+        old_product,
+        new_product,
+    })
+}
+
 pub async fn run_portfolio_convert(
-    _symbol: String,
-    _order_type: String,
-    _quantity: i32,
-    _from: String,
-    _to: String,
-    _api_client: &KiteConnectClient,
+    request: ConvertPosition,
+    dry_run: bool,
+    assume_yes: bool,
+    api_client: &KiteConnectClient,
+) -> Result<()> {
+    let summary = format!(
+        "{} {} {}:{} ({}) from {} to {}",
+        request.transaction_type,
+        request.quantity,
+        request.exchange,
+        request.tradingsymbol,
+        request.position_type,
+        request.old_product,
+        request.new_product
+    );
+
+    if dry_run {
+        eprintln!("[DRY RUN] Would convert position: {}", summary);
+        return Ok(());
+    }
+
+    if !confirm_or_prompt(&format!("Convert position {}?", summary), assume_yes)? {
+        eprintln!("Conversion cancelled.");
+        return Ok(());
+    }
+
+    api_client.convert_position(&request).await?;
+    eprintln!("✓ Position converted: {}", summary);
+
+    Ok(())
+}
+
+/// Market order that flattens `position`; `None` when it is already flat
+fn squareoff_order(position: &Position) -> Option<PlaceOrder> {
+    let transaction_type = match position.quantity {
+        0 => return None,
+        q if q > 0 => TransactionType::Sell,
+        _ => TransactionType::Buy,
+    };
+
+    Some(PlaceOrder {
+        exchange: position.exchange.to_string(),
+        tradingsymbol: position.tradingsymbol.clone(),
+        transaction_type,
+        quantity: position.quantity.unsigned_abs(),
+        order_type: OrderType::Market,
+        product: position.product.clone(),
+        price: None,
+        trigger_price: None,
+        validity: Some(Validity::Day),
+        validity_ttl: None,
+        disclosed_quantity: None,
+        variety: None,
+        tag: None,
+    })
+}
+
+/// Orders closing every open position, or only those in `symbol`
+fn squareoff_orders(positions: &[Position], symbol: Option<&(String, String)>) -> Vec<PlaceOrder> {
+    positions
+        .iter()
+        .filter(|p| {
+            symbol.is_none_or(|(exchange, tradingsymbol)| {
+                p.exchange.to_string() == *exchange && p.tradingsymbol == *tradingsymbol
+            })
+        })
+        .filter_map(squareoff_order)
+        .collect()
+}
+
+pub async fn run_portfolio_squareoff(
+    symbol: Option<(String, String)>,
+    max_order_value: Option<f64>,
+    override_risk: bool,
+    dry_run: bool,
+    assume_yes: bool,
+    api_client: &KiteConnectClient,
 ) -> Result<()> {
-    println!("Position conversion not yet implemented in CLI.");
+    let positions = api_client.get_positions().await?;
+    let orders = squareoff_orders(&positions.net, symbol.as_ref());
+
+    if orders.is_empty() {
+        match symbol {
+            Some((exchange, tradingsymbol)) => {
+                eprintln!("No open position in {}:{}.", exchange, tradingsymbol)
+            }
+            None => eprintln!("No open positions to square off."),
+        }
+        return Ok(());
+    }
+
+    eprintln!("Positions to square off:");
+    for order in &orders {
+        eprintln!(
+            "  {} {} {}:{} ({}) at MARKET",
+            order.transaction_type, order.quantity, order.exchange, order.tradingsymbol, order.product
+        );
+    }
+
+    // Each order is valued at its position's last price
+    for order in &orders {
+        let ltp = positions
+            .net
+            .iter()
+            .find(|p| {
+                p.exchange.to_string() == order.exchange
+                    && p.tradingsymbol == order.tradingsymbol
+                    && p.product == order.product
+            })
+            .map(|p| p.last_price);
+        check_order_value(order, &OrderPrice::Market(ltp), max_order_value, override_risk)
+            .with_context(|| format!("Can't square off {}", order.tradingsymbol))?;
+    }
+
+    if dry_run {
+        eprintln!("\n[DRY RUN] Would place {} market order(s).", orders.len());
+        return Ok(());
+    }
+
+    if !confirm_or_prompt(
+        &format!("\nSquare off {} position(s) at market?", orders.len()),
+        assume_yes,
+    )? {
+        eprintln!("Square-off cancelled.");
+        return Ok(());
+    }
+
+    let mut failed = 0;
+    for order in &orders {
+        match api_client.place_order(order).await {
+            Ok(response) => eprintln!(
+                "✓ {} {} {} (order {})",
+                order.transaction_type, order.quantity, order.tradingsymbol, response.order_id
+            ),
+            Err(e) => {
+                failed += 1;
+                eprintln!("✗ Failed to square off {}: {:#}", order.tradingsymbol, e);
+            }
+        }
+    }
+
+    if failed > 0 {
+        anyhow::bail!("{} of {} square-off orders failed", failed, orders.len());
+    }
+    Ok(())
+}
+
+fn print_summary(summary: &PortfolioSummary) -> Result<()> {
+    use comfy_table::{Cell, CellAlignment, ContentArrangement, Table};
+    use zerodha_cli_core::output::CellColorExt;
+
+    let mut out = output::writer();
+
+    let pnl = |value: f64| {
+        let color = if value >= 0.0 { output::theme().positive } else { output::theme().negative };
+        Cell::new(format_inr(value))
+            .color(color)
+            .set_alignment(CellAlignment::Right)
+    };
+    let amount = |value: f64| Cell::new(format_inr(value)).set_alignment(CellAlignment::Right);
+
+    let mut table = Table::new();
+    table.add_row(vec![Cell::new("Invested"), amount(summary.invested_value)]);
+    table.add_row(vec![Cell::new("Current value"), amount(summary.current_value)]);
+    table.add_row(vec![Cell::new("Total P&L"), pnl(summary.total_pnl)]);
+    table.add_row(vec![Cell::new("  Realised"), pnl(summary.realised_pnl)]);
+    table.add_row(vec![Cell::new("  Unrealised"), pnl(summary.unrealised_pnl)]);
+    table.add_row(vec![Cell::new("Day P&L"), pnl(summary.day_pnl)]);
+
+    writeln!(
+        out,
+        "Portfolio: {} holdings, {} positions",
+        summary.holdings_count, summary.positions_count
+    )?;
+    table.set_content_arrangement(ContentArrangement::Dynamic);
+    writeln!(out, "{table}")?;
+    writeln!(
+        out,
+        "Holdings return: {:.2}%",
+        summary.holdings_return_percentage()
+    )?;
+
+    Ok(())
+}
+
+fn print_pnl_table(rows: &[SymbolPnl]) -> Result<()> {
+    use comfy_table::{Attribute, Cell, CellAlignment, ContentArrangement, Table};
+    use zerodha_cli_core::output::CellColorExt;
+
+    let mut out = output::writer();
+
+    let pnl = |value: f64| {
+        let color = if value >= 0.0 { output::theme().positive } else { output::theme().negative };
+        Cell::new(format_inr(value))
+            .color(color)
+            .set_alignment(CellAlignment::Right)
+    };
+
+    let mut table = Table::new();
+    table.set_header(output::header(vec![
+        "Symbol", "Holding Qty", "Position Qty", "Realised", "Unrealised", "Total P&L", "Day P&L",
+    ]));
+
+    for row in rows {
+        table.add_row(vec![
+            Cell::new(format!("{}:{}", row.exchange, row.tradingsymbol)),
+            Cell::new(row.holding_quantity.to_string()),
+            Cell::new(row.position_quantity.to_string()),
+            pnl(row.realised_pnl),
+            pnl(row.unrealised_pnl),
+            pnl(row.total_pnl).add_attribute(Attribute::Bold),
+            pnl(row.day_pnl),
+        ]);
+    }
+
+    table.set_content_arrangement(ContentArrangement::Dynamic);
+    writeln!(out, "{table}")?;
+    writeln!(out)?;
+    writeln!(
+        out,
+        "Total P&L: {} | Day P&L: {}",
+        format_inr(rows.iter().map(|r| r.total_pnl).sum()),
+        format_inr(rows.iter().map(|r| r.day_pnl).sum())
+    )?;
+
     Ok(())
 }
 
-fn print_holdings_table(holdings: &[zerodha_cli_core::models::Holding]) {
-    use comfy_table::{Attribute, Cell, Color, ContentArrangement, Table};
+fn print_holdings_table(holdings: &[zerodha_cli_core::models::Holding]) -> Result<()> {
+    use comfy_table::{Attribute, Cell, ContentArrangement, Table};
+    use zerodha_cli_core::output::CellColorExt;
+
+    let mut out = output::writer();
 
     let mut table = Table::new();
-    table.set_header(vec!["Symbol", "Qty", "Avg Price", "LTP", "P&L", "Day Chg%"]);
+    table.set_header(output::header(vec!["Symbol", "Qty", "Avg Price", "LTP", "P&L", "Day Chg%"]));
 
     let mut total_pnl = 0.0;
 
@@ -94,42 +610,71 @@ fn print_holdings_table(holdings: &[zerodha_cli_core::models::Holding]) {
         total_pnl += holding.pnl;
 
         let pnl_cell = if holding.pnl >= 0.0 {
-            Cell::new(format!("₹{:.2}", holding.pnl))
-                .fg(Color::Green)
+            Cell::new(format_inr(holding.pnl))
+                .color(output::theme().positive)
                 .add_attribute(Attribute::Bold)
         } else {
-            Cell::new(format!("₹{:.2}", holding.pnl))
-                .fg(Color::Red)
+            Cell::new(format_inr(holding.pnl))
+                .color(output::theme().negative)
                 .add_attribute(Attribute::Bold)
         };
 
         let chg_cell = if holding.day_change_percentage >= 0.0 {
-            Cell::new(format!("{:.2}%", holding.day_change_percentage)).fg(Color::Green)
+            Cell::new(format!("{:.2}%", holding.day_change_percentage)).color(output::theme().positive)
         } else {
-            Cell::new(format!("{:.2}%", holding.day_change_percentage)).fg(Color::Red)
+            Cell::new(format!("{:.2}%", holding.day_change_percentage)).color(output::theme().negative)
         };
 
         table.add_row(vec![
             Cell::new(&holding.tradingsymbol),
             Cell::new(holding.quantity.to_string()),
-            Cell::new(format!("₹{:.2}", holding.average_price)),
-            Cell::new(format!("₹{:.2}", holding.last_price)),
+            Cell::new(format_inr(holding.average_price)),
+            Cell::new(format_inr(holding.last_price)),
             pnl_cell,
             chg_cell,
         ]);
     }
 
     table.set_content_arrangement(ContentArrangement::Dynamic);
-    println!("{table}");
-    println!();
-    println!("Total P&L: ₹{:.2}", total_pnl);
+    writeln!(out, "{table}")?;
+    writeln!(out)?;
+    writeln!(out, "Total P&L: {}", format_inr(total_pnl))?;
+
+    Ok(())
+}
+
+fn print_auctions_table(auctions: &[AuctionHolding]) -> Result<()> {
+    use comfy_table::{Cell, ContentArrangement, Table};
+
+    let mut out = output::writer();
+
+    let mut table = Table::new();
+    table.set_header(output::header(vec!["Symbol", "Exchange", "Qty", "LTP", "Auction No."]));
+
+    for auction in auctions {
+        table.add_row(vec![
+            Cell::new(&auction.tradingsymbol),
+            Cell::new(auction.exchange.to_string()),
+            Cell::new(auction.quantity.to_string()),
+            Cell::new(format_inr(auction.last_price)),
+            Cell::new(&auction.auction_number),
+        ]);
+    }
+
+    table.set_content_arrangement(ContentArrangement::Dynamic);
+    writeln!(out, "{table}")?;
+
+    Ok(())
 }
 
-fn print_positions_table(positions: &[zerodha_cli_core::models::Position]) {
-    use comfy_table::{Attribute, Cell, Color, ContentArrangement, Table};
+fn print_positions_table(positions: &[zerodha_cli_core::models::Position]) -> Result<()> {
+    use comfy_table::{Attribute, Cell, ContentArrangement, Table};
+    use zerodha_cli_core::output::CellColorExt;
+
+    let mut out = output::writer();
 
     let mut table = Table::new();
-    table.set_header(vec!["Symbol", "Qty", "Avg Price", "LTP", "P&L", "M2M"]);
+    table.set_header(output::header(vec!["Symbol", "Qty", "Avg Price", "LTP", "P&L", "M2M"]));
 
     let mut total_pnl = 0.0;
     let mut total_m2m = 0.0;
@@ -139,36 +684,148 @@ fn print_positions_table(positions: &[zerodha_cli_core::models::Position]) {
         total_m2m += position.m2m;
 
         let pnl_cell = if position.pnl >= 0.0 {
-            Cell::new(format!("₹{:.2}", position.pnl))
-                .fg(Color::Green)
+            Cell::new(format_inr(position.pnl))
+                .color(output::theme().positive)
                 .add_attribute(Attribute::Bold)
         } else {
-            Cell::new(format!("₹{:.2}", position.pnl))
-                .fg(Color::Red)
+            Cell::new(format_inr(position.pnl))
+                .color(output::theme().negative)
                 .add_attribute(Attribute::Bold)
         };
 
         let m2m_cell = if position.m2m >= 0.0 {
-            Cell::new(format!("₹{:.2}", position.m2m)).fg(Color::Green)
+            Cell::new(format_inr(position.m2m)).color(output::theme().positive)
         } else {
-            Cell::new(format!("₹{:.2}", position.m2m)).fg(Color::Red)
+            Cell::new(format_inr(position.m2m)).color(output::theme().negative)
         };
 
         table.add_row(vec![
             Cell::new(&position.tradingsymbol),
             Cell::new(position.quantity.to_string()),
-            Cell::new(format!("₹{:.2}", position.average_price)),
-            Cell::new(format!("₹{:.2}", position.last_price)),
+            Cell::new(format_inr(position.average_price)),
+            Cell::new(format_inr(position.last_price)),
             pnl_cell,
             m2m_cell,
         ]);
     }
 
     table.set_content_arrangement(ContentArrangement::Dynamic);
-    println!("{table}");
-    println!();
-    println!(
-        "Total P&L: ₹{:.2} | Total M2M: ₹{:.2}",
-        total_pnl, total_m2m
-    );
+    writeln!(out, "{table}")?;
+    writeln!(out)?;
+    writeln!(out,
+        "Total P&L: {} | Total M2M: {}",
+        format_inr(total_pnl),
+        format_inr(total_m2m)
+    )?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use zerodha_cli_core::models::PositionsResponse;
+
+    #[tokio::test]
+    async fn test_portfolio_all_rejects_csv() {
+        // Refused before any request is made
+        let client = KiteConnectClient::new("key".into(), "secret".into());
+        let err = run_portfolio_all("csv", &client).await.unwrap_err();
+        assert!(err.to_string().contains("no CSV output"), "{}", err);
+    }
+
+    #[test]
+    fn test_conversion_request() {
+        let request = conversion_request("nse:infy", "buy", 10, "mis", "cnc", "Overnight").unwrap();
+        assert_eq!(request.exchange, "NSE");
+        assert_eq!(request.tradingsymbol, "INFY");
+        assert_eq!(request.old_product, Product::MIS);
+        assert_eq!(request.new_product, Product::CNC);
+        assert_eq!(request.position_type, PositionType::Overnight);
+
+        assert!(conversion_request("NSE:INFY", "BUY", 10, "MIS", "MIS", "day").is_err());
+        assert!(conversion_request("NSE:INFY", "BUY", 0, "MIS", "CNC", "day").is_err());
+        assert!(conversion_request("XYZ:INFY", "BUY", 10, "MIS", "CNC", "day").is_err());
+        assert!(conversion_request("NSE:INFY", "BUY", 10, "MIS", "CNC", "week").is_err());
+
+        // Bare symbols fall back to the default exchange
+        let request = conversion_request("infy", "BUY", 10, "MIS", "CNC", "day").unwrap();
+        assert_eq!((request.exchange.as_str(), request.tradingsymbol.as_str()), ("NSE", "INFY"));
+    }
+
+    fn position(symbol: &str, product: Product, quantity: i32) -> Position {
+        serde_json::from_value(serde_json::json!({
+            "tradingsymbol": symbol,
+            "exchange": "NSE",
+            "instrument_token": 1,
+            "product": product,
+            "quantity": quantity,
+            "average_price": 100.0,
+            "close_price": 100.0,
+            "last_price": 100.0,
+            "pnl": 0.0
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_with_product_filters_mixed_positions() {
+        let response = PositionsResponse {
+            net: vec![
+                position("INFY", Product::MIS, 10),
+                position("INFY", Product::NRML, -5),
+                position("TCS", Product::CNC, 2),
+                position("SBIN", Product::MIS, 0),
+            ],
+            day: vec![],
+        };
+
+        let mis = with_product(response.net.clone(), Some(&Product::MIS));
+        assert_eq!(mis.len(), 2);
+        assert!(mis.iter().all(|p| p.product == Product::MIS));
+        assert!(with_product(response.net.clone(), Some(&Product::MTF)).is_empty());
+        assert_eq!(with_product(response.net.clone(), None).len(), 4);
+
+        let view = PortfolioView::new(vec![], response.net);
+        assert_eq!(
+            view.positions.keys().collect::<Vec<_>>(),
+            ["CNC", "MIS", "NRML"]
+        );
+    }
+
+    #[test]
+    fn test_squareoff_order_opposes_position() {
+        let long = squareoff_order(&position("INFY", Product::MIS, 25)).unwrap();
+        assert!(matches!(long.transaction_type, TransactionType::Sell));
+        assert_eq!(long.quantity, 25);
+        assert!(matches!(long.order_type, OrderType::Market));
+        assert_eq!(long.product, Product::MIS);
+        assert_eq!(long.exchange, "NSE");
+        assert!(matches!(long.validity, Some(Validity::Day)));
+
+        let short = squareoff_order(&position("TCS", Product::NRML, -50)).unwrap();
+        assert!(matches!(short.transaction_type, TransactionType::Buy));
+        assert_eq!(short.quantity, 50);
+
+        assert!(squareoff_order(&position("SBIN", Product::MIS, 0)).is_none());
+    }
+
+    #[test]
+    fn test_squareoff_orders_filters_by_symbol() {
+        let positions = [
+            position("INFY", Product::MIS, 10),
+            position("INFY", Product::NRML, -5),
+            position("TCS", Product::MIS, 0),
+            position("SBIN", Product::MIS, 3),
+        ];
+        assert_eq!(squareoff_orders(&positions, None).len(), 3);
+
+        let infy = ("NSE".to_string(), "INFY".to_string());
+        let orders = squareoff_orders(&positions, Some(&infy));
+        assert_eq!(orders.len(), 2);
+        assert!(orders.iter().all(|o| o.tradingsymbol == "INFY"));
+
+        let tcs = ("NSE".to_string(), "TCS".to_string());
+        assert!(squareoff_orders(&positions, Some(&tcs)).is_empty());
+    }
 }
@@ -1,8 +1,15 @@
 //! Quotes command handlers
 
 use anyhow::Result;
+use serde::Serialize;
 use serde_json;
-use zerodha_cli_core::api::KiteConnectClient;
+use std::io::Write;
+use zerodha_cli_core::{
+    api::KiteConnectClient,
+    models::{Depth, DepthTotals, Quote},
+    output,
+    validation::parse_symbol,
+};
 
 use super::QuotesCommands;
 
@@ -12,8 +19,11 @@ pub async fn run_quotes(
     output_format: &str,
 ) -> Result<()> {
     match cmd.command {
-        super::QuotesSubcommands::Get { symbols } => {
-            run_quotes_get(symbols, output_format, api_client).await?
+        super::QuotesSubcommands::Get { symbols, depth_levels } => {
+            run_quotes_get(symbols, depth_levels, output_format, api_client).await?
+        }
+        super::QuotesSubcommands::Snap { symbols } => {
+            run_quotes_snap(symbols, output_format, api_client).await?
         }
         super::QuotesSubcommands::Ohlc { symbols } => {
             run_quotes_ohlc(symbols, output_format, api_client).await?
@@ -27,6 +37,7 @@ pub async fn run_quotes(
 
 pub async fn run_quotes_get(
     symbols: Vec<String>,
+    depth_levels: usize,
     output_format: &str,
     api_client: &KiteConnectClient,
 ) -> Result<()> {
@@ -34,27 +45,121 @@ pub async fn run_quotes_get(
         anyhow::bail!("No symbols provided. Use: kite quotes get SYMBOL1 SYMBOL2 ...");
     }
 
+    let symbols = normalize_symbols(&symbols)?;
     let symbols_refs: Vec<&str> = symbols.iter().map(|s| s.as_str()).collect();
     let quotes_response = api_client.get_quotes(&symbols_refs).await?;
 
     // Display
     if output_format == "json" {
-        // QuoteResponse doesn't implement Serialize, so serialize each quote individually
+        // One document keyed by symbol, so several quotes still parse as JSON
+        output::emit(output::to_json(&quotes_response.data)?)?;
+    } else if output_format == "ndjson" {
         for (symbol, quote) in quotes_response.data {
-            let json = serde_json::json!({
-                symbol: quote
-            });
-            println!("{}", serde_json::to_string_pretty(&json)?);
+            output::emit(serde_json::to_string(&serde_json::json!({ symbol: quote }))?)?;
         }
+    } else if output_format == "yaml" {
+        output::write_yaml(&quotes_response.data, output::writer())?;
     } else {
         for (symbol, quote) in quotes_response.data {
-            print_quote(&symbol, &quote);
+            print_quote(&symbol, &quote, depth_levels)?;
         }
     }
 
     Ok(())
 }
 
+/// One compact quote line for `quotes snap`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Snap {
+    pub symbol: String,
+    pub last_price: f64,
+    /// Percent change from the previous close
+    pub change_percent: Option<f64>,
+    pub high: f64,
+    pub low: f64,
+    pub volume: Option<u64>,
+}
+
+impl Snap {
+    fn new(symbol: String, quote: &Quote) -> Self {
+        Self {
+            symbol,
+            last_price: quote.last_price,
+            change_percent: quote.day_change_percent(),
+            high: quote.ohlc.high,
+            low: quote.ohlc.low,
+            volume: quote.volume,
+        }
+    }
+}
+
+pub async fn run_quotes_snap(
+    symbols: Vec<String>,
+    output_format: &str,
+    api_client: &KiteConnectClient,
+) -> Result<()> {
+    if symbols.is_empty() {
+        anyhow::bail!("No symbols provided. Use: kite quotes snap SYMBOL1 SYMBOL2 ...");
+    }
+
+    let symbols = normalize_symbols(&symbols)?;
+    let symbols_refs: Vec<&str> = symbols.iter().map(|s| s.as_str()).collect();
+    let quotes_response = api_client.get_quotes(&symbols_refs).await?;
+
+    // Keep the order the symbols were typed in
+    let snaps: Vec<Snap> = symbols
+        .iter()
+        .filter_map(|symbol| {
+            let quote = quotes_response.data.get(symbol)?;
+            Some(Snap::new(symbol.clone(), quote))
+        })
+        .collect();
+
+    if output_format == "json" {
+        output::emit(output::to_json(&snaps)?)?;
+    } else if output_format == "ndjson" {
+        output::write_ndjson(&snaps, output::writer())?;
+    } else if output_format == "yaml" {
+        output::write_yaml(&snaps, output::writer())?;
+    } else if output_format == "csv" {
+        output::write_csv(&snaps, output::writer())?;
+    } else {
+        print_snaps_table(&snaps)?;
+    }
+
+    Ok(())
+}
+
+fn print_snaps_table(snaps: &[Snap]) -> Result<()> {
+    use comfy_table::{Cell, ContentArrangement, Table};
+    use zerodha_cli_core::output::CellColorExt;
+
+    let mut table = Table::new();
+    table.set_header(output::header(vec!["Symbol", "LTP", "Chg %", "High", "Low", "Volume"]));
+
+    for snap in snaps {
+        let change = match snap.change_percent {
+            Some(pct) if pct >= 0.0 => {
+                Cell::new(format!("+{:.2}%", pct)).color(output::theme().positive)
+            }
+            Some(pct) => Cell::new(format!("{:.2}%", pct)).color(output::theme().negative),
+            None => Cell::new("-"),
+        };
+        table.add_row(vec![
+            Cell::new(&snap.symbol),
+            Cell::new(format!("₹{:.2}", snap.last_price)),
+            change,
+            Cell::new(format!("₹{:.2}", snap.high)),
+            Cell::new(format!("₹{:.2}", snap.low)),
+            Cell::new(snap.volume.map_or_else(|| "-".to_string(), |v| v.to_string())),
+        ]);
+    }
+
+    table.set_content_arrangement(ContentArrangement::Dynamic);
+    output::emit(&table)?;
+    Ok(())
+}
+
 pub async fn run_quotes_ohlc(
     symbols: Vec<String>,
     output_format: &str,
@@ -64,15 +169,20 @@ pub async fn run_quotes_ohlc(
         anyhow::bail!("No symbols provided. Use: kite quotes ohlc SYMBOL1 SYMBOL2 ...");
     }
 
+    let symbols = normalize_symbols(&symbols)?;
     let symbols_refs: Vec<&str> = symbols.iter().map(|s| s.as_str()).collect();
     let ohlc_response = api_client.get_ohlc(&symbols_refs).await?;
 
     // Display
     if output_format == "json" {
-        println!("{}", serde_json::to_string_pretty(&ohlc_response)?);
+        output::emit(output::to_json(&ohlc_response)?)?;
+    } else if output_format == "ndjson" {
+        output::emit(serde_json::to_string(&ohlc_response)?)?;
+    } else if output_format == "yaml" {
+        output::write_yaml(&ohlc_response, output::writer())?;
     } else {
         for (symbol, ohlc) in ohlc_response.data {
-            print_ohlc(&symbol, &ohlc);
+            print_ohlc(&symbol, &ohlc)?;
         }
     }
 
@@ -88,17 +198,22 @@ pub async fn run_quotes_ltp(
         anyhow::bail!("No symbols provided. Use: kite quotes ltp SYMBOL1 SYMBOL2 ...");
     }
 
+    let symbols = normalize_symbols(&symbols)?;
     let symbols_refs: Vec<&str> = symbols.iter().map(|s| s.as_str()).collect();
     let ltp_response = api_client.get_ltp(&symbols_refs).await?;
 
     // Display
     if output_format == "json" {
-        println!("{}", serde_json::to_string_pretty(&ltp_response)?);
+        output::emit(output::to_json(&ltp_response)?)?;
+    } else if output_format == "ndjson" {
+        output::emit(serde_json::to_string(&ltp_response)?)?;
+    } else if output_format == "yaml" {
+        output::write_yaml(&ltp_response, output::writer())?;
     } else {
         use comfy_table::{Cell, ContentArrangement, Table};
 
         let mut table = Table::new();
-        table.set_header(vec!["Symbol", "Last Price"]);
+        table.set_header(output::header(vec!["Symbol", "Last Price"]));
 
         for (symbol, ltp_data) in ltp_response.data {
             table.add_row(vec![
@@ -108,77 +223,121 @@ pub async fn run_quotes_ltp(
         }
 
         table.set_content_arrangement(ContentArrangement::Dynamic);
-        println!("{table}");
+        output::emit(&table)?;
     }
 
     Ok(())
 }
 
-fn print_quote(symbol: &str, quote: &zerodha_cli_core::models::Quote) {
-    println!("Quote: {}", symbol);
-    println!();
-    println!("Last Price: ₹{:.2}", quote.last_price);
+/// `EXCHANGE:SYMBOL` keys for the quote APIs, whatever case or form was typed
+fn normalize_symbols(symbols: &[String]) -> Result<Vec<String>> {
+    symbols
+        .iter()
+        .map(|symbol| {
+            let (exchange, tradingsymbol) = parse_symbol(symbol)?;
+            Ok(format!("{}:{}", exchange, tradingsymbol))
+        })
+        .collect()
+}
+
+fn print_quote(
+    symbol: &str,
+    quote: &zerodha_cli_core::models::Quote,
+    depth_levels: usize,
+) -> Result<()> {
+    let mut out = output::writer();
+
+    writeln!(out, "Quote: {}", symbol)?;
+    writeln!(out)?;
+    writeln!(out, "Last Price: ₹{:.2}", quote.last_price)?;
 
     let ohlc = &quote.ohlc;
-    println!(
+    writeln!(out,
         "OHLC: O: ₹{:.2} | H: ₹{:.2} | L: ₹{:.2} | C: ₹{:.2}",
         ohlc.open, ohlc.high, ohlc.low, ohlc.close
-    );
+    )?;
 
     if let Some(oi) = quote.oi {
-        println!("Open Interest: {}", oi);
+        writeln!(out, "Open Interest: {}", oi)?;
     }
 
-    // Depth
-    if !quote.depth.buy.is_empty() {
-        println!();
-        println!("Buy Orders:");
-        for (i, entry) in quote.depth.buy.iter().enumerate().take(5) {
-            println!(
-                "  {}: {} @ ₹{:.2} ({} orders)",
-                i + 1,
-                entry.quantity,
-                entry.price,
-                entry.orders
-            );
+    print_depth(&mut out, &quote.depth, depth_levels)?;
+
+    let depth = &quote.depth;
+    if !depth.buy.is_empty() || !depth.sell.is_empty() {
+        writeln!(out)?;
+        let ratio = depth
+            .imbalance_ratio()
+            .map_or_else(|| "-".to_string(), |r| format!("{:.2}", r));
+        writeln!(out,
+            "Buy/Sell Qty: {} / {} (ratio {})",
+            depth.total_buy_quantity(),
+            depth.total_sell_quantity(),
+            ratio
+        )?;
+        match (depth.spread(), depth.spread_percent(quote.last_price)) {
+            (Some(spread), Some(pct)) => writeln!(out, "Spread: ₹{:.2} ({:.3}%)", spread, pct)?,
+            (Some(spread), None) => writeln!(out, "Spread: ₹{:.2}", spread)?,
+            _ => writeln!(out, "Spread: -")?,
         }
     }
 
-    if !quote.depth.sell.is_empty() {
-        println!();
-        println!("Sell Orders:");
-        for (i, entry) in quote.depth.sell.iter().enumerate().take(5) {
-            println!(
+    writeln!(out)?;
+
+    Ok(())
+}
+
+/// Print up to `levels` of market depth per side, each with a total/VWAP footer
+pub fn print_depth(out: &mut impl Write, depth: &Depth, levels: usize) -> Result<()> {
+    if levels == 0 {
+        return Ok(());
+    }
+    for (title, side) in [("Buy Orders", &depth.buy), ("Sell Orders", &depth.sell)] {
+        if side.is_empty() {
+            continue;
+        }
+        let shown = &side[..side.len().min(levels)];
+        writeln!(out)?;
+        writeln!(out, "{}:", title)?;
+        for (i, entry) in shown.iter().enumerate() {
+            writeln!(out,
                 "  {}: {} @ ₹{:.2} ({} orders)",
                 i + 1,
                 entry.quantity,
                 entry.price,
                 entry.orders
-            );
+            )?;
+        }
+        let totals = DepthTotals::of(shown);
+        match totals.vwap {
+            Some(vwap) => writeln!(out, "  Total: {} @ VWAP ₹{:.2}", totals.quantity, vwap)?,
+            None => writeln!(out, "  Total: {}", totals.quantity)?,
         }
     }
-
-    println!();
+    Ok(())
 }
 
-fn print_ohlc(symbol: &str, ohlc: &zerodha_cli_core::models::OHLCData) {
-    use comfy_table::{Attribute, Cell, Color, ContentArrangement, Table};
+fn print_ohlc(symbol: &str, ohlc: &zerodha_cli_core::models::OHLCData) -> Result<()> {
+    use comfy_table::{Attribute, Cell, ContentArrangement, Table};
+    use zerodha_cli_core::output::CellColorExt;
+
+    let mut out = output::writer();
 
     let change = ohlc.close - ohlc.open;
     let change_pct = (change / ohlc.open) * 100.0;
 
     let change_cell = if change >= 0.0 {
         Cell::new(format!("+₹{:.2} ({:.2}%)", change, change_pct))
-            .fg(Color::Green)
+            .color(output::theme().positive)
             .add_attribute(Attribute::Bold)
     } else {
         Cell::new(format!("₹{:.2} ({:.2}%)", change, change_pct))
-            .fg(Color::Red)
+            .color(output::theme().negative)
             .add_attribute(Attribute::Bold)
     };
 
     let mut table = Table::new();
-    table.set_header(vec!["Symbol", "Open", "High", "Low", "Close", "Change"]);
+    table.set_header(output::header(vec!["Symbol", "Open", "High", "Low", "Close", "Change"]));
 
     table.add_row(vec![
         Cell::new(symbol),
@@ -190,5 +349,33 @@ fn print_ohlc(symbol: &str, ohlc: &zerodha_cli_core::models::OHLCData) {
     ]);
 
     table.set_content_arrangement(ContentArrangement::Dynamic);
-    println!("{table}");
+    writeln!(out, "{table}")?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snap_is_flat() {
+        let quote: Quote = serde_json::from_value(serde_json::json!({
+            "instrument_token": 408065,
+            "last_price": 1470.0,
+            "volume": 90000,
+            "ohlc": {"open": 1490.0, "high": 1495.0, "low": 1465.0, "close": 1500.0},
+            "depth": {"buy": [], "sell": []},
+            "oi": null, "oi_day_high": null, "oi_day_low": null, "timestamp": null
+        }))
+        .unwrap();
+
+        let snap = Snap::new("NSE:INFY".to_string(), &quote);
+        assert!((snap.change_percent.unwrap() + 2.0).abs() < 1e-9);
+        let value = serde_json::to_value(&snap).unwrap();
+        assert_eq!(value["symbol"], "NSE:INFY");
+        assert_eq!(value["high"], 1495.0);
+        assert_eq!(value["volume"], 90000);
+        assert!(value.as_object().unwrap().values().all(|v| !v.is_object()));
+    }
 }
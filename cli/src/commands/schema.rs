@@ -0,0 +1,27 @@
+//! Model schema export (`kite schema`)
+
+use anyhow::Result;
+use zerodha_cli_core::{models::schema, output};
+
+/// Print the JSON Schema for `model`, or the model names when none is given
+pub fn run_schema(model: Option<&str>, output_format: &str) -> Result<()> {
+    let Some(model) = model else {
+        for name in schema::MODELS {
+            output::emit(name)?;
+        }
+        return Ok(());
+    };
+
+    let schema = schema::schema(model).ok_or_else(|| {
+        anyhow::anyhow!(
+            "Unknown model '{}'. Available: {}",
+            model,
+            schema::MODELS.join(", ")
+        )
+    })?;
+    if output_format == "yaml" {
+        output::write_yaml(&schema, output::writer())
+    } else {
+        output::emit(output::to_json(&schema)?)
+    }
+}
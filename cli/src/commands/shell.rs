@@ -1,29 +1,45 @@
 //! Interactive shell command handlers
 
 use anyhow::{Context, Result};
-use rustyline::DefaultEditor;
+use rustyline::history::DefaultHistory;
+use rustyline::{CompletionType, Editor};
+use std::path::Path;
 use std::sync::Arc;
 use tokio::sync::Mutex;
-use zerodha_cli_core::{api::KiteConnectClient, config::Config};
+use zerodha_cli_core::{api::KiteConnectClient, config::Config, output, shell::ShellStats};
 
 use super::{
-    auth, gtt, instruments, margins, orders, portfolio, quotes, status,
-    AuthCommands, AuthSubcommands, GttCommands, GttSubcommands, InstrumentsCommands,
+    alerts, auth, cache, gtt, instruments, margins, orders, portfolio, quotes, status,
+    AlertsCommands, AlertsSubcommands, AuthCommands, AuthSubcommands, CacheCommands, CacheSubcommands, GttCommands, GttModifyArgs, GttSubcommands, InstrumentsCommands,
     InstrumentsSubcommands, MarginsCommands, MarginsSubcommands, OrdersCommands,
-    OrdersSubcommands, PortfolioCommands, PortfolioSubcommands, QuotesCommands,
-    QuotesSubcommands,
+    OrdersSubcommands, PageArgs, PortfolioCommands, PortfolioSubcommands, QuotesCommands,
+    QuotesSubcommands, warn_renamed_flags, RENAMED_GTT_MODIFY_FLAGS, RENAMED_ORDER_FLAGS,
 };
+use super::shell_helper::ShellHelper;
+
+/// Line editor with command completion and hints
+type ShellEditor = Editor<ShellHelper, DefaultHistory>;
 
 pub async fn run_shell(
     config: Arc<Mutex<Config>>,
     api_client: Arc<KiteConnectClient>,
     default_output_format: &str,
+    track_stats: bool,
+    dry_run: bool,
+    assume_yes: bool,
 ) -> Result<()> {
     println!("Zerodha CLI Shell v{}", env!("CARGO_PKG_VERSION"));
     println!("Type 'help' for commands, 'exit' to quit.");
+    if dry_run {
+        println!("Dry-run mode: order, GTT and position changes will not be sent.");
+    }
     println!();
 
-    let mut rl = DefaultEditor::new()?;
+    let editor_config = rustyline::Config::builder()
+        .completion_type(CompletionType::List)
+        .build();
+    let mut rl = ShellEditor::with_config(editor_config)?;
+    rl.set_helper(Some(ShellHelper::new()));
     let history_path = zerodha_cli_core::shell::shell_history_path()?;
 
     // Try to load history
@@ -35,6 +51,10 @@ pub async fn run_shell(
 
     // Track commands executed in this session
     let mut commands_executed = 0;
+    let mut session_stats = ShellStats {
+        sessions: 1,
+        ..Default::default()
+    };
 
     loop {
         let readline = rl.readline("kite> ");
@@ -57,16 +77,27 @@ pub async fn run_shell(
                     continue;
                 }
 
+                if line == "stats" {
+                    if let Err(e) = print_shell_stats(track_stats.then_some(&session_stats)) {
+                        eprintln!("Error: {}", e);
+                    }
+                    continue;
+                }
+
                 // Parse and execute command
-                if let Err(e) = execute_shell_command(
+                let result = execute_shell_command(
                     line,
-                    &mut rl,
                     Arc::clone(&config),
                     Arc::clone(&api_client),
                     default_output_format,
+                    dry_run,
+                    assume_yes,
                 )
-                .await
-                {
+                .await;
+                if track_stats {
+                    session_stats.record(line, result.is_ok());
+                }
+                if let Err(e) = result {
                     eprintln!("Error: {}", e);
                 } else {
                     commands_executed += 1;
@@ -92,16 +123,132 @@ pub async fn run_shell(
         eprintln!("Warning: Failed to save history: {}", e);
     }
 
+    if track_stats {
+        if session_stats.total() > 0 {
+            print_stats_table("This session", &session_stats);
+        }
+        if let Err(e) = save_shell_stats(&session_stats) {
+            eprintln!("Warning: Failed to save shell stats: {}", e);
+        }
+    }
+
     println!("Goodbye! (Executed {} command{})", commands_executed, if commands_executed == 1 { "" } else { "s" });
     Ok(())
 }
 
+/// Add a session's stats to the saved totals
+fn save_shell_stats(session: &ShellStats) -> Result<()> {
+    let mut totals = ShellStats::load()?;
+    totals.merge(session);
+    totals.save()
+}
+
+/// Show this session's stats (when tracked) and the saved totals
+fn print_shell_stats(session: Option<&ShellStats>) -> Result<()> {
+    match session {
+        Some(session) if session.total() > 0 => print_stats_table("This session", session),
+        Some(_) => println!("No commands run yet this session."),
+        None => println!(
+            "Session stats are off. Start the shell with 'kite shell --stats' to track them."
+        ),
+    }
+
+    let totals = ShellStats::load()?;
+    if totals.total() > 0 {
+        print_stats_table(&format!("All time ({} sessions)", totals.sessions), &totals);
+    }
+    Ok(())
+}
+
+fn print_stats_table(title: &str, stats: &ShellStats) {
+    use comfy_table::{Cell, Table};
+
+    let mut table = Table::new();
+    table.set_header(output::header(vec!["Command", "Count", "Errors"]));
+    for (command, usage) in stats.ranked() {
+        table.add_row(vec![
+            Cell::new(command),
+            Cell::new(usage.count.to_string()),
+            Cell::new(usage.errors.to_string()),
+        ]);
+    }
+
+    println!("{}:", title);
+    println!("{table}");
+}
+
+/// Run shell commands from a file, one per line
+///
+/// Blank lines and `#` comments are skipped. Stops at the first failing
+/// command unless `continue_on_error` is set.
+pub async fn run_script(
+    path: &Path,
+    config: Arc<Mutex<Config>>,
+    api_client: Arc<KiteConnectClient>,
+    default_output_format: &str,
+    continue_on_error: bool,
+    dry_run: bool,
+    assume_yes: bool,
+) -> Result<()> {
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read script {}", path.display()))?;
+
+    let mut executed = 0;
+    let mut failed = 0;
+    for (line_no, line) in script_commands(&content) {
+        if line == "exit" || line == "quit" {
+            break;
+        }
+        if line == "help" {
+            print_shell_help();
+            continue;
+        }
+
+        executed += 1;
+        let result = execute_shell_command(
+            line,
+            Arc::clone(&config),
+            Arc::clone(&api_client),
+            default_output_format,
+            dry_run,
+            assume_yes,
+        )
+        .await;
+
+        if let Err(e) = result {
+            failed += 1;
+            let e = e.context(format!("Line {}: {}", line_no, line));
+            if !continue_on_error {
+                eprintln!("Executed {} command(s), {} failed", executed, failed);
+                return Err(e);
+            }
+            eprintln!("Error: {:#}", e);
+        }
+    }
+
+    eprintln!("Executed {} command(s), {} failed", executed, failed);
+    if failed > 0 {
+        anyhow::bail!("{} of {} script commands failed", failed, executed);
+    }
+    Ok(())
+}
+
+/// Non-empty, non-comment script lines with their 1-based line numbers
+fn script_commands(content: &str) -> impl Iterator<Item = (usize, &str)> {
+    content
+        .lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+}
+
 async fn execute_shell_command(
     line: &str,
-    _rl: &mut DefaultEditor,
     config: Arc<Mutex<Config>>,
     api_client: Arc<KiteConnectClient>,
     default_output_format: &str,
+    dry_run: bool,
+    assume_yes: bool,
 ) -> Result<()> {
     let parts: Vec<String> = shellwords::split(line)
         .with_context(|| format!("Failed to parse command: {}", line))?;
@@ -112,6 +259,8 @@ async fn execute_shell_command(
 
     let cmd = parts[0].to_lowercase();
     let args = &parts[1..];
+    let dry_run = dry_run || has_flag(args, &["--dry-run"]);
+    let assume_yes = assume_yes || has_flag(args, &["--yes", "-y", "--force"]);
 
     match cmd.as_str() {
         "auth" => {
@@ -123,7 +272,9 @@ async fn execute_shell_command(
             match subcmd.as_str() {
                 "login" => {
                     let auth_cmd = AuthCommands {
-                        command: AuthSubcommands::Login,
+                        command: AuthSubcommands::Login {
+                            port: parse_flag(args, &["--port"])?,
+                        },
                     };
                     auth::run_auth(auth_cmd, &mut *config.lock().await, &api_client).await?;
                 }
@@ -162,6 +313,32 @@ async fn execute_shell_command(
                     };
                     auth::run_auth(auth_cmd, &mut *config.lock().await, &api_client).await?;
                 }
+                "profiles" => {
+                    let auth_cmd = AuthCommands {
+                        command: AuthSubcommands::Profiles,
+                    };
+                    auth::run_auth(auth_cmd, &mut *config.lock().await, &api_client).await?;
+                }
+                "use" => {
+                    let Some(name) = args.get(1) else {
+                        eprintln!("Usage: auth use <PROFILE>");
+                        return Ok(());
+                    };
+                    let auth_cmd = AuthCommands {
+                        command: AuthSubcommands::Use { name: name.clone() },
+                    };
+                    auth::run_auth(auth_cmd, &mut *config.lock().await, &api_client).await?;
+                }
+                "verify-postback" => {
+                    let Some(payload) = flag_value(args, &["--payload"]) else {
+                        eprintln!("Usage: auth verify-postback --payload <FILE>");
+                        return Ok(());
+                    };
+                    let auth_cmd = AuthCommands {
+                        command: AuthSubcommands::VerifyPostback { payload },
+                    };
+                    auth::run_auth(auth_cmd, &mut *config.lock().await, &api_client).await?;
+                }
                 _ => {
                     eprintln!("Unknown auth subcommand: {}", subcmd);
                     print_shell_help_auth();
@@ -186,13 +363,28 @@ async fn execute_shell_command(
                         command: InstrumentsSubcommands::List {
                             exchange,
                             refresh,
+                            columns: flag_value(args, &["--columns"]),
+                            limit: parse_flag(args, &["--limit"])?.unwrap_or(50),
+                            all: has_flag(args, &["--all"]),
+                            instrument_type: flag_value(args, &["--type"]),
+                            expiry: flag_value(args, &["--expiry"]),
+                            strike: parse_flag(args, &["--strike"])?,
+                            page: page_args(args)?,
                         },
                     };
-                    instruments::run_instruments(instruments_cmd, &api_client, default_output_format).await?;
+                    instruments::run_instruments(
+                        instruments_cmd,
+                        &*config.lock().await,
+                        &api_client,
+                        default_output_format,
+                    )
+                    .await?;
                 }
                 "search" => {
                     if args.len() < 2 {
-                        eprintln!("Usage: instruments search <query> [--exchange <EXCH>]");
+                        eprintln!(
+                            "Usage: instruments search <query> [--exchange <EXCH>|--all-exchanges] [--limit N] [--exact]"
+                        );
                         return Ok(());
                     }
                     let query = args[1].to_string();
@@ -202,9 +394,56 @@ async fn execute_shell_command(
                         .and_then(|i| args.get(i + 1))
                         .cloned();
                     let instruments_cmd = InstrumentsCommands {
-                        command: InstrumentsSubcommands::Search { query, exchange },
+                        command: InstrumentsSubcommands::Search {
+                            query,
+                            exchange,
+                            all_exchanges: has_flag(args, &["--all-exchanges", "--exchange-all"]),
+                            limit: parse_flag(args, &["--limit"])?.unwrap_or(20),
+                            exact: has_flag(args, &["--exact"]),
+                            columns: flag_value(args, &["--columns"]),
+                        },
+                    };
+                    instruments::run_instruments(
+                        instruments_cmd,
+                        &*config.lock().await,
+                        &api_client,
+                        default_output_format,
+                    )
+                    .await?;
+                }
+                "refresh" => {
+                    let instruments_cmd = InstrumentsCommands {
+                        command: InstrumentsSubcommands::Refresh {
+                            exchange: flag_value(args, &["--exchange", "-e"]),
+                            all: has_flag(args, &["--all"]),
+                        },
                     };
-                    instruments::run_instruments(instruments_cmd, &api_client, default_output_format).await?;
+                    instruments::run_instruments(
+                        instruments_cmd,
+                        &*config.lock().await,
+                        &api_client,
+                        default_output_format,
+                    )
+                    .await?;
+                }
+                "chain" => {
+                    if args.len() < 2 {
+                        eprintln!("Usage: instruments chain <EXCHANGE:SYMBOL> [--expiry YYYY-MM-DD]");
+                        return Ok(());
+                    }
+                    let instruments_cmd = InstrumentsCommands {
+                        command: InstrumentsSubcommands::Chain {
+                            symbol: args[1].to_string(),
+                            expiry: flag_value(args, &["--expiry"]),
+                        },
+                    };
+                    instruments::run_instruments(
+                        instruments_cmd,
+                        &*config.lock().await,
+                        &api_client,
+                        default_output_format,
+                    )
+                    .await?;
                 }
                 "get" => {
                     if args.len() < 2 {
@@ -215,7 +454,32 @@ async fn execute_shell_command(
                     let instruments_cmd = InstrumentsCommands {
                         command: InstrumentsSubcommands::Get { symbol },
                     };
-                    instruments::run_instruments(instruments_cmd, &api_client, default_output_format).await?;
+                    instruments::run_instruments(
+                        instruments_cmd,
+                        &*config.lock().await,
+                        &api_client,
+                        default_output_format,
+                    )
+                    .await?;
+                }
+                "token" => {
+                    let Some(token) = args.get(1) else {
+                        eprintln!("Usage: instruments token <TOKEN>");
+                        return Ok(());
+                    };
+                    let token = token
+                        .parse()
+                        .map_err(|_| anyhow::anyhow!("Invalid instrument token: {}", token))?;
+                    let instruments_cmd = InstrumentsCommands {
+                        command: InstrumentsSubcommands::Token { token },
+                    };
+                    instruments::run_instruments(
+                        instruments_cmd,
+                        &*config.lock().await,
+                        &api_client,
+                        default_output_format,
+                    )
+                    .await?;
                 }
                 _ => {
                     eprintln!("Unknown instruments subcommand: {}", subcmd);
@@ -232,12 +496,24 @@ async fn execute_shell_command(
             match subcmd.as_str() {
                 "get" => {
                     if args.len() < 2 {
-                        eprintln!("Usage: quotes get <SYMBOL> [<SYMBOL> ...]");
+                        eprintln!("Usage: quotes get <SYMBOL> [<SYMBOL> ...] [--depth-levels N]");
+                        return Ok(());
+                    }
+                    let depth_levels = parse_flag(args, &["--depth-levels"])?.unwrap_or(5);
+                    let symbols = positional(&args[1..], &["--depth-levels"]);
+                    let quotes_cmd = QuotesCommands {
+                        command: QuotesSubcommands::Get { symbols, depth_levels },
+                    };
+                    quotes::run_quotes(quotes_cmd, &api_client, default_output_format).await?;
+                }
+                "snap" => {
+                    if args.len() < 2 {
+                        eprintln!("Usage: quotes snap <SYMBOL> [<SYMBOL> ...]");
                         return Ok(());
                     }
                     let symbols: Vec<String> = args[1..].to_vec();
                     let quotes_cmd = QuotesCommands {
-                        command: QuotesSubcommands::Get { symbols },
+                        command: QuotesSubcommands::Snap { symbols },
                     };
                     quotes::run_quotes(quotes_cmd, &api_client, default_output_format).await?;
                 }
@@ -282,10 +558,21 @@ async fn execute_shell_command(
                         .position(|a| a == "--status" || a == "-s")
                         .and_then(|i| args.get(i + 1))
                         .cloned();
+                    let tag = args
+                        .iter()
+                        .position(|a| a == "--tag")
+                        .and_then(|i| args.get(i + 1))
+                        .cloned();
                     let orders_cmd = OrdersCommands {
-                        command: OrdersSubcommands::List { status },
+                        command: OrdersSubcommands::List {
+                            status,
+                            tag,
+                            since: flag_value(args, &["--since"]),
+                            until: flag_value(args, &["--until"]),
+                            page: page_args(args)?,
+                        },
                     };
-                    orders::run_orders(orders_cmd, &*config.lock().await, &api_client, default_output_format).await?;
+                    orders::run_orders(orders_cmd, &*config.lock().await, &api_client, default_output_format, dry_run, assume_yes).await?;
                 }
                 "get" => {
                     if args.len() < 2 {
@@ -296,7 +583,7 @@ async fn execute_shell_command(
                     let orders_cmd = OrdersCommands {
                         command: OrdersSubcommands::Get { order_id },
                     };
-                    orders::run_orders(orders_cmd, &*config.lock().await, &api_client, default_output_format).await?;
+                    orders::run_orders(orders_cmd, &*config.lock().await, &api_client, default_output_format, dry_run, assume_yes).await?;
                 }
                 "cancel" => {
                     if args.len() < 2 {
@@ -316,18 +603,125 @@ async fn execute_shell_command(
                             variety,
                         },
                     };
-                    orders::run_orders(orders_cmd, &*config.lock().await, &api_client, default_output_format).await?;
+                    orders::run_orders(orders_cmd, &*config.lock().await, &api_client, default_output_format, dry_run, assume_yes).await?;
+                }
+                "stats" => {
+                    let orders_cmd = OrdersCommands { command: OrdersSubcommands::Stats };
+                    orders::run_orders(orders_cmd, &*config.lock().await, &api_client, default_output_format, dry_run, assume_yes).await?;
                 }
                 "trades" => {
-                    let order_id = args.get(1).cloned();
+                    let order_id = args.get(1).filter(|a| !a.starts_with('-')).cloned();
                     let orders_cmd = OrdersCommands {
-                        command: OrdersSubcommands::Trades { order_id },
+                        command: OrdersSubcommands::Trades {
+                            order_id,
+                            since: flag_value(args, &["--since"]),
+                            until: flag_value(args, &["--until"]),
+                            page: page_args(args)?,
+                        },
                     };
-                    orders::run_orders(orders_cmd, &*config.lock().await, &api_client, default_output_format).await?;
+                    orders::run_orders(orders_cmd, &*config.lock().await, &api_client, default_output_format, dry_run, assume_yes).await?;
+                }
+                "new" => {
+                    let orders_cmd = OrdersCommands {
+                        command: OrdersSubcommands::New {
+                            override_risk: has_flag(args, &["--override-risk"]),
+                        },
+                    };
+                    orders::run_orders(orders_cmd, &*config.lock().await, &api_client, default_output_format, dry_run, assume_yes).await?;
+                }
+                "place" => {
+                    warn_renamed_flags(args, &RENAMED_ORDER_FLAGS);
+                    let symbol = flag_value(args, &["--symbol", "-s"]);
+                    let side = flag_value(args, &["--side", "--order-type"]);
+                    let quantity = parse_flag::<i32>(args, &["--quantity", "-q"])?;
+                    let price = parse_flag::<f64>(args, &["--price", "-p"])?;
+                    let (Some(symbol), Some(side), Some(quantity), Some(price)) =
+                        (symbol, side, quantity, price)
+                    else {
+                        eprintln!("Usage: orders place --symbol <SYMBOL> --side <BUY|SELL> --quantity <QTY> --price <PRICE> [--type <TYPE>] [--trigger-price <PRICE>] [--disclosed-quantity <QTY>] [--product <PRODUCT>] [--validity <VALIDITY>] [--ttl-minutes <MIN>] [--variety <VARIETY>] [--tag <TAG>] [--dry-run [--show-payload]] [--wait] [--wait-timeout <SECS>] [--round-tick] [--max-loss <AMOUNT>] [--override-risk]");
+                        return Ok(());
+                    };
+                    let orders_cmd = OrdersCommands {
+                        command: OrdersSubcommands::Place {
+                            symbol,
+                            side,
+                            order_type: flag_value(args, &["--type", "--order-type-enum"]),
+                            quantity,
+                            price,
+                            trigger_price: parse_flag(args, &["--trigger-price"])?,
+                            disclosed_quantity: parse_flag(args, &["--disclosed-quantity"])?,
+                            product: flag_value(args, &["--product"]),
+                            validity: flag_value(args, &["--validity"]),
+                            ttl_minutes: parse_flag(args, &["--ttl-minutes"])?,
+                            show_payload: has_flag(args, &["--show-payload"]),
+                            variety: flag_value(args, &["--variety"])
+                                .unwrap_or_else(|| "regular".to_string()),
+                            tag: flag_value(args, &["--tag"]),
+                            wait: has_flag(args, &["--wait"]),
+                            wait_timeout: parse_flag(args, &["--wait-timeout"])?.unwrap_or(60),
+                            round_tick: has_flag(args, &["--round-tick"]),
+                            max_loss: parse_flag(args, &["--max-loss"])?,
+                            override_risk: has_flag(args, &["--override-risk"]),
+                        },
+                    };
+                    orders::run_orders(orders_cmd, &*config.lock().await, &api_client, default_output_format, dry_run, assume_yes).await?;
+                }
+                "market" => {
+                    warn_renamed_flags(args, &RENAMED_ORDER_FLAGS);
+                    let symbol = flag_value(args, &["--symbol", "-s"]);
+                    let side = flag_value(args, &["--side", "--order-type"]);
+                    let quantity = parse_flag::<i32>(args, &["--quantity", "-q"])?;
+                    let (Some(symbol), Some(side), Some(quantity)) = (symbol, side, quantity)
+                    else {
+                        eprintln!("Usage: orders market --symbol <SYMBOL> --side <BUY|SELL> --quantity <QTY> [--product <PRODUCT>] [--tag <TAG>] [--dry-run [--show-payload]] [--wait] [--wait-timeout <SECS>] [--max-loss <AMOUNT>] [--override-risk]");
+                        return Ok(());
+                    };
+                    let orders_cmd = OrdersCommands {
+                        command: OrdersSubcommands::Market {
+                            symbol,
+                            side,
+                            quantity,
+                            product: flag_value(args, &["--product", "-p"]),
+                            show_payload: has_flag(args, &["--show-payload"]),
+                            tag: flag_value(args, &["--tag"]),
+                            wait: has_flag(args, &["--wait"]),
+                            wait_timeout: parse_flag(args, &["--wait-timeout"])?.unwrap_or(60),
+                            max_loss: parse_flag(args, &["--max-loss"])?,
+                            override_risk: has_flag(args, &["--override-risk"]),
+                        },
+                    };
+                    orders::run_orders(orders_cmd, &*config.lock().await, &api_client, default_output_format, dry_run, assume_yes).await?;
+                }
+                "modify" => {
+                    if args.len() < 2 {
+                        eprintln!("Usage: orders modify <ORDER_ID> [--price <PRICE>] [--quantity <QTY>] [--trigger-price <PRICE>] [--validity <VALIDITY>] [--disclosed-quantity <QTY>] [--dry-run [--show-payload]]");
+                        return Ok(());
+                    }
+                    let orders_cmd = OrdersCommands {
+                        command: OrdersSubcommands::Modify {
+                            order_id: args[1].to_string(),
+                            price: parse_flag(args, &["--price", "-p"])?,
+                            quantity: parse_flag(args, &["--quantity", "-q"])?,
+                            trigger_price: parse_flag(args, &["--trigger-price"])?,
+                            validity: flag_value(args, &["--validity"]),
+                            disclosed_quantity: parse_flag(args, &["--disclosed-quantity"])?,
+                            show_payload: has_flag(args, &["--show-payload"]),
+                        },
+                    };
+                    orders::run_orders(orders_cmd, &*config.lock().await, &api_client, default_output_format, dry_run, assume_yes).await?;
+                }
+                "cancel-all" => {
+                    let orders_cmd = OrdersCommands {
+                        command: OrdersSubcommands::CancelAll {
+                            product: flag_value(args, &["--product"]),
+                            symbol: flag_value(args, &["--symbol"]),
+                            transaction_type: flag_value(args, &["--transaction-type"]),
+                        },
+                    };
+                    orders::run_orders(orders_cmd, &*config.lock().await, &api_client, default_output_format, dry_run, assume_yes).await?;
                 }
                 _ => {
                     eprintln!("Unknown orders subcommand: {}", subcmd);
-                    eprintln!("Note: place, market, modify, cancel-all not implemented in shell yet");
                     print_shell_help_orders();
                 }
             }
@@ -341,17 +735,63 @@ async fn execute_shell_command(
             match subcmd.as_str() {
                 "holdings" => {
                     let portfolio_cmd = PortfolioCommands {
-                        command: PortfolioSubcommands::Holdings,
+                        command: PortfolioSubcommands::Holdings {
+                            report: flag_value(args, &["--report"]),
+                            tradebook: flag_value(args, &["--tradebook"]).map(Into::into),
+                        },
+                    };
+                    portfolio::run_portfolio(portfolio_cmd, &*config.lock().await, &api_client, default_output_format, dry_run, assume_yes).await?;
+                }
+                "summary" => {
+                    let portfolio_cmd = PortfolioCommands {
+                        command: PortfolioSubcommands::Summary,
+                    };
+                    portfolio::run_portfolio(portfolio_cmd, &*config.lock().await, &api_client, default_output_format, dry_run, assume_yes).await?;
+                }
+                "pnl" => {
+                    let portfolio_cmd = PortfolioCommands {
+                        command: PortfolioSubcommands::Pnl,
+                    };
+                    portfolio::run_portfolio(portfolio_cmd, &*config.lock().await, &api_client, default_output_format, dry_run, assume_yes).await?;
+                }
+                "all" => {
+                    let portfolio_cmd = PortfolioCommands {
+                        command: PortfolioSubcommands::All,
                     };
-                    portfolio::run_portfolio(portfolio_cmd, &api_client, default_output_format).await?;
+                    portfolio::run_portfolio(portfolio_cmd, &*config.lock().await, &api_client, default_output_format, dry_run, assume_yes).await?;
+                }
+                "auctions" => {
+                    let portfolio_cmd = PortfolioCommands {
+                        command: PortfolioSubcommands::Auctions,
+                    };
+                    portfolio::run_portfolio(portfolio_cmd, &*config.lock().await, &api_client, default_output_format, dry_run, assume_yes).await?;
                 }
                 "positions" => {
                     let net = args.contains(&"--net".to_string());
                     let day = args.contains(&"--day".to_string());
                     let portfolio_cmd = PortfolioCommands {
-                        command: PortfolioSubcommands::Positions { net, day },
+                        command: PortfolioSubcommands::Positions {
+                            net,
+                            day,
+                            product: flag_value(args, &["--product"]),
+                        },
                     };
-                    portfolio::run_portfolio(portfolio_cmd, &api_client, default_output_format).await?;
+                    portfolio::run_portfolio(portfolio_cmd, &*config.lock().await, &api_client, default_output_format, dry_run, assume_yes).await?;
+                }
+                "squareoff" | "square-off" => {
+                    let symbol = flag_value(args, &["--symbol", "-s"]);
+                    let all = has_flag(args, &["--all"]);
+                    if symbol.is_some() == all {
+                        anyhow::bail!("Usage: portfolio squareoff --symbol <SYMBOL> | --all [--override-risk]");
+                    }
+                    let portfolio_cmd = PortfolioCommands {
+                        command: PortfolioSubcommands::Squareoff {
+                            symbol,
+                            all,
+                            override_risk: has_flag(args, &["--override-risk"]),
+                        },
+                    };
+                    portfolio::run_portfolio(portfolio_cmd, &*config.lock().await, &api_client, default_output_format, dry_run, assume_yes).await?;
                 }
                 _ => {
                     eprintln!("Unknown portfolio subcommand: {}", subcmd);
@@ -385,6 +825,29 @@ async fn execute_shell_command(
                     };
                     margins::run_margins(margins_cmd, &api_client, default_output_format).await?;
                 }
+                "basket" => {
+                    let Some(file) = args.get(1).cloned() else {
+                        eprintln!("Usage: margins basket <FILE>");
+                        return Ok(());
+                    };
+                    let margins_cmd = MarginsCommands {
+                        command: MarginsSubcommands::Basket { file },
+                    };
+                    margins::run_margins(margins_cmd, &api_client, default_output_format).await?;
+                }
+                "buying-power" => {
+                    let Some(symbol) = flag_value(args, &["--symbol", "-s"]) else {
+                        eprintln!("Usage: margins buying-power --symbol <SYMBOL> [--product <PRODUCT>]");
+                        return Ok(());
+                    };
+                    let margins_cmd = MarginsCommands {
+                        command: MarginsSubcommands::BuyingPower {
+                            symbol,
+                            product: flag_value(args, &["--product"]).unwrap_or_else(|| "CNC".to_string()),
+                        },
+                    };
+                    margins::run_margins(margins_cmd, &api_client, default_output_format).await?;
+                }
                 _ => {
                     eprintln!("Unknown margins subcommand: {}", subcmd);
                     print_shell_help_margins();
@@ -400,9 +863,11 @@ async fn execute_shell_command(
             match subcmd.as_str() {
                 "list" => {
                     let gtt_cmd = GttCommands {
-                        command: GttSubcommands::List,
+                        command: GttSubcommands::List {
+                            status: flag_value(args, &["--status", "-s"]),
+                        },
                     };
-                    gtt::run_gtt(gtt_cmd, &api_client, default_output_format).await?;
+                    gtt::run_gtt(gtt_cmd, &api_client, default_output_format, dry_run, assume_yes).await?;
                 }
                 "get" => {
                     if args.len() < 2 {
@@ -413,7 +878,7 @@ async fn execute_shell_command(
                     let gtt_cmd = GttCommands {
                         command: GttSubcommands::Get { trigger_id },
                     };
-                    gtt::run_gtt(gtt_cmd, &api_client, default_output_format).await?;
+                    gtt::run_gtt(gtt_cmd, &api_client, default_output_format, dry_run, assume_yes).await?;
                 }
                 "delete" => {
                     if args.len() < 2 {
@@ -424,37 +889,213 @@ async fn execute_shell_command(
                     let gtt_cmd = GttCommands {
                         command: GttSubcommands::Delete { trigger_id },
                     };
-                    gtt::run_gtt(gtt_cmd, &api_client, default_output_format).await?;
+                    gtt::run_gtt(gtt_cmd, &api_client, default_output_format, dry_run, assume_yes).await?;
+                }
+                "delete-all" => {
+                    let gtt_cmd = GttCommands {
+                        command: GttSubcommands::DeleteAll {
+                            status: flag_value(args, &["--status", "-s"]),
+                        },
+                    };
+                    gtt::run_gtt(gtt_cmd, &api_client, default_output_format, dry_run, assume_yes).await?;
+                }
+                "create" => {
+                    warn_renamed_flags(args, &RENAMED_ORDER_FLAGS);
+                    let symbol = flag_value(args, &["--symbol", "-s"]);
+                    let side = flag_value(args, &["--side", "--order-type"]);
+                    let quantity = parse_flag::<i32>(args, &["--quantity", "-q"])?;
+                    let price = parse_flag::<f64>(args, &["--price", "-p"])?;
+                    let trigger_price = parse_flag::<f64>(args, &["--trigger-price", "-t"])?;
+                    let (Some(symbol), Some(side), Some(quantity), Some(price), Some(trigger_price)) =
+                        (symbol, side, quantity, price, trigger_price)
+                    else {
+                        eprintln!("Usage: gtt create --symbol <SYMBOL> --side <BUY|SELL> --quantity <QTY> --price <PRICE> --trigger-price <PRICE> [--trigger-type <single|two-leg>] [--target-trigger-price <PRICE>] [--target-price <PRICE>] [--type <MARKET|LIMIT>] [--product <PRODUCT>] [--dry-run [--show-payload]]");
+                        return Ok(());
+                    };
+                    let gtt_cmd = GttCommands {
+                        command: GttSubcommands::Create {
+                            symbol,
+                            side,
+                            quantity,
+                            price,
+                            trigger_price,
+                            trigger_type: flag_value(args, &["--trigger-type"])
+                                .unwrap_or_else(|| "single".to_string()),
+                            target_trigger_price: parse_flag(args, &["--target-trigger-price"])?,
+                            target_price: parse_flag(args, &["--target-price"])?,
+                            order_type: flag_value(args, &["--type", "--order-type-enum"]),
+                            product: flag_value(args, &["--product"]),
+                            show_payload: has_flag(args, &["--show-payload"]),
+                        },
+                    };
+                    gtt::run_gtt(gtt_cmd, &api_client, default_output_format, dry_run, assume_yes).await?;
+                }
+                "modify" => {
+                    if args.len() < 2 {
+                        eprintln!("Usage: gtt modify <TRIGGER_ID> [--price <PRICE>] [--trigger-price <PRICE>] [--quantity <QTY>] [--type <TYPE>] [--trailing-stoploss X] [--stoploss X] [--squareoff X]");
+                        return Ok(());
+                    }
+                    warn_renamed_flags(args, &RENAMED_GTT_MODIFY_FLAGS);
+                    let trigger_id = args[1].to_string();
+                    let gtt_cmd = GttCommands {
+                        command: GttSubcommands::Modify {
+                            trigger_id,
+                            changes: GttModifyArgs {
+                                price: parse_flag(args, &["--price", "-p"])?,
+                                trigger_price: parse_flag(args, &["--trigger-price", "-t"])?,
+                                quantity: parse_flag(args, &["--quantity", "-q"])?,
+                                order_type: flag_value(args, &["--type", "--order-type", "--order-type-enum"]),
+                                trailing_stoploss: parse_flag(args, &["--trailing-stoploss"])?,
+                                stoploss: parse_flag(args, &["--stoploss"])?,
+                                squareoff: parse_flag(args, &["--squareoff"])?,
+                            },
+                        },
+                    };
+                    gtt::run_gtt(gtt_cmd, &api_client, default_output_format, dry_run, assume_yes).await?;
                 }
                 _ => {
                     eprintln!("Unknown GTT subcommand: {}", subcmd);
-                    eprintln!("Note: create, modify not implemented in shell yet");
                     print_shell_help_gtt();
                 }
             }
         }
-        "status" => {
-            status::run_status(&*config.lock().await, &api_client).await?;
+        "cache" => {
+            let command = match args.first().map(|a| a.to_lowercase()).as_deref() {
+                Some("clear") => CacheSubcommands::Clear,
+                Some("info") => CacheSubcommands::Info,
+                Some("path") => CacheSubcommands::Path,
+                Some(other) => {
+                    eprintln!("Unknown cache subcommand: {}", other);
+                    print_shell_help_cache();
+                    return Ok(());
+                }
+                None => {
+                    print_shell_help_cache();
+                    return Ok(());
+                }
+            };
+            cache::run_cache(CacheCommands { command }, &*config.lock().await)?;
         }
+        "alerts" => {
+            let command = match args.first().map(|a| a.to_lowercase()).as_deref() {
+                Some("list") => AlertsSubcommands::List,
+                Some("add") => {
+                    let Some(symbol) = flag_value(args, &["--symbol", "-s"]) else {
+                        eprintln!("Usage: alerts add --symbol <SYMBOL> [--above <PRICE>] [--below <PRICE>] [--exec <CMD>]");
+                        return Ok(());
+                    };
+                    AlertsSubcommands::Add {
+                        symbol,
+                        above: parse_flag(args, &["--above"])?,
+                        below: parse_flag(args, &["--below"])?,
+                        exec: flag_value(args, &["--exec"]),
+                    }
+                }
+                Some("rm") => {
+                    let Some(id) = args.get(1) else {
+                        eprintln!("Usage: alerts rm <ID>");
+                        return Ok(());
+                    };
+                    AlertsSubcommands::Rm {
+                        id: id
+                            .parse()
+                            .map_err(|_| anyhow::anyhow!("Invalid alert ID: {}", id))?,
+                    }
+                }
+                Some("watch") => AlertsSubcommands::Watch {
+                    symbol: flag_value(args, &["--symbol", "-s"]),
+                    above: parse_flag(args, &["--above"])?,
+                    below: parse_flag(args, &["--below"])?,
+                    exec: flag_value(args, &["--exec"]),
+                    interval: parse_flag(args, &["--interval"])?.unwrap_or(5),
+                    once: has_flag(args, &["--once"]),
+                },
+                Some(other) => {
+                    eprintln!("Unknown alerts subcommand: {}", other);
+                    print_shell_help_alerts();
+                    return Ok(());
+                }
+                None => {
+                    print_shell_help_alerts();
+                    return Ok(());
+                }
+            };
+            let config = config.lock().await;
+            alerts::run_alerts(AlertsCommands { command }, &config, &api_client, default_output_format)
+                .await?;
+        }
+        "status" => match args.first().map(|a| a.to_lowercase()).as_deref() {
+            Some("market") => status::run_market_status(default_output_format)?,
+            _ => status::run_status(&*config.lock().await, &api_client).await?,
+        },
         _ => {
-            eprintln!("Unknown command: {}", cmd);
-            print_shell_help();
+            // Fail so scripts don't silently skip mistyped commands
+            anyhow::bail!("Unknown command: {}. Type 'help' for commands.", cmd);
         }
     }
 
     Ok(())
 }
 
+/// Get the value following the first of the given flags
+fn flag_value(args: &[String], names: &[&str]) -> Option<String> {
+    args.iter()
+        .position(|a| names.contains(&a.as_str()))
+        .and_then(|i| args.get(i + 1))
+        .cloned()
+}
+
+/// Check whether any of the given flags is present
+fn has_flag(args: &[String], names: &[&str]) -> bool {
+    args.iter().any(|a| names.contains(&a.as_str()))
+}
+
+/// Get and parse the value following the first of the given flags
+fn parse_flag<T: std::str::FromStr>(args: &[String], names: &[&str]) -> Result<Option<T>> {
+    flag_value(args, names)
+        .map(|v| {
+            v.parse::<T>()
+                .map_err(|_| anyhow::anyhow!("Invalid value for {}: {}", names[0], v))
+        })
+        .transpose()
+}
+
+/// Arguments that are neither one of the value-taking flags `names` nor their values
+fn positional(args: &[String], names: &[&str]) -> Vec<String> {
+    let mut rest = Vec::new();
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if names.contains(&arg.as_str()) {
+            iter.next();
+        } else {
+            rest.push(arg.clone());
+        }
+    }
+    rest
+}
+
+/// Get `--page`/`--page-size` options
+fn page_args(args: &[String]) -> Result<PageArgs> {
+    Ok(PageArgs {
+        page: parse_flag(args, &["--page"])?,
+        page_size: parse_flag(args, &["--page-size"])?,
+    })
+}
+
 fn print_shell_help() {
     println!("Available commands:");
-    println!("  auth [login|status|logout|setup]  Authentication");
-    println!("  instruments [list|search|get]     Browse instruments");
+    println!("  auth [login|status|logout|setup|profiles|use]  Authentication");
+    println!("  instruments [list|search|get|token|refresh|chain]  Browse instruments");
     println!("  quotes [get|ohlc|ltp]             Market data");
-    println!("  orders [list|get|cancel|trades]   Order management");
-    println!("  portfolio [holdings|positions]   Portfolio");
-    println!("  margins [list|equity|commodity]   Margins");
-    println!("  gtt [list|get|delete]             GTT orders");
-    println!("  status                            System status");
+    println!("  orders [list|get|new|place|market|modify|cancel|cancel-all|trades]");
+    println!("                                    Order management");
+    println!("  portfolio [holdings|positions|all|summary|auctions|squareoff]  Portfolio");
+    println!("  margins [list|equity|commodity|basket|buying-power]  Margins");
+    println!("  gtt [list|get|create|modify|delete|delete-all]  GTT orders");
+    println!("  cache [info|clear|path]           Instrument cache");
+    println!("  alerts [list|add|rm|watch]        Price alerts");
+    println!("  status [market]                   System or market status");
+    println!("  stats                             Command usage (kite shell --stats)");
     println!("  help                              Show this help");
     println!("  exit, quit                        Quit shell");
     println!();
@@ -464,38 +1105,74 @@ fn print_shell_help() {
 
 fn print_shell_help_auth() {
     println!("Authentication commands:");
-    println!("  auth login                                    Start OAuth login flow");
+    println!("  auth login [--port <PORT>]                    Start OAuth login flow");
     println!("  auth status                                   Show authentication status");
     println!("  auth logout                                   Logout and invalidate session");
     println!("  auth setup --api-key <KEY> --api-secret <SECRET>  Configure API credentials");
+    println!("  auth profiles                                 List configured profiles");
+    println!("  auth use <PROFILE>                            Set the active profile");
+    println!("  auth verify-postback --payload <FILE>         Check an order postback's checksum");
 }
 
 fn print_shell_help_instruments() {
     println!("Instruments commands:");
-    println!("  instruments list [--exchange <EXCH>] [--refresh]  List instruments");
-    println!("  instruments search <query> [--exchange <EXCH>]     Search by symbol/name");
+    println!("  instruments list [--exchange <EXCH>] [--refresh] [--columns C,..] [--limit N|--all]");
+    println!("                   [--type CE|PE|FUT|EQ] [--expiry YYYY-MM-DD] [--strike X] [--page N] [--page-size M]");
+    println!("                                                   List instruments");
+    println!("  instruments search <query> [--exchange <EXCH>|--all-exchanges] [--limit N] [--exact] [--columns C,..]");
+    println!("                                                   Search by symbol/name");
     println!("  instruments get <SYMBOL>                         Get instrument details");
+    println!("  instruments token <TOKEN>                        Find a cached instrument by token");
+    println!("  instruments refresh [--exchange <EXCH>|--all]    Re-download instrument cache");
+    println!("  instruments chain <EXCH:SYMBOL> [--expiry DATE]  Show option chain");
 }
 
 fn print_shell_help_quotes() {
     println!("Quotes commands:");
-    println!("  quotes get <SYMBOL> [<SYMBOL> ...]   Get full quotes");
+    println!("  quotes get <SYMBOL> [<SYMBOL> ...] [--depth-levels N]  Get full quotes");
+    println!("  quotes snap <SYMBOL> [<SYMBOL> ...]  One line each: LTP, change, high/low, volume");
     println!("  quotes ohlc <SYMBOL> [<SYMBOL> ...]  Get OHLC data");
     println!("  quotes ltp <SYMBOL> [<SYMBOL> ...]   Get last traded price");
 }
 
 fn print_shell_help_orders() {
     println!("Orders commands:");
-    println!("  orders list [--status <STATUS>]           List orders");
+    println!("  orders list [--status <STATUS>] [--tag <TAG>] [--since T] [--until T] [--page N] [--page-size M]");
+    println!("                                            List orders");
     println!("  orders get <ORDER_ID>                     Get order details");
+    println!("  orders new [--override-risk]              Place an order interactively");
+    println!("  orders place --symbol <SYMBOL> --side <BUY|SELL> --quantity <QTY> --price <PRICE>");
+    println!("               [--type <MARKET|LIMIT|SL|SL-M>] [--trigger-price <PRICE>] [--product <PRODUCT>]");
+    println!("               [--validity <VALIDITY>] [--ttl-minutes <MIN>] [--variety <VARIETY>] [--tag <TAG>]");
+    println!("               [--dry-run] [--wait]");
+    println!("               [--max-loss <AMOUNT>] [--override-risk]");
+    println!("                                            Place a limit order");
+    println!("  orders market --symbol <SYMBOL> --side <BUY|SELL> --quantity <QTY>");
+    println!("                [--product <PRODUCT>] [--tag <TAG>] [--dry-run] [--wait]");
+    println!("                [--max-loss <AMOUNT>] [--override-risk]");
+    println!("                                            Place a market order");
+    println!("  orders modify <ORDER_ID> [--price <PRICE>] [--quantity <QTY>] [--trigger-price <PRICE>]");
+    println!("                [--validity <VALIDITY>] [--disclosed-quantity <QTY>]");
+    println!("                                            Modify an order");
     println!("  orders cancel <ORDER_ID>                  Cancel order");
-    println!("  orders trades [ORDER_ID]                  View trade history");
+    println!("  orders cancel-all [--product <PRODUCT>] [--symbol <SYMBOL>] [--transaction-type <BUY|SELL>]");
+    println!("                                            Cancel open orders");
+    println!("  orders trades [ORDER_ID] [--since T] [--until T] [--page N] [--page-size M]");
+    println!("                                            View trade history");
+    println!("  orders stats                              Today's order counts and traded value");
 }
 
 fn print_shell_help_portfolio() {
     println!("Portfolio commands:");
-    println!("  portfolio holdings               View holdings (long-term)");
-    println!("  portfolio positions [--net|--day] View positions");
+    println!("  portfolio holdings [--report FY --tradebook FILE]");
+    println!("                                   View holdings or a realized gains CSV");
+    println!("  portfolio positions [--net|--day] [--product P]  View positions");
+    println!("  portfolio all                    Holdings and positions by product");
+    println!("  portfolio summary                Account value and P&L overview");
+    println!("  portfolio pnl                    P&L per symbol across holdings and positions");
+    println!("  portfolio auctions               Holdings eligible for the auction market");
+    println!("  portfolio squareoff --symbol SYM|--all [--override-risk]");
+    println!("                                   Close positions at market");
 }
 
 fn print_shell_help_margins() {
@@ -503,11 +1180,64 @@ fn print_shell_help_margins() {
     println!("  margins list        View all margin segments");
     println!("  margins equity       View equity margins");
     println!("  margins commodity   View commodity margins");
+    println!("  margins basket <FILE>  Basket margin and hedge benefit");
+    println!("  margins buying-power --symbol <SYMBOL> [--product <PRODUCT>]");
+    println!("                      Estimate how many shares available funds can buy");
+}
+
+fn print_shell_help_cache() {
+    println!("Cache commands:");
+    println!("  cache info   Show cached exchanges, sizes and freshness");
+    println!("  cache clear  Delete all cached instrument files");
+    println!("  cache path   Print the cache directory");
+}
+
+fn print_shell_help_alerts() {
+    println!("Alerts commands:");
+    println!("  alerts list                                   List saved alerts");
+    println!("  alerts add --symbol <SYMBOL> [--above P] [--below P] [--exec CMD]  Save an alert");
+    println!("  alerts rm <ID>                                Remove a saved alert");
+    println!("  alerts watch [--symbol S --above P --below P] [--interval SECS] [--once]");
+    println!("                                                Watch saved or ad-hoc alerts");
 }
 
 fn print_shell_help_gtt() {
     println!("GTT (Good Till Triggered) commands:");
-    println!("  gtt list              List all GTT orders");
+    println!("  gtt list [--status <STATUS>]  List GTT orders (expiring soon in yellow)");
     println!("  gtt get <TRIGGER_ID>  Get GTT details");
+    println!("  gtt create --symbol <SYMBOL> --side <BUY|SELL> --quantity <QTY>");
+    println!("             --price <PRICE> --trigger-price <PRICE> [--trigger-type <single|two-leg>]");
+    println!("             [--target-trigger-price <PRICE>] [--target-price <PRICE>]");
+    println!("             [--type <MARKET|LIMIT>] [--product <PRODUCT>]");
+    println!("                        Create GTT order");
+    println!("  gtt modify <TRIGGER_ID> [--price <PRICE>] [--trigger-price <PRICE>] [--quantity <QTY>]");
+    println!("             [--type <MARKET|LIMIT>] [--trailing-stoploss X] [--stoploss X] [--squareoff X]");
+    println!("                        Modify GTT order");
     println!("  gtt delete <TRIGGER_ID>  Delete GTT order");
+    println!("  gtt delete-all [--status <STATUS>]  Delete every (matching) GTT order");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_script_commands_skip_blanks_and_comments() {
+        let script = "# morning routine\nauth status\n\n  margins list  \n  # positions\nportfolio positions\n";
+        let commands: Vec<_> = script_commands(script).collect();
+        assert_eq!(
+            commands,
+            vec![(2, "auth status"), (4, "margins list"), (6, "portfolio positions")]
+        );
+    }
+
+    #[test]
+    fn test_positional_skips_flag_values() {
+        let args: Vec<String> = ["NSE:INFY", "--depth-levels", "10", "NSE:TCS"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        assert_eq!(positional(&args, &["--depth-levels"]), ["NSE:INFY", "NSE:TCS"]);
+        assert_eq!(parse_flag::<usize>(&args, &["--depth-levels"]).unwrap(), Some(10));
+    }
 }
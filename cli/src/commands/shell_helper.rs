@@ -0,0 +1,338 @@
+//! Tab completion and argument hints for the interactive shell
+
+use rustyline::completion::{Completer, Pair};
+use rustyline::highlight::Highlighter;
+use rustyline::hint::Hinter;
+use rustyline::validate::Validator;
+use rustyline::{Context, Helper};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use zerodha_cli_core::cache::EXCHANGES;
+use zerodha_cli_core::instruments::SymbolIndex;
+
+/// Maximum symbol candidates offered for one completion
+const MAX_SYMBOL_CANDIDATES: usize = 100;
+
+/// Shell commands with their subcommands and the arguments each expects
+const COMMANDS: &[(&str, &[(&str, &str)])] = &[
+    (
+        "auth",
+        &[
+            ("login", "[--port <PORT>]"),
+            ("status", ""),
+            ("logout", ""),
+            ("setup", "--api-key <KEY> --api-secret <SECRET>"),
+            ("profiles", ""),
+            ("use", "<PROFILE>"),
+            ("verify-postback", "--payload <FILE>"),
+        ],
+    ),
+    (
+        "instruments",
+        &[
+            (
+                "list",
+                "[--exchange <EXCH>] [--refresh] [--columns C,..] [--limit N|--all] [--type T] [--expiry D] [--strike X]",
+            ),
+            ("search", "<QUERY> [--exchange <EXCH>|--all-exchanges] [--limit N] [--exact] [--columns C,..]"),
+            ("get", "<EXCH:SYMBOL>"),
+            ("token", "<TOKEN>"),
+            ("refresh", "[--exchange <EXCH>|--all]"),
+            ("chain", "<EXCH:SYMBOL> [--expiry DATE]"),
+        ],
+    ),
+    (
+        "quotes",
+        &[
+            ("get", "<SYMBOL> [<SYMBOL> ...] [--depth-levels N]"),
+            ("snap", "<SYMBOL> [<SYMBOL> ...]"),
+            ("ohlc", "<SYMBOL> [<SYMBOL> ...]"),
+            ("ltp", "<SYMBOL> [<SYMBOL> ...]"),
+        ],
+    ),
+    (
+        "orders",
+        &[
+            (
+                "list",
+                "[--status <STATUS>] [--tag <TAG>] [--since T] [--until T] [--page N]",
+            ),
+            ("get", "<ORDER_ID>"),
+            ("new", "[--override-risk]"),
+            (
+                "place",
+                "--symbol <SYMBOL> --side <BUY|SELL> --quantity <QTY> --price <PRICE>",
+            ),
+            ("market", "--symbol <SYMBOL> --side <BUY|SELL> --quantity <QTY>"),
+            ("modify", "<ORDER_ID> [--price <PRICE>] [--quantity <QTY>]"),
+            ("cancel", "<ORDER_ID>"),
+            ("cancel-all", "[--product <PRODUCT>] [--symbol <SYMBOL>]"),
+            ("trades", "[ORDER_ID] [--since T] [--until T] [--page N]"),
+            ("stats", ""),
+        ],
+    ),
+    (
+        "portfolio",
+        &[
+            ("holdings", "[--report FY --tradebook FILE]"),
+            ("positions", "[--net|--day] [--product <PRODUCT>]"),
+            ("all", ""),
+            ("summary", ""),
+            ("pnl", ""),
+            ("auctions", ""),
+            ("squareoff", "--symbol <SYMBOL> | --all [--override-risk]"),
+        ],
+    ),
+    (
+        "margins",
+        &[
+            ("list", ""),
+            ("equity", ""),
+            ("commodity", ""),
+            ("basket", "<FILE>"),
+            ("buying-power", "--symbol <SYMBOL> [--product <PRODUCT>]"),
+        ],
+    ),
+    (
+        "gtt",
+        &[
+            ("list", "[--status <STATUS>]"),
+            ("get", "<TRIGGER_ID>"),
+            (
+                "create",
+                "--symbol <SYMBOL> --side <BUY|SELL> --quantity <QTY> --price <PRICE> --trigger-price <PRICE>",
+            ),
+            (
+                "modify",
+                "<TRIGGER_ID> [--price <PRICE>] [--trigger-price <PRICE>] [--quantity <QTY>] [--type <TYPE>]",
+            ),
+            ("delete", "<TRIGGER_ID>"),
+            ("delete-all", "[--status <STATUS>]"),
+        ],
+    ),
+    ("cache", &[("info", ""), ("clear", ""), ("path", "")]),
+    (
+        "alerts",
+        &[
+            ("list", ""),
+            ("add", "--symbol <SYMBOL> [--above <PRICE>] [--below <PRICE>] [--exec <CMD>]"),
+            ("rm", "<ID>"),
+            ("watch", "[--symbol <SYMBOL> --above <PRICE> --below <PRICE>] [--interval SECS] [--once]"),
+        ],
+    ),
+    ("status", &[("market", "")]),
+    ("stats", &[]),
+    ("help", &[]),
+    ("exit", &[]),
+    ("quit", &[]),
+];
+
+/// Rustyline helper completing commands, subcommands and cached symbols
+#[derive(Default)]
+pub struct ShellHelper {
+    /// Symbols per exchange, loaded from the cache on first use
+    symbols: RefCell<HashMap<String, SymbolIndex>>,
+}
+
+impl ShellHelper {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Completion candidates for `word`, given the words typed before it
+    fn candidates(&self, previous: &[&str], word: &str) -> Vec<String> {
+        match previous {
+            [] => prefixed(COMMANDS.iter().map(|(name, _)| *name), word),
+            [cmd] => prefixed(subcommands(cmd).iter().map(|(name, _)| *name), word),
+            _ if expects_symbol(previous) => self.symbol_candidates(word),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Complete `EXCH:SYMBOL`, offering exchanges until a `:` is typed
+    fn symbol_candidates(&self, word: &str) -> Vec<String> {
+        let word = word.to_uppercase();
+        let Some((exchange, prefix)) = word.split_once(':') else {
+            return prefixed(EXCHANGES.iter().copied(), &word)
+                .into_iter()
+                .map(|exchange| format!("{}:", exchange))
+                .collect();
+        };
+
+        let mut symbols = self.symbols.borrow_mut();
+        symbols
+            .entry(exchange.to_string())
+            .or_insert_with(|| SymbolIndex::load(exchange))
+            .complete(prefix)
+            .take(MAX_SYMBOL_CANDIDATES)
+            .map(|s| format!("{}:{}", exchange, s))
+            .collect()
+    }
+
+    #[cfg(test)]
+    fn with_symbols(exchange: &str, symbols: &[&str]) -> Self {
+        let helper = Self::new();
+        let index = SymbolIndex::new(symbols.iter().map(|s| s.to_string()));
+        helper
+            .symbols
+            .borrow_mut()
+            .insert(exchange.to_string(), index);
+        helper
+    }
+}
+
+impl Completer for ShellHelper {
+    type Candidate = Pair;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _ctx: &Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<Pair>)> {
+        let line = &line[..pos];
+        let start = line.rfind(char::is_whitespace).map_or(0, |i| i + 1);
+        let previous: Vec<&str> = line[..start].split_whitespace().collect();
+
+        let pairs = self
+            .candidates(&previous, &line[start..])
+            .into_iter()
+            .map(|candidate| Pair {
+                display: candidate.clone(),
+                replacement: candidate,
+            })
+            .collect();
+        Ok((start, pairs))
+    }
+}
+
+impl Hinter for ShellHelper {
+    type Hint = String;
+
+    fn hint(&self, line: &str, pos: usize, _ctx: &Context<'_>) -> Option<String> {
+        if pos < line.len() {
+            return None;
+        }
+        let trailing_space = line.ends_with(char::is_whitespace);
+        let words: Vec<&str> = line.split_whitespace().collect();
+
+        let hint = match words.as_slice() {
+            [cmd] if trailing_space => {
+                let subs = subcommands(cmd);
+                if subs.is_empty() {
+                    return None;
+                }
+                let names: Vec<&str> = subs.iter().map(|(name, _)| *name).collect();
+                format!("[{}]", names.join("|"))
+            }
+            [cmd, sub] => usage(cmd, sub)?.to_string(),
+            _ => return None,
+        };
+
+        Some(if trailing_space {
+            hint
+        } else {
+            format!(" {}", hint)
+        })
+    }
+}
+
+impl Highlighter for ShellHelper {}
+
+impl Validator for ShellHelper {}
+
+impl Helper for ShellHelper {}
+
+/// Subcommands of a top-level command
+fn subcommands(cmd: &str) -> &'static [(&'static str, &'static str)] {
+    COMMANDS
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(cmd))
+        .map_or(&[], |(_, subs)| subs)
+}
+
+/// Arguments expected by a subcommand, if it takes any
+fn usage(cmd: &str, sub: &str) -> Option<&'static str> {
+    subcommands(cmd)
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(sub))
+        .map(|(_, usage)| *usage)
+        .filter(|usage| !usage.is_empty())
+}
+
+/// Whether the next word is an instrument symbol
+fn expects_symbol(previous: &[&str]) -> bool {
+    if previous.last() == Some(&"--symbol") {
+        return true;
+    }
+    let cmd = previous[0].to_lowercase();
+    let sub = previous[1].to_lowercase();
+    match (cmd.as_str(), sub.as_str()) {
+        ("quotes", "get" | "snap" | "ohlc" | "ltp") => true,
+        ("instruments", "get" | "chain") => previous.len() == 2,
+        _ => false,
+    }
+}
+
+fn prefixed<'a>(names: impl Iterator<Item = &'a str>, word: &str) -> Vec<String> {
+    names
+        .filter(|name| name.starts_with(word))
+        .map(str::to_string)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rustyline::history::DefaultHistory;
+
+    fn complete(helper: &ShellHelper, line: &str) -> (usize, Vec<String>) {
+        let history = DefaultHistory::new();
+        let ctx = Context::new(&history);
+        let (start, pairs) = helper.complete(line, line.len(), &ctx).unwrap();
+        (start, pairs.into_iter().map(|p| p.replacement).collect())
+    }
+
+    fn hint(helper: &ShellHelper, line: &str) -> Option<String> {
+        let history = DefaultHistory::new();
+        helper.hint(line, line.len(), &Context::new(&history))
+    }
+
+    #[test]
+    fn test_completes_commands_and_subcommands() {
+        let helper = ShellHelper::new();
+        assert_eq!(complete(&helper, "or"), (0, vec!["orders".to_string()]));
+        assert_eq!(
+            complete(&helper, "orders ca"),
+            (7, vec!["cancel".to_string(), "cancel-all".to_string()])
+        );
+        assert!(complete(&helper, "margins list x").1.is_empty());
+    }
+
+    #[test]
+    fn test_completes_cached_symbols() {
+        let helper = ShellHelper::with_symbols("NSE", &["INFY", "INDIGO", "TCS"]);
+        assert_eq!(
+            complete(&helper, "quotes ltp nse:IN").1,
+            vec!["NSE:INDIGO".to_string(), "NSE:INFY".to_string()]
+        );
+        assert_eq!(
+            complete(&helper, "orders place --symbol NSE:T").1,
+            vec!["NSE:TCS".to_string()]
+        );
+        assert_eq!(
+            complete(&helper, "quotes get B").1,
+            vec!["BSE:".to_string(), "BFO:".to_string()]
+        );
+        assert!(complete(&helper, "orders place --quantity ").1.is_empty());
+    }
+
+    #[test]
+    fn test_hints_next_argument() {
+        let helper = ShellHelper::new();
+        assert_eq!(hint(&helper, "quotes ").as_deref(), Some("[get|snap|ohlc|ltp]"));
+        assert_eq!(hint(&helper, "orders get").as_deref(), Some(" <ORDER_ID>"));
+        assert_eq!(hint(&helper, "margins list"), None);
+        assert_eq!(hint(&helper, "exit "), None);
+    }
+}
@@ -1,7 +1,15 @@
 //! Status command handlers
 
 use anyhow::Result;
-use zerodha_cli_core::{api::KiteConnectClient, auth::AuthStatus, config::Config};
+use std::io::Write;
+use std::time::{Duration, Instant};
+use zerodha_cli_core::{
+    api::{client::KITE_API_VERSION, KiteConnectClient},
+    auth::AuthStatus,
+    config::Config,
+    market::{self, MarketPhase, SegmentStatus},
+    output,
+};
 
 pub async fn run_status(config: &Config, api_client: &KiteConnectClient) -> Result<()> {
     println!("Zerodha CLI Status");
@@ -14,14 +22,13 @@ pub async fn run_status(config: &Config, api_client: &KiteConnectClient) -> Resu
 
     // Config
     println!("Configuration:");
-    if let Ok(config_path) = Config::config_path() {
-        println!("  Config: {}", config_path.display());
-        if config_path.exists() {
-            println!("  Config Status: ✓ Loaded");
-            println!("  API Key: {}", mask_key(&config.api.api_key));
-        } else {
-            println!("  Config Status: Not found (run 'kite auth setup')");
-        }
+    println!("  Config: {}", config.path.display());
+    if config.path.exists() {
+        println!("  Config Status: ✓ Loaded");
+        println!("  Profile: {}", config.profile);
+        println!("  API Key: {}", mask_key(&config.api.api_key));
+    } else {
+        println!("  Config Status: Not found (run 'kite auth setup')");
     }
     println!();
 
@@ -50,9 +57,11 @@ pub async fn run_status(config: &Config, api_client: &KiteConnectClient) -> Resu
 
     // Cache
     println!("Cache:");
-    let exchanges = ["NSE", "BSE", "NFO", "BFO", "MCX", "CDS"];
-    for exchange in exchanges {
-        if let Ok(valid) = zerodha_cli_core::cache::InstrumentCache::is_valid(exchange) {
+    for exchange in zerodha_cli_core::cache::EXCHANGES {
+        if let Ok(valid) = zerodha_cli_core::cache::InstrumentCache::is_valid_with_ttl(
+            exchange,
+            config.cache.instrument_ttl_hours,
+        ) {
             let status = if valid {
                 "✓ Cached"
             } else {
@@ -65,29 +74,142 @@ pub async fn run_status(config: &Config, api_client: &KiteConnectClient) -> Resu
 
     // API Connection
     println!("API Connection:");
-    println!("  Endpoint: https://api.kite.trade");
-    println!("  Status: Checking...");
+    println!("  Endpoint: {}", api_client.base_url());
+    println!("  Kite Version: {}", KITE_API_VERSION);
 
-    // Try a simple API call to check connectivity
-    match api_client.get_margins().await {
-        Ok(_) => println!("  Status: ✓ Connected"),
+    // Reach the host first so network trouble isn't mistaken for an auth failure
+    match api_client.check_reachable(PROBE_TIMEOUT).await {
+        Ok(elapsed) => println!("  Network: ✓ Reachable (round trip {})", format_latency(elapsed)),
+        Err(e) => {
+            println!("  Network: ✗ Unreachable");
+            println!("  Network Error: {:#}", e);
+            return Ok(());
+        }
+    }
+
+    let start = Instant::now();
+    let probe = api_client.get_margins().await;
+    let latency = start.elapsed();
+    // Latency only means something when the server answered
+    let answered = match probe {
+        Ok(_) => {
+            println!("  Status: ✓ Connected");
+            true
+        }
         Err(e) => {
             if e.to_string().contains("401") || e.to_string().contains("Authentication") {
                 println!("  Status: ⚠ Connected but not authenticated");
+                true
             } else {
                 println!("  Status: ✗ Connection failed");
                 println!("  Error: {}", e);
+                false
             }
         }
+    };
+    if answered {
+        println!("  Latency: {}", format_latency(latency));
     }
 
     Ok(())
 }
 
-fn mask_key(key: &str) -> String {
-    if key.len() <= 8 {
-        format!("{}****", &key[..2])
+/// Round trips slower than this are flagged in `kite status`
+const SLOW_LATENCY: Duration = Duration::from_millis(500);
+
+/// How long the reachability check may take
+const PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Milliseconds, with a warning past [`SLOW_LATENCY`]
+fn format_latency(latency: Duration) -> String {
+    let ms = latency.as_millis();
+    if latency > SLOW_LATENCY {
+        format!("{} ms ⚠ slow (over {} ms)", ms, SLOW_LATENCY.as_millis())
+    } else {
+        format!("{} ms", ms)
+    }
+}
+
+pub(super) fn mask_key(key: &str) -> String {
+    if key.is_empty() {
+        "(none)".to_string()
+    } else if key.len() <= 8 || !key.is_ascii() {
+        format!("{}****", key.chars().take(2).collect::<String>())
     } else {
         format!("{}...{}", &key[..4], &key[key.len() - 4..])
     }
 }
+
+pub fn run_market_status(output_format: &str) -> Result<()> {
+    let now = market::now_ist();
+    let statuses = market::market_status(now);
+
+    if output_format == "json" {
+        output::emit(output::to_json(&statuses)?)?;
+    } else if output_format == "ndjson" {
+        output::write_ndjson(&statuses, output::writer())?;
+    } else if output_format == "yaml" {
+        output::write_yaml(&statuses, output::writer())?;
+    } else {
+        print_market_status(now, &statuses)?;
+    }
+
+    Ok(())
+}
+
+fn print_market_status(now: chrono::NaiveDateTime, statuses: &[SegmentStatus]) -> Result<()> {
+    use comfy_table::{Cell, ContentArrangement, Table};
+    use zerodha_cli_core::output::CellColorExt;
+
+    let mut out = output::writer();
+
+    let mut table = Table::new();
+    table.set_header(output::header(vec!["Segment", "Status", "Note", "Next Open"]));
+
+    for status in statuses {
+        let phase_cell = match status.phase {
+            MarketPhase::Open => Cell::new("OPEN").color(output::theme().positive),
+            MarketPhase::PreOpen => Cell::new("PRE-OPEN").color(output::theme().pending),
+            MarketPhase::Closed => Cell::new("CLOSED").color(output::theme().negative),
+        };
+        table.add_row(vec![
+            Cell::new(status.segment),
+            phase_cell,
+            Cell::new(status.reason.as_deref().unwrap_or("-")),
+            Cell::new(
+                status
+                    .next_open
+                    .map(|t| t.format("%a %d %b %Y %H:%M").to_string())
+                    .unwrap_or_else(|| "-".to_string()),
+            ),
+        ]);
+    }
+
+    writeln!(out, "Market status at {} IST", now.format("%Y-%m-%d %H:%M"))?;
+    table.set_content_arrangement(ContentArrangement::Dynamic);
+    writeln!(out, "{table}")?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_latency() {
+        assert_eq!(format_latency(Duration::from_millis(87)), "87 ms");
+        assert_eq!(format_latency(Duration::from_millis(500)), "500 ms");
+        assert_eq!(
+            format_latency(Duration::from_millis(1240)),
+            "1240 ms ⚠ slow (over 500 ms)"
+        );
+    }
+
+    #[test]
+    fn test_mask_key() {
+        assert_eq!(mask_key(""), "(none)");
+        assert_eq!(mask_key("a"), "a****");
+        assert_eq!(mask_key("abcdefghijkl"), "abcd...ijkl");
+    }
+}
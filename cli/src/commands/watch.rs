@@ -0,0 +1,172 @@
+//! Re-run read-only commands on an interval (`--watch`)
+
+use anyhow::Result;
+use std::io::{IsTerminal, Write};
+use std::time::Duration;
+use zerodha_cli_core::{
+    api::KiteConnectClient,
+    config::Config,
+    error::{exit_code, exit_code_for, ZerodhaError},
+};
+
+use super::{
+    gtt, margins, orders, portfolio, quotes, status, Commands, GttCommands, GttSubcommands,
+    OrdersCommands, OrdersSubcommands, PortfolioCommands, PortfolioSubcommands, StatusSubcommands,
+};
+
+/// Whether a command only reads state and is safe to repeat
+pub fn is_read_only(command: &Commands) -> bool {
+    match command {
+        Commands::Quotes(_) | Commands::Margins(_) | Commands::Status { .. } => true,
+        Commands::Portfolio(PortfolioCommands { command }) => matches!(
+            command,
+            PortfolioSubcommands::Holdings { .. }
+                | PortfolioSubcommands::Summary
+                | PortfolioSubcommands::Pnl
+                | PortfolioSubcommands::Auctions
+                | PortfolioSubcommands::Positions { .. }
+                | PortfolioSubcommands::All
+        ),
+        Commands::Orders(OrdersCommands { command }) => matches!(
+            command,
+            OrdersSubcommands::List { .. }
+                | OrdersSubcommands::Get { .. }
+                | OrdersSubcommands::Trades { .. }
+                | OrdersSubcommands::Stats
+        ),
+        Commands::Gtt(GttCommands { command }) => {
+            matches!(command, GttSubcommands::List { .. } | GttSubcommands::Get { .. })
+        }
+        _ => false,
+    }
+}
+
+/// Refuse to watch anything that could place, modify or cancel orders
+pub fn ensure_watchable(command: &Commands) -> Result<()> {
+    if !is_read_only(command) {
+        return Err(ZerodhaError::Validation(
+            "--watch only works with read-only commands (quotes, portfolio, margins, \
+             orders list/get/trades, gtt list/get, status)"
+                .to_string(),
+        )
+        .into());
+    }
+    Ok(())
+}
+
+/// Clear the screen and re-run `command` every `interval_secs` until Ctrl+C
+pub async fn run_watch(
+    command: Commands,
+    interval_secs: u64,
+    config: &Config,
+    api_client: &KiteConnectClient,
+    output_format: &str,
+) -> Result<()> {
+    ensure_watchable(&command)?;
+
+    let clear = std::io::stdout().is_terminal();
+    let mut ticker = tokio::time::interval(Duration::from_secs(interval_secs.max(1)));
+    ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+    loop {
+        tokio::select! {
+            _ = ticker.tick() => {}
+            _ = tokio::signal::ctrl_c() => break,
+        }
+
+        if clear {
+            print!("\x1B[2J\x1B[H");
+            println!(
+                "Every {}s — {} (Ctrl+C to stop)\n",
+                interval_secs.max(1),
+                chrono::Local::now().format("%H:%M:%S")
+            );
+            std::io::stdout().flush()?;
+        }
+
+        match run_read_only(command.clone(), config, api_client, output_format).await {
+            Ok(()) => {}
+            Err(e) if exit_code_for(&e) == exit_code::AUTH => return Err(e),
+            Err(e) => eprintln!("Error: {:#}", e),
+        }
+    }
+
+    Ok(())
+}
+
+async fn run_read_only(
+    command: Commands,
+    config: &Config,
+    api_client: &KiteConnectClient,
+    output_format: &str,
+) -> Result<()> {
+    match command {
+        Commands::Quotes(cmd) => quotes::run_quotes(cmd, api_client, output_format).await,
+        Commands::Orders(cmd) => orders::run_orders(cmd, config, api_client, output_format, false, false).await,
+        Commands::Portfolio(cmd) => {
+            portfolio::run_portfolio(cmd, config, api_client, output_format, false, false).await
+        }
+        Commands::Margins(cmd) => margins::run_margins(cmd, api_client, output_format).await,
+        Commands::Gtt(cmd) => gtt::run_gtt(cmd, api_client, output_format, false, false).await,
+        Commands::Status { command: None } => status::run_status(config, api_client).await,
+        Commands::Status {
+            command: Some(StatusSubcommands::Market),
+        } => status::run_market_status(output_format),
+        _ => unreachable!("checked by ensure_watchable"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::{AlertsCommands, AlertsSubcommands, Cli};
+    use clap::Parser;
+
+    fn command(args: &[&str]) -> Commands {
+        Cli::parse_from(std::iter::once("kite").chain(args.iter().copied())).command
+    }
+
+    #[test]
+    fn test_only_read_commands_are_watchable() {
+        for args in [
+            &["quotes", "ltp", "NSE:INFY"][..],
+            &["portfolio", "positions"],
+            &["portfolio", "pnl"],
+            &["margins", "list"],
+            &["orders", "list"],
+            &["orders", "stats"],
+            &["gtt", "list"],
+            &["status", "market"],
+        ] {
+            assert!(ensure_watchable(&command(args)).is_ok(), "{:?}", args);
+        }
+
+        for args in [
+            &["orders", "cancel", "123"][..],
+            &["orders", "market", "-s", "NSE:INFY", "--order-type", "BUY", "-q", "1"],
+            &["gtt", "delete", "1"],
+            &["portfolio", "convert", "-s", "NSE:INFY", "--order-type", "BUY", "-q", "1", "--from", "MIS", "--to", "CNC"],
+            &["portfolio", "squareoff", "--all"],
+            &["alerts", "watch"],
+            &["instruments", "refresh"],
+        ] {
+            let err = ensure_watchable(&command(args)).unwrap_err();
+            assert_eq!(exit_code_for(&err), exit_code::VALIDATION, "{:?}", args);
+        }
+    }
+
+    #[test]
+    fn test_alerts_watch_keeps_its_own_interval() {
+        let cli = Cli::parse_from(["kite", "alerts", "watch", "--interval", "10"]);
+        assert!(!cli.watch);
+        assert!(matches!(
+            cli.command,
+            Commands::Alerts(AlertsCommands {
+                command: AlertsSubcommands::Watch { interval: 10, .. }
+            })
+        ));
+
+        let cli = Cli::parse_from(["kite", "--watch", "--interval", "2", "orders", "list"]);
+        assert_eq!(cli.interval, 2);
+    }
+}
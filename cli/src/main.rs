@@ -2,16 +2,13 @@
 //!
 //! A terminal-based trading tool for Zerodha's Kite Connect API
 
-use anyhow::Result;
+use zerodha_cli_core::error::exit_code_for;
 
 #[tokio::main]
-async fn main() -> Result<()> {
-    // Initialize logging
-    tracing_subscriber::fmt::init();
-
-    // Load .env from current directory
-    dotenv::from_filename(".env").ok();
-    
-    // Run CLI
-    zerodha_cli::run().await
+async fn main() {
+    // Run CLI, exiting with a code that identifies the failure kind
+    if let Err(e) = zerodha_cli::run().await {
+        eprintln!("Error: {:?}", e);
+        std::process::exit(exit_code_for(&e));
+    }
 }
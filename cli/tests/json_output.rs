@@ -0,0 +1,69 @@
+//! Data goes to stdout and everything else to stderr, so JSON output pipes cleanly
+
+use std::path::Path;
+use std::process::{Command, Output};
+use zerodha_cli_core::{cache::InstrumentCache, models::Instrument};
+
+fn kite(home: &Path, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_kite"))
+        .args(args)
+        .current_dir(home)
+        .env("HOME", home)
+        .env("XDG_CACHE_HOME", home.join("cache"))
+        .env("XDG_CONFIG_HOME", home.join("config"))
+        .env("ZERODHA_API_KEY", "test_key")
+        .env("ZERODHA_API_SECRET", "test_secret")
+        .env_remove("ZERODHA_ACCESS_TOKEN")
+        .output()
+        .expect("failed to run kite")
+}
+
+fn instrument(token: u64, symbol: &str) -> Instrument {
+    serde_json::from_value(serde_json::json!({
+        "instrument_token": token,
+        "exchange_token": token / 256,
+        "tradingsymbol": symbol,
+        "name": symbol,
+        "last_price": 0.0,
+        "expiry": null,
+        "strike": null,
+        "tick_size": 0.05,
+        "lot_size": 1,
+        "instrument_type": "EQ",
+        "segment": "NSE",
+        "exchange": "NSE"
+    }))
+    .unwrap()
+}
+
+#[test]
+fn test_json_output_parses_when_piped() {
+    let home = tempfile::tempdir().unwrap();
+    // The cache is written in-process, so point this process at the same directory
+    std::env::set_var("XDG_CACHE_HOME", home.path().join("cache"));
+    InstrumentCache::save(
+        "NSE",
+        &[instrument(408065, "INFY"), instrument(2953217, "TCS")],
+    )
+    .unwrap();
+
+    let out = kite(home.path(), &["-o", "json", "instruments", "list", "--exchange", "NSE"]);
+    assert!(out.status.success(), "{}", String::from_utf8_lossy(&out.stderr));
+    let listed: serde_json::Value = serde_json::from_slice(&out.stdout).unwrap();
+    assert_eq!(listed.as_array().unwrap().len(), 2);
+    assert!(String::from_utf8_lossy(&out.stderr).contains("Loading instruments from cache"));
+
+    let out = kite(home.path(), &["-o", "json", "instruments", "search", "INFY"]);
+    assert!(out.status.success(), "{}", String::from_utf8_lossy(&out.stderr));
+    let found: serde_json::Value = serde_json::from_slice(&out.stdout).unwrap();
+    assert_eq!(found[0]["tradingsymbol"], "INFY");
+
+    // --quiet leaves stderr free of progress messages
+    let out = kite(
+        home.path(),
+        &["--quiet", "-o", "json", "instruments", "list", "--exchange", "NSE"],
+    );
+    assert!(out.status.success());
+    assert!(out.stderr.is_empty(), "{}", String::from_utf8_lossy(&out.stderr));
+    serde_json::from_slice::<serde_json::Value>(&out.stdout).unwrap();
+}
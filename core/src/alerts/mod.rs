@@ -0,0 +1,321 @@
+//! Price alerts
+//!
+//! Alert definitions live in `alerts.toml` next to the config file. The
+//! monitor re-arms a fired alert only after the price moves back past the
+//! threshold by [`REARM_BAND`], so a price flickering around the level fires once.
+
+use crate::config::Config;
+use crate::validation::parse_symbol;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// Fraction of the threshold the price must retreat before an alert re-arms
+pub const REARM_BAND: f64 = 0.002;
+
+/// A price alert on one instrument
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Alert {
+    pub id: u32,
+    /// EXCHANGE:SYMBOL
+    pub symbol: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub above: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub below: Option<f64>,
+    /// Shell command run when the alert fires
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub command: Option<String>,
+}
+
+impl Alert {
+    /// Build an alert, normalizing the symbol and checking the thresholds
+    pub fn new(
+        id: u32,
+        symbol: &str,
+        above: Option<f64>,
+        below: Option<f64>,
+        command: Option<String>,
+    ) -> Result<Self> {
+        let (exchange, tradingsymbol) = parse_symbol(symbol)?;
+        match (above, below) {
+            (None, None) => anyhow::bail!("An alert needs --above and/or --below"),
+            (Some(above), Some(below)) if above <= below => {
+                anyhow::bail!("--above ({}) must be greater than --below ({})", above, below)
+            }
+            _ => {}
+        }
+        if above.into_iter().chain(below).any(|price| price <= 0.0) {
+            anyhow::bail!("Alert prices must be greater than 0");
+        }
+
+        Ok(Self {
+            id,
+            symbol: format!("{}:{}", exchange, tradingsymbol),
+            above,
+            below,
+            command: command.filter(|c| !c.trim().is_empty()),
+        })
+    }
+}
+
+/// Saved alert definitions
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AlertBook {
+    #[serde(default, rename = "alert")]
+    pub alerts: Vec<Alert>,
+}
+
+impl AlertBook {
+    /// Alerts file next to the config file, so `--config` moves it too
+    pub fn path(config: &Config) -> Result<PathBuf> {
+        let config_dir = config
+            .path
+            .parent()
+            .ok_or_else(|| anyhow::anyhow!("Invalid config path"))?;
+        Ok(config_dir.join("alerts.toml"))
+    }
+
+    /// Load saved alerts; a missing file means no alerts
+    pub fn load(config: &Config) -> Result<Self> {
+        Self::load_from(&Self::path(config)?)
+    }
+
+    pub fn load_from(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        toml::from_str(&content).with_context(|| format!("Failed to parse {}", path.display()))
+    }
+
+    pub fn save(&self, config: &Config) -> Result<()> {
+        self.save_to(&Self::path(config)?)
+    }
+
+    pub fn save_to(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).context("Failed to create config directory")?;
+        }
+        let content = toml::to_string_pretty(self).context("Failed to serialize alerts")?;
+        std::fs::write(path, content).with_context(|| format!("Failed to write {}", path.display()))
+    }
+
+    /// Add an alert with the next free id
+    pub fn add(
+        &mut self,
+        symbol: &str,
+        above: Option<f64>,
+        below: Option<f64>,
+        command: Option<String>,
+    ) -> Result<&Alert> {
+        let id = self.alerts.iter().map(|a| a.id).max().unwrap_or(0) + 1;
+        self.alerts.push(Alert::new(id, symbol, above, below, command)?);
+        Ok(&self.alerts[self.alerts.len() - 1])
+    }
+
+    /// Remove the alert with `id`
+    pub fn remove(&mut self, id: u32) -> Result<Alert> {
+        let index = self
+            .alerts
+            .iter()
+            .position(|a| a.id == id)
+            .ok_or_else(|| anyhow::anyhow!("No alert with id {}", id))?;
+        Ok(self.alerts.remove(index))
+    }
+}
+
+/// Which threshold was crossed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Direction {
+    Above,
+    Below,
+}
+
+impl fmt::Display for Direction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Direction::Above => write!(f, "above"),
+            Direction::Below => write!(f, "below"),
+        }
+    }
+}
+
+/// An alert that fired
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Trigger {
+    pub alert_id: u32,
+    pub symbol: String,
+    pub direction: Direction,
+    pub threshold: f64,
+    pub price: f64,
+    #[serde(skip)]
+    pub command: Option<String>,
+}
+
+/// Tracks which alert thresholds are armed between price checks
+pub struct AlertMonitor {
+    alerts: Vec<Alert>,
+    /// (alert id, direction) pairs that fired and have not re-armed yet
+    fired: HashSet<(u32, Direction)>,
+}
+
+impl AlertMonitor {
+    pub fn new(alerts: Vec<Alert>) -> Self {
+        Self {
+            alerts,
+            fired: HashSet::new(),
+        }
+    }
+
+    /// Distinct symbols to fetch prices for
+    pub fn symbols(&self) -> Vec<String> {
+        let mut symbols: Vec<String> = self.alerts.iter().map(|a| a.symbol.clone()).collect();
+        symbols.sort();
+        symbols.dedup();
+        symbols
+    }
+
+    /// Check the latest prices, returning alerts that just fired
+    ///
+    /// Symbols missing from `prices` are skipped and keep their state.
+    pub fn check(&mut self, prices: &HashMap<String, f64>) -> Vec<Trigger> {
+        let mut triggers = Vec::new();
+
+        for alert in &self.alerts {
+            let Some(&price) = prices.get(&alert.symbol) else {
+                continue;
+            };
+            let levels = [
+                (Direction::Above, alert.above),
+                (Direction::Below, alert.below),
+            ];
+            for (direction, threshold) in levels {
+                let Some(threshold) = threshold else {
+                    continue;
+                };
+                let key = (alert.id, direction);
+                let (crossed, rearmed) = match direction {
+                    Direction::Above => (
+                        price >= threshold,
+                        price < threshold * (1.0 - REARM_BAND),
+                    ),
+                    Direction::Below => (
+                        price <= threshold,
+                        price > threshold * (1.0 + REARM_BAND),
+                    ),
+                };
+
+                if self.fired.contains(&key) {
+                    if rearmed {
+                        self.fired.remove(&key);
+                    }
+                } else if crossed {
+                    self.fired.insert(key);
+                    triggers.push(Trigger {
+                        alert_id: alert.id,
+                        symbol: alert.symbol.clone(),
+                        direction,
+                        threshold,
+                        price,
+                        command: alert.command.clone(),
+                    });
+                }
+            }
+        }
+
+        triggers
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn prices(price: f64) -> HashMap<String, f64> {
+        HashMap::from([("NSE:INFY".to_string(), price)])
+    }
+
+    #[test]
+    fn test_alert_new_validates() {
+        let alert = Alert::new(1, "nse:infy", Some(1600.0), None, None).unwrap();
+        assert_eq!(alert.symbol, "NSE:INFY");
+
+        assert!(Alert::new(1, "NSE:INFY", None, None, None).is_err());
+        assert!(Alert::new(1, "NSE:INFY", Some(1400.0), Some(1600.0), None).is_err());
+        assert!(Alert::new(1, "XYZ:INFY", Some(1600.0), None, None).is_err());
+        // Bare symbols get the default exchange
+        assert_eq!(Alert::new(1, "infy", Some(1600.0), None, None).unwrap().symbol, "NSE:INFY");
+        assert!(Alert::new(1, "NSE:INFY", None, Some(-1.0), None).is_err());
+    }
+
+    #[test]
+    fn test_alert_book_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("alerts.toml");
+
+        let mut book = AlertBook::load_from(&path).unwrap();
+        assert!(book.alerts.is_empty());
+        book.add("NSE:INFY", Some(1600.0), Some(1400.0), None).unwrap();
+        book.add("NSE:TCS", None, Some(3000.0), Some("say hi".into()))
+            .unwrap();
+        book.remove(1).unwrap();
+        book.add("NSE:SBIN", Some(900.0), None, None).unwrap();
+        book.save_to(&path).unwrap();
+
+        let loaded = AlertBook::load_from(&path).unwrap();
+        let ids: Vec<u32> = loaded.alerts.iter().map(|a| a.id).collect();
+        assert_eq!(ids, vec![2, 3]);
+        assert_eq!(loaded.alerts[0].command.as_deref(), Some("say hi"));
+        assert!(loaded.clone().remove(7).is_err());
+    }
+
+    #[test]
+    fn test_alert_book_lives_next_to_config() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut config = Config::default();
+        config.path = dir.path().join("work/config.toml");
+        assert_eq!(AlertBook::path(&config).unwrap(), dir.path().join("work/alerts.toml"));
+
+        let mut book = AlertBook::default();
+        book.add("NSE:INFY", Some(1600.0), None, None).unwrap();
+        book.save(&config).unwrap();
+        assert_eq!(AlertBook::load(&config).unwrap().alerts.len(), 1);
+    }
+
+    #[test]
+    fn test_monitor_fires_once_until_rearmed() {
+        let alert = Alert::new(1, "NSE:INFY", Some(1600.0), Some(1400.0), None).unwrap();
+        let mut monitor = AlertMonitor::new(vec![alert]);
+
+        assert!(monitor.check(&prices(1500.0)).is_empty());
+
+        let fired = monitor.check(&prices(1600.5));
+        assert_eq!(fired.len(), 1);
+        assert_eq!(fired[0].direction, Direction::Above);
+
+        // Flickering around the level stays quiet
+        assert!(monitor.check(&prices(1599.0)).is_empty());
+        assert!(monitor.check(&prices(1601.0)).is_empty());
+
+        // Falling back beyond the band re-arms
+        assert!(monitor.check(&prices(1590.0)).is_empty());
+        assert_eq!(monitor.check(&prices(1600.0)).len(), 1);
+
+        let fired = monitor.check(&prices(1399.0));
+        assert_eq!(fired.len(), 1);
+        assert_eq!(fired[0].direction, Direction::Below);
+    }
+
+    #[test]
+    fn test_monitor_skips_missing_prices() {
+        let alert = Alert::new(1, "NSE:INFY", Some(1600.0), None, None).unwrap();
+        let mut monitor = AlertMonitor::new(vec![alert]);
+        assert!(monitor.check(&HashMap::new()).is_empty());
+        assert_eq!(monitor.symbols(), vec!["NSE:INFY".to_string()]);
+    }
+}
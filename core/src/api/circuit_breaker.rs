@@ -0,0 +1,246 @@
+//! Circuit breaker that pauses requests while the API keeps failing
+
+use crate::error::ZerodhaError;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime};
+
+/// When to stop sending requests after repeated failures
+#[derive(Debug, Clone, Copy)]
+pub struct CircuitBreakerPolicy {
+    /// Consecutive failures that open the circuit; 0 disables the breaker
+    pub failure_threshold: u32,
+    /// Failures only count as consecutive when they all fall within this window
+    pub window: Duration,
+    /// How long requests are refused once the circuit opens
+    pub cooldown: Duration,
+}
+
+impl Default for CircuitBreakerPolicy {
+    fn default() -> Self {
+        Self {
+            failure_threshold: 5,
+            window: Duration::from_secs(60),
+            cooldown: Duration::from_secs(30),
+        }
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct State {
+    failures: u32,
+    first_failure: Option<SystemTime>,
+    open_until: Option<SystemTime>,
+}
+
+impl State {
+    fn is_clear(&self) -> bool {
+        self.failures == 0 && self.first_failure.is_none() && self.open_until.is_none()
+    }
+}
+
+/// Where the CLI keeps the breaker state between runs
+pub fn default_state_file() -> Option<PathBuf> {
+    dirs::cache_dir().map(|dir| dir.join("zerodha-cli").join("circuit_breaker.json"))
+}
+
+/// Counts consecutive transport errors and 5xx responses
+///
+/// After a cooldown the next request is let through; if it fails too the
+/// circuit opens again straight away. With a state file the count carries
+/// over between runs, so repeated one-shot commands can trip it too.
+#[derive(Debug)]
+pub struct CircuitBreaker {
+    policy: CircuitBreakerPolicy,
+    state: Mutex<State>,
+    state_file: Option<PathBuf>,
+}
+
+impl CircuitBreaker {
+    pub fn new(policy: CircuitBreakerPolicy) -> Self {
+        Self {
+            policy,
+            state: Mutex::new(State::default()),
+            state_file: None,
+        }
+    }
+
+    /// Load the failure count from `path` and save every change back to it
+    ///
+    /// A missing or unreadable file starts with the circuit closed.
+    pub fn with_state_file(mut self, path: PathBuf) -> Self {
+        let state = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+        self.state = Mutex::new(state);
+        self.state_file = Some(path);
+        self
+    }
+
+    /// Refuse the request while the circuit is open
+    pub fn check(&self) -> Result<(), ZerodhaError> {
+        self.check_at(SystemTime::now())
+    }
+
+    /// Reset the failure count after a healthy response
+    pub fn record_success(&self) {
+        let mut state = self.lock();
+        if !state.is_clear() {
+            *state = State::default();
+            self.save(&state);
+        }
+    }
+
+    /// Count a failure, opening the circuit once the threshold is reached
+    pub fn record_failure(&self) {
+        self.record_failure_at(SystemTime::now())
+    }
+
+    fn check_at(&self, now: SystemTime) -> Result<(), ZerodhaError> {
+        let mut state = self.lock();
+        let Some(open_until) = state.open_until else {
+            return Ok(());
+        };
+        // A pause longer than the cooldown means the clock moved back; let it go
+        match open_until.duration_since(now) {
+            Ok(remaining) if !remaining.is_zero() && remaining <= self.policy.cooldown => {
+                return Err(ZerodhaError::CircuitOpen {
+                    retry_in_secs: remaining.as_secs_f64().ceil() as u64,
+                });
+            }
+            _ => {}
+        }
+        // Half-open: one more failure reopens the circuit
+        state.open_until = None;
+        state.failures = self.policy.failure_threshold.saturating_sub(1);
+        state.first_failure = Some(now);
+        self.save(&state);
+        Ok(())
+    }
+
+    fn record_failure_at(&self, now: SystemTime) {
+        if self.policy.failure_threshold == 0 {
+            return;
+        }
+        let mut state = self.lock();
+        let elapsed = |first: SystemTime| now.duration_since(first).unwrap_or_default();
+        match state.first_failure {
+            Some(first) if elapsed(first) <= self.policy.window => state.failures += 1,
+            _ => {
+                state.failures = 1;
+                state.first_failure = Some(now);
+            }
+        }
+        if state.failures >= self.policy.failure_threshold {
+            tracing::debug!(failures = state.failures, "circuit breaker opened");
+            state.open_until = Some(now + self.policy.cooldown);
+        }
+        self.save(&state);
+    }
+
+    /// Write the state file, if any; failing to only loses the count
+    fn save(&self, state: &State) {
+        let Some(ref path) = self.state_file else {
+            return;
+        };
+        let Ok(content) = serde_json::to_string(state) else {
+            return;
+        };
+        let written = path
+            .parent()
+            .map_or(Ok(()), std::fs::create_dir_all)
+            .and_then(|()| std::fs::write(path, content));
+        if let Err(e) = written {
+            tracing::debug!(path = %path.display(), error = %e, "could not save circuit breaker state");
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Default for CircuitBreaker {
+    fn default() -> Self {
+        Self::new(CircuitBreakerPolicy::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn breaker(failure_threshold: u32) -> CircuitBreaker {
+        CircuitBreaker::new(CircuitBreakerPolicy {
+            failure_threshold,
+            window: Duration::from_secs(60),
+            cooldown: Duration::from_secs(30),
+        })
+    }
+
+    #[test]
+    fn test_consecutive_failures_trip_breaker() {
+        let breaker = breaker(3);
+        let start = SystemTime::now();
+        breaker.record_failure_at(start);
+        breaker.record_failure_at(start);
+        assert!(breaker.check_at(start).is_ok());
+
+        breaker.record_failure_at(start + Duration::from_secs(1));
+        let err = breaker.check_at(start + Duration::from_secs(1)).unwrap_err();
+        assert!(matches!(err, ZerodhaError::CircuitOpen { retry_in_secs: 30 }));
+        assert!(err.to_string().contains("API appears down"));
+
+        // Half-open after the cooldown: one failure reopens it
+        let later = start + Duration::from_secs(32);
+        assert!(breaker.check_at(later).is_ok());
+        breaker.record_failure_at(later);
+        assert!(breaker.check_at(later).is_err());
+    }
+
+    #[test]
+    fn test_success_and_window_reset_count() {
+        let breaker = breaker(2);
+        let start = SystemTime::now();
+        breaker.record_failure_at(start);
+        breaker.record_success();
+        breaker.record_failure_at(start);
+        assert!(breaker.check_at(start).is_ok());
+
+        // Failures further apart than the window don't add up
+        breaker.record_failure_at(start + Duration::from_secs(61));
+        assert!(breaker.check_at(start + Duration::from_secs(61)).is_ok());
+
+        let disabled = self::breaker(0);
+        for _ in 0..10 {
+            disabled.record_failure_at(start);
+        }
+        assert!(disabled.check_at(start).is_ok());
+    }
+
+    #[test]
+    fn test_state_file_carries_failures_between_runs() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("cache/circuit_breaker.json");
+        let start = SystemTime::now();
+
+        // Each run sees one failure; together they trip the breaker
+        for _ in 0..3 {
+            assert!(breaker(3).with_state_file(path.clone()).check_at(start).is_ok());
+            breaker(3).with_state_file(path.clone()).record_failure_at(start);
+        }
+        let next_run = breaker(3).with_state_file(path.clone());
+        assert!(next_run.check_at(start).is_err());
+
+        next_run.check_at(start + Duration::from_secs(31)).unwrap();
+        next_run.record_success();
+        let after_success = breaker(3).with_state_file(path.clone());
+        assert!(after_success.check_at(start + Duration::from_secs(31)).is_ok());
+
+        // A corrupt file starts closed
+        std::fs::write(&path, "not json").unwrap();
+        assert!(breaker(3).with_state_file(path).check_at(start).is_ok());
+    }
+}
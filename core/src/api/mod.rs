@@ -1,7 +1,11 @@
 //! API Client module
 
+pub mod circuit_breaker;
 pub mod client;
 pub mod rate_limiter;
+pub mod recording;
 
-pub use client::KiteConnectClient;
+pub use circuit_breaker::{CircuitBreaker, CircuitBreakerPolicy};
+pub use client::{HttpOptions, KiteConnectClient, RetryPolicy};
 pub use rate_limiter::RateLimiter;
+pub use recording::RecordingMode;
@@ -7,9 +7,10 @@ use governor::{
     Quota, RateLimiter as GovernorLimiter,
 };
 use nonzero_ext::nonzero;
+use std::num::NonZeroU32;
 use std::time::Duration;
 
-/// Rate limiter enforcing 3 requests per second (Kite Connect limit)
+/// Rate limiter enforcing a per-second quota (3 req/sec Kite Connect limit by default)
 pub struct RateLimiter {
     limiter: GovernorLimiter<NotKeyed, InMemoryState, DefaultClock>,
 }
@@ -18,10 +19,20 @@ impl RateLimiter {
     /// Create a new rate limiter with 3 req/sec limit
     pub fn new() -> Self {
         // Kite Connect allows 3 requests per second
-        let quota = Quota::per_second(nonzero!(3u32));
-        let limiter = GovernorLimiter::direct(quota);
+        Self::from_quota(Quota::per_second(nonzero!(3u32)))
+    }
+
+    /// Create a rate limiter allowing `per_second` requests per second
+    pub fn with_quota(per_second: u32) -> Result<Self> {
+        let per_second = NonZeroU32::new(per_second)
+            .ok_or_else(|| anyhow::anyhow!("Rate limit must be at least 1 request per second"))?;
+        Ok(Self::from_quota(Quota::per_second(per_second)))
+    }
 
-        Self { limiter }
+    fn from_quota(quota: Quota) -> Self {
+        Self {
+            limiter: GovernorLimiter::direct(quota),
+        }
     }
 
     /// Acquire a permit, waiting if necessary
@@ -90,4 +101,16 @@ mod tests {
         // Should have waited at least some time
         assert!(elapsed >= Duration::from_millis(100));
     }
+
+    #[tokio::test]
+    async fn test_rate_limiter_with_quota() {
+        assert!(RateLimiter::with_quota(0).is_err());
+
+        let limiter = RateLimiter::with_quota(5).unwrap();
+        let start = std::time::Instant::now();
+        for _ in 0..5 {
+            assert!(limiter.acquire().await.is_ok());
+        }
+        assert!(start.elapsed() < Duration::from_millis(100));
+    }
 }
@@ -0,0 +1,209 @@
+//! Record API exchanges to disk and replay them offline
+//!
+//! With `ZERODHA_RECORD=dir` every request/response pair is saved as a JSON
+//! file in `dir`; with `ZERODHA_REPLAY=dir` responses are served from those
+//! files and nothing goes over the network. Requests are matched on method,
+//! path, query and body, with repeats of the same request replayed in the
+//! order they were recorded. Credentials and account details are redacted
+//! before anything is written, and the Authorization header is never saved.
+//!
+//! Only GET requests are replayed unless `ZERODHA_REPLAY_WRITES=1` is set, so a
+//! leftover `ZERODHA_REPLAY` can't make an order look placed when it never
+//! reached Kite.
+
+use super::client::{redact_secrets, request_body};
+use anyhow::{Context, Result};
+use reqwest::{Client, Response};
+use serde::{Deserialize, Serialize};
+use sha2::Digest;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Directory to record exchanges into
+pub const RECORD_ENV: &str = "ZERODHA_RECORD";
+/// Directory to replay exchanges from
+pub const REPLAY_ENV: &str = "ZERODHA_REPLAY";
+/// Set to `1` to also replay requests that change state (orders, GTTs)
+pub const REPLAY_WRITES_ENV: &str = "ZERODHA_REPLAY_WRITES";
+
+/// Whether exchanges are saved or served from disk
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RecordingMode {
+    Record(PathBuf),
+    Replay(PathBuf),
+}
+
+impl RecordingMode {
+    /// Mode chosen by `ZERODHA_REPLAY` or `ZERODHA_RECORD`; replay wins if both are set
+    pub fn from_env() -> Option<Self> {
+        let dir = |name| std::env::var_os(name).filter(|v| !v.is_empty()).map(PathBuf::from);
+        dir(REPLAY_ENV)
+            .map(Self::Replay)
+            .or_else(|| dir(RECORD_ENV).map(Self::Record))
+    }
+}
+
+/// One saved request/response pair
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Recording {
+    method: String,
+    path: String,
+    query: String,
+    request_body: String,
+    status: u16,
+    headers: BTreeMap<String, String>,
+    body: String,
+}
+
+/// Saves or replays exchanges according to its mode
+#[derive(Debug)]
+pub struct Recorder {
+    mode: RecordingMode,
+    /// How often each request has been seen, so repeats map to separate files
+    seen: Mutex<HashMap<String, usize>>,
+    /// Whether non-GET requests may be served from disk (`ZERODHA_REPLAY_WRITES`)
+    replay_writes: bool,
+}
+
+impl Recorder {
+    pub fn new(mode: RecordingMode) -> Self {
+        Self {
+            mode,
+            seen: Mutex::new(HashMap::new()),
+            replay_writes: std::env::var(REPLAY_WRITES_ENV).is_ok_and(|v| v == "1"),
+        }
+    }
+
+    pub fn mode(&self) -> &RecordingMode {
+        &self.mode
+    }
+
+    /// Send `request` with `client` and save the exchange, or serve it from disk
+    pub async fn execute(&self, client: &Client, request: reqwest::Request) -> Result<Response> {
+        let method = request.method().to_string();
+        let path = request.url().path().to_string();
+        let query = redact_secrets(request.url().query().unwrap_or_default());
+        let body = redact_secrets(&request_body(&request));
+        let file = self.next_file(&method, &path, &query, &body);
+
+        match self.mode {
+            RecordingMode::Replay(_) if method != "GET" && !self.replay_writes => {
+                anyhow::bail!(
+                    "Refusing to replay {} {} from recordings, since it would not reach Kite; \
+                     set {}=1 to allow it",
+                    method,
+                    path,
+                    REPLAY_WRITES_ENV
+                )
+            }
+            RecordingMode::Replay(_) => replay(&file, &method, &path),
+            RecordingMode::Record(ref dir) => {
+                let response = client.execute(request).await?;
+                let recording = Recording {
+                    method,
+                    path,
+                    query,
+                    request_body: body,
+                    status: response.status().as_u16(),
+                    headers: response
+                        .headers()
+                        .iter()
+                        .filter(|(name, _)| *name != reqwest::header::SET_COOKIE)
+                        .filter_map(|(name, value)| {
+                            Some((name.to_string(), value.to_str().ok()?.to_string()))
+                        })
+                        .collect(),
+                    body: redact_secrets(&response.text().await?),
+                };
+                std::fs::create_dir_all(dir)
+                    .with_context(|| format!("Failed to create {}", dir.display()))?;
+                std::fs::write(&file, serde_json::to_string_pretty(&recording)?)
+                    .with_context(|| format!("Failed to write recording {}", file.display()))?;
+                into_response(recording)
+            }
+        }
+    }
+
+    /// File for the next occurrence of this request
+    fn next_file(&self, method: &str, path: &str, query: &str, body: &str) -> PathBuf {
+        let mut hasher = sha2::Sha256::new();
+        for part in [method, path, query, body] {
+            hasher.update(part.as_bytes());
+            hasher.update([0]);
+        }
+        let hash = hex::encode(hasher.finalize());
+        let slug: String = path
+            .trim_matches('/')
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+            .collect();
+        let key = format!("{}_{}-{}", method, slug, &hash[..12]);
+
+        let mut seen = self.seen.lock().unwrap_or_else(|e| e.into_inner());
+        let count = seen.entry(key.clone()).or_default();
+        let file = format!("{}-{}.json", key, count);
+        *count += 1;
+
+        let dir = match self.mode {
+            RecordingMode::Record(ref dir) | RecordingMode::Replay(ref dir) => dir,
+        };
+        dir.join(file)
+    }
+}
+
+fn replay(file: &Path, method: &str, path: &str) -> Result<Response> {
+    let content = std::fs::read_to_string(file).with_context(|| {
+        format!("No recording for {} {} (expected {})", method, path, file.display())
+    })?;
+    let recording: Recording = serde_json::from_str(&content)
+        .with_context(|| format!("Invalid recording {}", file.display()))?;
+    into_response(recording)
+}
+
+fn into_response(recording: Recording) -> Result<Response> {
+    let mut builder = http::Response::builder().status(recording.status);
+    for (name, value) in &recording.headers {
+        builder = builder.header(name, value);
+    }
+    let response = builder
+        .body(recording.body)
+        .context("Invalid recorded response")?;
+    Ok(Response::from(response))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_repeated_requests_get_separate_files() {
+        let recorder = Recorder::new(RecordingMode::Replay(PathBuf::from("rec")));
+        let first = recorder.next_file("GET", "/orders", "", "");
+        let second = recorder.next_file("GET", "/orders", "", "");
+        let other = recorder.next_file("GET", "/orders/1", "", "");
+
+        assert_eq!(first.parent(), Some(Path::new("rec")));
+        let name = first.file_name().unwrap().to_str().unwrap();
+        assert!(name.starts_with("GET_orders-") && name.ends_with("-0.json"), "{}", name);
+        assert!(second.to_str().unwrap().ends_with("-1.json"));
+        assert!(other.file_name().unwrap().to_str().unwrap().starts_with("GET_orders_1-"));
+    }
+
+    #[tokio::test]
+    async fn test_replay_refuses_writes_without_opt_in() {
+        let dir = tempfile::tempdir().unwrap();
+        let client = Client::new();
+        let request = || client.post("http://localhost/orders/regular").build().unwrap();
+
+        let mut recorder = Recorder::new(RecordingMode::Replay(dir.path().to_path_buf()));
+        recorder.replay_writes = false;
+        let err = recorder.execute(&client, request()).await.unwrap_err();
+        assert!(err.to_string().contains(REPLAY_WRITES_ENV), "{}", err);
+
+        // With the opt-in it looks for a recording instead
+        recorder.replay_writes = true;
+        let err = recorder.execute(&client, request()).await.unwrap_err();
+        assert!(err.to_string().contains("No recording"), "{}", err);
+    }
+}
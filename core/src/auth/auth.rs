@@ -1,8 +1,11 @@
 //! Authentication logic
 
 use crate::api::KiteConnectClient;
+use crate::auth::redirect;
 use crate::config::Config;
 use anyhow::{Context, Result};
+use std::io::Write;
+use std::path::Path;
 
 /// Authentication status
 #[derive(Debug, Clone)]
@@ -13,50 +16,49 @@ pub enum AuthStatus {
 }
 
 /// Initiate OAuth login flow
-pub async fn login(api_client: &KiteConnectClient, config: &mut Config) -> Result<String> {
+///
+/// With `redirect_port`, the request token is captured from a redirect to
+/// `http://127.0.0.1:<port>/`; otherwise it is pasted by hand.
+pub async fn login(
+    api_client: &KiteConnectClient,
+    config: &mut Config,
+    redirect_port: Option<u16>,
+) -> Result<String> {
     // 1. Generate login URL
     let login_url = api_client.login_url();
 
-    println!("========================================");
-    println!("  Zerodha Kite Connect Authentication");
-    println!("========================================\n");
-    println!("Opening browser to login page...");
+    eprintln!("========================================");
+    eprintln!("  Zerodha Kite Connect Authentication");
+    eprintln!("========================================\n");
+    eprintln!("Opening browser to login page...");
+
+    // Listen before opening the browser so the redirect can't be missed
+    let listener = match redirect_port {
+        Some(port) => bind_redirect_listener(port).await,
+        None => None,
+    };
 
     // 2. Open browser
     if let Err(e) = webbrowser::open(&login_url) {
-        println!("Failed to open browser: {}", e);
-        println!("\nPlease open this URL manually in your browser:\n");
-        println!("{}\n", login_url);
+        eprintln!("Failed to open browser: {}", e);
+        eprintln!("\nPlease open this URL manually in your browser:\n");
+        eprintln!("{}\n", login_url);
     } else {
-        println!("\nBrowser opened successfully!");
-    }
-
-    println!("\nAfter completing login in your browser, you'll be redirected to a page");
-    println!("with a 'request_token' parameter in the URL.\n");
-    println!("Example URL: https://kite.zerodha.com/connect/login?v=3&api_key=XXX&request_token=abc123\n");
-
-    // 3. Prompt user for request_token
-    println!("\nAfter completing login, you'll be redirected to a URL.");
-    println!("Copy the FULL request_token from the URL (everything after 'request_token=').\n");
-    
-    print!("Enter the request_token: ");
-
-    // Use tokio for async stdin reading
-    let mut request_token = String::new();
-    tokio::task::block_in_place(|| std::io::stdin().read_line(&mut request_token))
-        .context("Failed to read request token")?;
-
-    let request_token = request_token.trim();
-
-    if request_token.is_empty() {
-        anyhow::bail!("Request token cannot be empty");
+        eprintln!("\nBrowser opened successfully!");
     }
 
-    println!("Debug: Token length = {} chars", request_token.len());
-    println!("Debug: Token prefix = {}", &request_token[..request_token.len().min(10)]);
+    // 3. Capture the request_token from the redirect, or ask for it
+    let request_token = match listener {
+        Some(listener) => match capture_request_token(listener).await? {
+            Some(token) => token,
+            None => prompt_request_token()?,
+        },
+        None => prompt_request_token()?,
+    };
+    let request_token = request_token.as_str();
 
     // 4. Exchange for access token
-    println!("\nExchanging request token for access token...");
+    eprintln!("\nExchanging request token for access token...");
 
     let access_token = api_client
         .exchange_token(request_token)
@@ -69,6 +71,7 @@ pub async fn login(api_client: &KiteConnectClient, config: &mut Config) -> Resul
 
     config.api.access_token = Some(access_token.clone());
     config.api.token_expiry = Some(expiry_str);
+    config.api.refresh_token = api_client.get_refresh_token().await;
     // Also save the API credentials that were used for this login
     // so subsequent commands use the same credentials
     // (Note: api_client doesn't expose these, so we trust config already has them)
@@ -78,9 +81,9 @@ pub async fn login(api_client: &KiteConnectClient, config: &mut Config) -> Resul
     // 6. Update API client
     api_client.set_access_token(access_token.clone()).await?;
 
-    println!("\n✓ Authentication successful!");
-    println!("✓ Access token saved to config");
-    println!(
+    eprintln!("\n✓ Authentication successful!");
+    eprintln!("✓ Access token saved to config");
+    eprintln!(
         "✓ Token expires: {}",
         expiry.format("%Y-%m-%d %H:%M:%S UTC")
     );
@@ -88,10 +91,77 @@ pub async fn login(api_client: &KiteConnectClient, config: &mut Config) -> Resul
     Ok(access_token)
 }
 
+/// Bind the redirect listener; `None` means fall back to manual entry
+async fn bind_redirect_listener(port: u16) -> Option<tokio::net::TcpListener> {
+    match tokio::net::TcpListener::bind(("127.0.0.1", port)).await {
+        Ok(listener) => Some(listener),
+        Err(e) => {
+            eprintln!("Could not listen on 127.0.0.1:{} ({}).", port, e);
+            None
+        }
+    }
+}
+
+/// Wait for the login redirect; `None` means fall back to manual entry
+async fn capture_request_token(listener: tokio::net::TcpListener) -> Result<Option<String>> {
+    let port = listener.local_addr()?.port();
+    eprintln!(
+        "\nWaiting for the login redirect on http://127.0.0.1:{}/ (set this as your app's redirect URL)...",
+        port
+    );
+    let token = redirect::wait_for_request_token(listener, redirect::REDIRECT_TIMEOUT).await?;
+    if token.is_none() {
+        eprintln!(
+            "No redirect received within {} seconds.",
+            redirect::REDIRECT_TIMEOUT.as_secs()
+        );
+    }
+    Ok(token)
+}
+
+/// Ask the user to paste the request_token from the redirect URL
+fn prompt_request_token() -> Result<String> {
+    eprintln!("\nAfter completing login, you'll be redirected to a URL.");
+    eprintln!("Copy the FULL request_token from the URL (everything after 'request_token=').\n");
+
+    eprint!("Enter the request_token: ");
+    std::io::stderr().flush()?;
+
+    let mut request_token = String::new();
+    tokio::task::block_in_place(|| std::io::stdin().read_line(&mut request_token))
+        .context("Failed to read request token")?;
+
+    let request_token = request_token.trim();
+    if request_token.is_empty() {
+        anyhow::bail!("Request token cannot be empty");
+    }
+    Ok(request_token.to_string())
+}
+
+/// Save tokens issued by a session refresh to `profile` in the config at `config_path`
+pub fn save_refreshed_session(
+    config_path: &Path,
+    profile: Option<&str>,
+    access_token: &str,
+    refresh_token: Option<&str>,
+) -> Result<()> {
+    let mut config = Config::load_from(config_path, profile)?;
+    let expiry = chrono::Utc::now() + chrono::Duration::days(1);
+
+    config.api.access_token = Some(access_token.to_string());
+    config.api.token_expiry = Some(expiry.to_rfc3339());
+    if let Some(token) = refresh_token {
+        config.api.refresh_token = Some(token.to_string());
+    }
+
+    config.save().context("Failed to save config")
+}
+
 /// Logout and invalidate session
 pub fn logout(config: &mut Config) -> Result<()> {
     config.api.access_token = None;
     config.api.token_expiry = None;
+    config.api.refresh_token = None;
 
     config.save().context("Failed to save config")?;
 
@@ -2,4 +2,6 @@
 
 #[allow(clippy::module_inception)]
 pub mod auth;
-pub use auth::{login, logout, print_status, status, AuthStatus};
+pub mod postback;
+pub mod redirect;
+pub use auth::{login, logout, print_status, save_refreshed_session, status, AuthStatus};
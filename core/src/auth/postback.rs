@@ -0,0 +1,74 @@
+//! Order postback checksum verification
+//!
+//! Kite signs each postback with SHA-256 of `order_id + order_timestamp +
+//! api_secret`, hex-encoded in the payload's `checksum` field.
+
+use crate::api::client::sha256_digest;
+use anyhow::{Context, Result};
+use serde_json::Value;
+
+/// Checksum Kite sends for an order postback
+pub fn postback_checksum(order_id: &str, order_timestamp: &str, api_secret: &str) -> String {
+    sha256_digest(&format!("{}{}{}", order_id, order_timestamp, api_secret))
+}
+
+/// Whether the `checksum` in a postback payload was made with `api_secret`
+pub fn verify_postback(payload: &Value, api_secret: &str) -> Result<bool> {
+    let field = |name: &str| {
+        payload
+            .get(name)
+            .and_then(Value::as_str)
+            .with_context(|| format!("Postback payload has no '{}' string field", name))
+    };
+    let expected = postback_checksum(field("order_id")?, field("order_timestamp")?, api_secret);
+    let checksum = field("checksum")?.to_ascii_lowercase();
+
+    // Compare every byte so timing doesn't reveal how much matched
+    Ok(checksum.len() == expected.len()
+        && checksum
+            .bytes()
+            .zip(expected.bytes())
+            .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+            == 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const CHECKSUM: &str = "fd92e445f265bce20579249ce9faa657b515536fcdee893e9c8037cf931caa0b";
+
+    fn payload(checksum: &str) -> Value {
+        json!({
+            "order_id": "240303000308932",
+            "order_timestamp": "2024-03-03 09:39:10",
+            "status": "COMPLETE",
+            "tradingsymbol": "INFY",
+            "checksum": checksum,
+        })
+    }
+
+    #[test]
+    fn test_known_postback_checksum() {
+        assert_eq!(
+            postback_checksum("240303000308932", "2024-03-03 09:39:10", "test-secret"),
+            CHECKSUM
+        );
+        assert!(verify_postback(&payload(CHECKSUM), "test-secret").unwrap());
+        assert!(verify_postback(&payload(&CHECKSUM.to_uppercase()), "test-secret").unwrap());
+    }
+
+    #[test]
+    fn test_tampered_or_incomplete_postbacks() {
+        assert!(!verify_postback(&payload(CHECKSUM), "other-secret").unwrap());
+        assert!(!verify_postback(&payload("abc"), "test-secret").unwrap());
+
+        let mut tampered = payload(CHECKSUM);
+        tampered["order_id"] = json!("240303000308933");
+        assert!(!verify_postback(&tampered, "test-secret").unwrap());
+
+        let err = verify_postback(&json!({"order_id": "1"}), "test-secret").unwrap_err();
+        assert!(err.to_string().contains("order_timestamp"));
+    }
+}
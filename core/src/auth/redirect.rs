@@ -0,0 +1,120 @@
+//! Local redirect listener for the OAuth login flow
+
+use anyhow::{Context, Result};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+/// How long to wait for the browser redirect before falling back to manual entry
+pub const REDIRECT_TIMEOUT: Duration = Duration::from_secs(120);
+
+const SUCCESS_PAGE: &str = "<html><body><h3>Login complete.</h3>\
+    <p>You can close this tab and return to the terminal.</p></body></html>";
+
+/// Wait for a redirect carrying `request_token`; `None` on timeout
+pub async fn wait_for_request_token(
+    listener: TcpListener,
+    timeout: Duration,
+) -> Result<Option<String>> {
+    let accept_loop = async {
+        loop {
+            let (stream, _) = listener
+                .accept()
+                .await
+                .context("Failed to accept redirect")?;
+            if let Some(token) = handle_redirect(stream).await? {
+                return Ok(token);
+            }
+        }
+    };
+
+    match tokio::time::timeout(timeout, accept_loop).await {
+        Ok(result) => result.map(Some),
+        Err(_) => Ok(None),
+    }
+}
+
+/// Answer one browser request, returning the token if it carried one
+async fn handle_redirect(mut stream: TcpStream) -> Result<Option<String>> {
+    let mut buf = vec![0u8; 8192];
+    let n = stream.read(&mut buf).await.unwrap_or(0);
+    let request = String::from_utf8_lossy(&buf[..n]);
+
+    let token = request
+        .lines()
+        .next()
+        .and_then(|line| line.split_whitespace().nth(1))
+        .and_then(request_token_from_path);
+
+    let response = match token {
+        Some(_) => format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: text/html\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            SUCCESS_PAGE.len(),
+            SUCCESS_PAGE
+        ),
+        None => "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+            .to_string(),
+    };
+    // The token is still usable if the browser hangs up early
+    let _ = stream.write_all(response.as_bytes()).await;
+
+    Ok(token)
+}
+
+/// Extract `request_token` from a redirect path like `/?request_token=abc&status=success`
+fn request_token_from_path(path: &str) -> Option<String> {
+    let (_, query) = path.split_once('?')?;
+    query
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .find(|(key, _)| *key == "request_token")
+        .map(|(_, value)| value.to_string())
+        .filter(|value| !value.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_request_token_from_path() {
+        assert_eq!(
+            request_token_from_path("/?action=login&status=success&request_token=abc123"),
+            Some("abc123".to_string())
+        );
+        assert_eq!(request_token_from_path("/favicon.ico"), None);
+        assert_eq!(request_token_from_path("/?request_token="), None);
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_request_token() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let browser = tokio::spawn(async move {
+            // A stray favicon request must not end the wait
+            for path in ["/favicon.ico", "/?status=success&request_token=tok42"] {
+                let mut stream = TcpStream::connect(addr).await.unwrap();
+                let req = format!("GET {} HTTP/1.1\r\nHost: localhost\r\n\r\n", path);
+                stream.write_all(req.as_bytes()).await.unwrap();
+                let mut response = String::new();
+                stream.read_to_string(&mut response).await.unwrap();
+            }
+        });
+
+        let token = wait_for_request_token(listener, Duration::from_secs(5))
+            .await
+            .unwrap();
+        browser.await.unwrap();
+        assert_eq!(token.as_deref(), Some("tok42"));
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_request_token_times_out() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let token = wait_for_request_token(listener, Duration::from_millis(50))
+            .await
+            .unwrap();
+        assert!(token.is_none());
+    }
+}
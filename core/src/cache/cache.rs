@@ -3,8 +3,46 @@
 use crate::models::Instrument;
 use anyhow::{Context, Result};
 use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fs;
-use std::path::PathBuf;
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+
+/// Default instrument cache lifetime
+pub const DEFAULT_TTL_HOURS: u32 = 24;
+
+/// Exchanges refreshed by `InstrumentCache::refresh_all`
+pub const EXCHANGES: [&str; 6] = ["NSE", "BSE", "NFO", "BFO", "MCX", "CDS"];
+
+/// Outcome of refreshing one exchange
+#[derive(Debug)]
+pub struct RefreshResult {
+    pub exchange: &'static str,
+    /// Number of instruments saved, or why the exchange failed
+    pub result: Result<usize>,
+}
+
+/// Key for looking up an instrument in the index
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct InstrumentKey {
+    pub exchange: String,
+    pub tradingsymbol: String,
+}
+
+impl InstrumentKey {
+    /// Create a key (exchange and symbol are normalized to uppercase)
+    pub fn new(exchange: &str, tradingsymbol: &str) -> Self {
+        Self {
+            exchange: exchange.to_uppercase(),
+            tradingsymbol: tradingsymbol.to_uppercase(),
+        }
+    }
+}
+
+/// In-memory instrument index keyed by exchange and symbol
+pub type InstrumentIndex = HashMap<InstrumentKey, Instrument>;
 
 /// Instrument cache manager
 pub struct InstrumentCache;
@@ -22,29 +60,69 @@ impl InstrumentCache {
         Ok(dir)
     }
 
-    /// Get cache file path for exchange
+    /// Get cache file path for exchange (gzip-compressed CSV)
     pub fn cache_file(exchange: &str) -> Result<PathBuf> {
+        let cache_dir = Self::cache_dir()?;
+        Ok(cache_dir.join(format!("{}.csv.gz", exchange.to_lowercase())))
+    }
+
+    /// Uncompressed cache path written by older versions
+    fn legacy_cache_file(exchange: &str) -> Result<PathBuf> {
         let cache_dir = Self::cache_dir()?;
         Ok(cache_dir.join(format!("{}.csv", exchange.to_lowercase())))
     }
 
+    /// The cache file to read: the compressed one, else a legacy plain CSV
+    fn existing_cache_file(exchange: &str) -> Result<Option<PathBuf>> {
+        Ok(Self::pick_existing(
+            Self::cache_file(exchange)?,
+            Self::legacy_cache_file(exchange)?,
+        ))
+    }
+
+    fn pick_existing(compressed: PathBuf, legacy: PathBuf) -> Option<PathBuf> {
+        [compressed, legacy].into_iter().find(|path| path.exists())
+    }
+
+    /// Exchange a cache file belongs to, from its `<exchange>.csv[.gz]` name
+    fn exchange_of(path: &Path) -> Option<&str> {
+        let name = path.file_name()?.to_str()?;
+        name.strip_suffix(".csv.gz").or_else(|| name.strip_suffix(".csv"))
+    }
+
+    /// Get binary index sidecar path for exchange
+    pub fn index_file(exchange: &str) -> Result<PathBuf> {
+        let cache_dir = Self::cache_dir()?;
+        Ok(cache_dir.join(format!("{}.idx", exchange.to_lowercase())))
+    }
+
     /// Get cache file path with date
     pub fn cache_file_with_date(exchange: &str, date: DateTime<Utc>) -> Result<PathBuf> {
         let cache_dir = Self::cache_dir()?;
         let date_str = date.format("%Y-%m-%d");
-        Ok(cache_dir.join(format!("{}_{}.csv", exchange.to_lowercase(), date_str)))
+        Ok(cache_dir.join(format!("{}_{}.csv.gz", exchange.to_lowercase(), date_str)))
     }
 
-    /// Check if cache is valid (not expired)
+    /// Check if cache is valid (not expired) using the default 24h TTL
     pub fn is_valid(exchange: &str) -> Result<bool> {
-        let cache_file = Self::cache_file(exchange)?;
+        Self::is_valid_with_ttl(exchange, DEFAULT_TTL_HOURS)
+    }
+
+    /// Check if cache is younger than `ttl_hours`
+    pub fn is_valid_with_ttl(exchange: &str, ttl_hours: u32) -> Result<bool> {
+        match Self::existing_cache_file(exchange)? {
+            Some(cache_file) => Self::is_fresh(&cache_file, ttl_hours),
+            None => Ok(false),
+        }
+    }
 
+    fn is_fresh(cache_file: &Path, ttl_hours: u32) -> Result<bool> {
         if !cache_file.exists() {
             return Ok(false);
         }
 
-        // Check modification time (max 24h)
-        let metadata = fs::metadata(&cache_file).context("Failed to read cache metadata")?;
+        // Check modification time
+        let metadata = fs::metadata(cache_file).context("Failed to read cache metadata")?;
         let modified = metadata
             .modified()
             .context("Failed to get modification time")?;
@@ -53,19 +131,27 @@ impl InstrumentCache {
         let now = Utc::now();
         let age = now - modified_time;
 
-        // Cache is valid if less than 24 hours old
-        Ok(age.num_hours() < 24)
+        Ok(age < chrono::Duration::hours(i64::from(ttl_hours)))
     }
 
     /// Load instruments from cache
     pub fn load(exchange: &str) -> Result<Vec<Instrument>> {
-        let cache_file = Self::cache_file(exchange)?;
-
-        if !cache_file.exists() {
+        let Some(cache_file) = Self::existing_cache_file(exchange)? else {
             anyhow::bail!("Cache file not found for exchange: {}", exchange);
-        }
+        };
 
-        let mut rdr = csv::Reader::from_path(&cache_file).context("Failed to open cache file")?;
+        Self::load_from_path(&cache_file)
+    }
+
+    /// Read a cache file, decompressing it unless it is a legacy plain CSV
+    fn load_from_path(path: &Path) -> Result<Vec<Instrument>> {
+        let file = fs::File::open(path).context("Failed to open cache file")?;
+        let reader: Box<dyn Read> = if path.extension().is_some_and(|ext| ext == "gz") {
+            Box::new(GzDecoder::new(BufReader::new(file)))
+        } else {
+            Box::new(BufReader::new(file))
+        };
+        let mut rdr = csv::Reader::from_reader(reader);
         let mut instruments = Vec::new();
 
         for result in rdr.deserialize() {
@@ -85,16 +171,126 @@ impl InstrumentCache {
             fs::create_dir_all(parent).context("Failed to create cache parent directory")?;
         }
 
-        let mut wtr = csv::Writer::from_path(&cache_file).context("Failed to create cache file")?;
+        Self::save_to_path(&cache_file, instruments)?;
+
+        // The compressed file supersedes any plain CSV from older versions
+        let legacy = Self::legacy_cache_file(exchange)?;
+        if legacy.exists() {
+            fs::remove_file(&legacy).context("Failed to remove legacy cache file")?;
+        }
+
+        // Rebuild the sidecar so the next load doesn't have to re-parse the CSV
+        let index_file = Self::index_file(exchange)?;
+        Self::write_index(&index_file, &Self::build_index(instruments.to_vec()))?;
+
+        Ok(())
+    }
+
+    fn save_to_path(path: &Path, instruments: &[Instrument]) -> Result<()> {
+        let file = fs::File::create(path).context("Failed to create cache file")?;
+        let mut wtr = csv::Writer::from_writer(GzEncoder::new(BufWriter::new(file), Compression::default()));
 
         for instrument in instruments {
             wtr.serialize(instrument)
                 .context("Failed to serialize instrument to cache")?;
         }
 
-        wtr.flush().context("Failed to write cache file")?;
+        wtr.into_inner()
+            .map_err(|e| anyhow::anyhow!("{}", e.error()))
+            .and_then(|gz| Ok(gz.finish()?))
+            .and_then(|mut buf| Ok(buf.flush()?))
+            .context("Failed to write cache file")
+    }
 
-        Ok(())
+    /// Load the instrument index for an exchange
+    ///
+    /// Uses the binary sidecar when it is newer than the CSV, otherwise
+    /// parses the CSV and rewrites the sidecar.
+    pub fn load_index(exchange: &str) -> Result<InstrumentIndex> {
+        let Some(cache_file) = Self::existing_cache_file(exchange)? else {
+            anyhow::bail!("Cache file not found for exchange: {}", exchange);
+        };
+
+        Self::load_index_from(&cache_file, &Self::index_file(exchange)?)
+    }
+
+    /// Load every exchange in `EXCHANGES` that has a cache file, whatever its age
+    ///
+    /// Returns the combined instruments and the exchanges that couldn't be loaded.
+    pub fn load_all() -> (Vec<Instrument>, Vec<&'static str>) {
+        let mut instruments = Vec::new();
+        let mut missing = Vec::new();
+
+        for exchange in EXCHANGES {
+            match Self::load(exchange) {
+                Ok(loaded) => instruments.extend(loaded),
+                Err(e) => {
+                    tracing::debug!("Skipping {} instruments: {:#}", exchange, e);
+                    missing.push(exchange);
+                }
+            }
+        }
+
+        (instruments, missing)
+    }
+
+    /// Look up a single instrument in the cached index
+    pub fn lookup(exchange: &str, tradingsymbol: &str) -> Result<Option<Instrument>> {
+        let mut index = Self::load_index(exchange)?;
+        Ok(index.remove(&InstrumentKey::new(exchange, tradingsymbol)))
+    }
+
+    /// Build an index from a list of instruments
+    pub fn build_index(instruments: Vec<Instrument>) -> InstrumentIndex {
+        instruments
+            .into_iter()
+            .map(|inst| {
+                let key = InstrumentKey::new(&inst.exchange.to_string(), &inst.tradingsymbol);
+                (key, inst)
+            })
+            .collect()
+    }
+
+    fn load_index_from(csv_path: &Path, index_path: &Path) -> Result<InstrumentIndex> {
+        if Self::is_index_fresh(csv_path, index_path)? {
+            match Self::read_index(index_path) {
+                Ok(index) => return Ok(index),
+                Err(e) => tracing::warn!("Ignoring unreadable instrument index: {:#}", e),
+            }
+        }
+
+        let instruments = Self::load_from_path(csv_path)?;
+        let index = Self::build_index(instruments);
+        Self::write_index(index_path, &index)?;
+
+        Ok(index)
+    }
+
+    /// Sidecar is fresh if it exists and was written no earlier than the CSV
+    fn is_index_fresh(csv_path: &Path, index_path: &Path) -> Result<bool> {
+        if !index_path.exists() {
+            return Ok(false);
+        }
+
+        let csv_modified = fs::metadata(csv_path)
+            .and_then(|m| m.modified())
+            .context("Failed to get cache modification time")?;
+        let index_modified = fs::metadata(index_path)
+            .and_then(|m| m.modified())
+            .context("Failed to get index modification time")?;
+
+        Ok(index_modified >= csv_modified)
+    }
+
+    fn read_index(path: &Path) -> Result<InstrumentIndex> {
+        let file = fs::File::open(path).context("Failed to open instrument index")?;
+        bincode::deserialize_from(BufReader::new(file)).context("Failed to decode instrument index")
+    }
+
+    fn write_index(path: &Path, index: &InstrumentIndex) -> Result<()> {
+        let file = fs::File::create(path).context("Failed to create instrument index")?;
+        bincode::serialize_into(BufWriter::new(file), index)
+            .context("Failed to write instrument index")
     }
 
     /// Refresh cache by fetching from API and saving
@@ -102,23 +298,57 @@ impl InstrumentCache {
         exchange: &str,
         api_client: &crate::api::KiteConnectClient,
     ) -> Result<Vec<Instrument>> {
-        println!("Fetching instruments for {}...", exchange);
+        crate::output::progress(format_args!("Fetching instruments for {}...", exchange));
 
         let instruments = api_client
             .list_instruments(Some(exchange))
             .await
             .context("Failed to fetch instruments from API")?;
 
-        println!("Found {} instruments", instruments.len());
+        crate::output::progress(format_args!("Found {} instruments", instruments.len()));
 
         // Save to cache
         Self::save(exchange, &instruments).context("Failed to save instruments to cache")?;
 
-        println!("Cache updated: {} instruments saved", instruments.len());
+        crate::output::progress(format_args!("Cache updated: {} instruments saved", instruments.len()));
 
         Ok(instruments)
     }
 
+    /// Refresh every exchange concurrently, saving each independently
+    ///
+    /// Requests share the client's rate limiter; one exchange failing does not
+    /// stop the others.
+    pub async fn refresh_all(api_client: &crate::api::KiteConnectClient) -> Vec<RefreshResult> {
+        let fetched = Self::fetch_all(api_client, &EXCHANGES).await;
+
+        fetched
+            .into_iter()
+            .map(|(exchange, instruments)| RefreshResult {
+                exchange,
+                result: instruments.and_then(|instruments| {
+                    Self::save(exchange, &instruments)
+                        .context("Failed to save instruments to cache")?;
+                    Ok(instruments.len())
+                }),
+            })
+            .collect()
+    }
+
+    async fn fetch_all(
+        api_client: &crate::api::KiteConnectClient,
+        exchanges: &[&'static str],
+    ) -> Vec<(&'static str, Result<Vec<Instrument>>)> {
+        let downloads = exchanges.iter().map(|&exchange| async move {
+            let instruments = api_client
+                .list_instruments(Some(exchange))
+                .await
+                .with_context(|| format!("Failed to fetch {} instruments", exchange));
+            (exchange, instruments)
+        });
+        futures::future::join_all(downloads).await
+    }
+
     /// Load from cache, or refresh if invalid
     pub async fn load_or_refresh(
         exchange: &str,
@@ -126,10 +356,10 @@ impl InstrumentCache {
         force_refresh: bool,
     ) -> Result<Vec<Instrument>> {
         if force_refresh || !Self::is_valid(exchange)? {
-            println!("Cache for {} is expired or refresh requested", exchange);
+            crate::output::progress(format_args!("Cache for {} is expired or refresh requested", exchange));
             Self::refresh(exchange, api_client).await
         } else {
-            println!("Loading {} instruments from cache...", exchange);
+            crate::output::progress(format_args!("Loading {} instruments from cache...", exchange));
             Self::load(exchange)
         }
     }
@@ -149,7 +379,7 @@ impl InstrumentCache {
             let entry = entry.context("Failed to read directory entry")?;
             let path = entry.path();
 
-            if path.extension().is_some_and(|ext| ext == "csv") {
+            if Self::exchange_of(&path).is_some() || path.extension().is_some_and(|ext| ext == "idx") {
                 fs::remove_file(&path)
                     .context(format!("Failed to remove cache file: {:?}", path))?;
                 cleared_count += 1;
@@ -181,17 +411,13 @@ impl InstrumentCache {
             let entry = entry.context("Failed to read directory entry")?;
             let path = entry.path();
 
-            if path.extension().is_some_and(|ext| ext == "csv") {
+            if let Some(exchange) = Self::exchange_of(&path) {
                 let metadata = fs::metadata(&path).context("Failed to read file metadata")?;
                 let size = metadata.len();
                 let modified: DateTime<Utc> = metadata.modified()?.into();
 
                 files.push(CacheFile {
-                    exchange: path
-                        .file_stem()
-                        .and_then(|s| s.to_str())
-                        .unwrap_or("unknown")
-                        .to_string(),
+                    exchange: exchange.to_string(),
                     size,
                     modified,
                 });
@@ -235,6 +461,137 @@ mod tests {
         assert!(path.to_str().unwrap().to_lowercase().contains("nse"));
     }
 
+    fn sample_instrument(symbol: &str) -> Instrument {
+        use crate::models::{Exchange, InstrumentType, Segment};
+
+        Instrument {
+            instrument_token: 408065,
+            exchange_token: 1594,
+            tradingsymbol: symbol.to_string(),
+            name: symbol.to_string(),
+            last_price: Some(0.0),
+            expiry: None,
+            strike: Some(0.0),
+            tick_size: 0.05,
+            lot_size: 1,
+            instrument_type: InstrumentType::Equity,
+            segment: Segment::NSE,
+            exchange: Exchange::NSE,
+        }
+    }
+
+    fn write_csv(path: &Path, instruments: &[Instrument]) {
+        let mut wtr = csv::Writer::from_path(path).unwrap();
+        for instrument in instruments {
+            wtr.serialize(instrument).unwrap();
+        }
+        wtr.flush().unwrap();
+    }
+
+    #[test]
+    fn test_index_sidecar_used_when_fresh() {
+        let dir = tempfile::tempdir().unwrap();
+        let csv_path = dir.path().join("nse.csv");
+        let idx_path = dir.path().join("nse.idx");
+
+        write_csv(&csv_path, &[sample_instrument("INFY")]);
+
+        // First load parses the CSV and writes the sidecar
+        let index = InstrumentCache::load_index_from(&csv_path, &idx_path).unwrap();
+        assert_eq!(index.len(), 1);
+        assert!(idx_path.exists());
+
+        // Replace the sidecar with different contents; a fresh sidecar wins over the CSV
+        let sidecar = InstrumentCache::build_index(vec![
+            sample_instrument("INFY"),
+            sample_instrument("TCS"),
+        ]);
+        InstrumentCache::write_index(&idx_path, &sidecar).unwrap();
+
+        let index = InstrumentCache::load_index_from(&csv_path, &idx_path).unwrap();
+        assert_eq!(index.len(), 2);
+        assert!(index.contains_key(&InstrumentKey::new("nse", "tcs")));
+    }
+
+    #[test]
+    fn test_index_sidecar_rebuilt_when_stale() {
+        let dir = tempfile::tempdir().unwrap();
+        let csv_path = dir.path().join("nse.csv");
+        let idx_path = dir.path().join("nse.idx");
+
+        write_csv(&csv_path, &[sample_instrument("INFY")]);
+        InstrumentCache::load_index_from(&csv_path, &idx_path).unwrap();
+
+        // Rewrite the CSV and make it newer than the sidecar
+        write_csv(
+            &csv_path,
+            &[
+                sample_instrument("INFY"),
+                sample_instrument("TCS"),
+                sample_instrument("WIPRO"),
+            ],
+        );
+        let later = std::time::SystemTime::now() + std::time::Duration::from_secs(60);
+        fs::File::options()
+            .write(true)
+            .open(&csv_path)
+            .unwrap()
+            .set_modified(later)
+            .unwrap();
+
+        let index = InstrumentCache::load_index_from(&csv_path, &idx_path).unwrap();
+        assert_eq!(index.len(), 3);
+
+        // The sidecar was rewritten with the new contents
+        let sidecar = InstrumentCache::read_index(&idx_path).unwrap();
+        assert_eq!(sidecar.len(), 3);
+    }
+
+    #[test]
+    fn test_compressed_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("nse.csv.gz");
+        let mut instruments: Vec<Instrument> = ["INFY", "TCS", "WIPRO"]
+            .into_iter()
+            .map(sample_instrument)
+            .collect();
+        instruments[1].expiry = Some("2024-06-27".to_string());
+        instruments[2].strike = Some(1500.5);
+
+        InstrumentCache::save_to_path(&path, &instruments).unwrap();
+        let bytes = fs::read(&path).unwrap();
+        assert_eq!(&bytes[..2], &[0x1f, 0x8b], "not gzip");
+
+        let loaded = InstrumentCache::load_from_path(&path).unwrap();
+        assert_eq!(
+            serde_json::to_value(&loaded).unwrap(),
+            serde_json::to_value(&instruments).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_legacy_plain_csv_is_read_when_compressed_missing() {
+        let dir = tempfile::tempdir().unwrap();
+        let compressed = dir.path().join("nse.csv.gz");
+        let legacy = dir.path().join("nse.csv");
+        assert_eq!(InstrumentCache::pick_existing(compressed.clone(), legacy.clone()), None);
+
+        write_csv(&legacy, &[sample_instrument("INFY")]);
+        let path = InstrumentCache::pick_existing(compressed.clone(), legacy.clone()).unwrap();
+        assert_eq!(path, legacy);
+        assert_eq!(InstrumentCache::load_from_path(&path).unwrap().len(), 1);
+
+        InstrumentCache::save_to_path(&compressed, &[sample_instrument("TCS")]).unwrap();
+        assert_eq!(InstrumentCache::pick_existing(compressed.clone(), legacy), Some(compressed));
+    }
+
+    #[test]
+    fn test_exchange_of_cache_file() {
+        assert_eq!(InstrumentCache::exchange_of(Path::new("/c/nse.csv.gz")), Some("nse"));
+        assert_eq!(InstrumentCache::exchange_of(Path::new("/c/bse.csv")), Some("bse"));
+        assert_eq!(InstrumentCache::exchange_of(Path::new("/c/nse.idx")), None);
+    }
+
     #[test]
     fn test_is_valid_no_file() {
         // Use a non-existent exchange
@@ -242,4 +599,46 @@ mod tests {
         assert!(result.is_ok());
         assert!(!result.unwrap());
     }
+
+    #[test]
+    fn test_zero_ttl_is_always_invalid() {
+        let dir = tempfile::tempdir().unwrap();
+        let csv_path = dir.path().join("nse.csv");
+        write_csv(&csv_path, &[sample_instrument("INFY")]);
+
+        assert!(InstrumentCache::is_fresh(&csv_path, DEFAULT_TTL_HOURS).unwrap());
+        assert!(!InstrumentCache::is_fresh(&csv_path, 0).unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_fetch_all_isolates_failures() {
+        let mut server = mockito::Server::new_async().await;
+        let header = "instrument_token,exchange_token,tradingsymbol,name,last_price,expiry,strike,tick_size,lot_size,instrument_type,segment,exchange";
+        let nse = server
+            .mock("GET", "/instruments/NSE")
+            .with_body(format!(
+                "{}\n408065,1594,INFY,INFOSYS,0,,0,0.05,1,EQ,NSE,NSE\n",
+                header
+            ))
+            .create_async()
+            .await;
+        let bse = server
+            .mock("GET", "/instruments/BSE")
+            .with_status(500)
+            .create_async()
+            .await;
+
+        let client = crate::api::KiteConnectClient::new("key".into(), "secret".into())
+            .with_base_url(server.url());
+        client.set_access_token("token".into()).await.unwrap();
+
+        let results = InstrumentCache::fetch_all(&client, &["NSE", "BSE"]).await;
+
+        assert_eq!(results[0].0, "NSE");
+        assert_eq!(results[0].1.as_ref().unwrap().len(), 1);
+        assert_eq!(results[1].0, "BSE");
+        assert!(results[1].1.is_err());
+        nse.assert_async().await;
+        bse.assert_async().await;
+    }
 }
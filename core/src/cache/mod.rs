@@ -2,4 +2,9 @@
 
 #[allow(clippy::module_inception)]
 pub mod cache;
-pub use cache::{CacheFile, CacheInfo, InstrumentCache};
+pub mod search;
+pub use cache::{
+    CacheFile, CacheInfo, InstrumentCache, InstrumentIndex, InstrumentKey, RefreshResult,
+    DEFAULT_TTL_HOURS, EXCHANGES,
+};
+pub use search::search_instruments;
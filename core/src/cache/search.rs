@@ -0,0 +1,117 @@
+//! Instrument search with fuzzy scoring
+
+use crate::models::Instrument;
+use fuzzy_matcher::skim::SkimMatcherV2;
+use fuzzy_matcher::FuzzyMatcher;
+
+/// Search instruments by symbol or name, best matches first
+///
+/// An exact (case-insensitive) symbol match always ranks first. With `exact`,
+/// only substring matches are kept.
+pub fn search_instruments(
+    instruments: Vec<Instrument>,
+    query: &str,
+    exact: bool,
+    limit: usize,
+) -> Vec<Instrument> {
+    let matcher = SkimMatcherV2::default().ignore_case();
+    let query_lower = query.to_lowercase();
+
+    let mut scored: Vec<(i64, Instrument)> = instruments
+        .into_iter()
+        .filter_map(|inst| {
+            let score = if inst.tradingsymbol.eq_ignore_ascii_case(query) {
+                Some(i64::MAX)
+            } else if exact {
+                let symbol = inst.tradingsymbol.to_lowercase();
+                let name = inst.name.to_lowercase();
+                if symbol.starts_with(&query_lower) {
+                    Some(2)
+                } else if symbol.contains(&query_lower) || name.contains(&query_lower) {
+                    Some(1)
+                } else {
+                    None
+                }
+            } else {
+                // Symbol hits weigh more than name hits
+                let symbol = matcher
+                    .fuzzy_match(&inst.tradingsymbol, query)
+                    .map(|s| s * 2);
+                let name = matcher.fuzzy_match(&inst.name, query);
+                symbol.max(name)
+            };
+            score.map(|s| (s, inst))
+        })
+        .collect();
+
+    scored.sort_by(|(a_score, a), (b_score, b)| {
+        b_score
+            .cmp(a_score)
+            .then_with(|| a.tradingsymbol.cmp(&b.tradingsymbol))
+    });
+    scored.truncate(limit);
+    scored.into_iter().map(|(_, inst)| inst).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Exchange, InstrumentType, Segment};
+
+    fn instrument(symbol: &str, name: &str) -> Instrument {
+        Instrument {
+            instrument_token: 1,
+            exchange_token: 1,
+            tradingsymbol: symbol.to_string(),
+            name: name.to_string(),
+            last_price: None,
+            expiry: None,
+            strike: None,
+            tick_size: 0.05,
+            lot_size: 1,
+            instrument_type: InstrumentType::Equity,
+            segment: Segment::NSE,
+            exchange: Exchange::NSE,
+        }
+    }
+
+    fn symbols(results: &[Instrument]) -> Vec<&str> {
+        results.iter().map(|i| i.tradingsymbol.as_str()).collect()
+    }
+
+    fn universe() -> Vec<Instrument> {
+        vec![
+            instrument("INFYBEES", "NIPPON INDIA ETF INFY"),
+            instrument("TCS", "TATA CONSULTANCY SERV LT"),
+            instrument("INFY", "INFOSYS"),
+            instrument("NIFTYBEES", "NIPPON INDIA ETF NIFTY"),
+        ]
+    }
+
+    #[test]
+    fn test_fuzzy_matches_misspelled_name() {
+        let results = search_instruments(universe(), "infsys", false, 10);
+        assert_eq!(symbols(&results), vec!["INFY"]);
+    }
+
+    #[test]
+    fn test_exact_symbol_ranks_first() {
+        let results = search_instruments(universe(), "infy", false, 10);
+        assert_eq!(results[0].tradingsymbol, "INFY");
+        assert!(symbols(&results).contains(&"INFYBEES"));
+    }
+
+    #[test]
+    fn test_exact_mode_uses_substring_match() {
+        let results = search_instruments(universe(), "bees", true, 10);
+        assert_eq!(symbols(&results), vec!["INFYBEES", "NIFTYBEES"]);
+
+        assert!(search_instruments(universe(), "infsys", true, 10).is_empty());
+    }
+
+    #[test]
+    fn test_limit_caps_results() {
+        let results = search_instruments(universe(), "i", false, 2);
+        assert_eq!(results.len(), 2);
+    }
+}
@@ -0,0 +1,199 @@
+//! Option chain construction
+
+use crate::api::KiteConnectClient;
+use crate::models::{Instrument, InstrumentType};
+use anyhow::Result;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// Maximum instruments per LTP request
+const LTP_BATCH_SIZE: usize = 500;
+
+/// One side (call or put) of an option chain row
+#[derive(Debug, Clone, Serialize)]
+pub struct ChainLeg {
+    pub tradingsymbol: String,
+    pub instrument_token: u64,
+    pub last_price: Option<f64>,
+}
+
+/// Call and put contracts sharing a strike
+#[derive(Debug, Clone, Serialize)]
+pub struct ChainRow {
+    pub strike: f64,
+    pub call: Option<ChainLeg>,
+    pub put: Option<ChainLeg>,
+}
+
+/// Derivatives exchange holding options on an underlying's exchange
+pub fn options_exchange(exchange: &str) -> &str {
+    match exchange.to_uppercase().as_str() {
+        "NSE" => "NFO",
+        "BSE" => "BFO",
+        _ => exchange,
+    }
+}
+
+/// Earliest expiry on or after `today` (YYYY-MM-DD) with options on `underlying`
+pub fn nearest_expiry(instruments: &[Instrument], underlying: &str, today: &str) -> Option<String> {
+    instruments
+        .iter()
+        .filter(|inst| is_option_on(inst, underlying))
+        .filter_map(|inst| inst.expiry.as_deref())
+        .filter(|expiry| *expiry >= today)
+        .min()
+        .map(str::to_string)
+}
+
+/// Pair CE/PE contracts of `underlying` expiring on `expiry` by strike, sorted by strike
+///
+/// Contracts without a strike are skipped.
+pub fn build_chain(instruments: &[Instrument], underlying: &str, expiry: &str) -> Vec<ChainRow> {
+    // Key strikes in paise so float strikes group reliably
+    let mut rows: BTreeMap<i64, ChainRow> = BTreeMap::new();
+
+    for inst in instruments {
+        if !is_option_on(inst, underlying) || inst.expiry.as_deref() != Some(expiry) {
+            continue;
+        }
+        let Some(strike) = inst.strike.filter(|s| *s > 0.0) else {
+            continue;
+        };
+
+        let row = rows
+            .entry((strike * 100.0).round() as i64)
+            .or_insert_with(|| ChainRow {
+                strike,
+                call: None,
+                put: None,
+            });
+        let leg = Some(ChainLeg {
+            tradingsymbol: inst.tradingsymbol.clone(),
+            instrument_token: inst.instrument_token,
+            last_price: None,
+        });
+        match inst.instrument_type {
+            InstrumentType::CallOption => row.call = leg,
+            InstrumentType::PutOption => row.put = leg,
+            _ => {}
+        }
+    }
+
+    rows.into_values().collect()
+}
+
+/// Build the chain and fill in last traded prices
+///
+/// Legs whose LTP is not returned keep `last_price: None`.
+pub async fn option_chain(
+    api_client: &KiteConnectClient,
+    instruments: &[Instrument],
+    exchange: &str,
+    underlying: &str,
+    expiry: &str,
+) -> Result<Vec<ChainRow>> {
+    let mut rows = build_chain(instruments, underlying, expiry);
+
+    let symbols: Vec<String> = rows
+        .iter()
+        .flat_map(|row| [&row.call, &row.put])
+        .flatten()
+        .map(|leg| format!("{}:{}", exchange, leg.tradingsymbol))
+        .collect();
+
+    let mut prices = std::collections::HashMap::new();
+    for batch in symbols.chunks(LTP_BATCH_SIZE) {
+        let refs: Vec<&str> = batch.iter().map(String::as_str).collect();
+        let response = api_client.get_ltp(&refs).await?;
+        prices.extend(
+            response
+                .data
+                .into_iter()
+                .map(|(symbol, ltp)| (symbol, ltp.last_price)),
+        );
+    }
+
+    for row in &mut rows {
+        for leg in [&mut row.call, &mut row.put].into_iter().flatten() {
+            leg.last_price = prices
+                .get(&format!("{}:{}", exchange, leg.tradingsymbol))
+                .copied();
+        }
+    }
+
+    Ok(rows)
+}
+
+fn is_option_on(inst: &Instrument, underlying: &str) -> bool {
+    matches!(
+        inst.instrument_type,
+        InstrumentType::CallOption | InstrumentType::PutOption
+    ) && inst.name.eq_ignore_ascii_case(underlying)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Exchange, Segment};
+
+    fn option(symbol: &str, kind: InstrumentType, strike: Option<f64>, expiry: &str) -> Instrument {
+        Instrument {
+            instrument_token: 1,
+            exchange_token: 1,
+            tradingsymbol: symbol.to_string(),
+            name: "NIFTY".to_string(),
+            last_price: None,
+            expiry: Some(expiry.to_string()),
+            strike,
+            tick_size: 0.05,
+            lot_size: 25,
+            instrument_type: kind,
+            segment: Segment::NFOOPT,
+            exchange: Exchange::NFO,
+        }
+    }
+
+    fn instruments() -> Vec<Instrument> {
+        vec![
+            option("NIFTY24JUN22100CE", InstrumentType::CallOption, Some(22100.0), "2024-06-27"),
+            option("NIFTY24JUN22000PE", InstrumentType::PutOption, Some(22000.0), "2024-06-27"),
+            option("NIFTY24JUN22000CE", InstrumentType::CallOption, Some(22000.0), "2024-06-27"),
+            option("NIFTY24JULCE", InstrumentType::CallOption, None, "2024-06-27"),
+            option("NIFTY24JUL22000CE", InstrumentType::CallOption, Some(22000.0), "2024-07-25"),
+        ]
+    }
+
+    #[test]
+    fn test_build_chain_pairs_by_strike() {
+        let rows = build_chain(&instruments(), "nifty", "2024-06-27");
+
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].strike, 22000.0);
+        assert_eq!(
+            rows[0].call.as_ref().unwrap().tradingsymbol,
+            "NIFTY24JUN22000CE"
+        );
+        assert_eq!(
+            rows[0].put.as_ref().unwrap().tradingsymbol,
+            "NIFTY24JUN22000PE"
+        );
+        assert_eq!(rows[1].strike, 22100.0);
+        assert!(rows[1].put.is_none());
+    }
+
+    #[test]
+    fn test_nearest_expiry() {
+        assert_eq!(
+            nearest_expiry(&instruments(), "NIFTY", "2024-06-28").as_deref(),
+            Some("2024-07-25")
+        );
+        assert!(nearest_expiry(&instruments(), "BANKNIFTY", "2024-06-01").is_none());
+    }
+
+    #[test]
+    fn test_options_exchange() {
+        assert_eq!(options_exchange("NSE"), "NFO");
+        assert_eq!(options_exchange("bse"), "BFO");
+        assert_eq!(options_exchange("NFO"), "NFO");
+    }
+}
@@ -2,25 +2,120 @@
 
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
 
-/// Initialize dotenv (load .env file if present)
-fn init_dotenv() {
-    // Try to load .env from current directory or home directory
-    let _ = dotenv::from_filename(".env").or_else(|_| dotenv::from_filename("~/.env")).ok();
+mod secret_store;
+use secret_store::Token;
+
+/// Profile used when none is selected
+pub const DEFAULT_PROFILE: &str = "default";
+
+/// Load `ZERODHA_*` variables from a `.env` file
+///
+/// Uses `path` when given (it must exist and parse), otherwise the nearest
+/// `.env` in the current directory or its parents; a discovered file that
+/// doesn't parse is skipped with a warning. Other keys, and the record/replay
+/// switches, are ignored so a project's `.env` can't leak unrelated secrets
+/// into the process or change how requests are sent. Variables already set in
+/// the environment are never overwritten, so they keep precedence over the
+/// file.
+pub fn load_env_file(path: Option<&Path>) -> Result<Option<PathBuf>> {
+    let explicit = path.is_some();
+    let path = match path {
+        Some(path) => path.to_path_buf(),
+        None => match std::env::current_dir().ok().and_then(|dir| find_env_file(&dir)) {
+            Some(path) => path,
+            None => return Ok(None),
+        },
+    };
+
+    let vars = match read_env_file(&path) {
+        Ok(vars) => vars,
+        Err(e) if !explicit => {
+            eprintln!("Warning: ignoring {}: {:#}", path.display(), e);
+            return Ok(None);
+        }
+        Err(e) => return Err(e),
+    };
+    for (key, value) in vars {
+        if std::env::var_os(&key).is_none() {
+            std::env::set_var(key, value);
+        }
+    }
+    Ok(Some(path))
+}
+
+/// `ZERODHA_*` entries of an env file, failing on the first line that doesn't parse
+fn read_env_file(path: &Path) -> Result<Vec<(String, String)>> {
+    use crate::api::recording::{RECORD_ENV, REPLAY_ENV, REPLAY_WRITES_ENV};
+
+    // The suggested replacement, `from_path`, exports every key in the file
+    #[allow(deprecated)]
+    let entries = dotenv::from_path_iter(path)
+        .with_context(|| format!("Failed to load env file {}", path.display()))?;
+    let mut vars = Vec::new();
+    for entry in entries {
+        let (key, value) =
+            entry.with_context(|| format!("Failed to parse env file {}", path.display()))?;
+        let switch = [RECORD_ENV, REPLAY_ENV, REPLAY_WRITES_ENV].contains(&key.as_str());
+        if key.starts_with("ZERODHA_") && !switch {
+            vars.push((key, value));
+        }
+    }
+    Ok(vars)
+}
+
+/// Nearest `.env` in `start` or one of its ancestors, like git finds `.git`
+pub fn find_env_file(start: &Path) -> Option<PathBuf> {
+    start
+        .ancestors()
+        .map(|dir| dir.join(".env"))
+        .find(|candidate| candidate.is_file())
 }
 
 /// Configuration file structure
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
+    /// Profile selected by `kite auth use`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub active_profile: Option<String>,
+    /// Credentials of the loaded profile; a legacy `[api]` section is read as `default`
+    #[serde(default, skip_serializing)]
     pub api: ApiConfig,
+    /// Name of the profile loaded into `api`
+    #[serde(skip)]
+    pub profile: String,
+    /// File this config was loaded from and saves back to
+    #[serde(skip)]
+    pub path: PathBuf,
+    /// Per-account credentials, keyed by profile name
+    #[serde(default)]
+    pub profiles: BTreeMap<String, ApiConfig>,
     #[serde(default)]
     pub defaults: DefaultsConfig,
     #[serde(default)]
     pub output: OutputConfig,
+    #[serde(default)]
+    pub security: SecurityConfig,
+    #[serde(default)]
+    pub cache: CacheConfig,
+    #[serde(default)]
+    pub risk: RiskConfig,
+    /// Credentials replaced by flags or environment variables, kept out of the file
+    #[serde(skip)]
+    overrides: CredentialOverrides,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// File value and replacement for each overridden credential
+#[derive(Debug, Clone, Default)]
+struct CredentialOverrides {
+    api_key: Option<(String, String)>,
+    api_secret: Option<(String, String)>,
+    access_token: Option<(Option<String>, String)>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct ApiConfig {
     pub api_key: String,
     pub api_secret: String,
@@ -28,9 +123,45 @@ pub struct ApiConfig {
     pub access_token: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub token_expiry: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub refresh_token: Option<String>,
+    /// Retries after a 429 before giving up
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_retries: Option<u32>,
+    /// Consecutive failures (errors, 5xx) that pause requests; 0 disables (defaults to 5)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub circuit_breaker_threshold: Option<u32>,
+    /// Window in seconds the failures must fall within (defaults to 60)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub circuit_breaker_window_secs: Option<u64>,
+    /// Seconds requests stay paused once tripped (defaults to 30)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub circuit_breaker_cooldown_secs: Option<u64>,
+    /// Requests per second (defaults to Kite's limit of 3)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rate_limit: Option<u32>,
+    /// Request timeout in seconds (defaults to 10)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timeout_secs: Option<u64>,
+    /// Connection timeout in seconds (defaults to 5)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub connect_timeout_secs: Option<u64>,
+    /// Proxy URL for all requests, e.g. http://host:port or socks5://host:port
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub proxy: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub proxy_username: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub proxy_password: Option<String>,
+    /// PEM file with extra root certificates, for TLS-intercepting proxies
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ca_cert: Option<String>,
+    /// Skip TLS certificate verification; exposes the session to interception
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub danger_accept_invalid_certs: Option<bool>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DefaultsConfig {
     #[serde(default = "default_exchange")]
     pub exchange: String,
@@ -42,10 +173,80 @@ pub struct DefaultsConfig {
     pub validity: String,
 }
 
+impl Default for DefaultsConfig {
+    fn default() -> Self {
+        Self {
+            exchange: default_exchange(),
+            product: default_product(),
+            order_type: default_order_type(),
+            validity: default_validity(),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct OutputConfig {
     #[serde(default = "default_format")]
     pub format: String,
+    #[serde(default, skip_serializing_if = "ThemeConfig::is_default")]
+    pub theme: ThemeConfig,
+}
+
+/// `[output.theme]`: a preset plus optional per-role colors
+///
+/// Colors are names like `green` or `dark_red`, or `#rrggbb`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
+pub struct ThemeConfig {
+    /// `default`, `high-contrast` or `colorblind`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub preset: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub positive: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub negative: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pending: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub header: Option<String>,
+}
+
+impl ThemeConfig {
+    fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct SecurityConfig {
+    /// Keep the access and refresh tokens in the OS keyring instead of this file
+    #[serde(default)]
+    pub use_keyring: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct RiskConfig {
+    /// Largest order value (quantity × price) `orders place`/`market` will send
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_order_value: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CacheConfig {
+    /// Hours before cached instrument lists are re-downloaded
+    #[serde(default = "default_instrument_ttl_hours")]
+    pub instrument_ttl_hours: u32,
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        Self {
+            instrument_ttl_hours: default_instrument_ttl_hours(),
+        }
+    }
+}
+
+fn default_instrument_ttl_hours() -> u32 {
+    crate::cache::DEFAULT_TTL_HOURS
 }
 
 fn default_exchange() -> String {
@@ -67,76 +268,244 @@ fn default_format() -> String {
 impl Default for Config {
     fn default() -> Self {
         Self {
-            api: ApiConfig {
-                api_key: String::new(),
-                api_secret: String::new(),
-                access_token: None,
-                token_expiry: None,
-            },
+            active_profile: None,
+            api: ApiConfig::default(),
+            profile: DEFAULT_PROFILE.to_string(),
+            path: PathBuf::new(),
+            profiles: BTreeMap::new(),
             defaults: DefaultsConfig::default(),
             output: OutputConfig::default(),
+            security: SecurityConfig::default(),
+            cache: CacheConfig::default(),
+            risk: RiskConfig::default(),
+            overrides: CredentialOverrides::default(),
         }
     }
 }
 
 impl Config {
-    /// Load config from file
+    /// Load config from file using the active profile
     pub fn load() -> Result<Self> {
-        // Initialize dotenv (load .env file if present)
-        init_dotenv();
-        // First try to load from config file
+        Self::load_profile(None)
+    }
+
+    /// Load config from file, selecting `profile` (or the active one)
+    pub fn load_profile(profile: Option<&str>) -> Result<Self> {
         let config_path = Self::config_path().context("Failed to get config path")?;
+        Self::load_from(&config_path, profile)
+    }
 
-        let mut config = if config_path.exists() {
-            let content = std::fs::read_to_string(&config_path).context("Failed to read config")?;
-            let config: Self = toml::from_str(&content).context("Failed to parse config")?;
-            config
+    /// Load config from `path`, selecting `profile` (or the active one)
+    ///
+    /// A missing file gives the defaults; saving creates it at `path`.
+    pub fn load_from(path: &Path, profile: Option<&str>) -> Result<Self> {
+        let mut config = if path.exists() {
+            let content = std::fs::read_to_string(path)
+                .with_context(|| format!("Failed to read config from {}", path.display()))?;
+            Self::from_toml(&content, profile).context("Failed to parse config")?
         } else {
-            Self::default()
+            let mut config = Self::default();
+            config.select_profile(profile.unwrap_or(DEFAULT_PROFILE));
+            config
         };
+        config.path = path.to_path_buf();
+        config.load_keyring_token();
 
-        // Override with environment variables if set
-        if let Ok(api_key) = std::env::var("ZERODHA_API_KEY") {
-            if !api_key.is_empty() {
-                eprintln!("Debug: Loaded API key from env: {} chars", api_key.len());
-                config.api.api_key = api_key;
-            }
-        } else {
-            eprintln!("Debug: ZERODHA_API_KEY not set");
+        config.apply_env_overrides();
+
+        Ok(config)
+    }
+
+    /// Parse config contents and select `profile` (or the active one)
+    pub fn from_toml(content: &str, profile: Option<&str>) -> Result<Self> {
+        let mut config: Self = toml::from_str(content)?;
+
+        // Older configs keep a single account under [api]
+        if !config.api.api_key.is_empty() && !config.profiles.contains_key(DEFAULT_PROFILE) {
+            config
+                .profiles
+                .insert(DEFAULT_PROFILE.to_string(), config.api.clone());
         }
-        if let Ok(api_secret) = std::env::var("ZERODHA_API_SECRET") {
-            if !api_secret.is_empty() {
-                config.api.api_secret = api_secret;
-            }
+
+        let name = profile
+            .map(str::to_string)
+            .or_else(|| config.active_profile.clone())
+            .unwrap_or_else(|| DEFAULT_PROFILE.to_string());
+        config.select_profile(&name);
+        Ok(config)
+    }
+
+    /// Override credentials from `ZERODHA_API_KEY`, `ZERODHA_API_SECRET` and
+    /// `ZERODHA_ACCESS_TOKEN`
+    pub fn apply_env_overrides(&mut self) {
+        let var = |name: &str| std::env::var(name).ok();
+        self.override_credentials(
+            var("ZERODHA_API_KEY").as_deref(),
+            var("ZERODHA_API_SECRET").as_deref(),
+            var("ZERODHA_ACCESS_TOKEN").as_deref(),
+        );
+    }
+
+    /// Replace the loaded credentials with any non-empty values given
+    ///
+    /// The replacements last for this run only: `save` writes back the file's
+    /// values unless a credential was changed again after the override.
+    pub fn override_credentials(
+        &mut self,
+        api_key: Option<&str>,
+        api_secret: Option<&str>,
+        access_token: Option<&str>,
+    ) {
+        let given = |value: Option<&str>| value.filter(|v| !v.is_empty()).map(str::to_string);
+        let overrides = &mut self.overrides;
+
+        if let Some(api_key) = given(api_key) {
+            let original = overrides.api_key.take().map(|(file, _)| file);
+            let original = original.unwrap_or_else(|| self.api.api_key.clone());
+            self.api.api_key = api_key.clone();
+            overrides.api_key = Some((original, api_key));
+        }
+        if let Some(api_secret) = given(api_secret) {
+            let original = overrides.api_secret.take().map(|(file, _)| file);
+            let original = original.unwrap_or_else(|| self.api.api_secret.clone());
+            self.api.api_secret = api_secret.clone();
+            overrides.api_secret = Some((original, api_secret));
+        }
+        if let Some(access_token) = given(access_token) {
+            let original = overrides.access_token.take().map(|(file, _)| file);
+            let original = original.unwrap_or_else(|| self.api.access_token.clone());
+            self.api.access_token = Some(access_token.clone());
+            overrides.access_token = Some((original, access_token));
+        }
+    }
+
+    /// Fail with a hint on where credentials are read from when no API key is set
+    pub fn require_credentials(&self) -> Result<()> {
+        if self.api.api_key.is_empty() {
+            return Err(crate::error::ZerodhaError::Auth(
+                "No API key configured. Credentials are read from --api-key/--access-token, \
+                 then ZERODHA_API_KEY/ZERODHA_ACCESS_TOKEN, then the config file \
+                 (run 'kite auth setup')."
+                    .to_string(),
+            )
+            .into());
         }
-        if let Ok(access_token) = std::env::var("ZERODHA_ACCESS_TOKEN") {
-            if !access_token.is_empty() {
-                config.api.access_token = Some(access_token);
+        Ok(())
+    }
+
+    /// Load `name`'s credentials into `api`; unknown profiles start empty
+    pub fn select_profile(&mut self, name: &str) {
+        self.profile = name.to_string();
+        self.api = self.profiles.get(name).cloned().unwrap_or_default();
+        self.overrides = CredentialOverrides::default();
+    }
+
+    /// Replace the access and refresh tokens with the keyring copies when
+    /// `use_keyring` is set
+    ///
+    /// Falls back to the tokens in the file if the keyring is unavailable.
+    pub fn load_keyring_token(&mut self) {
+        if !self.security.use_keyring {
+            return;
+        }
+        for (token, slot) in [
+            (Token::Access, &mut self.api.access_token),
+            (Token::Refresh, &mut self.api.refresh_token),
+        ] {
+            match secret_store::load_token(&self.profile, token) {
+                Ok(Some(value)) => *slot = Some(value),
+                Ok(None) => {}
+                Err(e) => {
+                    eprintln!(
+                        "Warning: OS keyring unavailable ({}); using tokens from config file",
+                        e
+                    );
+                    return;
+                }
             }
         }
+    }
 
-        Ok(config)
+    /// Names of all configured profiles
+    pub fn profile_names(&self) -> Vec<&str> {
+        self.profiles.keys().map(String::as_str).collect()
+    }
+
+    /// Make `name` the profile used when `--profile` is not given
+    pub fn set_active_profile(&mut self, name: &str) -> Result<()> {
+        if !self.profiles.contains_key(name) {
+            anyhow::bail!(
+                "Profile '{}' not found. Run 'kite --profile {} auth setup' first.",
+                name,
+                name
+            );
+        }
+        self.active_profile = Some(name.to_string());
+        Ok(())
     }
 
-    /// Save config to file
+    /// Save config to the file it was loaded from
+    ///
+    /// Only the loaded profile's entry is updated from `api`. Credentials that
+    /// still hold a flag or environment override are saved with their file values.
     pub fn save(&self) -> Result<()> {
-        let config_path = Self::config_path().context("Failed to get config path")?;
+        let config_path = &self.path;
         let config_dir = config_path
             .parent()
             .ok_or_else(|| anyhow::anyhow!("Invalid config path"))?;
 
+        let mut profile_api = self.api.clone();
+        let overrides = &self.overrides;
+        if let Some((file, value)) = &overrides.api_key {
+            if profile_api.api_key == *value {
+                profile_api.api_key = file.clone();
+            }
+        }
+        if let Some((file, value)) = &overrides.api_secret {
+            if profile_api.api_secret == *value {
+                profile_api.api_secret = file.clone();
+            }
+        }
+        if let Some((file, value)) = &overrides.access_token {
+            if profile_api.access_token.as_ref() == Some(value) {
+                profile_api.access_token = file.clone();
+            }
+        }
+        if self.security.use_keyring {
+            for (token, slot) in [
+                (Token::Access, &mut profile_api.access_token),
+                (Token::Refresh, &mut profile_api.refresh_token),
+            ] {
+                let stored = match slot {
+                    Some(value) => secret_store::store_token(&self.profile, token, value),
+                    None => secret_store::delete_token(&self.profile, token),
+                };
+                match stored {
+                    Ok(()) => *slot = None,
+                    Err(e) => {
+                        eprintln!(
+                            "Warning: OS keyring unavailable ({}); storing tokens in config file",
+                            e
+                        );
+                        break;
+                    }
+                }
+            }
+        }
+
+        let mut on_disk = self.clone();
+        on_disk.profiles.insert(self.profile.clone(), profile_api);
+
         std::fs::create_dir_all(config_dir).context("Failed to create config directory")?;
-        let content = toml::to_string_pretty(self).context("Failed to serialize config")?;
-        std::fs::write(&config_path, content).context("Failed to write config")?;
+        let content = toml::to_string_pretty(&on_disk).context("Failed to serialize config")?;
+        std::fs::write(config_path, content).context("Failed to write config")?;
 
         Ok(())
     }
 
-    /// Get config file path
+    /// Get config file path: `ZERODHA_CONFIG` if set, else the platform config dir
     pub fn config_path() -> Result<PathBuf> {
-        let config_dir =
-            dirs::config_dir().ok_or_else(|| anyhow::anyhow!("Failed to get config directory"))?;
-        Ok(config_dir.join("zerodha-cli").join("config.toml"))
+        config_path_from(std::env::var_os("ZERODHA_CONFIG").map(PathBuf::from))
     }
 
     /// Check if token is valid
@@ -150,6 +519,15 @@ impl Config {
     }
 }
 
+fn config_path_from(env_override: Option<PathBuf>) -> Result<PathBuf> {
+    if let Some(path) = env_override.filter(|p| !p.as_os_str().is_empty()) {
+        return Ok(path);
+    }
+    let config_dir =
+        dirs::config_dir().ok_or_else(|| anyhow::anyhow!("Failed to get config directory"))?;
+    Ok(config_dir.join("zerodha-cli").join("config.toml"))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -161,15 +539,65 @@ mod tests {
         assert!(config.api.api_secret.is_empty());
         assert!(config.api.access_token.is_none());
         assert!(config.api.token_expiry.is_none());
-        // DefaultsConfig uses Default trait which gives empty strings for String
-        // The serde default functions only apply during deserialization
-        assert!(config.defaults.exchange.is_empty());
-        assert!(config.defaults.product.is_empty());
-        assert!(config.defaults.order_type.is_empty());
-        assert!(config.defaults.validity.is_empty());
+        assert_eq!(config.defaults.exchange, "NSE");
+        assert_eq!(config.defaults.product, "CNC");
+        assert_eq!(config.defaults.order_type, "LIMIT");
+        assert_eq!(config.defaults.validity, "DAY");
         assert!(config.output.format.is_empty());
     }
 
+    #[test]
+    fn test_find_env_file_walks_up() {
+        let root = tempfile::tempdir().unwrap();
+        let nested = root.path().join("project/src/bin");
+        std::fs::create_dir_all(&nested).unwrap();
+        assert_eq!(find_env_file(&nested), None);
+
+        std::fs::write(root.path().join("project/.env"), "X=1\n").unwrap();
+        assert_eq!(find_env_file(&nested), Some(root.path().join("project/.env")));
+
+        // A closer file shadows the outer one
+        std::fs::write(nested.join(".env"), "X=2\n").unwrap();
+        assert_eq!(find_env_file(&nested), Some(nested.join(".env")));
+    }
+
+    #[test]
+    fn test_load_env_file_keeps_existing_vars() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("kite.env");
+        std::fs::write(
+            &path,
+            "ZERODHA_ENV_FILE_TEST_SET=from_file\nZERODHA_ENV_FILE_TEST_NEW=from_file\n",
+        )
+        .unwrap();
+        std::env::set_var("ZERODHA_ENV_FILE_TEST_SET", "from_env");
+
+        assert_eq!(load_env_file(Some(&path)).unwrap(), Some(path.clone()));
+        assert_eq!(std::env::var("ZERODHA_ENV_FILE_TEST_SET").unwrap(), "from_env");
+        assert_eq!(std::env::var("ZERODHA_ENV_FILE_TEST_NEW").unwrap(), "from_file");
+
+        assert!(load_env_file(Some(&dir.path().join("missing.env"))).is_err());
+    }
+
+    #[test]
+    fn test_env_file_only_loads_zerodha_vars() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(".env");
+        std::fs::write(
+            &path,
+            "ZERODHA_ENV_FILTER_TEST=kept\nAWS_SECRET_ACCESS_KEY=leak\nZERODHA_REPLAY=cassettes\n",
+        )
+        .unwrap();
+        assert_eq!(
+            read_env_file(&path).unwrap(),
+            [("ZERODHA_ENV_FILTER_TEST".to_string(), "kept".to_string())]
+        );
+
+        std::fs::write(&path, "ZERODHA_API_KEY=ok\nnot a valid line\n").unwrap();
+        assert!(read_env_file(&path).is_err());
+        assert!(load_env_file(Some(&path)).is_err());
+    }
+
     #[test]
     fn test_is_token_valid_no_token() {
         let config = Config::default();
@@ -219,6 +647,29 @@ mod tests {
         assert!(path.to_str().unwrap().contains("config.toml"));
     }
 
+    #[test]
+    fn test_config_path_env_override() {
+        let custom = PathBuf::from("/tmp/kite/custom.toml");
+        assert_eq!(config_path_from(Some(custom.clone())).unwrap(), custom);
+        // An empty ZERODHA_CONFIG falls back to the platform path
+        let fallback = config_path_from(Some(PathBuf::new())).unwrap();
+        assert!(fallback.ends_with("zerodha-cli/config.toml"));
+    }
+
+    #[test]
+    fn test_load_from_saves_back_to_same_path() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("nested").join("kite.toml");
+
+        let mut config = Config::load_from(&path, Some("trading")).unwrap();
+        assert_eq!(config.path, path);
+        config.api.api_key = "custom-key".to_string();
+        config.save().unwrap();
+
+        let reloaded = Config::load_from(&path, Some("trading")).unwrap();
+        assert_eq!(reloaded.api.api_key, "custom-key");
+    }
+
     #[test]
     fn test_serialize_deserialize() {
         let config = Config::default();
@@ -232,4 +683,140 @@ mod tests {
         assert_eq!(config.defaults.validity, deserialized.defaults.validity);
         assert_eq!(config.output.format, deserialized.output.format);
     }
+
+    #[test]
+    fn test_legacy_api_section_becomes_default_profile() {
+        let content = r#"
+[api]
+api_key = "legacy_key"
+api_secret = "legacy_secret"
+"#;
+        let config = Config::from_toml(content, None).unwrap();
+        assert_eq!(config.profile, DEFAULT_PROFILE);
+        assert_eq!(config.api.api_key, "legacy_key");
+        assert_eq!(config.profile_names(), vec![DEFAULT_PROFILE]);
+    }
+
+    #[test]
+    fn test_missing_defaults_section_uses_default_values() {
+        let content = "[api]\napi_key = \"k\"\napi_secret = \"s\"\n";
+        let config = Config::from_toml(content, None).unwrap();
+        assert_eq!(config.defaults.exchange, "NSE");
+        assert_eq!(config.defaults.product, "CNC");
+
+        let config = Config::from_toml("[defaults]\nproduct = \"MIS\"\n", None).unwrap();
+        assert_eq!(config.defaults.product, "MIS");
+        assert_eq!(config.defaults.validity, "DAY");
+    }
+
+    #[test]
+    fn test_profiles_are_isolated() {
+        let content = r#"
+active_profile = "work"
+
+[profiles.work]
+api_key = "work_key"
+api_secret = "work_secret"
+access_token = "work_token"
+
+[profiles.personal]
+api_key = "personal_key"
+api_secret = "personal_secret"
+"#;
+        let active = Config::from_toml(content, None).unwrap();
+        assert_eq!(active.profile, "work");
+        assert_eq!(active.api.access_token.as_deref(), Some("work_token"));
+
+        let personal = Config::from_toml(content, Some("personal")).unwrap();
+        assert_eq!(personal.api.api_key, "personal_key");
+        assert!(personal.api.access_token.is_none());
+
+        // The loaded profile's credentials are never written under [api]
+        let toml_str = toml::to_string_pretty(&personal).unwrap();
+        assert!(!toml_str.contains("[api]"));
+    }
+
+    #[test]
+    fn test_security_section() {
+        let config = Config::from_toml("", None).unwrap();
+        assert!(!config.security.use_keyring);
+
+        let content = r#"
+[security]
+use_keyring = true
+"#;
+        let config = Config::from_toml(content, None).unwrap();
+        assert!(config.security.use_keyring);
+    }
+
+    #[test]
+    fn test_cache_ttl_config() {
+        let config = Config::from_toml("", None).unwrap();
+        assert_eq!(config.cache.instrument_ttl_hours, 24);
+
+        let config = Config::from_toml("[cache]\ninstrument_ttl_hours = 2\n", None).unwrap();
+        assert_eq!(config.cache.instrument_ttl_hours, 2);
+    }
+
+    #[test]
+    fn test_set_active_profile_requires_existing_profile() {
+        let mut config = Config::default();
+        assert!(config.set_active_profile("missing").is_err());
+
+        config
+            .profiles
+            .insert("work".to_string(), ApiConfig::default());
+        config.set_active_profile("work").unwrap();
+        assert_eq!(config.active_profile.as_deref(), Some("work"));
+    }
+
+    #[test]
+    fn test_override_credentials() {
+        let mut config = Config::from_toml(
+            "[api]\napi_key = \"file_key\"\napi_secret = \"file_secret\"\naccess_token = \"file_token\"\n",
+            None,
+        )
+        .unwrap();
+
+        config.override_credentials(Some("flag_key"), Some(""), None);
+
+        assert_eq!(config.api.api_key, "flag_key");
+        assert_eq!(config.api.api_secret, "file_secret");
+        assert_eq!(config.api.access_token.as_deref(), Some("file_token"));
+    }
+
+    #[test]
+    fn test_save_keeps_overrides_out_of_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut config = Config::from_toml(
+            "[api]\napi_key = \"file_key\"\napi_secret = \"file_secret\"\n",
+            None,
+        )
+        .unwrap();
+        config.path = dir.path().join("config.toml");
+
+        config.override_credentials(Some("env_key"), Some("env_secret"), Some("env_token"));
+        config.override_credentials(Some("flag_key"), None, None);
+        // Set again after the override, as `auth setup` does
+        config.api.api_secret = "new_secret".to_string();
+        config.save().unwrap();
+
+        let saved = std::fs::read_to_string(&config.path).unwrap();
+        let saved = Config::from_toml(&saved, None).unwrap();
+        assert_eq!(saved.api.api_key, "file_key");
+        assert_eq!(saved.api.api_secret, "new_secret");
+        assert!(saved.api.access_token.is_none());
+        assert_eq!(config.api.api_key, "flag_key");
+    }
+
+    #[test]
+    fn test_require_credentials() {
+        let mut config = Config::default();
+        let err = config.require_credentials().unwrap_err().to_string();
+        assert!(err.contains("--api-key"));
+        assert!(err.contains("ZERODHA_API_KEY"));
+
+        config.override_credentials(Some("key"), None, None);
+        assert!(config.require_credentials().is_ok());
+    }
 }
@@ -0,0 +1,45 @@
+//! Token storage in the OS keyring
+
+use anyhow::Result;
+
+/// Keyring service name; entries are keyed by profile
+const SERVICE: &str = "zerodha-cli";
+
+/// Which of a profile's tokens an entry holds
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Token {
+    Access,
+    Refresh,
+}
+
+/// The access token keeps the bare profile name it was first stored under
+fn entry(profile: &str, token: Token) -> Result<keyring::Entry> {
+    let user = match token {
+        Token::Access => profile.to_string(),
+        Token::Refresh => format!("{}:refresh_token", profile),
+    };
+    Ok(keyring::Entry::new(SERVICE, &user)?)
+}
+
+/// Read a stored token for `profile`
+pub fn load_token(profile: &str, token: Token) -> Result<Option<String>> {
+    match entry(profile, token)?.get_password() {
+        Ok(value) => Ok(Some(value)),
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Store a token for `profile`
+pub fn store_token(profile: &str, token: Token, value: &str) -> Result<()> {
+    entry(profile, token)?.set_password(value)?;
+    Ok(())
+}
+
+/// Remove a stored token for `profile`
+pub fn delete_token(profile: &str, token: Token) -> Result<()> {
+    match entry(profile, token)?.delete_credential() {
+        Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+        Err(e) => Err(e.into()),
+    }
+}
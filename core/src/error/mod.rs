@@ -2,6 +2,22 @@
 
 use thiserror::Error;
 
+/// Process exit codes, so scripts can tell failure kinds apart
+pub mod exit_code {
+    /// Any failure without a more specific code
+    pub const GENERIC: i32 = 1;
+    /// Missing credentials, expired token or insufficient permissions
+    pub const AUTH: i32 = 2;
+    /// Invalid arguments or input rejected before or by the API
+    pub const VALIDATION: i32 = 3;
+    /// Kite rate limit exceeded
+    pub const RATE_LIMIT: i32 = 4;
+    /// Network failure or exchange connectivity problem
+    pub const NETWORK: i32 = 5;
+    /// Order, margin or holding check rejected by the broker
+    pub const REJECTED: i32 = 6;
+}
+
 #[derive(Error, Debug)]
 pub enum ZerodhaError {
     #[error("API error: {message}")]
@@ -22,6 +38,9 @@ pub enum ZerodhaError {
     #[error("Validation error: {0}")]
     Validation(String),
 
+    #[error("Instrument not found: {0}")]
+    InstrumentNotFound(String),
+
     #[error("Cache error: {0}")]
     Cache(String),
 
@@ -30,4 +49,111 @@ pub enum ZerodhaError {
 
     #[error("Parse error: {0}")]
     Parse(String),
+
+    #[error("Token error: {0}. Please run 'kite auth login'")]
+    Token(String),
+
+    #[error("Input error: {0}")]
+    Input(String),
+
+    #[error("Order error: {0}")]
+    Order(String),
+
+    #[error("Margin error: {0}")]
+    Margin(String),
+
+    #[error("Holding error: {0}")]
+    Holding(String),
+
+    #[error("Permission denied: {0}")]
+    Permission(String),
+
+    #[error("Data error: {0}")]
+    Data(String),
+
+    #[error("Exchange connectivity error: {0}. Please try again later")]
+    Backend(String),
+
+    #[error("Kite API appears down, retrying paused for {retry_in_secs}s")]
+    CircuitOpen { retry_in_secs: u64 },
+}
+
+impl ZerodhaError {
+    /// Exit code the CLI should terminate with for this error
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            ZerodhaError::Auth(_) | ZerodhaError::Token(_) | ZerodhaError::Permission(_) => {
+                exit_code::AUTH
+            }
+            ZerodhaError::Validation(_)
+            | ZerodhaError::Input(_)
+            | ZerodhaError::InstrumentNotFound(_) => exit_code::VALIDATION,
+            ZerodhaError::RateLimit => exit_code::RATE_LIMIT,
+            ZerodhaError::Network(_)
+            | ZerodhaError::Backend(_)
+            | ZerodhaError::CircuitOpen { .. } => exit_code::NETWORK,
+            ZerodhaError::Order(_) | ZerodhaError::Margin(_) | ZerodhaError::Holding(_) => {
+                exit_code::REJECTED
+            }
+            ZerodhaError::Api { .. }
+            | ZerodhaError::IO(_)
+            | ZerodhaError::Config(_)
+            | ZerodhaError::Cache(_)
+            | ZerodhaError::Parse(_)
+            | ZerodhaError::Data(_) => exit_code::GENERIC,
+        }
+    }
+}
+
+/// Exit code for an error returned from a command
+///
+/// Walks the context chain for the first `ZerodhaError`, treating a bare
+/// `reqwest::Error` as a network failure.
+pub fn exit_code_for(err: &anyhow::Error) -> i32 {
+    for cause in err.chain() {
+        if let Some(e) = cause.downcast_ref::<ZerodhaError>() {
+            return e.exit_code();
+        }
+        if cause.downcast_ref::<reqwest::Error>().is_some() {
+            return exit_code::NETWORK;
+        }
+    }
+    exit_code::GENERIC
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Context;
+
+    #[test]
+    fn test_exit_code_by_variant() {
+        assert_eq!(ZerodhaError::Token("expired".into()).exit_code(), exit_code::AUTH);
+        assert_eq!(ZerodhaError::Input("bad".into()).exit_code(), exit_code::VALIDATION);
+        assert_eq!(
+            ZerodhaError::InstrumentNotFound("NSE:INFI".into()).exit_code(),
+            exit_code::VALIDATION
+        );
+        assert_eq!(ZerodhaError::RateLimit.exit_code(), exit_code::RATE_LIMIT);
+        assert_eq!(ZerodhaError::Backend("down".into()).exit_code(), exit_code::NETWORK);
+        assert_eq!(
+            ZerodhaError::CircuitOpen { retry_in_secs: 30 }.exit_code(),
+            exit_code::NETWORK
+        );
+        assert_eq!(ZerodhaError::Margin("short".into()).exit_code(), exit_code::REJECTED);
+        assert_eq!(ZerodhaError::Parse("json".into()).exit_code(), exit_code::GENERIC);
+    }
+
+    #[test]
+    fn test_exit_code_for_walks_context() {
+        let err = anyhow::Error::from(ZerodhaError::RateLimit);
+        assert_eq!(exit_code_for(&err), exit_code::RATE_LIMIT);
+
+        let err = Err::<(), _>(ZerodhaError::Order("rejected".into()))
+            .context("Failed to place order")
+            .unwrap_err();
+        assert_eq!(exit_code_for(&err), exit_code::REJECTED);
+
+        assert_eq!(exit_code_for(&anyhow::anyhow!("boom")), exit_code::GENERIC);
+    }
 }
@@ -0,0 +1,268 @@
+//! Symbol lookup over the instrument cache
+//!
+//! Works offline: symbols come from the cached instrument files regardless
+//! of their age, and an uncached exchange simply has no symbols.
+
+use crate::cache::{InstrumentCache, EXCHANGES};
+use crate::models::{Instrument, InstrumentType};
+use anyhow::{Context, Result};
+use chrono::NaiveDate;
+
+/// Shortest prefix worth suggesting a symbol for
+const MIN_SUGGESTION_PREFIX: usize = 2;
+
+/// Sorted tradingsymbols of one exchange, for prefix lookups
+#[derive(Debug, Clone, Default)]
+pub struct SymbolIndex {
+    symbols: Vec<String>,
+}
+
+impl SymbolIndex {
+    /// Build an index from tradingsymbols (normalized to uppercase)
+    pub fn new(symbols: impl IntoIterator<Item = String>) -> Self {
+        let mut symbols: Vec<String> = symbols.into_iter().map(|s| s.to_uppercase()).collect();
+        symbols.sort();
+        symbols.dedup();
+        Self { symbols }
+    }
+
+    /// Load an exchange's symbols from the cache; empty when it isn't cached
+    pub fn load(exchange: &str) -> Self {
+        InstrumentCache::load_index(exchange)
+            .map(|index| Self::new(index.into_keys().map(|key| key.tradingsymbol)))
+            .unwrap_or_default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.symbols.is_empty()
+    }
+
+    /// Whether the exchange lists this symbol
+    pub fn contains(&self, symbol: &str) -> bool {
+        self.symbols
+            .binary_search(&symbol.to_uppercase())
+            .is_ok()
+    }
+
+    /// Symbols starting with `prefix`, in sorted order
+    pub fn complete<'a>(&'a self, prefix: &str) -> impl Iterator<Item = &'a str> + 'a {
+        let prefix = prefix.to_uppercase();
+        // Symbols are sorted, so matches form one contiguous run
+        let start = self.symbols.partition_point(|s| s.as_str() < prefix.as_str());
+        self.symbols[start..]
+            .iter()
+            .take_while(move |s| s.starts_with(&prefix))
+            .map(String::as_str)
+    }
+
+    /// Closest listed symbol to one that isn't listed
+    ///
+    /// Drops trailing characters until some symbol shares the prefix, and
+    /// picks the first such symbol.
+    pub fn suggest(&self, symbol: &str) -> Option<&str> {
+        if self.contains(symbol) {
+            return None;
+        }
+        let symbol = symbol.to_uppercase();
+        let mut ends: Vec<usize> = symbol.char_indices().map(|(i, _)| i).skip(1).collect();
+        ends.push(symbol.len());
+        ends.into_iter()
+            .rev()
+            .filter(|&end| symbol[..end].chars().count() >= MIN_SUGGESTION_PREFIX)
+            .find_map(|end| self.complete(&symbol[..end]).next())
+    }
+}
+
+/// Cached `EXCHANGE:SYMBOL` names whose symbol starts with `partial`
+pub fn resolve_symbol(exchange: &str, partial: &str) -> Vec<String> {
+    let exchange = exchange.to_uppercase();
+    SymbolIndex::load(&exchange)
+        .complete(partial)
+        .map(|symbol| format!("{}:{}", exchange, symbol))
+        .collect()
+}
+
+/// Cached `EXCHANGE:SYMBOL` to suggest for a symbol that wasn't found
+pub fn suggest_symbol(exchange: &str, symbol: &str) -> Option<String> {
+    let exchange = exchange.to_uppercase();
+    SymbolIndex::load(&exchange)
+        .suggest(symbol)
+        .map(|symbol| format!("{}:{}", exchange, symbol))
+}
+
+/// Filters for instrument listings; unset fields match everything
+#[derive(Debug, Clone, Default)]
+pub struct InstrumentFilter {
+    pub instrument_type: Option<InstrumentType>,
+    /// Expiry date as `YYYY-MM-DD`, the format the instrument dump uses
+    pub expiry: Option<String>,
+    pub strike: Option<f64>,
+}
+
+impl InstrumentFilter {
+    /// Build a filter from `--type`, `--expiry` and `--strike` values
+    pub fn parse(
+        instrument_type: Option<&str>,
+        expiry: Option<&str>,
+        strike: Option<f64>,
+    ) -> Result<Self> {
+        let instrument_type = instrument_type
+            .map(|s| {
+                match serde_json::from_value(serde_json::Value::String(s.to_uppercase())) {
+                    Ok(InstrumentType::Unknown) | Err(_) => {
+                        anyhow::bail!("Invalid instrument type. Use CE, PE, FUT or EQ")
+                    }
+                    Ok(instrument_type) => Ok(instrument_type),
+                }
+            })
+            .transpose()?;
+        let expiry = expiry
+            .map(|s| {
+                NaiveDate::parse_from_str(s, "%Y-%m-%d")
+                    .with_context(|| format!("Invalid expiry '{}'. Use YYYY-MM-DD", s))
+                    .map(|date| date.format("%Y-%m-%d").to_string())
+            })
+            .transpose()?;
+
+        Ok(Self {
+            instrument_type,
+            expiry,
+            strike,
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.instrument_type.is_none() && self.expiry.is_none() && self.strike.is_none()
+    }
+
+    /// Whether an instrument passes every set filter
+    pub fn matches(&self, instrument: &Instrument) -> bool {
+        let type_ok = self
+            .instrument_type
+            .as_ref()
+            .is_none_or(|wanted| same_type(wanted, &instrument.instrument_type));
+        let expiry_ok = self
+            .expiry
+            .as_ref()
+            .is_none_or(|wanted| instrument.expiry.as_ref() == Some(wanted));
+        let strike_ok = self.strike.is_none_or(|wanted| {
+            instrument
+                .strike
+                .is_some_and(|strike| (strike - wanted).abs() < 1e-6)
+        });
+        type_ok && expiry_ok && strike_ok
+    }
+}
+
+/// Instrument types, treating the dump's `F`/`O` abbreviations as FUT/options
+fn same_type(wanted: &InstrumentType, actual: &InstrumentType) -> bool {
+    use InstrumentType::*;
+
+    matches!(
+        (wanted, actual),
+        (Equity, Equity)
+            | (CallOption, CallOption)
+            | (PutOption, PutOption)
+            | (Future | FutureAbbrev, Future | FutureAbbrev)
+            | (Option, Option | CallOption | PutOption)
+    )
+}
+
+/// Cached instrument with this `instrument_token`, searching every exchange
+pub fn find_by_token(token: u64) -> Option<Instrument> {
+    EXCHANGES.iter().find_map(|exchange| {
+        InstrumentCache::load_index(exchange)
+            .ok()?
+            .into_values()
+            .find(|instrument| instrument.instrument_token == token)
+    })
+}
+
+/// `EXCHANGE:SYMBOL` for a cached `instrument_token`
+pub fn token_to_symbol(token: u64) -> Option<String> {
+    find_by_token(token)
+        .map(|instrument| format!("{}:{}", instrument.exchange, instrument.tradingsymbol))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn index() -> SymbolIndex {
+        SymbolIndex::new(
+            ["infy", "INDIGO", "TCS", "INFY", "INFRATEL"]
+                .iter()
+                .map(|s| s.to_string()),
+        )
+    }
+
+    #[test]
+    fn test_complete_prefix() {
+        let index = index();
+        assert_eq!(
+            index.complete("inf").collect::<Vec<_>>(),
+            vec!["INFRATEL", "INFY"]
+        );
+        assert_eq!(index.complete("X").count(), 0);
+        assert!(index.contains("tcs"));
+        assert!(!index.contains("TC"));
+    }
+
+    #[test]
+    fn test_suggest_closest_symbol() {
+        let index = index();
+        assert_eq!(index.suggest("INFYY"), Some("INFY"));
+        assert_eq!(index.suggest("INDIGOO"), Some("INDIGO"));
+        assert_eq!(index.suggest("INFY"), None);
+        assert_eq!(index.suggest("ZOMATO"), None);
+        assert_eq!(index.suggest("TX"), None);
+    }
+
+    fn instrument(kind: InstrumentType, expiry: Option<&str>, strike: Option<f64>) -> Instrument {
+        use crate::models::{Exchange, Segment};
+
+        Instrument {
+            instrument_token: 1,
+            exchange_token: 1,
+            tradingsymbol: "NIFTY".to_string(),
+            name: "NIFTY".to_string(),
+            last_price: None,
+            expiry: expiry.map(str::to_string),
+            strike,
+            tick_size: 0.05,
+            lot_size: 25,
+            instrument_type: kind,
+            segment: Segment::NFOOPT,
+            exchange: Exchange::NFO,
+        }
+    }
+
+    #[test]
+    fn test_filter_by_type_expiry_and_strike() {
+        let call = instrument(InstrumentType::CallOption, Some("2024-06-27"), Some(22000.0));
+        let put = instrument(InstrumentType::PutOption, Some("2024-06-27"), Some(22000.0));
+        let equity = instrument(InstrumentType::Equity, None, None);
+
+        let filter = InstrumentFilter::parse(Some("ce"), Some("2024-06-27"), Some(22000.0)).unwrap();
+        assert!(filter.matches(&call));
+        assert!(!filter.matches(&put));
+        assert!(!filter.matches(&equity));
+
+        // No expiry never matches an expiry filter
+        let by_expiry = InstrumentFilter::parse(None, Some("2024-06-27"), None).unwrap();
+        assert!(by_expiry.matches(&put));
+        assert!(!by_expiry.matches(&equity));
+
+        let futures = InstrumentFilter::parse(Some("FUT"), None, None).unwrap();
+        assert!(futures.matches(&instrument(InstrumentType::FutureAbbrev, None, None)));
+
+        assert!(InstrumentFilter::default().matches(&equity));
+        assert!(InstrumentFilter::default().is_empty());
+    }
+
+    #[test]
+    fn test_filter_rejects_bad_values() {
+        assert!(InstrumentFilter::parse(Some("XX"), None, None).is_err());
+        assert!(InstrumentFilter::parse(None, Some("27-06-2024"), None).is_err());
+    }
+}
@@ -2,12 +2,20 @@
 //!
 //! Core business logic, API client, and domain models
 
+pub mod alerts;
 pub mod api;
 pub mod auth;
 pub mod cache;
+pub mod chain;
 pub mod config;
 pub mod error;
+pub mod instruments;
+pub mod margins;
+pub mod market;
 pub mod models;
+pub mod orders;
 pub mod output;
+pub mod portfolio;
+pub mod report;
 pub mod shell;
 pub mod validation;
@@ -0,0 +1,84 @@
+//! Buying power estimates for equity orders
+//!
+//! The leverage table is approximate: Kite sets intraday and MTF margins per
+//! stock and revises them with volatility, so results are estimates only.
+
+use crate::models::Product;
+use serde::Serialize;
+
+/// Approximate leverage Kite extends on equity for a product
+pub fn leverage(product: &Product) -> f64 {
+    match product {
+        // Intraday, typically up to 5x on liquid stocks
+        Product::MIS | Product::BO => 5.0,
+        // Margin trading funds roughly half the position
+        Product::MTF => 2.0,
+        Product::CNC | Product::NRML => 1.0,
+    }
+}
+
+/// How much of a symbol the available funds could buy
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BuyingPower {
+    pub symbol: String,
+    pub product: String,
+    /// Funds available for trading (net equity margin)
+    pub available: f64,
+    pub leverage: f64,
+    /// Available funds times leverage
+    pub buying_power: f64,
+    pub last_price: f64,
+    /// Whole shares the buying power covers at the last price
+    pub max_quantity: u64,
+}
+
+impl BuyingPower {
+    /// Estimate buying power; negative funds count as none
+    pub fn estimate(symbol: &str, product: &Product, available: f64, last_price: f64) -> Self {
+        let leverage = leverage(product);
+        let buying_power = available.max(0.0) * leverage;
+        let max_quantity = if last_price > 0.0 {
+            (buying_power / last_price).floor() as u64
+        } else {
+            0
+        };
+
+        Self {
+            symbol: symbol.to_string(),
+            product: product.to_string(),
+            available,
+            leverage,
+            buying_power,
+            last_price,
+            max_quantity,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_intraday_leverage_applies() {
+        let estimate = BuyingPower::estimate("NSE:INFY", &Product::MIS, 10_000.0, 1_500.0);
+        assert_eq!(estimate.leverage, 5.0);
+        assert_eq!(estimate.buying_power, 50_000.0);
+        assert_eq!(estimate.max_quantity, 33);
+
+        let delivery = BuyingPower::estimate("NSE:INFY", &Product::CNC, 10_000.0, 1_500.0);
+        assert_eq!(delivery.buying_power, 10_000.0);
+        assert_eq!(delivery.max_quantity, 6);
+    }
+
+    #[test]
+    fn test_no_funds_or_price_buys_nothing() {
+        let overdrawn = BuyingPower::estimate("NSE:INFY", &Product::MIS, -500.0, 1_500.0);
+        assert_eq!(overdrawn.buying_power, 0.0);
+        assert_eq!(overdrawn.max_quantity, 0);
+        assert_eq!(
+            BuyingPower::estimate("NSE:INFY", &Product::CNC, 1_000.0, 0.0).max_quantity,
+            0
+        );
+    }
+}
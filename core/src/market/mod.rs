@@ -0,0 +1,181 @@
+//! Market hours and trading holidays
+//!
+//! All times are IST. Holidays come from the embedded NSE calendar in
+//! `holidays.json`.
+
+use chrono::{Datelike, Duration, FixedOffset, NaiveDate, NaiveDateTime, NaiveTime, Utc, Weekday};
+use serde::{Deserialize, Serialize};
+use std::sync::OnceLock;
+
+/// IST offset from UTC in seconds
+const IST_OFFSET_SECS: i32 = 5 * 3600 + 30 * 60;
+
+const PRE_OPEN: (u32, u32) = (9, 0);
+const SESSION_OPEN: (u32, u32) = (9, 15);
+const SESSION_CLOSE: (u32, u32) = (15, 30);
+
+/// NSE trading holiday
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Holiday {
+    pub date: NaiveDate,
+    pub description: String,
+}
+
+/// Trading phase of a segment
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MarketPhase {
+    PreOpen,
+    Open,
+    Closed,
+}
+
+/// Status of one market segment
+#[derive(Debug, Clone, Serialize)]
+pub struct SegmentStatus {
+    pub segment: &'static str,
+    pub phase: MarketPhase,
+    /// Why the segment is closed (weekend, holiday name, after hours)
+    pub reason: Option<String>,
+    /// Next regular session open when not currently open
+    pub next_open: Option<NaiveDateTime>,
+}
+
+/// Embedded NSE holiday calendar
+pub fn holidays() -> &'static [Holiday] {
+    static HOLIDAYS: OnceLock<Vec<Holiday>> = OnceLock::new();
+    HOLIDAYS.get_or_init(|| {
+        serde_json::from_str(include_str!("holidays.json"))
+            .expect("embedded holidays.json is valid")
+    })
+}
+
+/// Current time in IST
+pub fn now_ist() -> NaiveDateTime {
+    let ist = FixedOffset::east_opt(IST_OFFSET_SECS).expect("valid IST offset");
+    Utc::now().with_timezone(&ist).naive_local()
+}
+
+/// Holiday falling on `date`, if any
+pub fn holiday_on(date: NaiveDate) -> Option<&'static Holiday> {
+    holidays().iter().find(|holiday| holiday.date == date)
+}
+
+/// Whether the exchanges trade on `date`
+pub fn is_trading_day(date: NaiveDate) -> bool {
+    !matches!(date.weekday(), Weekday::Sat | Weekday::Sun) && holiday_on(date).is_none()
+}
+
+/// Equity (NSE/BSE) and F&O (NFO/BFO) status at `now` (IST)
+pub fn market_status(now: NaiveDateTime) -> Vec<SegmentStatus> {
+    vec![
+        segment_status("Equity (NSE/BSE)", now, true),
+        segment_status("F&O (NFO/BFO)", now, false),
+    ]
+}
+
+fn segment_status(segment: &'static str, now: NaiveDateTime, has_pre_open: bool) -> SegmentStatus {
+    let date = now.date();
+    let time = now.time();
+
+    let (phase, reason) = if matches!(date.weekday(), Weekday::Sat | Weekday::Sun) {
+        (MarketPhase::Closed, Some("Weekend".to_string()))
+    } else if let Some(holiday) = holiday_on(date) {
+        (
+            MarketPhase::Closed,
+            Some(format!("Holiday: {}", holiday.description)),
+        )
+    } else if time >= at(SESSION_OPEN) && time < at(SESSION_CLOSE) {
+        (MarketPhase::Open, None)
+    } else if has_pre_open && time >= at(PRE_OPEN) && time < at(SESSION_OPEN) {
+        (MarketPhase::PreOpen, None)
+    } else if time < at(SESSION_OPEN) {
+        (MarketPhase::Closed, Some("Before market hours".to_string()))
+    } else {
+        (MarketPhase::Closed, Some("After market hours".to_string()))
+    };
+
+    let next_open = (phase != MarketPhase::Open).then(|| next_open(now));
+
+    SegmentStatus {
+        segment,
+        phase,
+        reason,
+        next_open,
+    }
+}
+
+/// Next regular session open strictly after `now`
+pub fn next_open(now: NaiveDateTime) -> NaiveDateTime {
+    let mut date = now.date();
+    if now.time() >= at(SESSION_OPEN) {
+        date += Duration::days(1);
+    }
+    while !is_trading_day(date) {
+        date += Duration::days(1);
+    }
+    date.and_time(at(SESSION_OPEN))
+}
+
+fn at((hour, minute): (u32, u32)) -> NaiveTime {
+    NaiveTime::from_hms_opt(hour, minute, 0).expect("valid session time")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ist(s: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M").unwrap()
+    }
+
+    #[test]
+    fn test_weekend() {
+        // Saturday
+        let status = market_status(ist("2026-10-17 11:00"));
+        assert_eq!(status[0].phase, MarketPhase::Closed);
+        assert_eq!(status[0].reason.as_deref(), Some("Weekend"));
+        assert_eq!(status[0].next_open, Some(ist("2026-10-19 09:15")));
+    }
+
+    #[test]
+    fn test_holiday() {
+        // Dussehra (Tuesday)
+        let status = market_status(ist("2026-10-20 10:00"));
+        assert_eq!(status[1].phase, MarketPhase::Closed);
+        assert_eq!(status[1].reason.as_deref(), Some("Holiday: Dussehra"));
+        assert_eq!(status[1].next_open, Some(ist("2026-10-21 09:15")));
+    }
+
+    #[test]
+    fn test_pre_open() {
+        let status = market_status(ist("2026-10-16 09:05"));
+        assert_eq!(status[0].phase, MarketPhase::PreOpen);
+        assert_eq!(status[0].next_open, Some(ist("2026-10-16 09:15")));
+        // F&O has no pre-open session
+        assert_eq!(status[1].phase, MarketPhase::Closed);
+    }
+
+    #[test]
+    fn test_mid_session() {
+        let status = market_status(ist("2026-10-16 12:30"));
+        assert!(status.iter().all(|s| s.phase == MarketPhase::Open));
+        assert!(status.iter().all(|s| s.next_open.is_none()));
+    }
+
+    #[test]
+    fn test_after_close_skips_to_next_trading_day() {
+        // Friday after close, Monday is a trading day
+        let status = market_status(ist("2026-10-16 15:30"));
+        assert_eq!(status[0].phase, MarketPhase::Closed);
+        assert_eq!(status[0].next_open, Some(ist("2026-10-19 09:15")));
+    }
+
+    #[test]
+    fn test_holidays_are_weekdays() {
+        assert!(!holidays().is_empty());
+        assert!(holidays()
+            .iter()
+            .all(|h| !matches!(h.date.weekday(), Weekday::Sat | Weekday::Sun)));
+    }
+}
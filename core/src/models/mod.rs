@@ -1,29 +1,58 @@
 //! Domain models for Kite Connect API
 
+pub mod schema;
+pub mod timestamp;
+
+use chrono::NaiveDateTime;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fmt::Display;
+use std::str::FromStr;
 
 // ==================== INSTRUMENTS ====================
 
 /// Instrument (trading symbol)
-#[derive(Debug, Clone, Serialize, Deserialize)]
+///
+/// Index rows in the dump leave price, expiry and size columns blank; blank
+/// prices and expiries are `None` and blank sizes are 0.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Instrument {
     pub instrument_token: u64,
     pub exchange_token: u64,
     pub tradingsymbol: String,
     pub name: String,
+    #[serde(default)]
     pub last_price: Option<f64>,
+    #[serde(default)]
     pub expiry: Option<String>,
+    #[serde(default)]
     pub strike: Option<f64>,
+    #[serde(default, deserialize_with = "blank_as_default")]
     pub tick_size: f64,
+    #[serde(default, deserialize_with = "blank_as_default")]
     pub lot_size: u32,
     pub instrument_type: InstrumentType,
     pub segment: Segment,
     pub exchange: Exchange,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Deserialize a number, reading an empty CSV field or null as the default
+///
+/// Binary formats like the bincode index carry the plain value.
+fn blank_as_default<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: serde::Deserializer<'de>,
+    T: Deserialize<'de> + Default,
+{
+    if deserializer.is_human_readable() {
+        Ok(Option::<T>::deserialize(deserializer)?.unwrap_or_default())
+    } else {
+        T::deserialize(deserializer)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub enum InstrumentType {
     #[serde(rename = "EQ")]
     Equity,
@@ -37,9 +66,12 @@ pub enum InstrumentType {
     FutureAbbrev,
     #[serde(rename = "O")]
     Option,
+    /// Types Kite adds later, so one new row doesn't fail the whole dump
+    #[serde(other)]
+    Unknown,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub enum Segment {
     #[serde(rename = "NSE")]
     NSE,
@@ -85,7 +117,66 @@ pub enum Segment {
     Unknown,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+impl FromStr for Segment {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s.to_uppercase().as_str() {
+            "NSE" => Segment::NSE,
+            "BSE" => Segment::BSE,
+            "NFO" => Segment::NFO,
+            "BFO" => Segment::BFO,
+            "MCX" => Segment::MCX,
+            "CDS" => Segment::CDS,
+            "NSE-FUT" => Segment::NSEFUT,
+            "NSE-OPT" => Segment::NSEOPT,
+            "BSE-FUT" => Segment::BSEFUT,
+            "BSE-OPT" => Segment::BSEOPT,
+            "NFO-FUT" => Segment::NFOFUT,
+            "NFO-OPT" => Segment::NFOOPT,
+            "BFO-FUT" => Segment::BFOFUT,
+            "BFO-OPT" => Segment::BFOOPT,
+            "MCX-FUT" => Segment::MCXFUT,
+            "MCX-OPT" => Segment::MCXOPT,
+            "CDS-FUT" => Segment::CDSFUT,
+            "CDS-OPT" => Segment::CDSOPT,
+            "INDICES" => Segment::INDICES,
+            "NSEIX" => Segment::NSEIX,
+            _ => anyhow::bail!("Invalid segment '{}'", s),
+        })
+    }
+}
+
+impl Display for Segment {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Segment::NSE => "NSE",
+            Segment::BSE => "BSE",
+            Segment::NFO => "NFO",
+            Segment::BFO => "BFO",
+            Segment::MCX => "MCX",
+            Segment::CDS => "CDS",
+            Segment::NSEFUT => "NSE-FUT",
+            Segment::NSEOPT => "NSE-OPT",
+            Segment::BSEFUT => "BSE-FUT",
+            Segment::BSEOPT => "BSE-OPT",
+            Segment::NFOFUT => "NFO-FUT",
+            Segment::NFOOPT => "NFO-OPT",
+            Segment::BFOFUT => "BFO-FUT",
+            Segment::BFOOPT => "BFO-OPT",
+            Segment::MCXFUT => "MCX-FUT",
+            Segment::MCXOPT => "MCX-OPT",
+            Segment::CDSFUT => "CDS-FUT",
+            Segment::CDSOPT => "CDS-OPT",
+            Segment::INDICES => "INDICES",
+            Segment::NSEIX => "NSEIX",
+            Segment::Unknown => "UNKNOWN",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub enum Exchange {
     #[serde(rename = "NSE")]
     NSE,
@@ -126,10 +217,44 @@ impl Display for Exchange {
     }
 }
 
+impl Exchange {
+    /// Exchanges accepted when parsing user input
+    pub const ALL: [Exchange; 9] = [
+        Exchange::NSE,
+        Exchange::BSE,
+        Exchange::NFO,
+        Exchange::BFO,
+        Exchange::MCX,
+        Exchange::CDS,
+        Exchange::INDICES,
+        Exchange::NSEIX,
+        Exchange::GLOBAL,
+    ];
+}
+
+impl FromStr for Exchange {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let upper = s.to_uppercase();
+        Exchange::ALL
+            .into_iter()
+            .find(|exchange| exchange.to_string() == upper)
+            .ok_or_else(|| {
+                let valid: Vec<String> = Exchange::ALL.iter().map(|e| e.to_string()).collect();
+                anyhow::anyhow!(
+                    "Invalid exchange '{}'. Valid exchanges: {}",
+                    s,
+                    valid.join(", ")
+                )
+            })
+    }
+}
+
 // ==================== ORDERS ====================
 
 /// Order
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Order {
     pub order_id: String,
     pub exchange_order_id: Option<String>,
@@ -152,11 +277,17 @@ pub struct Order {
     pub filled_quantity: i32,
     pub cancelled_quantity: i32,
     pub placed_by: String,
-    pub order_timestamp: String,
-    pub update_timestamp: Option<String>,
+    #[serde(with = "timestamp::required")]
+    #[schemars(with = "NaiveDateTime")]
+    pub order_timestamp: NaiveDateTime,
+    #[serde(default, with = "timestamp::optional")]
+    #[schemars(with = "Option<NaiveDateTime>")]
+    pub update_timestamp: Option<NaiveDateTime>,
+    #[serde(default)]
+    pub tag: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum OrderStatus {
     Open,
@@ -169,6 +300,16 @@ pub enum OrderStatus {
     ValidationPending,
 }
 
+impl OrderStatus {
+    /// Whether the order can no longer change state
+    pub fn is_terminal(&self) -> bool {
+        matches!(
+            self,
+            OrderStatus::Complete | OrderStatus::Cancelled | OrderStatus::Rejected
+        )
+    }
+}
+
 impl Display for OrderStatus {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -182,7 +323,7 @@ impl Display for OrderStatus {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "lowercase")]
 pub enum OrderVariety {
     Regular,
@@ -202,11 +343,12 @@ impl Display for OrderVariety {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum OrderType {
     Market,
     Limit,
+    #[serde(rename = "SL")]
     SL,
     #[serde(rename = "SL-M")]
     SLM,
@@ -223,7 +365,7 @@ impl Display for OrderType {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum TransactionType {
     Buy,
@@ -239,11 +381,13 @@ impl Display for TransactionType {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum Validity {
     Day,
+    #[serde(rename = "IOC")]
     IOC,
+    #[serde(rename = "TTL")]
     TTL,
 }
 
@@ -257,13 +401,18 @@ impl Display for Validity {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum Product {
+    #[serde(rename = "CNC")]
     CNC,
+    #[serde(rename = "MIS")]
     MIS,
+    #[serde(rename = "NRML")]
     NRML,
+    #[serde(rename = "MTF")]
     MTF,
+    #[serde(rename = "BO")]
     BO,
 }
 
@@ -282,7 +431,7 @@ impl Display for Product {
 // ==================== TRADES ====================
 
 /// Trade
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Trade {
     pub trade_id: String,
     pub order_id: String,
@@ -293,27 +442,41 @@ pub struct Trade {
     pub product: Product,
     pub average_price: f64,
     pub quantity: i32,
-    pub fill_timestamp: String,
-    #[serde(rename = "trade_timestamp")]
-    pub trade_timestamp: Option<String>,
+    #[serde(with = "timestamp::required")]
+    #[schemars(with = "NaiveDateTime")]
+    pub fill_timestamp: NaiveDateTime,
+    #[serde(default, with = "timestamp::optional")]
+    #[schemars(with = "Option<NaiveDateTime>")]
+    pub trade_timestamp: Option<NaiveDateTime>,
 }
 
 // ==================== QUOTES ====================
 
 /// Quote
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Quote {
     pub instrument_token: u64,
     pub last_price: f64,
     pub ohlc: OHLC,
     pub depth: Depth,
+    /// Shares traded today
+    #[serde(default)]
+    pub volume: Option<u64>,
     pub oi: Option<i64>,
     pub oi_day_high: Option<i64>,
     pub oi_day_low: Option<i64>,
     pub timestamp: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+impl Quote {
+    /// Change from the previous close, in percent; `None` without a close
+    pub fn day_change_percent(&self) -> Option<f64> {
+        let close = self.ohlc.close;
+        (close > 0.0).then(|| (self.last_price - close) / close * 100.0)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct OHLC {
     pub open: f64,
     pub high: f64,
@@ -321,32 +484,90 @@ pub struct OHLC {
     pub close: f64,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct DepthEntry {
     pub quantity: i32,
     pub price: f64,
     pub orders: i32,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Depth {
     pub buy: Vec<DepthEntry>,
     pub sell: Vec<DepthEntry>,
 }
 
+impl Depth {
+    /// Total quantity bid across the visible levels
+    pub fn total_buy_quantity(&self) -> i64 {
+        self.buy.iter().map(|e| e.quantity as i64).sum()
+    }
+
+    /// Total quantity offered across the visible levels
+    pub fn total_sell_quantity(&self) -> i64 {
+        self.sell.iter().map(|e| e.quantity as i64).sum()
+    }
+
+    /// Buy quantity per unit of sell quantity; `None` when nothing is offered
+    pub fn imbalance_ratio(&self) -> Option<f64> {
+        let sell = self.total_sell_quantity();
+        (sell > 0).then(|| self.total_buy_quantity() as f64 / sell as f64)
+    }
+
+    /// Best ask minus best bid; `None` when either side is empty
+    ///
+    /// Kite pads missing levels with zero-priced entries, which are skipped.
+    pub fn spread(&self) -> Option<f64> {
+        let best = |side: &[DepthEntry]| side.iter().map(|e| e.price).find(|&p| p > 0.0);
+        Some(best(&self.sell)? - best(&self.buy)?)
+    }
+
+    /// Spread as a percentage of `last_price`
+    pub fn spread_percent(&self, last_price: f64) -> Option<f64> {
+        if last_price <= 0.0 {
+            return None;
+        }
+        self.spread().map(|spread| spread / last_price * 100.0)
+    }
+}
+
+/// Total quantity and volume-weighted average price over depth levels
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DepthTotals {
+    pub quantity: i64,
+    /// `None` when no level has both a price and a quantity
+    pub vwap: Option<f64>,
+}
+
+impl DepthTotals {
+    /// Sum `levels`, skipping the zero-priced padding Kite sends for empty levels
+    pub fn of(levels: &[DepthEntry]) -> Self {
+        let (quantity, value) = levels
+            .iter()
+            .filter(|e| e.price > 0.0 && e.quantity > 0)
+            .fold((0i64, 0.0), |(qty, value), e| {
+                (qty + e.quantity as i64, value + e.quantity as f64 * e.price)
+            });
+        Self {
+            quantity,
+            vwap: (quantity > 0).then(|| value / quantity as f64),
+        }
+    }
+}
+
 /// Quote response
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct QuoteResponse {
     pub data: HashMap<String, Quote>,
 }
 
 /// OHLC response
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct OHLCResponse {
     pub data: HashMap<String, OHLCData>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct OHLCData {
     pub instrument_token: u64,
     pub last_price: f64,
@@ -357,12 +578,12 @@ pub struct OHLCData {
 }
 
 /// LTP response
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct LTPResponse {
     pub data: HashMap<String, LTPData>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct LTPData {
     pub instrument_token: u64,
     pub last_price: f64,
@@ -371,7 +592,7 @@ pub struct LTPData {
 // ==================== PORTFOLIO ====================
 
 /// Holding (long-term equity)
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Holding {
     pub tradingsymbol: String,
     pub exchange: Exchange,
@@ -388,8 +609,26 @@ pub struct Holding {
     pub day_change_percentage: f64,
 }
 
+/// Holding eligible for the exchange auction market (e.g. after a short delivery)
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct AuctionHolding {
+    pub tradingsymbol: String,
+    pub exchange: Exchange,
+    pub instrument_token: u64,
+    #[serde(default)]
+    pub isin: String,
+    pub quantity: i32,
+    #[serde(default)]
+    pub average_price: f64,
+    pub last_price: f64,
+    #[serde(default)]
+    pub pnl: f64,
+    /// Auction to place the sell order in
+    pub auction_number: String,
+}
+
 /// Position (intraday/F&O)
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Position {
     pub tradingsymbol: String,
     pub exchange: Exchange,
@@ -441,7 +680,7 @@ pub struct Position {
 }
 
 /// Positions response
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct PositionsResponse {
     pub net: Vec<Position>,
     pub day: Vec<Position>,
@@ -450,7 +689,7 @@ pub struct PositionsResponse {
 // ==================== MARGINS ====================
 
 /// Margin
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Margin {
     #[serde(default)]
     pub enabled: bool,
@@ -462,7 +701,7 @@ pub struct Margin {
     pub utilised: MarginUtilised,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, JsonSchema)]
 pub struct MarginDetail {
     #[serde(default)]
     pub cash: f64,
@@ -474,7 +713,7 @@ pub struct MarginDetail {
     pub collateral: f64,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, JsonSchema)]
 pub struct MarginUtilised {
     #[serde(default)]
     pub debits: f64,
@@ -499,7 +738,7 @@ pub struct MarginUtilised {
 }
 
 /// Margin response
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct MarginResponse {
     #[serde(default)]
     pub equity: Option<Margin>,
@@ -508,14 +747,49 @@ pub struct MarginResponse {
 }
 
 /// Equity margins
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct EquityMargins {
     #[serde(default)]
     pub equity: Option<Margin>,
 }
 
+/// Margin required for an order or basket
+#[derive(Debug, Clone, Serialize, Deserialize, Default, JsonSchema)]
+pub struct OrderMargin {
+    #[serde(default)]
+    pub tradingsymbol: Option<String>,
+    #[serde(default)]
+    pub span: f64,
+    #[serde(default)]
+    pub exposure: f64,
+    #[serde(default)]
+    pub option_premium: f64,
+    #[serde(default)]
+    pub total: f64,
+}
+
+/// Basket margins response
+#[derive(Debug, Clone, Serialize, Deserialize, Default, JsonSchema)]
+pub struct BasketMargins {
+    /// Margin for the orders taken individually
+    #[serde(default)]
+    pub initial: OrderMargin,
+    /// Margin for the whole basket after spread/hedge benefits
+    #[serde(default, rename = "final")]
+    pub final_margin: OrderMargin,
+    #[serde(default)]
+    pub orders: Vec<OrderMargin>,
+}
+
+impl BasketMargins {
+    /// Margin saved by offsetting legs, compared to placing each order on its own
+    pub fn benefit(&self) -> f64 {
+        self.initial.total - self.final_margin.total
+    }
+}
+
 /// Commodity margins
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct CommodityMargins {
     #[serde(default)]
     pub commodity: Option<Margin>,
@@ -524,7 +798,7 @@ pub struct CommodityMargins {
 // ==================== GTT ====================
 
 /// GTT (Good Till Triggered)
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct GTTTrigger {
     pub id: u64,
     pub user_id: String,
@@ -540,16 +814,24 @@ pub struct GTTTrigger {
     pub trailing_stoploss: Option<f64>,
     pub stoploss: Option<f64>,
     pub squareoff: Option<f64>,
-    pub generated_at: String,
-    pub updated_at: Option<String>,
-    pub expires_at: Option<String>,
+    #[serde(with = "timestamp::required")]
+    #[schemars(with = "NaiveDateTime")]
+    pub generated_at: NaiveDateTime,
+    #[serde(default, with = "timestamp::optional")]
+    #[schemars(with = "Option<NaiveDateTime>")]
+    pub updated_at: Option<NaiveDateTime>,
+    #[serde(default, with = "timestamp::optional")]
+    #[schemars(with = "Option<NaiveDateTime>")]
+    pub expires_at: Option<NaiveDateTime>,
     pub status: String,
 }
 
 // ==================== REQUEST/RESPONSE ====================
 
 /// Place order request
-#[derive(Debug, Clone, Serialize)]
+///
+/// Also deserializable so a basket of orders can be read from a JSON file.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct PlaceOrder {
     pub exchange: String,
     pub tradingsymbol: String,
@@ -563,14 +845,30 @@ pub struct PlaceOrder {
     pub trigger_price: Option<f64>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub validity: Option<Validity>,
+    /// Order life in minutes, only with TTL validity
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub validity_ttl: Option<u32>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub disclosed_quantity: Option<u32>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub variety: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tag: Option<String>,
+}
+
+impl PlaceOrder {
+    /// Parse a basket file (a JSON array of order specs)
+    pub fn parse_basket(json: &str) -> anyhow::Result<Vec<PlaceOrder>> {
+        let orders: Vec<PlaceOrder> = serde_json::from_str(json)?;
+        if orders.is_empty() {
+            anyhow::bail!("Basket contains no orders");
+        }
+        Ok(orders)
+    }
 }
 
 /// Place order response
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct OrderResponse {
     pub order_id: String,
     pub status: OrderStatus,
@@ -578,14 +876,14 @@ pub struct OrderResponse {
 }
 
 /// Cancel order response
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct CancelResponse {
     pub order_id: String,
     pub status: String,
 }
 
 /// Modify order request
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, JsonSchema)]
 pub struct ModifyOrder {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub quantity: Option<u32>,
@@ -600,18 +898,36 @@ pub struct ModifyOrder {
 }
 
 /// Convert position request
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, JsonSchema)]
 pub struct ConvertPosition {
     pub exchange: String,
     pub tradingsymbol: String,
     pub transaction_type: TransactionType,
+    pub position_type: PositionType,
     pub quantity: u32,
-    pub from_product: Product,
-    pub to_product: Product,
+    pub old_product: Product,
+    pub new_product: Product,
+}
+
+/// Whether a position was opened today or carried over
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum PositionType {
+    Day,
+    Overnight,
+}
+
+impl Display for PositionType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PositionType::Day => write!(f, "day"),
+            PositionType::Overnight => write!(f, "overnight"),
+        }
+    }
 }
 
 /// Place GTT request
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, JsonSchema)]
 pub struct PlaceGTT {
     pub tradingsymbol: String,
     pub exchange: String,
@@ -627,10 +943,54 @@ pub struct PlaceGTT {
     pub stoploss: Option<f64>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub squareoff: Option<f64>,
+    /// Trigger type (single or two-leg OCO)
+    #[serde(rename = "type")]
+    pub trigger_type: GTTTriggerType,
+    pub condition: GTTCondition,
+    /// One order per trigger value, in the same order
+    pub orders: Vec<GTTOrder>,
+}
+
+/// GTT trigger type
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum GTTTriggerType {
+    Single,
+    TwoLeg,
+}
+
+impl Display for GTTTriggerType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GTTTriggerType::Single => write!(f, "single"),
+            GTTTriggerType::TwoLeg => write!(f, "two-leg"),
+        }
+    }
+}
+
+/// GTT trigger condition
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct GTTCondition {
+    pub exchange: String,
+    pub tradingsymbol: String,
+    pub trigger_values: Vec<f64>,
+    pub last_price: f64,
+}
+
+/// Order placed when a GTT leg triggers
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct GTTOrder {
+    pub exchange: String,
+    pub tradingsymbol: String,
+    pub transaction_type: TransactionType,
+    pub quantity: u32,
+    pub order_type: OrderType,
+    pub product: Product,
+    pub price: f64,
 }
 
 /// Modify GTT request
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, JsonSchema)]
 pub struct ModifyGTT {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub order_type: Option<OrderType>,
@@ -649,7 +1009,7 @@ pub struct ModifyGTT {
 }
 
 /// GTT response
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct GTTResponse {
     pub trigger_id: u64,
     pub status: String,
@@ -674,3 +1034,266 @@ pub struct SessionResponse {
     pub products: Vec<String>,
     pub order_types: Vec<String>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_basket() {
+        let json = r#"[
+            {
+                "exchange": "NFO",
+                "tradingsymbol": "NIFTY24FEB22000CE",
+                "transaction_type": "BUY",
+                "quantity": 50,
+                "order_type": "LIMIT",
+                "product": "NRML",
+                "price": 120.5
+            },
+            {
+                "exchange": "NFO",
+                "tradingsymbol": "NIFTY24FEB22500CE",
+                "transaction_type": "SELL",
+                "quantity": 50,
+                "order_type": "MARKET",
+                "product": "NRML"
+            }
+        ]"#;
+
+        let orders = PlaceOrder::parse_basket(json).unwrap();
+        assert_eq!(orders.len(), 2);
+        assert_eq!(orders[0].tradingsymbol, "NIFTY24FEB22000CE");
+        assert!(matches!(orders[0].transaction_type, TransactionType::Buy));
+        assert_eq!(orders[0].price, Some(120.5));
+        assert!(matches!(orders[1].order_type, OrderType::Market));
+        assert!(orders[1].price.is_none());
+        assert!(orders[1].tag.is_none());
+    }
+
+    #[test]
+    fn test_acronym_variants_round_trip() {
+        assert_eq!(serde_json::to_string(&Product::NRML).unwrap(), "\"NRML\"");
+        assert_eq!(serde_json::to_string(&Validity::IOC).unwrap(), "\"IOC\"");
+        assert_eq!(serde_json::to_string(&OrderType::SL).unwrap(), "\"SL\"");
+        assert!(matches!(
+            serde_json::from_str::<Product>("\"MIS\"").unwrap(),
+            Product::MIS
+        ));
+    }
+
+    fn gtt_leg(price: f64) -> GTTOrder {
+        GTTOrder {
+            exchange: "NSE".to_string(),
+            tradingsymbol: "INFY".to_string(),
+            transaction_type: TransactionType::Sell,
+            quantity: 10,
+            order_type: OrderType::Limit,
+            product: Product::CNC,
+            price,
+        }
+    }
+
+    fn gtt_request(trigger_type: GTTTriggerType, triggers: Vec<f64>, legs: Vec<GTTOrder>) -> PlaceGTT {
+        PlaceGTT {
+            tradingsymbol: "INFY".to_string(),
+            exchange: "NSE".to_string(),
+            transaction_type: TransactionType::Sell,
+            product: Product::CNC,
+            order_type: OrderType::Limit,
+            quantity: 10,
+            price: legs[0].price,
+            trigger_price: triggers[0],
+            trailing_stoploss: None,
+            stoploss: None,
+            squareoff: None,
+            trigger_type,
+            condition: GTTCondition {
+                exchange: "NSE".to_string(),
+                tradingsymbol: "INFY".to_string(),
+                trigger_values: triggers,
+                last_price: 1500.0,
+            },
+            orders: legs,
+        }
+    }
+
+    #[test]
+    fn test_place_gtt_single_body() {
+        let req = gtt_request(GTTTriggerType::Single, vec![1600.0], vec![gtt_leg(1605.0)]);
+        let json = serde_json::to_value(&req).unwrap();
+
+        assert_eq!(json["type"], "single");
+        assert_eq!(json["condition"]["trigger_values"], serde_json::json!([1600.0]));
+        assert_eq!(json["condition"]["last_price"], 1500.0);
+        assert_eq!(json["orders"].as_array().unwrap().len(), 1);
+        assert_eq!(json["orders"][0]["transaction_type"], "SELL");
+        assert_eq!(json["orders"][0]["price"], 1605.0);
+        assert!(json.get("stoploss").is_none());
+    }
+
+    #[test]
+    fn test_place_gtt_two_leg_body() {
+        let req = gtt_request(
+            GTTTriggerType::TwoLeg,
+            vec![1400.0, 1600.0],
+            vec![gtt_leg(1395.0), gtt_leg(1605.0)],
+        );
+        let json = serde_json::to_value(&req).unwrap();
+
+        assert_eq!(json["type"], "two-leg");
+        assert_eq!(
+            json["condition"]["trigger_values"],
+            serde_json::json!([1400.0, 1600.0])
+        );
+        let orders = json["orders"].as_array().unwrap();
+        assert_eq!(orders.len(), 2);
+        assert_eq!(orders[0]["price"], 1395.0);
+        assert_eq!(orders[1]["price"], 1605.0);
+        assert_eq!(orders[1]["product"], "CNC");
+    }
+
+    #[test]
+    fn test_parse_basket_empty() {
+        assert!(PlaceOrder::parse_basket("[]").is_err());
+    }
+
+    #[test]
+    fn test_parse_basket_missing_field() {
+        let json = r#"[{ "exchange": "NSE", "tradingsymbol": "INFY" }]"#;
+        assert!(PlaceOrder::parse_basket(json).is_err());
+    }
+
+    #[test]
+    fn test_basket_margins_spread_benefit() {
+        // Bull call spread: long 22000 CE, short 22200 CE
+        let margins: BasketMargins = serde_json::from_str(
+            r#"{
+                "initial": {"type":"","tradingsymbol":"","span":118250.5,"exposure":27690.0,
+                            "option_premium":6225.0,"total":152165.5},
+                "final": {"type":"","tradingsymbol":"","span":10420.0,"exposure":4150.0,
+                          "option_premium":6225.0,"total":20795.0},
+                "orders": [
+                    {"type":"equity","tradingsymbol":"NIFTY24JUN22000CE","exchange":"NFO",
+                     "span":0,"exposure":0,"option_premium":6225.0,"total":6225.0},
+                    {"type":"equity","tradingsymbol":"NIFTY24JUN22200CE","exchange":"NFO",
+                     "span":118250.5,"exposure":27690.0,"option_premium":0,"total":145940.5}
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(margins.orders.len(), 2);
+        assert_eq!(margins.final_margin.total, 20795.0);
+        assert!((margins.benefit() - 131370.5).abs() < 1e-6);
+    }
+
+    fn depth(buy: &[(i32, f64)], sell: &[(i32, f64)]) -> Depth {
+        let side = |levels: &[(i32, f64)]| {
+            levels
+                .iter()
+                .map(|&(quantity, price)| DepthEntry { quantity, price, orders: 1 })
+                .collect()
+        };
+        Depth { buy: side(buy), sell: side(sell) }
+    }
+
+    #[test]
+    fn test_depth_imbalance() {
+        let book = depth(&[(300, 100.0), (900, 99.95)], &[(400, 100.10), (400, 100.15)]);
+        assert_eq!(book.total_buy_quantity(), 1200);
+        assert_eq!(book.total_sell_quantity(), 800);
+        assert!((book.imbalance_ratio().unwrap() - 1.5).abs() < 1e-9);
+
+        assert_eq!(depth(&[(100, 10.0)], &[]).imbalance_ratio(), None);
+        assert_eq!(depth(&[], &[(100, 10.0)]).imbalance_ratio(), Some(0.0));
+    }
+
+    #[test]
+    fn test_quote_volume_and_day_change() {
+        let quote: Quote = serde_json::from_value(serde_json::json!({
+            "instrument_token": 408065,
+            "last_price": 1530.0,
+            "volume": 1250000,
+            "ohlc": {"open": 1505.0, "high": 1540.0, "low": 1500.0, "close": 1500.0},
+            "depth": {"buy": [], "sell": []},
+            "oi": null, "oi_day_high": null, "oi_day_low": null, "timestamp": null
+        }))
+        .unwrap();
+        assert_eq!(quote.volume, Some(1250000));
+        assert!((quote.day_change_percent().unwrap() - 2.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_depth_totals_vwap() {
+        let book = depth(&[(100, 100.0), (300, 99.0), (0, 0.0)], &[(50, 101.0), (0, 0.0)]);
+        let buy = DepthTotals::of(&book.buy);
+        assert_eq!(buy.quantity, 400);
+        // (100 * 100 + 300 * 99) / 400
+        assert!((buy.vwap.unwrap() - 99.25).abs() < 1e-9);
+
+        let sell = DepthTotals::of(&book.sell);
+        assert_eq!(sell, DepthTotals { quantity: 50, vwap: Some(101.0) });
+
+        // Only the levels passed in count
+        assert_eq!(DepthTotals::of(&book.buy[..1]).vwap, Some(100.0));
+        assert_eq!(DepthTotals::of(&[]), DepthTotals { quantity: 0, vwap: None });
+    }
+
+    #[test]
+    fn test_depth_spread() {
+        let book = depth(&[(300, 100.0)], &[(400, 100.25)]);
+        assert!((book.spread().unwrap() - 0.25).abs() < 1e-9);
+        assert!((book.spread_percent(125.0).unwrap() - 0.2).abs() < 1e-9);
+        assert_eq!(book.spread_percent(0.0), None);
+
+        // Zero-priced padding levels don't count as a best price
+        assert_eq!(depth(&[(0, 0.0)], &[(400, 100.25)]).spread(), None);
+        assert_eq!(depth(&[(300, 100.0)], &[]).spread(), None);
+    }
+
+    #[test]
+    fn test_auction_holding_deserialize() {
+        let auction: AuctionHolding = serde_json::from_str(
+            r#"{"tradingsymbol":"ASHOKLEY","exchange":"NSE","instrument_token":54273,
+                "isin":"INE208A01029","product":"CNC","price":0,"quantity":1,
+                "t1_quantity":0,"average_price":95.2,"last_price":95.35,
+                "close_price":95.75,"pnl":0.15,"auction_number":"20"}"#,
+        )
+        .unwrap();
+        assert_eq!(auction.tradingsymbol, "ASHOKLEY");
+        assert_eq!(auction.quantity, 1);
+        assert_eq!(auction.auction_number, "20");
+    }
+
+    #[test]
+    fn test_exchange_from_str() {
+        assert!(matches!("NSE".parse::<Exchange>().unwrap(), Exchange::NSE));
+        assert!(matches!("bfo".parse::<Exchange>().unwrap(), Exchange::BFO));
+
+        let err = "XYZ".parse::<Exchange>().unwrap_err().to_string();
+        assert!(err.contains("Invalid exchange 'XYZ'"));
+        assert!(err.contains("NSE, BSE"));
+        assert!("UNKNOWN".parse::<Exchange>().is_err());
+    }
+
+    #[test]
+    fn test_segment_from_str() {
+        assert!(matches!("NFO-OPT".parse::<Segment>().unwrap(), Segment::NFOOPT));
+        assert!(matches!("mcx-fut".parse::<Segment>().unwrap(), Segment::MCXFUT));
+        assert!("NFO_OPT".parse::<Segment>().is_err());
+    }
+
+    #[test]
+    fn test_segment_deserialize() {
+        for name in ["NFO-OPT", "NFO-FUT", "BFO-OPT", "BFO-FUT", "MCX-FUT", "INDICES"] {
+            let segment: Segment = serde_json::from_value(serde_json::json!(name)).unwrap();
+            assert_eq!(segment.to_string(), name);
+            assert_eq!(name.parse::<Segment>().unwrap().to_string(), name);
+        }
+
+        // Segments Kite adds later still parse instead of failing the whole dump
+        let segment: Segment = serde_json::from_value(serde_json::json!("BCD-OPT")).unwrap();
+        assert!(matches!(segment, Segment::Unknown));
+    }
+}
@@ -0,0 +1,71 @@
+//! JSON Schemas for the models printed by `-o json`
+
+use super::*;
+use schemars::{schema::RootSchema, schema_for};
+
+/// Models with a published schema, by the name `kite schema` accepts
+pub const MODELS: &[&str] = &[
+    "Instrument",
+    "Order",
+    "Trade",
+    "Quote",
+    "OHLCData",
+    "LTPData",
+    "Holding",
+    "AuctionHolding",
+    "Position",
+    "Margin",
+    "OrderMargin",
+    "GTTTrigger",
+];
+
+/// JSON Schema for the model called `name` (case-insensitive)
+pub fn schema(name: &str) -> Option<RootSchema> {
+    let schema = match name.to_lowercase().as_str() {
+        "instrument" => schema_for!(Instrument),
+        "order" => schema_for!(Order),
+        "trade" => schema_for!(Trade),
+        "quote" => schema_for!(Quote),
+        "ohlcdata" => schema_for!(OHLCData),
+        "ltpdata" => schema_for!(LTPData),
+        "holding" => schema_for!(Holding),
+        "auctionholding" => schema_for!(AuctionHolding),
+        "position" => schema_for!(Position),
+        "margin" => schema_for!(Margin),
+        "ordermargin" => schema_for!(OrderMargin),
+        "gtttrigger" => schema_for!(GTTTrigger),
+        _ => return None,
+    };
+    Some(schema)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_order_schema_lists_required_fields() {
+        let schema = serde_json::to_value(schema("order").unwrap()).unwrap();
+        assert_eq!(schema["title"], "Order");
+        let required: Vec<&str> = schema["required"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|v| v.as_str().unwrap())
+            .collect();
+        for field in ["order_id", "tradingsymbol", "transaction_type", "quantity", "status"] {
+            assert!(required.contains(&field), "{} not required in {:?}", field, required);
+        }
+        // Timestamps are strings, whatever serde helper writes them
+        assert_eq!(schema["properties"]["order_timestamp"]["type"], "string");
+    }
+
+    #[test]
+    fn test_every_listed_model_has_a_schema() {
+        for name in MODELS {
+            assert!(schema(name).is_some(), "{}", name);
+        }
+        assert!(schema("GttTrigger").is_some());
+        assert!(schema("Nope").is_none());
+    }
+}
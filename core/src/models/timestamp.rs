@@ -0,0 +1,227 @@
+//! Serde helpers for Kite timestamps
+//!
+//! Kite sends local (IST) times as `YYYY-MM-DD HH:MM:SS`. They are kept as
+//! `NaiveDateTime` and serialized back out as ISO-8601.
+
+use anyhow::{bail, Result};
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime, Timelike};
+use serde::{Deserialize, Deserializer, Serializer};
+
+/// Format used by the Kite API
+pub const KITE_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+/// Parse a Kite (or ISO-8601) timestamp
+pub fn parse(s: &str) -> Option<NaiveDateTime> {
+    let s = s.trim();
+    NaiveDateTime::parse_from_str(s, KITE_FORMAT)
+        .or_else(|_| s.parse::<NaiveDateTime>())
+        .ok()
+}
+
+/// Inclusive time window for filtering timestamps
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TimeRange {
+    pub since: Option<NaiveDateTime>,
+    pub until: Option<NaiveDateTime>,
+}
+
+impl TimeRange {
+    /// Parse `--since`/`--until` values; bare times fall on `today`
+    ///
+    /// An `until` given to the minute covers that whole minute.
+    pub fn parse(since: Option<&str>, until: Option<&str>, today: NaiveDate) -> Result<Self> {
+        let since = since.map(|s| parse_bound(s, today, false)).transpose()?;
+        let until = until.map(|s| parse_bound(s, today, true)).transpose()?;
+
+        if let (Some(since), Some(until)) = (since, until) {
+            if since > until {
+                bail!("--since must not be later than --until");
+            }
+        }
+
+        Ok(TimeRange { since, until })
+    }
+
+    pub fn contains(&self, timestamp: &NaiveDateTime) -> bool {
+        self.since.is_none_or(|since| *timestamp >= since)
+            && self.until.is_none_or(|until| *timestamp <= until)
+    }
+}
+
+/// Accepts `HH:MM[:SS]` (on `today`) or `YYYY-MM-DD HH:MM[:SS]`
+fn parse_bound(s: &str, today: NaiveDate, end_of_minute: bool) -> Result<NaiveDateTime> {
+    let s = s.trim();
+    let to_minute = |t: NaiveDateTime| {
+        if end_of_minute {
+            t.with_second(59).unwrap_or(t)
+        } else {
+            t
+        }
+    };
+
+    if let Ok(time) = NaiveTime::parse_from_str(s, "%H:%M:%S") {
+        return Ok(today.and_time(time));
+    }
+    if let Ok(time) = NaiveTime::parse_from_str(s, "%H:%M") {
+        return Ok(to_minute(today.and_time(time)));
+    }
+    if let Some(timestamp) = parse(s) {
+        return Ok(timestamp);
+    }
+    for format in ["%Y-%m-%d %H:%M", "%Y-%m-%dT%H:%M"] {
+        if let Ok(timestamp) = NaiveDateTime::parse_from_str(s, format) {
+            return Ok(to_minute(timestamp));
+        }
+    }
+
+    bail!(
+        "Invalid time '{}'. Use HH:MM, HH:MM:SS or YYYY-MM-DD HH:MM[:SS]",
+        s
+    )
+}
+
+/// `#[serde(with = "...")]` for required timestamps
+pub mod required {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(
+        value: &NaiveDateTime,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(&value.format("%Y-%m-%dT%H:%M:%S"))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<NaiveDateTime, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        parse(&s).ok_or_else(|| serde::de::Error::custom(format!("invalid timestamp '{}'", s)))
+    }
+}
+
+/// `#[serde(default, with = "...")]` for optional timestamps; null and "" become `None`
+pub mod optional {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(
+        value: &Option<NaiveDateTime>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match value {
+            Some(value) => required::serialize(value, serializer),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<NaiveDateTime>, D::Error> {
+        match Option::<String>::deserialize(deserializer)? {
+            Some(s) if !s.trim().is_empty() => parse(&s)
+                .map(Some)
+                .ok_or_else(|| serde::de::Error::custom(format!("invalid timestamp '{}'", s))),
+            _ => Ok(None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, Serialize, Deserialize)]
+    struct Stamped {
+        #[serde(with = "super::required")]
+        at: chrono::NaiveDateTime,
+        #[serde(default, with = "super::optional")]
+        updated: Option<chrono::NaiveDateTime>,
+    }
+
+    #[test]
+    fn test_parses_kite_format() {
+        let s: Stamped = serde_json::from_str(
+            r#"{"at": "2024-02-25 10:30:05", "updated": "2024-02-25 11:00:00"}"#,
+        )
+        .unwrap();
+        assert_eq!(s.at.to_string(), "2024-02-25 10:30:05");
+        assert_eq!(s.updated.unwrap().to_string(), "2024-02-25 11:00:00");
+    }
+
+    #[test]
+    fn test_empty_and_missing_optional() {
+        for json in [
+            r#"{"at": "2024-02-25 10:30:05", "updated": ""}"#,
+            r#"{"at": "2024-02-25 10:30:05", "updated": null}"#,
+            r#"{"at": "2024-02-25 10:30:05"}"#,
+        ] {
+            let s: Stamped = serde_json::from_str(json).unwrap();
+            assert!(s.updated.is_none());
+        }
+    }
+
+    #[test]
+    fn test_rejects_garbage() {
+        assert!(serde_json::from_str::<Stamped>(r#"{"at": "yesterday"}"#).is_err());
+        assert!(serde_json::from_str::<Stamped>(r#"{"at": ""}"#).is_err());
+    }
+
+    #[test]
+    fn test_serializes_iso_and_round_trips() {
+        let s: Stamped = serde_json::from_str(r#"{"at": "2024-02-25 10:30:05"}"#).unwrap();
+        let json = serde_json::to_string(&s).unwrap();
+        assert_eq!(json, r#"{"at":"2024-02-25T10:30:05","updated":null}"#);
+
+        let back: Stamped = serde_json::from_str(&json).unwrap();
+        assert_eq!(back.at, s.at);
+    }
+
+    fn today() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 2, 25).unwrap()
+    }
+
+    fn at(s: &str) -> NaiveDateTime {
+        super::parse(s).unwrap()
+    }
+
+    #[test]
+    fn test_time_range_bounds_are_inclusive() {
+        let range = TimeRange::parse(Some("09:15"), Some("10:00:00"), today()).unwrap();
+
+        assert!(range.contains(&at("2024-02-25 09:15:00")));
+        assert!(range.contains(&at("2024-02-25 10:00:00")));
+        assert!(!range.contains(&at("2024-02-25 09:14:59")));
+        assert!(!range.contains(&at("2024-02-25 10:00:01")));
+        assert!(!range.contains(&at("2024-02-24 09:30:00")));
+    }
+
+    #[test]
+    fn test_until_minute_covers_whole_minute() {
+        let range = TimeRange::parse(None, Some("10:00"), today()).unwrap();
+
+        assert!(range.contains(&at("2024-02-25 10:00:59")));
+        assert!(!range.contains(&at("2024-02-25 10:01:00")));
+        assert!(range.contains(&at("2024-02-20 15:00:00")));
+    }
+
+    #[test]
+    fn test_time_range_full_datetimes() {
+        let range =
+            TimeRange::parse(Some("2024-02-24 15:00"), Some("2024-02-25 09:30:00"), today())
+                .unwrap();
+
+        assert!(range.contains(&at("2024-02-24 15:00:00")));
+        assert!(range.contains(&at("2024-02-25 09:30:00")));
+        assert!(!range.contains(&at("2024-02-24 14:59:59")));
+    }
+
+    #[test]
+    fn test_time_range_errors() {
+        assert!(TimeRange::parse(Some("9am"), None, today()).is_err());
+        assert!(TimeRange::parse(Some("10:00"), Some("09:00"), today()).is_err());
+        assert_eq!(
+            TimeRange::parse(None, None, today()).unwrap(),
+            TimeRange::default()
+        );
+    }
+}
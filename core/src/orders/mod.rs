@@ -0,0 +1,174 @@
+//! Order book aggregation
+
+use crate::models::{Order, Trade, TransactionType};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// Traded quantity and value for one instrument
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct SymbolActivity {
+    /// `EXCHANGE:TRADINGSYMBOL`
+    pub symbol: String,
+    pub trades: usize,
+    pub buy_quantity: i64,
+    pub sell_quantity: i64,
+    /// Bought minus sold
+    pub net_quantity: i64,
+    pub buy_value: f64,
+    pub sell_value: f64,
+}
+
+/// The day's order and trade counts, for keeping an eye on over-trading
+///
+/// Values come from fills (quantity × average price), so they match what a
+/// contract note shows rather than what was ordered.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct DayStats {
+    pub orders: usize,
+    /// Order count per status, e.g. `COMPLETE` or `REJECTED`
+    pub orders_by_status: BTreeMap<String, usize>,
+    pub trades: usize,
+    pub buy_value: f64,
+    pub sell_value: f64,
+    /// Per instrument, sorted by symbol
+    pub symbols: Vec<SymbolActivity>,
+}
+
+impl DayStats {
+    pub fn new(orders: &[Order], trades: &[Trade]) -> Self {
+        let mut stats = Self {
+            orders: orders.len(),
+            trades: trades.len(),
+            ..Self::default()
+        };
+
+        for order in orders {
+            *stats
+                .orders_by_status
+                .entry(order.status.to_string())
+                .or_default() += 1;
+        }
+
+        let mut symbols: BTreeMap<String, SymbolActivity> = BTreeMap::new();
+        for trade in trades {
+            let symbol = format!("{}:{}", trade.exchange, trade.tradingsymbol);
+            let activity = symbols.entry(symbol.clone()).or_insert_with(|| SymbolActivity {
+                symbol,
+                ..SymbolActivity::default()
+            });
+            let quantity = i64::from(trade.quantity);
+            let value = trade.average_price * f64::from(trade.quantity);
+            activity.trades += 1;
+            match trade.transaction_type {
+                TransactionType::Buy => {
+                    activity.buy_quantity += quantity;
+                    activity.buy_value += value;
+                    stats.buy_value += value;
+                }
+                TransactionType::Sell => {
+                    activity.sell_quantity += quantity;
+                    activity.sell_value += value;
+                    stats.sell_value += value;
+                }
+            }
+            activity.net_quantity = activity.buy_quantity - activity.sell_quantity;
+        }
+        stats.symbols = symbols.into_values().collect();
+        stats
+    }
+
+    /// Combined buy and sell value
+    pub fn turnover(&self) -> f64 {
+        self.buy_value + self.sell_value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn order(status: &str) -> Order {
+        serde_json::from_value(serde_json::json!({
+            "order_id": "1",
+            "exchange_order_id": null,
+            "parent_order_id": null,
+            "status": status,
+            "status_message": null,
+            "tradingsymbol": "INFY",
+            "exchange": "NSE",
+            "variety": "regular",
+            "order_type": "LIMIT",
+            "transaction_type": "BUY",
+            "validity": "DAY",
+            "product": "CNC",
+            "quantity": 10,
+            "disclosed_quantity": null,
+            "price": 1500.0,
+            "trigger_price": null,
+            "average_price": null,
+            "pending_quantity": 0,
+            "filled_quantity": 0,
+            "cancelled_quantity": 0,
+            "placed_by": "AB1234",
+            "order_timestamp": "2024-06-03 09:15:00",
+        }))
+        .unwrap()
+    }
+
+    fn trade(symbol: &str, side: &str, quantity: i32, price: f64) -> Trade {
+        serde_json::from_value(serde_json::json!({
+            "trade_id": "1",
+            "order_id": "1",
+            "exchange_order_id": null,
+            "tradingsymbol": symbol,
+            "exchange": "NSE",
+            "transaction_type": side,
+            "product": "MIS",
+            "average_price": price,
+            "quantity": quantity,
+            "fill_timestamp": "2024-06-03 09:15:00",
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_day_stats_counts_and_values() {
+        let orders = vec![
+            order("COMPLETE"),
+            order("COMPLETE"),
+            order("REJECTED"),
+            order("TRIGGER PENDING"),
+        ];
+        let trades = vec![
+            trade("INFY", "BUY", 10, 1500.0),
+            trade("INFY", "BUY", 5, 1510.0),
+            trade("INFY", "SELL", 12, 1520.0),
+            trade("TCS", "SELL", 2, 3800.0),
+        ];
+
+        let stats = DayStats::new(&orders, &trades);
+
+        assert_eq!(stats.orders, 4);
+        assert_eq!(stats.orders_by_status["COMPLETE"], 2);
+        assert_eq!(stats.orders_by_status["REJECTED"], 1);
+        assert_eq!(stats.orders_by_status["TRIGGER PENDING"], 1);
+        assert_eq!(stats.trades, 4);
+        assert_eq!(stats.buy_value, 15_000.0 + 7_550.0);
+        assert_eq!(stats.sell_value, 18_240.0 + 7_600.0);
+        assert_eq!(stats.turnover(), 48_390.0);
+
+        let symbols: Vec<_> = stats.symbols.iter().map(|s| s.symbol.as_str()).collect();
+        assert_eq!(symbols, ["NSE:INFY", "NSE:TCS"]);
+        let infy = &stats.symbols[0];
+        assert_eq!((infy.trades, infy.buy_quantity, infy.sell_quantity), (3, 15, 12));
+        assert_eq!(infy.net_quantity, 3);
+        assert_eq!(stats.symbols[1].net_quantity, -2);
+    }
+
+    #[test]
+    fn test_day_stats_empty() {
+        let stats = DayStats::new(&[], &[]);
+        assert_eq!(stats, DayStats::default());
+        assert!(stats.symbols.is_empty());
+    }
+}
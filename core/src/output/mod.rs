@@ -1,24 +1,228 @@
 //! Output formatting for CLI commands
+//!
+//! Only a command's result goes to stdout, through [`writer`] or [`emit`], so
+//! `-o json` output can be piped straight into other tools. Everything else
+//! goes to stderr: [`progress`] for chatter that `--quiet` hides, and
+//! [`notice`] for messages the user should always see, like empty results.
 
-use crate::models::{Holding, Instrument, Order, Position};
+use crate::models::{Holding, Instrument, Order, Position, Trade};
 use comfy_table::{
     modifiers::UTF8_ROUND_CORNERS, presets::UTF8_FULL, Attribute, Cell, Color, ContentArrangement,
     Table,
 };
+use serde::Serialize;
+use std::fmt::Display;
+use std::io::Write;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+mod number;
+pub use number::{format_inr, format_inr_full, format_inr_short, set_human_amounts};
+
+mod page;
+pub use page::{paginate, Page};
+
+mod sink;
+pub use sink::{emit, set_output_file, start_capture, take_capture, writer};
+
+mod template;
+pub use template::{set_template, template, write_template};
+
+mod theme;
+pub use theme::{header, set_theme, theme, Theme};
+
+static COLOR_ENABLED: AtomicBool = AtomicBool::new(true);
+static PRETTY_JSON: AtomicBool = AtomicBool::new(true);
+static QUIET: AtomicBool = AtomicBool::new(false);
+
+/// Serializes tests that change the process-wide output settings above,
+/// the theme, the capture buffer or the output file
+#[cfg(test)]
+pub(crate) fn lock_output_state() -> std::sync::MutexGuard<'static, ()> {
+    static LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+    LOCK.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Decide whether table cells are colored.
+///
+/// Color is disabled by `--no-color`, a non-empty `NO_COLOR` variable, or
+/// output that doesn't go to a terminal, including `--output-file`, so call
+/// this after `set_output_file`.
+pub fn init_color(no_color_flag: bool) {
+    let no_color_env = std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty());
+    set_color_enabled(!no_color_flag && !no_color_env && sink::is_terminal());
+}
+
+/// Enable or disable colored table cells
+pub fn set_color_enabled(enabled: bool) {
+    COLOR_ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+/// Whether table cells should be colored
+pub fn color_enabled() -> bool {
+    COLOR_ENABLED.load(Ordering::Relaxed)
+}
+
+/// Silence progress messages (`--quiet`)
+pub fn set_quiet(quiet: bool) {
+    QUIET.store(quiet, Ordering::Relaxed);
+}
+
+/// Report progress on stderr, keeping stdout for results; silent with `--quiet`
+pub fn progress(message: impl std::fmt::Display) {
+    if !QUIET.load(Ordering::Relaxed) {
+        eprintln!("{}", message);
+    }
+}
+
+/// Tell the user something that isn't part of the result, on stderr
+pub fn notice(message: impl std::fmt::Display) {
+    eprintln!("{}", message);
+}
+
+/// Decide whether JSON output is indented.
+///
+/// `--pretty` and `--compact` force a style; otherwise JSON is pretty on a
+/// terminal and single-line when piped or written to `--output-file`.
+pub fn init_json_style(pretty_flag: bool, compact_flag: bool) {
+    let pretty = if pretty_flag || compact_flag {
+        pretty_flag
+    } else {
+        sink::is_terminal()
+    };
+    PRETTY_JSON.store(pretty, Ordering::Relaxed);
+}
+
+/// Serialize a value as JSON in the style chosen by `init_json_style`
+pub fn to_json<T: Serialize + ?Sized>(value: &T) -> anyhow::Result<String> {
+    json_string(value, PRETTY_JSON.load(Ordering::Relaxed))
+}
+
+fn json_string<T: Serialize + ?Sized>(value: &T, pretty: bool) -> anyhow::Result<String> {
+    Ok(if pretty {
+        serde_json::to_string_pretty(value)?
+    } else {
+        serde_json::to_string(value)?
+    })
+}
+
+/// Color a cell only when color output is enabled
+pub trait CellColorExt {
+    fn color(self, color: Color) -> Self;
+}
+
+impl CellColorExt for Cell {
+    fn color(self, color: Color) -> Self {
+        if color_enabled() {
+            self.fg(color)
+        } else {
+            self
+        }
+    }
+}
+
+/// Supported output formats
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    Table,
+    Json,
+    Csv,
+    Yaml,
+    Ndjson,
+}
+
+impl FromStr for OutputFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "table" => Ok(OutputFormat::Table),
+            "json" => Ok(OutputFormat::Json),
+            "csv" => Ok(OutputFormat::Csv),
+            "yaml" => Ok(OutputFormat::Yaml),
+            "ndjson" => Ok(OutputFormat::Ndjson),
+            _ => anyhow::bail!(
+                "Invalid output format '{}'. Use table, json, csv, yaml, or ndjson",
+                s
+            ),
+        }
+    }
+}
+
+impl Display for OutputFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OutputFormat::Table => write!(f, "table"),
+            OutputFormat::Json => write!(f, "json"),
+            OutputFormat::Csv => write!(f, "csv"),
+            OutputFormat::Yaml => write!(f, "yaml"),
+            OutputFormat::Ndjson => write!(f, "ndjson"),
+        }
+    }
+}
 
 /// Trait for formatted output
 pub trait OutputFormatter {
-    /// Print as table
-    fn print(&self) -> anyhow::Result<()>;
+    /// Print as table to `w`
+    fn print(&self, w: &mut dyn Write) -> anyhow::Result<()>;
+
+    /// Print as JSON to `w`, pretty or compact as chosen by `init_json_style`
+    fn print_json(&self, w: &mut dyn Write) -> anyhow::Result<()>
+    where
+        Self: Serialize,
+    {
+        writeln!(w, "{}", to_json(self)?)?;
+        Ok(())
+    }
+
+    /// Print as CSV with a header line to `w`
+    fn print_csv(&self, w: &mut dyn Write) -> anyhow::Result<()>;
+
+    /// Print as YAML to `w`
+    fn print_yaml(&self, w: &mut dyn Write) -> anyhow::Result<()>;
 
-    /// Print as JSON
-    fn print_json(&self) -> anyhow::Result<()>;
+    /// Print one compact JSON object per line to `w`
+    fn print_ndjson(&self, w: &mut dyn Write) -> anyhow::Result<()>;
+
+    /// Print one line per record to `w`, filling `{field}` from its JSON fields
+    fn print_template(&self, template: &str, w: &mut dyn Write) -> anyhow::Result<()>
+    where
+        Self: Serialize,
+    {
+        write_template(self, template, w)
+    }
+}
+
+/// Write rows as RFC 4180 CSV with a header line
+pub fn write_csv<T: Serialize, W: Write>(rows: &[T], writer: W) -> anyhow::Result<()> {
+    let mut wtr = csv::Writer::from_writer(writer);
+    for row in rows {
+        wtr.serialize(row)?;
+    }
+    wtr.flush()?;
+    Ok(())
+}
+
+/// Write rows as newline-delimited JSON, one compact object per line
+pub fn write_ndjson<T: Serialize, W: Write>(rows: &[T], mut writer: W) -> anyhow::Result<()> {
+    for row in rows {
+        serde_json::to_writer(&mut writer, row)?;
+        writer.write_all(b"\n")?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+/// Write a value as a YAML document
+pub fn write_yaml<T: Serialize + ?Sized, W: Write>(value: &T, writer: W) -> anyhow::Result<()> {
+    serde_yaml::to_writer(writer, value)?;
+    Ok(())
 }
 
 impl OutputFormatter for Vec<Holding> {
-    fn print(&self) -> anyhow::Result<()> {
+    fn print(&self, w: &mut dyn Write) -> anyhow::Result<()> {
         if self.is_empty() {
-            println!("No holdings found");
+            writeln!(w, "No holdings found")?;
             return Ok(());
         }
 
@@ -27,10 +231,10 @@ impl OutputFormatter for Vec<Holding> {
             .load_preset(UTF8_FULL)
             .apply_modifier(UTF8_ROUND_CORNERS)
             .set_content_arrangement(ContentArrangement::Dynamic)
-            .set_header(vec!["Symbol", "Qty", "Avg Price", "LTP", "P&L", "Day Chg%"]);
+            .set_header(header(vec!["Symbol", "Qty", "Avg Price", "LTP", "P&L", "Day Chg%"]));
 
         for holding in self {
-            let pnl_cell = cell_color(format!("₹{:.2}", holding.pnl), holding.pnl >= 0.0, true);
+            let pnl_cell = cell_color(format_inr(holding.pnl), holding.pnl >= 0.0, true);
 
             let chg_cell = cell_color(
                 format!("{:.2}%", holding.day_change_percentage),
@@ -41,27 +245,34 @@ impl OutputFormatter for Vec<Holding> {
             table.add_row(vec![
                 Cell::new(&holding.tradingsymbol),
                 Cell::new(holding.quantity.to_string()),
-                Cell::new(format!("₹{:.2}", holding.average_price)),
-                Cell::new(format!("₹{:.2}", holding.last_price)),
+                Cell::new(format_inr(holding.average_price)),
+                Cell::new(format_inr(holding.last_price)),
                 pnl_cell,
                 chg_cell,
             ]);
         }
 
-        println!("{table}");
+        writeln!(w, "{table}")?;
         Ok(())
     }
 
-    fn print_json(&self) -> anyhow::Result<()> {
-        println!("{}", serde_json::to_string_pretty(self)?);
-        Ok(())
+    fn print_csv(&self, w: &mut dyn Write) -> anyhow::Result<()> {
+        write_csv(self, w)
+    }
+
+    fn print_yaml(&self, w: &mut dyn Write) -> anyhow::Result<()> {
+        write_yaml(self, w)
+    }
+
+    fn print_ndjson(&self, w: &mut dyn Write) -> anyhow::Result<()> {
+        write_ndjson(self, w)
     }
 }
 
 impl OutputFormatter for Vec<Order> {
-    fn print(&self) -> anyhow::Result<()> {
+    fn print(&self, w: &mut dyn Write) -> anyhow::Result<()> {
         if self.is_empty() {
-            println!("No orders found");
+            writeln!(w, "No orders found")?;
             return Ok(());
         }
 
@@ -70,9 +281,9 @@ impl OutputFormatter for Vec<Order> {
             .load_preset(UTF8_FULL)
             .apply_modifier(UTF8_ROUND_CORNERS)
             .set_content_arrangement(ContentArrangement::Dynamic)
-            .set_header(vec![
+            .set_header(header(vec![
                 "Order ID", "Symbol", "Type", "Qty", "Price", "Status", "Time",
-            ]);
+            ]));
 
         for order in self {
             let status_cell = cell_order_status(&order.status);
@@ -88,20 +299,27 @@ impl OutputFormatter for Vec<Order> {
             ]);
         }
 
-        println!("{table}");
+        writeln!(w, "{table}")?;
         Ok(())
     }
 
-    fn print_json(&self) -> anyhow::Result<()> {
-        println!("{}", serde_json::to_string_pretty(self)?);
-        Ok(())
+    fn print_csv(&self, w: &mut dyn Write) -> anyhow::Result<()> {
+        write_csv(self, w)
+    }
+
+    fn print_yaml(&self, w: &mut dyn Write) -> anyhow::Result<()> {
+        write_yaml(self, w)
+    }
+
+    fn print_ndjson(&self, w: &mut dyn Write) -> anyhow::Result<()> {
+        write_ndjson(self, w)
     }
 }
 
 impl OutputFormatter for Vec<Position> {
-    fn print(&self) -> anyhow::Result<()> {
+    fn print(&self, w: &mut dyn Write) -> anyhow::Result<()> {
         if self.is_empty() {
-            println!("No positions found");
+            writeln!(w, "No positions found")?;
             return Ok(());
         }
 
@@ -110,7 +328,7 @@ impl OutputFormatter for Vec<Position> {
             .load_preset(UTF8_FULL)
             .apply_modifier(UTF8_ROUND_CORNERS)
             .set_content_arrangement(ContentArrangement::Dynamic)
-            .set_header(vec![
+            .set_header(header(vec![
                 "Symbol",
                 "Product",
                 "Qty",
@@ -118,13 +336,13 @@ impl OutputFormatter for Vec<Position> {
                 "LTP",
                 "P&L",
                 "Unrealised",
-            ]);
+            ]));
 
         for position in self {
-            let pnl_cell = cell_color(format!("₹{:.2}", position.pnl), position.pnl >= 0.0, true);
+            let pnl_cell = cell_color(format_inr(position.pnl), position.pnl >= 0.0, true);
 
             let unrealised_cell = cell_color(
-                format!("₹{:.2}", position.unrealised),
+                format_inr(position.unrealised),
                 position.unrealised >= 0.0,
                 true,
             );
@@ -133,27 +351,34 @@ impl OutputFormatter for Vec<Position> {
                 Cell::new(&position.tradingsymbol),
                 Cell::new(format!("{:?}", position.product)),
                 Cell::new(position.quantity.to_string()),
-                Cell::new(format!("₹{:.2}", position.average_price)),
-                Cell::new(format!("₹{:.2}", position.last_price)),
+                Cell::new(format_inr(position.average_price)),
+                Cell::new(format_inr(position.last_price)),
                 pnl_cell,
                 unrealised_cell,
             ]);
         }
 
-        println!("{table}");
+        writeln!(w, "{table}")?;
         Ok(())
     }
 
-    fn print_json(&self) -> anyhow::Result<()> {
-        println!("{}", serde_json::to_string_pretty(self)?);
-        Ok(())
+    fn print_csv(&self, w: &mut dyn Write) -> anyhow::Result<()> {
+        write_csv(self, w)
+    }
+
+    fn print_yaml(&self, w: &mut dyn Write) -> anyhow::Result<()> {
+        write_yaml(self, w)
+    }
+
+    fn print_ndjson(&self, w: &mut dyn Write) -> anyhow::Result<()> {
+        write_ndjson(self, w)
     }
 }
 
 impl OutputFormatter for Vec<Instrument> {
-    fn print(&self) -> anyhow::Result<()> {
+    fn print(&self, w: &mut dyn Write) -> anyhow::Result<()> {
         if self.is_empty() {
-            println!("No instruments found");
+            writeln!(w, "No instruments found")?;
             return Ok(());
         }
 
@@ -162,40 +387,92 @@ impl OutputFormatter for Vec<Instrument> {
             .load_preset(UTF8_FULL)
             .apply_modifier(UTF8_ROUND_CORNERS)
             .set_content_arrangement(ContentArrangement::Dynamic)
-            .set_header(vec![
+            .set_header(header(vec![
                 "Symbol", "Name", "Exchange", "Segment", "Type", "Lot Size",
-            ]);
+            ]));
 
         for instrument in self {
             table.add_row(vec![
                 Cell::new(&instrument.tradingsymbol),
                 Cell::new(&instrument.name),
                 Cell::new(format!("{:?}", instrument.exchange)),
-                Cell::new(format!("{:?}", instrument.segment)),
+                Cell::new(instrument.segment.to_string()),
                 Cell::new(format!("{:?}", instrument.instrument_type)),
                 Cell::new(instrument.lot_size.to_string()),
             ]);
         }
 
-        println!("{table}");
+        writeln!(w, "{table}")?;
         Ok(())
     }
 
-    fn print_json(&self) -> anyhow::Result<()> {
-        println!("{}", serde_json::to_string_pretty(self)?);
+    fn print_csv(&self, w: &mut dyn Write) -> anyhow::Result<()> {
+        write_csv(self, w)
+    }
+
+    fn print_yaml(&self, w: &mut dyn Write) -> anyhow::Result<()> {
+        write_yaml(self, w)
+    }
+
+    fn print_ndjson(&self, w: &mut dyn Write) -> anyhow::Result<()> {
+        write_ndjson(self, w)
+    }
+}
+
+impl OutputFormatter for Vec<Trade> {
+    fn print(&self, w: &mut dyn Write) -> anyhow::Result<()> {
+        if self.is_empty() {
+            writeln!(w, "No trades found")?;
+            return Ok(());
+        }
+
+        let mut table = Table::new();
+        table
+            .load_preset(UTF8_FULL)
+            .apply_modifier(UTF8_ROUND_CORNERS)
+            .set_content_arrangement(ContentArrangement::Dynamic)
+            .set_header(header(vec![
+                "Trade ID", "Order ID", "Symbol", "Type", "Qty", "Price", "Time",
+            ]));
+
+        for trade in self {
+            table.add_row(vec![
+                Cell::new(&trade.trade_id),
+                Cell::new(&trade.order_id),
+                Cell::new(&trade.tradingsymbol),
+                Cell::new(trade.transaction_type.to_string()),
+                Cell::new(trade.quantity.to_string()),
+                Cell::new(format!("₹{:.2}", trade.average_price)),
+                Cell::new(format_time(&trade.fill_timestamp)),
+            ]);
+        }
+
+        writeln!(w, "{table}")?;
         Ok(())
     }
+
+    fn print_csv(&self, w: &mut dyn Write) -> anyhow::Result<()> {
+        write_csv(self, w)
+    }
+
+    fn print_yaml(&self, w: &mut dyn Write) -> anyhow::Result<()> {
+        write_yaml(self, w)
+    }
+
+    fn print_ndjson(&self, w: &mut dyn Write) -> anyhow::Result<()> {
+        write_ndjson(self, w)
+    }
 }
 
 /// Create a colored cell based on value
 fn cell_color(value: String, is_positive: bool, bold: bool) -> Cell {
     let color = if is_positive {
-        Color::Green
+        theme().positive
     } else {
-        Color::Red
+        theme().negative
     };
 
-    let mut cell = Cell::new(value).fg(color);
+    let mut cell = Cell::new(value).color(color);
 
     if bold {
         cell = cell.add_attribute(Attribute::Bold);
@@ -206,42 +483,169 @@ fn cell_color(value: String, is_positive: bool, bold: bool) -> Cell {
 
 /// Create a colored cell for order status
 fn cell_order_status(status: &crate::models::OrderStatus) -> Cell {
+    let theme = theme();
     let (text, color) = match status {
-        crate::models::OrderStatus::Complete => ("COMPLETE", Color::Green),
-        crate::models::OrderStatus::Open => ("OPEN", Color::Yellow),
-        crate::models::OrderStatus::Cancelled => ("CANCELLED", Color::Red),
-        crate::models::OrderStatus::Rejected => ("REJECTED", Color::Red),
-        crate::models::OrderStatus::TriggerPending => ("TRIGGER PENDING", Color::Cyan),
-        crate::models::OrderStatus::ValidationPending => ("VALIDATION PENDING", Color::Cyan),
+        crate::models::OrderStatus::Complete => ("COMPLETE", theme.positive),
+        crate::models::OrderStatus::Open => ("OPEN", theme.pending),
+        crate::models::OrderStatus::Cancelled => ("CANCELLED", theme.negative),
+        crate::models::OrderStatus::Rejected => ("REJECTED", theme.negative),
+        crate::models::OrderStatus::TriggerPending => ("TRIGGER PENDING", theme.pending),
+        crate::models::OrderStatus::ValidationPending => ("VALIDATION PENDING", theme.pending),
     };
 
-    Cell::new(text).fg(color)
+    Cell::new(text).color(color)
 }
 
-/// Format timestamp for display
-fn format_time(timestamp: &str) -> String {
-    // Try to parse ISO format and format nicely
-    if let Ok(dt) = chrono::DateTime::parse_from_rfc3339(timestamp) {
-        dt.format("%Y-%m-%d %H:%M").to_string()
-    } else if let Some(ts) = timestamp.strip_suffix("Z") {
-        // Try with Z suffix removed
-        if let Ok(dt) = chrono::DateTime::parse_from_rfc3339(&format!("{}Z", ts)) {
-            dt.format("%Y-%m-%d %H:%M").to_string()
-        } else {
-            timestamp.to_string()
-        }
-    } else {
-        timestamp.to_string()
-    }
+/// Format a Kite timestamp for table display
+pub fn format_time(timestamp: &chrono::NaiveDateTime) -> String {
+    timestamp.format("%Y-%m-%d %H:%M").to_string()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_output_format_from_str() {
+        assert_eq!("CSV".parse::<OutputFormat>().unwrap(), OutputFormat::Csv);
+        assert_eq!("json".parse::<OutputFormat>().unwrap(), OutputFormat::Json);
+        assert!("xml".parse::<OutputFormat>().is_err());
+    }
+
+    #[test]
+    fn test_json_pretty_and_compact() {
+        let holdings = sample_holdings();
+        let pretty = json_string(&holdings, true).unwrap();
+        let compact = json_string(&holdings, false).unwrap();
+        assert!(pretty.contains("\n  "));
+        assert!(!compact.contains('\n'));
+        let pretty: serde_json::Value = serde_json::from_str(&pretty).unwrap();
+        let compact: serde_json::Value = serde_json::from_str(&compact).unwrap();
+        assert_eq!(pretty, compact);
+    }
+
+    #[test]
+    fn test_color_can_be_disabled() {
+        let render = || {
+            let mut table = Table::new();
+            table.enforce_styling();
+            table.add_row(vec![cell_color("1.00".to_string(), true, false)]);
+            table.to_string()
+        };
+
+        let _guard = lock_output_state();
+        let previous = color_enabled();
+        set_color_enabled(true);
+        let colored = render();
+        set_color_enabled(false);
+        let plain = render();
+        set_color_enabled(previous);
+
+        assert!(colored.contains('\x1b'));
+        assert!(!plain.contains('\x1b'));
+    }
+
+    fn sample_holdings() -> Vec<Holding> {
+        use crate::models::Exchange;
+
+        vec![Holding {
+            tradingsymbol: "INFY".to_string(),
+            exchange: Exchange::NSE,
+            instrument_token: 408065,
+            isin: "INE009A01021".to_string(),
+            quantity: 10,
+            authorised_quantity: 0,
+            average_price: 1400.5,
+            last_price: 1500.0,
+            close_price: 1490.0,
+            pnl: 995.0,
+            day_change: 10.0,
+            day_change_percentage: 0.67,
+        }]
+    }
+
+    #[test]
+    fn test_write_csv_holdings() {
+        let holdings = sample_holdings();
+
+        let mut buf = Vec::new();
+        write_csv(&holdings, &mut buf).unwrap();
+        let out = String::from_utf8(buf).unwrap();
+        let mut lines = out.lines();
+
+        assert_eq!(
+            lines.next().unwrap(),
+            "tradingsymbol,exchange,instrument_token,isin,quantity,authorised_quantity,average_price,last_price,close_price,pnl,day_change,day_change_percentage"
+        );
+        assert_eq!(
+            lines.next().unwrap(),
+            "INFY,NSE,408065,INE009A01021,10,0,1400.5,1500.0,1490.0,995.0,10.0,0.67"
+        );
+        assert!(lines.next().is_none());
+    }
+
+    #[test]
+    fn test_yaml_holdings_round_trip() {
+        let holdings = sample_holdings();
+
+        let mut buf = Vec::new();
+        holdings.print_yaml(&mut buf).unwrap();
+        let parsed: Vec<Holding> = serde_yaml::from_str(&String::from_utf8(buf).unwrap()).unwrap();
+
+        assert_eq!(parsed.len(), 1);
+        assert_eq!(parsed[0].tradingsymbol, "INFY");
+        assert_eq!(parsed[0].exchange.to_string(), "NSE");
+        assert_eq!(parsed[0].quantity, 10);
+        assert_eq!(parsed[0].pnl, 995.0);
+    }
+
+    #[test]
+    fn test_template_holdings() {
+        let mut holdings = sample_holdings();
+        holdings.push(holdings[0].clone());
+        holdings[1].tradingsymbol = "TCS".to_string();
+
+        let mut buf = Vec::new();
+        holdings
+            .print_template("{exchange}:{tradingsymbol} {last_price} {pnl}{ltp}", &mut buf)
+            .unwrap();
+
+        // Unknown fields render empty
+        assert_eq!(
+            String::from_utf8(buf).unwrap(),
+            "NSE:INFY 1500.0 995.0\nNSE:TCS 1500.0 995.0\n"
+        );
+    }
+
+    #[test]
+    fn test_ndjson_one_line_per_row() {
+        let mut holdings = sample_holdings();
+        holdings.push(holdings[0].clone());
+        holdings[1].tradingsymbol = "TCS".to_string();
+
+        let mut buf = Vec::new();
+        holdings.print_ndjson(&mut buf).unwrap();
+        let out = String::from_utf8(buf).unwrap();
+
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(lines.len(), holdings.len());
+        assert!(out.ends_with('\n'));
+        for (line, holding) in lines.iter().zip(&holdings) {
+            let parsed: Holding = serde_json::from_str(line).unwrap();
+            assert_eq!(parsed.tradingsymbol, holding.tradingsymbol);
+        }
+    }
+
+    #[test]
+    fn test_ndjson_format_parses() {
+        assert_eq!("NDJSON".parse::<OutputFormat>().unwrap(), OutputFormat::Ndjson);
+        assert_eq!(OutputFormat::Ndjson.to_string(), "ndjson");
+    }
+
     #[test]
     fn test_format_time() {
-        let result = format_time("2024-02-25T10:30:00+05:30");
+        let timestamp = crate::models::timestamp::parse("2024-02-25 10:30:45").unwrap();
+        let result = format_time(&timestamp);
         assert_eq!(result, "2024-02-25 10:30");
     }
 }
@@ -0,0 +1,103 @@
+//! Indian-style rupee formatting
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static HUMAN_AMOUNTS: AtomicBool = AtomicBool::new(false);
+
+const LAKH: f64 = 100_000.0;
+const CRORE: f64 = 10_000_000.0;
+
+/// Abbreviate large amounts to lakh/crore in `format_inr`
+pub fn set_human_amounts(enabled: bool) {
+    HUMAN_AMOUNTS.store(enabled, Ordering::Relaxed);
+}
+
+/// Format a rupee amount, abbreviated when `--human` is set
+pub fn format_inr(value: f64) -> String {
+    if HUMAN_AMOUNTS.load(Ordering::Relaxed) {
+        format_inr_short(value)
+    } else {
+        format_inr_full(value)
+    }
+}
+
+/// Format a rupee amount with Indian digit grouping (e.g. `₹12,34,567.89`)
+pub fn format_inr_full(value: f64) -> String {
+    let formatted = format!("{:.2}", value.abs());
+    let (int_part, frac_part) = formatted.split_once('.').unwrap_or((&formatted, "00"));
+
+    let sign = if value < 0.0 && formatted != "0.00" { "-" } else { "" };
+    format!("{}₹{}.{}", sign, group_indian(int_part), frac_part)
+}
+
+/// Format a rupee amount abbreviated to lakh/crore (e.g. `₹12.3L`, `₹1.2Cr`)
+pub fn format_inr_short(value: f64) -> String {
+    let abs = value.abs();
+    let sign = if value < 0.0 { "-" } else { "" };
+
+    // Pick the unit after rounding, so 99.96 lakh reads ₹1.0Cr and not ₹100.0L
+    let lakhs = (abs / LAKH * 10.0).round() / 10.0;
+    if lakhs >= CRORE / LAKH {
+        format!("{}₹{:.1}Cr", sign, abs / CRORE)
+    } else if abs >= LAKH {
+        format!("{}₹{:.1}L", sign, lakhs)
+    } else {
+        format_inr_full(value)
+    }
+}
+
+/// Group an integer string as 12,34,567
+fn group_indian(digits: &str) -> String {
+    if digits.len() <= 3 {
+        return digits.to_string();
+    }
+
+    let (head, last_three) = digits.split_at(digits.len() - 3);
+    let mut groups = Vec::new();
+    let mut rest = head;
+    while rest.len() > 2 {
+        let (left, right) = rest.split_at(rest.len() - 2);
+        groups.push(right);
+        rest = left;
+    }
+    groups.push(rest);
+    groups.reverse();
+
+    format!("{},{}", groups.join(","), last_three)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_inr_lakh() {
+        assert_eq!(format_inr_full(100000.0), "₹1,00,000.00");
+        assert_eq!(format_inr_short(100000.0), "₹1.0L");
+        assert_eq!(format_inr_short(1234567.0), "₹12.3L");
+    }
+
+    #[test]
+    fn test_format_inr_crore() {
+        assert_eq!(format_inr_full(10000000.0), "₹1,00,00,000.00");
+        assert_eq!(format_inr_short(10000000.0), "₹1.0Cr");
+        assert_eq!(format_inr_short(12000000.0), "₹1.2Cr");
+        // Rounds up into the next unit
+        assert_eq!(format_inr_short(9999999.0), "₹1.0Cr");
+        assert_eq!(format_inr_short(9994999.0), "₹99.9L");
+    }
+
+    #[test]
+    fn test_format_inr_negative() {
+        assert_eq!(format_inr_full(-1234567.891), "-₹12,34,567.89");
+        assert_eq!(format_inr_short(-250000.0), "-₹2.5L");
+        assert_eq!(format_inr_full(-0.001), "₹0.00");
+    }
+
+    #[test]
+    fn test_format_inr_small() {
+        assert_eq!(format_inr_full(0.0), "₹0.00");
+        assert_eq!(format_inr_full(999.5), "₹999.50");
+        assert_eq!(format_inr_short(99999.0), "₹99,999.00");
+    }
+}
@@ -0,0 +1,89 @@
+//! Pagination of long result sets
+
+use anyhow::{bail, Result};
+
+/// One page of a result set
+#[derive(Debug, PartialEq)]
+pub struct Page<'a, T> {
+    pub items: &'a [T],
+    /// 1-based page number
+    pub number: usize,
+    pub total_pages: usize,
+    pub total_items: usize,
+}
+
+impl<T> Page<'_, T> {
+    /// "Page N of K" line printed under a table
+    pub fn footer(&self) -> String {
+        format!(
+            "Page {} of {} ({} total)",
+            self.number, self.total_pages, self.total_items
+        )
+    }
+}
+
+/// Slice page `page` (1-based) of `size` items out of `items`
+///
+/// An empty result set has a single empty page.
+pub fn paginate<T>(items: &[T], page: usize, size: usize) -> Result<Page<'_, T>> {
+    if page == 0 {
+        bail!("Page numbers start at 1");
+    }
+    if size == 0 {
+        bail!("Page size must be greater than 0");
+    }
+
+    let total_pages = items.len().div_ceil(size).max(1);
+    if page > total_pages {
+        bail!(
+            "Page {} is out of range ({} page{})",
+            page,
+            total_pages,
+            if total_pages == 1 { "" } else { "s" }
+        );
+    }
+
+    let start = (page - 1) * size;
+    let end = (start + size).min(items.len());
+    Ok(Page {
+        items: &items[start..end],
+        number: page,
+        total_pages,
+        total_items: items.len(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_paginate_boundaries() {
+        let items: Vec<u32> = (1..=10).collect();
+
+        let first = paginate(&items, 1, 4).unwrap();
+        assert_eq!(first.items, &[1, 2, 3, 4]);
+        assert_eq!(first.total_pages, 3);
+
+        let last = paginate(&items, 3, 4).unwrap();
+        assert_eq!(last.items, &[9, 10]);
+        assert_eq!(last.footer(), "Page 3 of 3 (10 total)");
+
+        let exact = paginate(&items, 2, 5).unwrap();
+        assert_eq!(exact.items, &[6, 7, 8, 9, 10]);
+        assert_eq!(exact.total_pages, 2);
+
+        assert!(paginate(&items, 4, 4).is_err());
+        assert!(paginate(&items, 0, 4).is_err());
+        assert!(paginate(&items, 1, 0).is_err());
+    }
+
+    #[test]
+    fn test_paginate_empty() {
+        let items: Vec<u32> = Vec::new();
+        let page = paginate(&items, 1, 10).unwrap();
+        assert!(page.items.is_empty());
+        assert_eq!(page.total_pages, 1);
+        assert!(paginate(&items, 2, 10).is_err());
+    }
+}
@@ -0,0 +1,106 @@
+//! Destination for formatted command output
+
+use anyhow::Context;
+use std::fmt::Display;
+use std::fs::File;
+use std::io::{self, Write};
+use std::path::Path;
+use std::sync::Mutex;
+
+static OUTPUT_FILE: Mutex<Option<File>> = Mutex::new(None);
+static CAPTURE: Mutex<Option<Vec<u8>>> = Mutex::new(None);
+
+/// Redirect formatted output to `path`, creating parent directories as needed
+pub fn set_output_file(path: &Path) -> anyhow::Result<()> {
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create directory {}", parent.display()))?;
+    }
+
+    let file = File::create(path)
+        .with_context(|| format!("Failed to open output file {}", path.display()))?;
+    *OUTPUT_FILE.lock().unwrap_or_else(|e| e.into_inner()) = Some(file);
+    Ok(())
+}
+
+/// Collect formatted output in memory until `take_capture` is called
+pub fn start_capture() {
+    *CAPTURE.lock().unwrap_or_else(|e| e.into_inner()) = Some(Vec::new());
+}
+
+/// Stop capturing and return what was written since `start_capture`
+pub fn take_capture() -> String {
+    let captured = CAPTURE.lock().unwrap_or_else(|e| e.into_inner()).take();
+    String::from_utf8_lossy(&captured.unwrap_or_default()).into_owned()
+}
+
+/// Whether formatted output ends up on a terminal: stdout is one and no
+/// `--output-file` is set
+pub fn is_terminal() -> bool {
+    use std::io::IsTerminal;
+
+    OUTPUT_FILE.lock().unwrap_or_else(|e| e.into_inner()).is_none() && io::stdout().is_terminal()
+}
+
+/// Writer for formatted output: the capture buffer, the `--output-file` target, or stdout
+pub fn writer() -> Box<dyn Write> {
+    Box::new(OutputSink)
+}
+
+/// Write a line of formatted output
+pub fn emit(value: impl Display) -> anyhow::Result<()> {
+    writeln!(writer(), "{}", value)?;
+    Ok(())
+}
+
+struct OutputSink;
+
+impl Write for OutputSink {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if let Some(captured) = CAPTURE.lock().unwrap_or_else(|e| e.into_inner()).as_mut() {
+            captured.extend_from_slice(buf);
+            return Ok(buf.len());
+        }
+        match OUTPUT_FILE.lock().unwrap_or_else(|e| e.into_inner()).as_mut() {
+            Some(file) => file.write(buf),
+            None => io::stdout().write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if CAPTURE.lock().unwrap_or_else(|e| e.into_inner()).is_some() {
+            return Ok(());
+        }
+        match OUTPUT_FILE.lock().unwrap_or_else(|e| e.into_inner()).as_mut() {
+            Some(file) => file.flush(),
+            None => io::stdout().flush(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_output_file_creates_parent_dirs() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("nested").join("orders.json");
+
+        let _guard = crate::output::lock_output_state();
+        set_output_file(&path).unwrap();
+        let terminal = is_terminal();
+        emit("[]").unwrap();
+        *OUTPUT_FILE.lock().unwrap() = None;
+
+        assert!(!terminal);
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "[]\n");
+    }
+
+    #[test]
+    fn test_output_file_open_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let err = set_output_file(dir.path()).unwrap_err();
+        assert!(err.to_string().contains("Failed to open output file"));
+    }
+}
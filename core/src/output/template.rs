@@ -0,0 +1,113 @@
+//! `--template` output: one line per record with `{field}` placeholders
+//!
+//! Fields are looked up in the record's JSON form, so they use the same names
+//! as `-o json` (`{tradingsymbol}`, `{last_price}`); `{a.b}` reaches into
+//! nested objects.
+
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::BTreeSet;
+use std::io::Write;
+use std::sync::RwLock;
+
+static TEMPLATE: RwLock<Option<String>> = RwLock::new(None);
+
+/// Render list output through `template` instead of `-o` (`--template`)
+pub fn set_template(template: Option<String>) {
+    *TEMPLATE.write().unwrap_or_else(|e| e.into_inner()) = template;
+}
+
+/// Template set by `--template`, if any
+pub fn template() -> Option<String> {
+    TEMPLATE.read().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+/// Write each record of `value` (or `value` itself if it isn't a list) as one
+/// line of `template`
+///
+/// Unknown fields render empty, with one warning per field on stderr.
+pub fn write_template<T: Serialize + ?Sized>(
+    value: &T,
+    template: &str,
+    w: &mut dyn Write,
+) -> anyhow::Result<()> {
+    let value = serde_json::to_value(value)?;
+    let records = match &value {
+        Value::Array(items) => items.iter().collect(),
+        other => vec![other],
+    };
+
+    let mut unknown = BTreeSet::new();
+    for record in records {
+        writeln!(w, "{}", render(record, template, &mut unknown))?;
+    }
+    for field in unknown {
+        super::notice(format_args!("Warning: unknown template field '{}'", field));
+    }
+    Ok(())
+}
+
+/// Fill `{field}` placeholders from `record`, collecting fields it doesn't have
+fn render(record: &Value, template: &str, unknown: &mut BTreeSet<String>) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(start) = rest.find('{') {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let Some(end) = after.find('}') else {
+            // An unclosed brace is kept as text
+            out.push_str(&rest[start..]);
+            return out;
+        };
+
+        let field = after[..end].trim();
+        match lookup(record, field) {
+            Some(value) => out.push_str(&plain(value)),
+            None => {
+                unknown.insert(field.to_string());
+            }
+        }
+        rest = &after[end + 1..];
+    }
+
+    out.push_str(rest);
+    out
+}
+
+fn lookup<'a>(record: &'a Value, field: &str) -> Option<&'a Value> {
+    field
+        .split('.')
+        .try_fold(record, |value, key| value.get(key))
+}
+
+/// Strings without quotes, null as nothing, anything else as compact JSON
+fn plain(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Null => String::new(),
+        other => other.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_render_fields_and_unknowns() {
+        let record = json!({"symbol": "INFY", "qty": 10, "meta": {"tag": "swing"}, "note": null});
+        let mut unknown = BTreeSet::new();
+
+        assert_eq!(
+            render(&record, "{symbol} x{qty} [{meta.tag}] {note}|{ltp}", &mut unknown),
+            "INFY x10 [swing] |"
+        );
+        assert_eq!(unknown.into_iter().collect::<Vec<_>>(), ["ltp"]);
+
+        let mut unknown = BTreeSet::new();
+        assert_eq!(render(&record, "{symbol} {oops", &mut unknown), "INFY {oops");
+        assert!(unknown.is_empty());
+    }
+}
@@ -0,0 +1,184 @@
+//! Table colors, configurable under `[output.theme]`
+
+use crate::config::ThemeConfig;
+use anyhow::{bail, Context, Result};
+use comfy_table::{Cell, Color};
+use std::sync::RwLock;
+
+static THEME: RwLock<Theme> = RwLock::new(Theme::DEFAULT);
+
+/// Colors for the roles table cells play
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Theme {
+    /// Gains, completed orders, open markets
+    pub positive: Color,
+    /// Losses, rejected or cancelled orders
+    pub negative: Color,
+    /// Open, triggered or otherwise waiting states
+    pub pending: Color,
+    /// Table headers; `None` leaves them uncolored
+    pub header: Option<Color>,
+}
+
+impl Theme {
+    pub const DEFAULT: Theme = Theme {
+        positive: Color::Green,
+        negative: Color::Red,
+        pending: Color::Yellow,
+        header: None,
+    };
+
+    /// Saturated colors and white headers for low-contrast terminals
+    pub const HIGH_CONTRAST: Theme = Theme {
+        positive: Color::Rgb { r: 0, g: 255, b: 0 },
+        negative: Color::Rgb { r: 255, g: 64, b: 64 },
+        pending: Color::Rgb { r: 255, g: 255, b: 0 },
+        header: Some(Color::White),
+    };
+
+    /// Okabe-Ito blue/vermillion, distinguishable with red-green color blindness
+    pub const COLORBLIND: Theme = Theme {
+        positive: Color::Rgb { r: 0, g: 114, b: 178 },
+        negative: Color::Rgb { r: 213, g: 94, b: 0 },
+        pending: Color::Rgb { r: 240, g: 228, b: 66 },
+        header: None,
+    };
+
+    /// Start from the configured preset and apply individual overrides
+    pub fn from_config(config: &ThemeConfig) -> Result<Self> {
+        let mut theme = match config.preset.as_deref().map(str::to_lowercase).as_deref() {
+            None | Some("default") => Self::DEFAULT,
+            Some("high-contrast") => Self::HIGH_CONTRAST,
+            Some("colorblind") => Self::COLORBLIND,
+            Some(other) => bail!(
+                "Unknown theme preset '{}'. Use default, high-contrast or colorblind",
+                other
+            ),
+        };
+
+        if let Some(ref color) = config.positive {
+            theme.positive = parse_color(color).context("Invalid theme color for positive")?;
+        }
+        if let Some(ref color) = config.negative {
+            theme.negative = parse_color(color).context("Invalid theme color for negative")?;
+        }
+        if let Some(ref color) = config.pending {
+            theme.pending = parse_color(color).context("Invalid theme color for pending")?;
+        }
+        if let Some(ref color) = config.header {
+            theme.header = Some(parse_color(color).context("Invalid theme color for header")?);
+        }
+
+        Ok(theme)
+    }
+}
+
+/// Use `theme` for every table from now on
+pub fn set_theme(theme: Theme) {
+    *THEME.write().unwrap_or_else(|e| e.into_inner()) = theme;
+}
+
+/// The theme tables are colored with
+pub fn theme() -> Theme {
+    *THEME.read().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Header cells in the theme's header color (plain when color is off)
+pub fn header<T: ToString>(names: impl IntoIterator<Item = T>) -> Vec<Cell> {
+    use super::CellColorExt;
+
+    let color = theme().header;
+    names
+        .into_iter()
+        .map(|name| {
+            let cell = Cell::new(name.to_string());
+            match color {
+                Some(color) => cell.color(color),
+                None => cell,
+            }
+        })
+        .collect()
+}
+
+/// Parse a color name (`green`, `dark_red`, ...) or `#rrggbb`
+fn parse_color(s: &str) -> Result<Color> {
+    let lower = s.trim().to_lowercase().replace('-', "_");
+    if let Some(hex) = lower.strip_prefix('#') {
+        let channel = |i: usize| {
+            hex.get(i..i + 2)
+                .and_then(|c| u8::from_str_radix(c, 16).ok())
+        };
+        return match (hex.len(), channel(0), channel(2), channel(4)) {
+            (6, Some(r), Some(g), Some(b)) => Ok(Color::Rgb { r, g, b }),
+            _ => bail!("'{}' is not a #rrggbb color", s),
+        };
+    }
+
+    Ok(match lower.as_str() {
+        "black" => Color::Black,
+        "red" => Color::Red,
+        "dark_red" => Color::DarkRed,
+        "green" => Color::Green,
+        "dark_green" => Color::DarkGreen,
+        "yellow" => Color::Yellow,
+        "dark_yellow" => Color::DarkYellow,
+        "blue" => Color::Blue,
+        "dark_blue" => Color::DarkBlue,
+        "magenta" => Color::Magenta,
+        "dark_magenta" => Color::DarkMagenta,
+        "cyan" => Color::Cyan,
+        "dark_cyan" => Color::DarkCyan,
+        "white" => Color::White,
+        "grey" | "gray" => Color::Grey,
+        "dark_grey" | "dark_gray" => Color::DarkGrey,
+        _ => bail!("Unknown color '{}'. Use a name like green or dark_red, or #rrggbb", s),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(preset: Option<&str>) -> ThemeConfig {
+        ThemeConfig {
+            preset: preset.map(str::to_string),
+            ..ThemeConfig::default()
+        }
+    }
+
+    #[test]
+    fn test_presets_map_to_colors() {
+        assert_eq!(Theme::from_config(&config(None)).unwrap(), Theme::DEFAULT);
+        assert_eq!(Theme::DEFAULT.positive, Color::Green);
+        assert_eq!(Theme::DEFAULT.negative, Color::Red);
+
+        let colorblind = Theme::from_config(&config(Some("colorblind"))).unwrap();
+        assert_eq!(colorblind.positive, Color::Rgb { r: 0, g: 114, b: 178 });
+        assert_eq!(colorblind.negative, Color::Rgb { r: 213, g: 94, b: 0 });
+
+        let high_contrast = Theme::from_config(&config(Some("High-Contrast"))).unwrap();
+        assert_eq!(high_contrast.header, Some(Color::White));
+
+        assert!(Theme::from_config(&config(Some("neon"))).is_err());
+    }
+
+    #[test]
+    fn test_overrides_apply_on_top_of_preset() {
+        let theme = Theme::from_config(&ThemeConfig {
+            preset: Some("colorblind".to_string()),
+            pending: Some("dark-cyan".to_string()),
+            header: Some("#FF8000".to_string()),
+            ..ThemeConfig::default()
+        })
+        .unwrap();
+        assert_eq!(theme.positive, Theme::COLORBLIND.positive);
+        assert_eq!(theme.pending, Color::DarkCyan);
+        assert_eq!(theme.header, Some(Color::Rgb { r: 255, g: 128, b: 0 }));
+
+        let bad = ThemeConfig {
+            negative: Some("#12345".to_string()),
+            ..ThemeConfig::default()
+        };
+        assert!(Theme::from_config(&bad).is_err());
+    }
+}
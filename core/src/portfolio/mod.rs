@@ -0,0 +1,249 @@
+//! Portfolio aggregation
+
+use crate::models::{Holding, Position, PositionsResponse};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// Account-wide totals across holdings and net positions
+///
+/// Invested and current value cover holdings only; open positions contribute
+/// to P&L.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PortfolioSummary {
+    pub holdings_count: usize,
+    pub positions_count: usize,
+    pub invested_value: f64,
+    pub current_value: f64,
+    pub total_pnl: f64,
+    pub day_pnl: f64,
+    pub realised_pnl: f64,
+    pub unrealised_pnl: f64,
+}
+
+impl PortfolioSummary {
+    pub fn new(holdings: &[Holding], positions: &PositionsResponse) -> Self {
+        let mut summary = Self {
+            holdings_count: holdings.len(),
+            positions_count: positions.net.len(),
+            ..Self::default()
+        };
+
+        for holding in holdings {
+            let quantity = f64::from(holding.quantity);
+            summary.invested_value += holding.average_price * quantity;
+            summary.current_value += holding.last_price * quantity;
+            summary.unrealised_pnl += holding.pnl;
+            summary.day_pnl += holding.day_change * quantity;
+        }
+
+        for position in &positions.net {
+            summary.realised_pnl += position.realised;
+            summary.unrealised_pnl += position.unrealised;
+            summary.day_pnl += position.m2m;
+        }
+
+        summary.total_pnl = summary.realised_pnl + summary.unrealised_pnl;
+        summary
+    }
+
+    /// Holdings P&L as a percentage of invested value
+    pub fn holdings_return_percentage(&self) -> f64 {
+        if self.invested_value == 0.0 {
+            0.0
+        } else {
+            (self.current_value - self.invested_value) / self.invested_value * 100.0
+        }
+    }
+}
+
+/// P&L for one instrument across holdings and net positions
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SymbolPnl {
+    pub exchange: String,
+    pub tradingsymbol: String,
+    pub holding_quantity: i32,
+    pub position_quantity: i32,
+    pub day_pnl: f64,
+    pub realised_pnl: f64,
+    pub unrealised_pnl: f64,
+    pub total_pnl: f64,
+}
+
+/// Merge holdings and net positions by `(exchange, tradingsymbol)`
+///
+/// Symbols held in only one book still get a row. Rows are sorted by total
+/// P&L, best first.
+pub fn pnl_by_symbol(holdings: &[Holding], positions: &[Position]) -> Vec<SymbolPnl> {
+    let mut rows: BTreeMap<(String, String), SymbolPnl> = BTreeMap::new();
+
+    for holding in holdings {
+        let entry = symbol_row(&mut rows, holding.exchange.to_string(), &holding.tradingsymbol);
+        entry.holding_quantity += holding.quantity;
+        entry.unrealised_pnl += holding.pnl;
+        entry.day_pnl += holding.day_change * f64::from(holding.quantity);
+    }
+
+    for position in positions {
+        let entry = symbol_row(&mut rows, position.exchange.to_string(), &position.tradingsymbol);
+        entry.position_quantity += position.quantity;
+        entry.realised_pnl += position.realised;
+        entry.unrealised_pnl += position.unrealised;
+        entry.day_pnl += position.m2m;
+    }
+
+    let mut rows: Vec<SymbolPnl> = rows
+        .into_values()
+        .map(|mut row| {
+            row.total_pnl = row.realised_pnl + row.unrealised_pnl;
+            row
+        })
+        .collect();
+    // Stable sort keeps the (exchange, symbol) order for equal totals
+    rows.sort_by(|a, b| b.total_pnl.total_cmp(&a.total_pnl));
+    rows
+}
+
+fn symbol_row<'a>(
+    rows: &'a mut BTreeMap<(String, String), SymbolPnl>,
+    exchange: String,
+    tradingsymbol: &str,
+) -> &'a mut SymbolPnl {
+    rows.entry((exchange.clone(), tradingsymbol.to_string()))
+        .or_insert_with(|| SymbolPnl {
+            exchange,
+            tradingsymbol: tradingsymbol.to_string(),
+            ..SymbolPnl::default()
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Exchange, Position};
+
+    fn holding(symbol: &str, quantity: i32, average: f64, last: f64, day_change: f64) -> Holding {
+        Holding {
+            tradingsymbol: symbol.to_string(),
+            exchange: Exchange::NSE,
+            instrument_token: 1,
+            isin: String::new(),
+            quantity,
+            authorised_quantity: 0,
+            average_price: average,
+            last_price: last,
+            close_price: last - day_change,
+            pnl: (last - average) * f64::from(quantity),
+            day_change,
+            day_change_percentage: 0.0,
+        }
+    }
+
+    fn position(realised: f64, unrealised: f64, m2m: f64) -> Position {
+        symbol_position("NFO", "NIFTY24JUNFUT", realised, unrealised, m2m)
+    }
+
+    fn symbol_position(
+        exchange: &str,
+        symbol: &str,
+        realised: f64,
+        unrealised: f64,
+        m2m: f64,
+    ) -> Position {
+        serde_json::from_value(serde_json::json!({
+            "tradingsymbol": symbol,
+            "exchange": exchange,
+            "instrument_token": 2,
+            "product": "NRML",
+            "quantity": 25,
+            "average_price": 22000.0,
+            "close_price": 22000.0,
+            "last_price": 22010.0,
+            "pnl": realised + unrealised,
+            "realised": realised,
+            "unrealised": unrealised,
+            "m2m": m2m,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_summary_with_mixed_pnl() {
+        let holdings = vec![
+            holding("INFY", 10, 1400.0, 1500.0, 10.0),
+            holding("TCS", 5, 4000.0, 3800.0, -20.0),
+        ];
+        let positions = PositionsResponse {
+            net: vec![position(500.0, -250.0, -100.0), position(-200.0, 0.0, 50.0)],
+            day: Vec::new(),
+        };
+
+        let summary = PortfolioSummary::new(&holdings, &positions);
+
+        assert_eq!(summary.holdings_count, 2);
+        assert_eq!(summary.positions_count, 2);
+        assert_eq!(summary.invested_value, 34_000.0);
+        assert_eq!(summary.current_value, 34_000.0);
+        // Holdings: +1000 and -1000; positions: -250
+        assert_eq!(summary.unrealised_pnl, -250.0);
+        assert_eq!(summary.realised_pnl, 300.0);
+        assert_eq!(summary.total_pnl, 50.0);
+        // Holdings: +100 and -100; positions: -100 and +50
+        assert_eq!(summary.day_pnl, -50.0);
+        assert_eq!(summary.holdings_return_percentage(), 0.0);
+    }
+
+    #[test]
+    fn test_empty_summary() {
+        let positions = PositionsResponse {
+            net: Vec::new(),
+            day: Vec::new(),
+        };
+        let summary = PortfolioSummary::new(&[], &positions);
+
+        assert_eq!(summary.total_pnl, 0.0);
+        assert_eq!(summary.holdings_return_percentage(), 0.0);
+    }
+
+    #[test]
+    fn test_pnl_by_symbol_merges_books() {
+        let holdings = vec![
+            holding("INFY", 10, 1400.0, 1500.0, 10.0),
+            holding("TCS", 5, 4000.0, 3800.0, -20.0),
+        ];
+        let positions = vec![
+            symbol_position("NSE", "INFY", 300.0, -50.0, 40.0),
+            symbol_position("NFO", "NIFTY24JUNFUT", -200.0, 0.0, 50.0),
+            // Same symbol on another exchange is a separate row
+            symbol_position("BSE", "INFY", 0.0, 20.0, 20.0),
+        ];
+
+        let rows = pnl_by_symbol(&holdings, &positions);
+        let keys: Vec<_> = rows
+            .iter()
+            .map(|r| format!("{}:{}", r.exchange, r.tradingsymbol))
+            .collect();
+        assert_eq!(keys, ["NSE:INFY", "BSE:INFY", "NFO:NIFTY24JUNFUT", "NSE:TCS"]);
+
+        let infy = &rows[0];
+        assert_eq!(infy.holding_quantity, 10);
+        assert_eq!(infy.position_quantity, 25);
+        assert_eq!(infy.realised_pnl, 300.0);
+        assert_eq!(infy.unrealised_pnl, 950.0);
+        assert_eq!(infy.total_pnl, 1250.0);
+        assert_eq!(infy.day_pnl, 140.0);
+
+        // Holding-only and position-only rows
+        let tcs = &rows[3];
+        assert_eq!((tcs.holding_quantity, tcs.position_quantity), (5, 0));
+        assert_eq!(tcs.total_pnl, -1000.0);
+        assert_eq!(tcs.day_pnl, -100.0);
+        let future = &rows[2];
+        assert_eq!((future.holding_quantity, future.position_quantity), (0, 25));
+        assert_eq!(future.total_pnl, -200.0);
+    }
+
+    #[test]
+    fn test_pnl_by_symbol_empty() {
+        assert!(pnl_by_symbol(&[], &[]).is_empty());
+    }
+}
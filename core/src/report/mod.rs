@@ -0,0 +1,614 @@
+//! Realized capital gains report
+//!
+//! Sells are matched against earlier buys first-in-first-out per symbol. The
+//! Kite API only returns the current day's trades, so the trade history comes
+//! from a tradebook exported from Console (see [`read_tradebook`]).
+
+use crate::models::{timestamp, Exchange, Holding, Product, Trade, TransactionType};
+use anyhow::{bail, Context, Result};
+use chrono::{Months, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::io::Read;
+use std::str::FromStr;
+
+/// Indian financial year, 1 April to 31 March
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FinancialYear {
+    /// Calendar year in which the financial year starts
+    pub start_year: i32,
+}
+
+impl FinancialYear {
+    pub fn start(&self) -> NaiveDate {
+        NaiveDate::from_ymd_opt(self.start_year, 4, 1).expect("valid FY start")
+    }
+
+    pub fn end(&self) -> NaiveDate {
+        NaiveDate::from_ymd_opt(self.start_year + 1, 3, 31).expect("valid FY end")
+    }
+
+    pub fn contains(&self, date: NaiveDate) -> bool {
+        date >= self.start() && date <= self.end()
+    }
+}
+
+impl FromStr for FinancialYear {
+    type Err = anyhow::Error;
+
+    /// Accepts `fy2023` or `fy2023-24` for April 2023 to March 2024
+    fn from_str(s: &str) -> Result<Self> {
+        let lower = s.trim().to_lowercase();
+        let Some(rest) = lower.strip_prefix("fy") else {
+            bail!(
+                "Invalid financial year '{}'. Expected e.g. fy2023 or fy2023-24",
+                s
+            );
+        };
+        let (start, end) = match rest.split_once('-') {
+            Some((start, end)) => (start, Some(end)),
+            None => (rest, None),
+        };
+
+        let start_year: i32 = start
+            .parse()
+            .ok()
+            .filter(|year| (1900..=9999).contains(year))
+            .with_context(|| format!("Invalid financial year '{}'. Expected e.g. fy2023", s))?;
+
+        if let Some(end) = end {
+            let expected = format!("{:02}", (start_year + 1) % 100);
+            if end != expected && end != (start_year + 1).to_string() {
+                bail!(
+                    "Invalid financial year '{}'. FY{} ends in {}",
+                    s,
+                    start_year,
+                    start_year + 1
+                );
+            }
+        }
+
+        Ok(FinancialYear { start_year })
+    }
+}
+
+/// Capital gains holding period classification
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum Term {
+    #[serde(rename = "short")]
+    Short,
+    #[serde(rename = "long")]
+    Long,
+}
+
+impl Term {
+    /// Long-term when held for more than one year
+    pub fn classify(buy_date: NaiveDate, sell_date: NaiveDate) -> Self {
+        match buy_date.checked_add_months(Months::new(12)) {
+            Some(one_year) if sell_date > one_year => Term::Long,
+            _ => Term::Short,
+        }
+    }
+}
+
+/// One matched buy/sell lot
+#[derive(Debug, Clone, Serialize)]
+pub struct RealizedGain {
+    pub symbol: String,
+    pub isin: String,
+    pub buy_date: NaiveDate,
+    pub sell_date: NaiveDate,
+    pub quantity: i32,
+    pub buy_value: f64,
+    pub sell_value: f64,
+    pub gain: f64,
+    pub term: Term,
+}
+
+/// Sell quantity with no earlier buy in the supplied trades
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnmatchedSell {
+    pub symbol: String,
+    pub sell_date: NaiveDate,
+    pub quantity: i32,
+}
+
+/// Realized gains for one financial year
+#[derive(Debug, Clone, Default)]
+pub struct TaxReport {
+    pub gains: Vec<RealizedGain>,
+    pub unmatched: Vec<UnmatchedSell>,
+}
+
+impl TaxReport {
+    /// Match trades FIFO and keep sells that fall within `year`
+    ///
+    /// Holdings supply ISINs for symbols that have them.
+    pub fn build(trades: &[Trade], holdings: &[Holding], year: FinancialYear) -> Self {
+        let isins: HashMap<&str, &str> = holdings
+            .iter()
+            .map(|h| (h.tradingsymbol.as_str(), h.isin.as_str()))
+            .collect();
+
+        let (gains, unmatched) = match_fifo(trades);
+
+        TaxReport {
+            gains: gains
+                .into_iter()
+                .filter(|gain| year.contains(gain.sell_date))
+                .map(|mut gain| {
+                    gain.isin = isins.get(gain.symbol.as_str()).unwrap_or(&"").to_string();
+                    gain
+                })
+                .collect(),
+            unmatched: unmatched
+                .into_iter()
+                .filter(|sell| year.contains(sell.sell_date))
+                .collect(),
+        }
+    }
+
+    pub fn total_gain(&self, term: Term) -> f64 {
+        self.gains
+            .iter()
+            .filter(|gain| gain.term == term)
+            .map(|gain| gain.gain)
+            .sum()
+    }
+}
+
+/// One row of a Console tradebook export
+#[derive(Debug, Deserialize)]
+struct TradebookRow {
+    symbol: String,
+    trade_date: NaiveDate,
+    exchange: Exchange,
+    trade_type: String,
+    quantity: f64,
+    price: f64,
+    trade_id: String,
+    order_id: String,
+    #[serde(default)]
+    order_execution_time: String,
+}
+
+/// Read trades from a Console tradebook CSV export
+///
+/// Expects Console's column names (`symbol`, `trade_date`, `exchange`,
+/// `trade_type`, `quantity`, `price`, `trade_id`, `order_id`, and optionally
+/// `order_execution_time`); other columns are ignored.
+pub fn read_tradebook(reader: impl Read) -> Result<Vec<Trade>> {
+    let mut rows = csv::Reader::from_reader(reader);
+    let mut trades = Vec::new();
+
+    for (i, row) in rows.deserialize::<TradebookRow>().enumerate() {
+        // Line 1 is the header
+        let line = i + 2;
+        let row = row.with_context(|| format!("Invalid tradebook row on line {}", line))?;
+        let transaction_type = match row.trade_type.trim().to_lowercase().as_str() {
+            "buy" => TransactionType::Buy,
+            "sell" => TransactionType::Sell,
+            other => bail!("Invalid trade_type '{}' on line {}", other, line),
+        };
+        let fill_timestamp = timestamp::parse(&row.order_execution_time)
+            .unwrap_or_else(|| row.trade_date.and_hms_opt(0, 0, 0).expect("valid midnight"));
+
+        trades.push(Trade {
+            trade_id: row.trade_id,
+            order_id: row.order_id,
+            exchange_order_id: None,
+            tradingsymbol: row.symbol,
+            exchange: row.exchange,
+            transaction_type,
+            product: Product::CNC,
+            average_price: row.price,
+            quantity: row.quantity.round() as i32,
+            fill_timestamp,
+            trade_timestamp: None,
+        });
+    }
+
+    Ok(trades)
+}
+
+struct Lot {
+    date: NaiveDate,
+    quantity: i32,
+    price: f64,
+}
+
+/// Pair every sell with the oldest open buys of the same symbol
+fn match_fifo(trades: &[Trade]) -> (Vec<RealizedGain>, Vec<UnmatchedSell>) {
+    let mut sorted: Vec<&Trade> = trades.iter().collect();
+    sorted.sort_by_key(|trade| trade.fill_timestamp);
+
+    let mut lots: BTreeMap<&str, VecDeque<Lot>> = BTreeMap::new();
+    let mut gains = Vec::new();
+    let mut unmatched = Vec::new();
+
+    for trade in sorted {
+        let date = trade.fill_timestamp.date();
+        let open = lots.entry(trade.tradingsymbol.as_str()).or_default();
+
+        match trade.transaction_type {
+            TransactionType::Buy => open.push_back(Lot {
+                date,
+                quantity: trade.quantity,
+                price: trade.average_price,
+            }),
+            TransactionType::Sell => {
+                let mut remaining = trade.quantity;
+                while remaining > 0 {
+                    let Some(lot) = open.front_mut() else {
+                        break;
+                    };
+                    let quantity = remaining.min(lot.quantity);
+                    let buy_value = lot.price * f64::from(quantity);
+                    let sell_value = trade.average_price * f64::from(quantity);
+
+                    gains.push(RealizedGain {
+                        symbol: trade.tradingsymbol.clone(),
+                        isin: String::new(),
+                        buy_date: lot.date,
+                        sell_date: date,
+                        quantity,
+                        buy_value,
+                        sell_value,
+                        gain: sell_value - buy_value,
+                        term: Term::classify(lot.date, date),
+                    });
+
+                    remaining -= quantity;
+                    lot.quantity -= quantity;
+                    if lot.quantity == 0 {
+                        open.pop_front();
+                    }
+                }
+
+                if remaining > 0 {
+                    unmatched.push(UnmatchedSell {
+                        symbol: trade.tradingsymbol.clone(),
+                        sell_date: date,
+                        quantity: remaining,
+                    });
+                }
+            }
+        }
+    }
+
+    (gains, unmatched)
+}
+
+/// Open quantity at one price; positive for longs, negative for shorts
+struct OpenLot {
+    quantity: i32,
+    price: f64,
+}
+
+/// Realized P&L of each trade that closes part of a position, by trade ID
+///
+/// Trades are matched FIFO per symbol in fill order, so intraday shorts are
+/// covered as well. A trade closing lots at several prices gets the summed
+/// (quantity-weighted) P&L; trades that only open a position are absent.
+pub fn realized_pnl_by_trade(trades: &[Trade]) -> HashMap<String, f64> {
+    let mut sorted: Vec<&Trade> = trades.iter().collect();
+    sorted.sort_by_key(|trade| trade.fill_timestamp);
+
+    let mut lots: HashMap<&str, VecDeque<OpenLot>> = HashMap::new();
+    let mut pnl = HashMap::new();
+
+    for trade in sorted {
+        let side = match trade.transaction_type {
+            TransactionType::Buy => 1,
+            TransactionType::Sell => -1,
+        };
+        let open = lots.entry(trade.tradingsymbol.as_str()).or_default();
+        let mut remaining = trade.quantity;
+        let mut realized = None;
+
+        while remaining > 0 {
+            // Lots on the same side as this trade only grow the position
+            let Some(lot) = open.front_mut().filter(|lot| lot.quantity.signum() == -side) else {
+                break;
+            };
+            let quantity = remaining.min(lot.quantity.abs());
+            let gain = (trade.average_price - lot.price) * f64::from(quantity) * f64::from(-side);
+            *realized.get_or_insert(0.0) += gain;
+
+            remaining -= quantity;
+            lot.quantity += side * quantity;
+            if lot.quantity == 0 {
+                open.pop_front();
+            }
+        }
+
+        if remaining > 0 {
+            open.push_back(OpenLot {
+                quantity: side * remaining,
+                price: trade.average_price,
+            });
+        }
+        if let Some(realized) = realized {
+            pnl.insert(trade.trade_id.clone(), realized);
+        }
+    }
+
+    pnl
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Exchange, Product};
+
+    fn trade(symbol: &str, side: TransactionType, quantity: i32, price: f64, date: &str) -> Trade {
+        Trade {
+            trade_id: format!("{}-{}", symbol, date),
+            order_id: "1".to_string(),
+            exchange_order_id: None,
+            tradingsymbol: symbol.to_string(),
+            exchange: Exchange::NSE,
+            transaction_type: side,
+            product: Product::CNC,
+            average_price: price,
+            quantity,
+            fill_timestamp: crate::models::timestamp::parse(&format!("{} 10:00:00", date)).unwrap(),
+            trade_timestamp: None,
+        }
+    }
+
+    fn date(s: &str) -> NaiveDate {
+        s.parse().unwrap()
+    }
+
+    fn fy(start_year: i32) -> FinancialYear {
+        FinancialYear { start_year }
+    }
+
+    #[test]
+    fn test_parse_financial_year() {
+        assert_eq!("fy2023".parse::<FinancialYear>().unwrap(), fy(2023));
+        assert_eq!("FY2023-24".parse::<FinancialYear>().unwrap(), fy(2023));
+        assert_eq!("fy2023-2024".parse::<FinancialYear>().unwrap(), fy(2023));
+        assert!("fy2023-25".parse::<FinancialYear>().is_err());
+        assert!("2023".parse::<FinancialYear>().is_err());
+        assert!("fyabc".parse::<FinancialYear>().is_err());
+    }
+
+    #[test]
+    fn test_read_tradebook() {
+        let csv = "\
+symbol,isin,trade_date,exchange,segment,series,trade_type,auction,quantity,price,trade_id,order_id,order_execution_time
+INFY,INE009A01021,2023-04-03,NSE,EQ,EQ,buy,false,10.000000,1400.50,11,21,2023-04-03T09:15:23
+INFY,INE009A01021,2024-01-10,NSE,EQ,EQ,SELL,false,10.000000,1600.00,12,22,
+";
+        let trades = read_tradebook(csv.as_bytes()).unwrap();
+        assert_eq!(trades.len(), 2);
+        assert_eq!(trades[0].quantity, 10);
+        assert_eq!(trades[0].average_price, 1400.50);
+        assert_eq!(trades[0].fill_timestamp.to_string(), "2023-04-03 09:15:23");
+        assert!(matches!(trades[1].transaction_type, TransactionType::Sell));
+        assert_eq!(trades[1].fill_timestamp.date(), date("2024-01-10"));
+
+        let report = TaxReport::build(&trades, &[], fy(2023));
+        assert_eq!(report.total_gain(Term::Short), 1995.0);
+
+        let bad = "symbol,trade_date,exchange,trade_type,quantity,price,trade_id,order_id\n\
+                   INFY,2023-04-03,NSE,hold,1,1,1,1\n";
+        let err = read_tradebook(bad.as_bytes()).unwrap_err();
+        assert!(err.to_string().contains("line 2"));
+    }
+
+    #[test]
+    fn test_financial_year_bounds() {
+        let year = fy(2023);
+        assert!(year.contains(date("2023-04-01")));
+        assert!(year.contains(date("2024-03-31")));
+        assert!(!year.contains(date("2023-03-31")));
+        assert!(!year.contains(date("2024-04-01")));
+    }
+
+    #[test]
+    fn test_term_one_year_rule() {
+        assert_eq!(
+            Term::classify(date("2022-06-15"), date("2023-06-15")),
+            Term::Short
+        );
+        assert_eq!(
+            Term::classify(date("2022-06-15"), date("2023-06-16")),
+            Term::Long
+        );
+        assert_eq!(
+            Term::classify(date("2023-01-01"), date("2023-02-01")),
+            Term::Short
+        );
+    }
+
+    #[test]
+    fn test_fifo_splits_across_lots() {
+        let trades = vec![
+            trade("INFY", TransactionType::Buy, 10, 1000.0, "2022-05-01"),
+            trade("INFY", TransactionType::Buy, 10, 1200.0, "2023-06-01"),
+            trade("INFY", TransactionType::Sell, 15, 1500.0, "2023-08-01"),
+        ];
+
+        let report = TaxReport::build(&trades, &[], fy(2023));
+
+        assert_eq!(report.gains.len(), 2);
+        let first = &report.gains[0];
+        assert_eq!(first.quantity, 10);
+        assert_eq!(first.buy_date, date("2022-05-01"));
+        assert_eq!(first.gain, 5000.0);
+        assert_eq!(first.term, Term::Long);
+
+        let second = &report.gains[1];
+        assert_eq!(second.quantity, 5);
+        assert_eq!(second.buy_value, 6000.0);
+        assert_eq!(second.sell_value, 7500.0);
+        assert_eq!(second.term, Term::Short);
+
+        assert_eq!(report.total_gain(Term::Long), 5000.0);
+        assert_eq!(report.total_gain(Term::Short), 1500.0);
+    }
+
+    #[test]
+    fn test_losses_and_multiple_symbols() {
+        let trades = vec![
+            trade("TCS", TransactionType::Buy, 5, 4000.0, "2023-04-10"),
+            trade("INFY", TransactionType::Buy, 2, 1500.0, "2023-04-10"),
+            trade("TCS", TransactionType::Sell, 5, 3600.0, "2023-09-10"),
+            trade("INFY", TransactionType::Sell, 2, 1600.0, "2023-09-11"),
+        ];
+
+        let report = TaxReport::build(&trades, &[], fy(2023));
+
+        assert_eq!(report.gains.len(), 2);
+        assert_eq!(report.gains[0].symbol, "TCS");
+        assert_eq!(report.gains[0].gain, -2000.0);
+        assert_eq!(report.gains[1].symbol, "INFY");
+        assert_eq!(report.gains[1].gain, 200.0);
+        assert_eq!(report.total_gain(Term::Short), -1800.0);
+    }
+
+    #[test]
+    fn test_unsorted_input_is_matched_chronologically() {
+        let trades = vec![
+            trade("INFY", TransactionType::Sell, 5, 1300.0, "2023-07-01"),
+            trade("INFY", TransactionType::Buy, 5, 1100.0, "2023-05-01"),
+            trade("INFY", TransactionType::Buy, 5, 1000.0, "2023-04-01"),
+        ];
+
+        let report = TaxReport::build(&trades, &[], fy(2023));
+
+        assert_eq!(report.gains.len(), 1);
+        assert_eq!(report.gains[0].buy_date, date("2023-04-01"));
+        assert_eq!(report.gains[0].gain, 1500.0);
+    }
+
+    #[test]
+    fn test_only_sells_in_year_are_reported() {
+        let trades = vec![
+            trade("INFY", TransactionType::Buy, 10, 1000.0, "2022-04-01"),
+            trade("INFY", TransactionType::Sell, 4, 1100.0, "2023-03-15"),
+            trade("INFY", TransactionType::Sell, 6, 1200.0, "2023-04-15"),
+        ];
+
+        let report = TaxReport::build(&trades, &[], fy(2023));
+
+        assert_eq!(report.gains.len(), 1);
+        assert_eq!(report.gains[0].quantity, 6);
+        // Earlier FY sell consumed the first 4 shares of the lot
+        assert_eq!(report.gains[0].buy_value, 6000.0);
+        assert_eq!(report.gains[0].term, Term::Long);
+    }
+
+    #[test]
+    fn test_sell_without_buy_is_unmatched() {
+        let trades = vec![
+            trade("SBIN", TransactionType::Buy, 3, 500.0, "2023-05-01"),
+            trade("SBIN", TransactionType::Sell, 5, 550.0, "2023-06-01"),
+        ];
+
+        let report = TaxReport::build(&trades, &[], fy(2023));
+
+        assert_eq!(report.gains.len(), 1);
+        assert_eq!(report.gains[0].quantity, 3);
+        assert_eq!(
+            report.unmatched,
+            vec![UnmatchedSell {
+                symbol: "SBIN".to_string(),
+                sell_date: date("2023-06-01"),
+                quantity: 2,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_isin_comes_from_holdings() {
+        let trades = vec![
+            trade("INFY", TransactionType::Buy, 1, 1000.0, "2023-05-01"),
+            trade("INFY", TransactionType::Sell, 1, 1100.0, "2023-06-01"),
+        ];
+        let holdings = vec![Holding {
+            tradingsymbol: "INFY".to_string(),
+            exchange: Exchange::NSE,
+            instrument_token: 408065,
+            isin: "INE009A01021".to_string(),
+            quantity: 1,
+            authorised_quantity: 0,
+            average_price: 1000.0,
+            last_price: 1100.0,
+            close_price: 1100.0,
+            pnl: 100.0,
+            day_change: 0.0,
+            day_change_percentage: 0.0,
+        }];
+
+        let report = TaxReport::build(&trades, &holdings, fy(2023));
+
+        assert_eq!(report.gains[0].isin, "INE009A01021");
+    }
+
+    #[test]
+    fn test_csv_columns() {
+        let trades = vec![
+            trade("INFY", TransactionType::Buy, 1, 1000.0, "2023-05-01"),
+            trade("INFY", TransactionType::Sell, 1, 1100.0, "2023-06-01"),
+        ];
+        let report = TaxReport::build(&trades, &[], fy(2023));
+
+        let mut buf = Vec::new();
+        crate::output::write_csv(&report.gains, &mut buf).unwrap();
+        let csv = String::from_utf8(buf).unwrap();
+        let mut lines = csv.lines();
+
+        assert_eq!(
+            lines.next().unwrap(),
+            "symbol,isin,buy_date,sell_date,quantity,buy_value,sell_value,gain,term"
+        );
+        assert_eq!(
+            lines.next().unwrap(),
+            "INFY,,2023-05-01,2023-06-01,1,1000.0,1100.0,100.0,short"
+        );
+    }
+
+    #[test]
+    fn test_realized_pnl_by_trade() {
+        let mut trades = vec![
+            trade("INFY", TransactionType::Buy, 10, 100.0, "2024-06-03"),
+            trade("INFY", TransactionType::Buy, 10, 110.0, "2024-06-03"),
+            trade("INFY", TransactionType::Sell, 15, 120.0, "2024-06-03"),
+            trade("TCS", TransactionType::Sell, 5, 200.0, "2024-06-03"),
+            trade("TCS", TransactionType::Buy, 5, 190.0, "2024-06-03"),
+        ];
+        for (i, trade) in trades.iter_mut().enumerate() {
+            trade.trade_id = i.to_string();
+            trade.fill_timestamp += chrono::Duration::minutes(i as i64);
+        }
+
+        let pnl = realized_pnl_by_trade(&trades);
+        assert_eq!(pnl.len(), 2);
+        // 10 @ 100 and 5 @ 110 closed at 120
+        assert_eq!(pnl["2"], 10.0 * 20.0 + 5.0 * 10.0);
+        // Short covered 10 lower
+        assert_eq!(pnl["4"], 50.0);
+    }
+
+    #[test]
+    fn test_realized_pnl_flips_position() {
+        let mut trades = vec![
+            trade("INFY", TransactionType::Buy, 5, 100.0, "2024-06-03"),
+            trade("INFY", TransactionType::Sell, 10, 90.0, "2024-06-03"),
+            trade("INFY", TransactionType::Buy, 5, 80.0, "2024-06-03"),
+        ];
+        for (i, trade) in trades.iter_mut().enumerate() {
+            trade.trade_id = i.to_string();
+            trade.fill_timestamp += chrono::Duration::minutes(i as i64);
+        }
+
+        let pnl = realized_pnl_by_trade(&trades);
+        assert_eq!(pnl["1"], -50.0);
+        assert_eq!(pnl["2"], 50.0);
+        assert!(!pnl.contains_key("0"));
+    }
+}
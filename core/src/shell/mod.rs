@@ -1,15 +1,168 @@
-//! Interactive shell module
+//! Interactive shell support
+//!
+//! The REPL itself lives in the CLI crate.
 
-use anyhow::Result;
-use std::path::PathBuf;
-
-#[allow(clippy::module_inception)]
-pub mod shell;
-pub use shell::run;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
 
 /// Get shell history file path
 pub fn shell_history_path() -> Result<PathBuf> {
+    Ok(shell_data_dir()?.join("history"))
+}
+
+/// Get the cumulative shell stats file path
+pub fn shell_stats_path() -> Result<PathBuf> {
+    Ok(shell_data_dir()?.join("shell_stats.json"))
+}
+
+fn shell_data_dir() -> Result<PathBuf> {
     let data_dir =
         dirs::data_local_dir().ok_or_else(|| anyhow::anyhow!("Failed to get data directory"))?;
-    Ok(data_dir.join("zerodha-cli").join("history"))
+    Ok(data_dir.join("zerodha-cli"))
+}
+
+/// Usage of one shell command
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CommandStats {
+    pub count: u64,
+    pub errors: u64,
+}
+
+/// Per-command usage counts, for a session or accumulated across sessions
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ShellStats {
+    #[serde(default)]
+    pub sessions: u64,
+    #[serde(default)]
+    pub commands: BTreeMap<String, CommandStats>,
+}
+
+impl ShellStats {
+    /// Load cumulative stats; a missing file means no stats yet
+    pub fn load() -> Result<Self> {
+        Self::load_from(&shell_stats_path()?)
+    }
+
+    pub fn load_from(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        serde_json::from_str(&content)
+            .with_context(|| format!("Failed to parse {}", path.display()))
+    }
+
+    pub fn save(&self) -> Result<()> {
+        self.save_to(&shell_stats_path()?)
+    }
+
+    pub fn save_to(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).context("Failed to create data directory")?;
+        }
+        let content = serde_json::to_string_pretty(self).context("Failed to serialize stats")?;
+        std::fs::write(path, content).with_context(|| format!("Failed to write {}", path.display()))
+    }
+
+    /// Record one run of the command on `line`
+    pub fn record(&mut self, line: &str, ok: bool) {
+        let Some(key) = command_key(line) else {
+            return;
+        };
+        let stats = self.commands.entry(key).or_default();
+        stats.count += 1;
+        if !ok {
+            stats.errors += 1;
+        }
+    }
+
+    /// Total commands run
+    pub fn total(&self) -> u64 {
+        self.commands.values().map(|s| s.count).sum()
+    }
+
+    /// Add a session's stats into these cumulative stats
+    pub fn merge(&mut self, session: &ShellStats) {
+        self.sessions += session.sessions;
+        for (key, stats) in &session.commands {
+            let total = self.commands.entry(key.clone()).or_default();
+            total.count += stats.count;
+            total.errors += stats.errors;
+        }
+    }
+
+    /// Commands ordered by usage, most used first
+    pub fn ranked(&self) -> Vec<(&str, CommandStats)> {
+        let mut ranked: Vec<(&str, CommandStats)> = self
+            .commands
+            .iter()
+            .map(|(key, stats)| (key.as_str(), *stats))
+            .collect();
+        ranked.sort_by(|a, b| b.1.count.cmp(&a.1.count).then(a.0.cmp(b.0)));
+        ranked
+    }
+}
+
+/// Stats key for a command line: the command and its subcommand, without arguments
+fn command_key(line: &str) -> Option<String> {
+    let mut words = line.split_whitespace();
+    let cmd = words.next()?.to_lowercase();
+    match words.next() {
+        Some(sub) if !sub.starts_with('-') && !sub.contains(':') => {
+            Some(format!("{} {}", cmd, sub.to_lowercase()))
+        }
+        _ => Some(cmd),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_groups_by_subcommand() {
+        let mut stats = ShellStats::default();
+        stats.record("orders list --status OPEN", true);
+        stats.record("ORDERS List", false);
+        stats.record("quotes ltp NSE:INFY", true);
+        stats.record("status", true);
+        stats.record("   ", true);
+
+        assert_eq!(stats.total(), 4);
+        assert_eq!(
+            stats.commands["orders list"],
+            CommandStats {
+                count: 2,
+                errors: 1
+            }
+        );
+        assert_eq!(stats.ranked()[0].0, "orders list");
+        assert!(stats.commands.contains_key("status"));
+    }
+
+    #[test]
+    fn test_merge_and_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("shell_stats.json");
+
+        let mut session = ShellStats {
+            sessions: 1,
+            ..Default::default()
+        };
+        session.record("orders list", true);
+        session.record("orders cancel 123", false);
+
+        let mut total = ShellStats::load_from(&path).unwrap();
+        total.merge(&session);
+        total.merge(&session);
+        total.save_to(&path).unwrap();
+
+        let loaded = ShellStats::load_from(&path).unwrap();
+        assert_eq!(loaded.sessions, 2);
+        assert_eq!(loaded.total(), 4);
+        assert_eq!(loaded.commands["orders cancel"].errors, 2);
+    }
 }
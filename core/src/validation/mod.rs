@@ -1,24 +1,64 @@
 //! Validation module
 
-use crate::models::{OrderType, Product};
-use anyhow::{bail, Result};
+use crate::cache::InstrumentKey;
+use crate::models::{Exchange, OrderType, Product, TransactionType, Validity};
+use crate::error::ZerodhaError;
+use anyhow::Result;
+use std::sync::RwLock;
+
+/// Exchange assumed for bare symbols; empty until configured, meaning NSE
+static DEFAULT_EXCHANGE: RwLock<String> = RwLock::new(String::new());
+
+/// Return early with a `ZerodhaError::Validation`
+macro_rules! invalid {
+    ($($arg:tt)*) => {
+        return Err(ZerodhaError::Validation(format!($($arg)*)).into())
+    };
+}
 
 /// Validate order parameters
+///
+/// `price` is ignored for MARKET and SL-M orders. When `last_price` is known,
+/// stop-loss triggers must sit on the side of it
+/// the order protects: above for BUY, below for SELL. A disclosed quantity
+/// must be between 10% of `quantity` and `quantity` itself.
+#[allow(clippy::too_many_arguments)]
 pub fn validate_order(
     order_type: OrderType,
+    transaction_type: TransactionType,
     quantity: i32,
     price: f64,
     trigger_price: Option<f64>,
+    disclosed_quantity: Option<i32>,
     _product: Product,
+    last_price: Option<f64>,
 ) -> Result<()> {
     // Quantity must be positive
     if quantity <= 0 {
-        bail!("Quantity must be greater than 0");
+        invalid!("Quantity must be greater than 0");
+    }
+
+    // Market and SL-M orders fill at the market price, so only others need one
+    if matches!(order_type, OrderType::Limit | OrderType::SL) && price <= 0.0 {
+        invalid!("Price must be greater than 0");
     }
 
-    // Price must be positive
-    if price <= 0.0 {
-        bail!("Price must be greater than 0");
+    if let Some(disclosed) = disclosed_quantity {
+        if disclosed > quantity {
+            invalid!(
+                "Disclosed quantity {} can't exceed the order quantity {}",
+                disclosed,
+                quantity
+            );
+        }
+        // At least 10% of the order, without rounding the percentage down
+        if i64::from(disclosed) * 10 < i64::from(quantity) {
+            invalid!(
+                "Disclosed quantity {} must be at least 10% of the order quantity {}",
+                disclosed,
+                quantity
+            );
+        }
     }
 
     // Validate order type requirements
@@ -27,14 +67,170 @@ pub fn validate_order(
         OrderType::Limit => {}
         OrderType::SL => {
             if trigger_price.is_none() {
-                bail!("Stop Loss orders require a trigger price");
+                invalid!("Stop Loss orders require a trigger price");
             }
         }
         OrderType::SLM => {
             if trigger_price.is_none() {
-                bail!("Stop Loss Market orders require a trigger price");
+                invalid!("Stop Loss Market orders require a trigger price");
+            }
+        }
+    }
+
+    if let (OrderType::SL | OrderType::SLM, Some(trigger), Some(ltp)) =
+        (&order_type, trigger_price, last_price)
+    {
+        match transaction_type {
+            TransactionType::Buy if trigger <= ltp => invalid!(
+                "Trigger price {} for a BUY stop-loss must be above the last price {}",
+                trigger,
+                ltp
+            ),
+            TransactionType::Sell if trigger >= ltp => invalid!(
+                "Trigger price {} for a SELL stop-loss must be below the last price {}",
+                trigger,
+                ltp
+            ),
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
+
+/// Validate that a quantity is a whole number of lots
+///
+/// The error suggests the nearest valid quantity (at least one lot).
+pub fn validate_lot_size(quantity: i32, lot_size: u32) -> Result<()> {
+    let lot = lot_size as i64;
+    let quantity = quantity as i64;
+    if lot <= 1 || quantity % lot == 0 {
+        return Ok(());
+    }
+
+    let lower = quantity / lot * lot;
+    let upper = lower + lot;
+    let nearest = if lower > 0 && quantity - lower <= upper - quantity {
+        lower
+    } else {
+        upper
+    };
+    invalid!(
+        "Quantity {} is not a multiple of the lot size {}; nearest valid quantity is {}",
+        quantity,
+        lot,
+        nearest
+    )
+}
+
+/// Round a price to the nearest multiple of `tick`
+///
+/// The result is trimmed to 4 decimals so float noise doesn't leak into requests.
+pub fn round_to_tick(price: f64, tick: f64) -> f64 {
+    if tick <= 0.0 {
+        return price;
+    }
+    ((price / tick).round() * tick * 10_000.0).round() / 10_000.0
+}
+
+/// Validate that a price is a multiple of the instrument's tick size
+///
+/// The error lists the valid prices on either side.
+pub fn validate_tick_size(price: f64, tick: f64) -> Result<()> {
+    if tick <= 0.0 {
+        return Ok(());
+    }
+    let ticks = price / tick;
+    if (ticks - ticks.round()).abs() < 1e-6 {
+        return Ok(());
+    }
+
+    let below = round_to_tick(ticks.floor() * tick, tick);
+    let above = round_to_tick(ticks.ceil() * tick, tick);
+    invalid!(
+        "Price {} is not a multiple of the tick size {}; use {} or {} (or pass --round-tick)",
+        price,
+        tick,
+        below,
+        above
+    )
+}
+
+/// Validate TTL minutes against the chosen validity
+///
+/// TTL orders need a lifetime of 1-365 minutes; other validities must not set one.
+pub fn validate_validity_ttl(validity: &Validity, ttl_minutes: Option<u32>) -> Result<()> {
+    match (validity, ttl_minutes) {
+        (Validity::TTL, Some(minutes)) => {
+            if !(1..=365).contains(&minutes) {
+                invalid!("TTL minutes must be between 1 and 365");
             }
         }
+        (Validity::TTL, None) => invalid!("TTL validity requires --ttl-minutes"),
+        (_, Some(_)) => invalid!("--ttl-minutes is only allowed with TTL validity"),
+        (_, None) => {}
+    }
+
+    Ok(())
+}
+
+/// Validate the trigger prices of a two-leg (OCO) GTT
+///
+/// For a SELL exit the stoploss trigger must sit below the target trigger;
+/// for a BUY exit (covering a short) it is the other way round. Both triggers
+/// must straddle the last traded price.
+pub fn validate_two_leg_gtt(
+    transaction_type: &TransactionType,
+    stoploss_trigger: f64,
+    target_trigger: f64,
+    last_price: f64,
+) -> Result<()> {
+    if stoploss_trigger <= 0.0 || target_trigger <= 0.0 {
+        invalid!("Trigger prices must be greater than 0");
+    }
+
+    let (lower, upper) = match transaction_type {
+        TransactionType::Sell => {
+            if stoploss_trigger >= target_trigger {
+                invalid!("For a SELL two-leg GTT the stoploss trigger must be below the target trigger");
+            }
+            (stoploss_trigger, target_trigger)
+        }
+        TransactionType::Buy => {
+            if stoploss_trigger <= target_trigger {
+                invalid!("For a BUY two-leg GTT the stoploss trigger must be above the target trigger");
+            }
+            (target_trigger, stoploss_trigger)
+        }
+    };
+
+    if last_price > 0.0 && !(lower < last_price && last_price < upper) {
+        invalid!(
+            "Last price ₹{:.2} must lie between the two triggers (₹{:.2} - ₹{:.2})",
+            last_price,
+            lower,
+            upper
+        );
+    }
+
+    Ok(())
+}
+
+/// Maximum length of an order tag accepted by Kite
+pub const MAX_TAG_LEN: usize = 20;
+
+/// Validate order tag (alphanumeric, max 20 chars)
+pub fn validate_tag(tag: &str) -> Result<()> {
+    if tag.is_empty() {
+        invalid!("Tag cannot be empty");
+    }
+
+    if tag.len() > MAX_TAG_LEN {
+        invalid!("Tag must be at most {} characters", MAX_TAG_LEN);
+    }
+
+    if !tag.chars().all(|c| c.is_ascii_alphanumeric()) {
+        invalid!("Tag must be alphanumeric");
     }
 
     Ok(())
@@ -42,36 +238,67 @@ pub fn validate_order(
 
 /// Validate symbol format (EXCHANGE:SYMBOL)
 pub fn validate_symbol(symbol: &str) -> Result<(String, String)> {
-    let parts: Vec<&str> = symbol.split(':').collect();
-    if parts.len() != 2 {
-        bail!("Invalid symbol format. Expected: EXCHANGE:SYMBOL (e.g., NSE:INFY)");
+    if symbol.matches(':').count() != 1 {
+        invalid!("Invalid symbol format. Expected: EXCHANGE:SYMBOL (e.g., NSE:INFY)");
     }
 
-    let exchange = parts[0].to_uppercase();
-    let tradingsymbol = parts[1].to_uppercase();
+    let key = normalize_symbol(symbol, "")?;
+    Ok((key.exchange, key.tradingsymbol))
+}
 
-    // Validate exchange
-    match exchange.as_str() {
-        "NSE" | "BSE" | "NFO" | "BFO" | "MCX" | "CDS" => {}
-        _ => bail!("Invalid exchange. Valid exchanges: NSE, BSE, NFO, BFO, MCX, CDS"),
+/// Normalize `INFY`, `infy`, `NSE:INFY` or `nse:infy` to an uppercase key
+///
+/// Symbols without an exchange get `default_exchange`.
+pub fn normalize_symbol(input: &str, default_exchange: &str) -> Result<InstrumentKey> {
+    let (exchange, tradingsymbol) = input
+        .trim()
+        .split_once(':')
+        .unwrap_or((default_exchange, input.trim()));
+    let tradingsymbol = tradingsymbol.trim();
+    if tradingsymbol.is_empty() || tradingsymbol.contains(':') {
+        invalid!("Invalid symbol '{}'. Expected SYMBOL or EXCHANGE:SYMBOL (e.g., NSE:INFY)", input);
     }
 
-    Ok((exchange, tradingsymbol))
+    let exchange: Exchange = exchange
+        .trim()
+        .parse()
+        .map_err(|e: anyhow::Error| ZerodhaError::Validation(e.to_string()))?;
+    Ok(InstrumentKey::new(&exchange.to_string(), tradingsymbol))
+}
+
+/// Use `exchange` for symbols given without one (`defaults.exchange` in config)
+pub fn set_default_exchange(exchange: &str) {
+    *DEFAULT_EXCHANGE.write().unwrap_or_else(|e| e.into_inner()) = exchange.to_string();
+}
+
+/// Exchange and symbol of user input, defaulting the exchange from config
+pub fn parse_symbol(input: &str) -> Result<(String, String)> {
+    let default_exchange = DEFAULT_EXCHANGE.read().unwrap_or_else(|e| e.into_inner()).clone();
+    let default_exchange = if default_exchange.is_empty() {
+        "NSE"
+    } else {
+        default_exchange.as_str()
+    };
+    let key = normalize_symbol(input, default_exchange)?;
+    Ok((key.exchange, key.tradingsymbol))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::models::{OrderType, Product};
+    use crate::models::{OrderType, Product, TransactionType, Validity};
 
     #[test]
     fn test_validate_order_valid_limit() {
         let result = validate_order(
             OrderType::Limit,
+            TransactionType::Buy,
             10,
             1400.0,
             None,
+            None,
             Product::CNC,
+            None,
         );
         assert!(result.is_ok());
     }
@@ -80,10 +307,13 @@ mod tests {
     fn test_validate_order_valid_market() {
         let result = validate_order(
             OrderType::Market,
+            TransactionType::Buy,
             10,
-            1000.0, // Price must be > 0 even for market orders per validation logic
+            0.0, // Market orders carry no price
+            None,
             None,
             Product::MIS,
+            None,
         );
         assert!(result.is_ok());
     }
@@ -92,10 +322,13 @@ mod tests {
     fn test_validate_order_valid_sl() {
         let result = validate_order(
             OrderType::SL,
+            TransactionType::Buy,
             10,
             1400.0,
             Some(1395.0),
+            None,
             Product::NRML,
+            None,
         );
         assert!(result.is_ok());
     }
@@ -104,10 +337,13 @@ mod tests {
     fn test_validate_order_quantity_zero() {
         let result = validate_order(
             OrderType::Limit,
+            TransactionType::Buy,
             0,
             1400.0,
             None,
+            None,
             Product::CNC,
+            None,
         );
         assert!(result.is_err());
         assert!(result.unwrap_err().to_string().contains("Quantity must be greater than 0"));
@@ -117,10 +353,13 @@ mod tests {
     fn test_validate_order_quantity_negative() {
         let result = validate_order(
             OrderType::Limit,
+            TransactionType::Buy,
             -10,
             1400.0,
             None,
+            None,
             Product::CNC,
+            None,
         );
         assert!(result.is_err());
         assert!(result.unwrap_err().to_string().contains("Quantity must be greater than 0"));
@@ -130,10 +369,13 @@ mod tests {
     fn test_validate_order_price_zero() {
         let result = validate_order(
             OrderType::Limit,
+            TransactionType::Buy,
             10,
             0.0,
             None,
+            None,
             Product::CNC,
+            None,
         );
         assert!(result.is_err());
         assert!(result.unwrap_err().to_string().contains("Price must be greater than 0"));
@@ -143,10 +385,13 @@ mod tests {
     fn test_validate_order_price_negative() {
         let result = validate_order(
             OrderType::Limit,
+            TransactionType::Buy,
             10,
             -1400.0,
             None,
+            None,
             Product::CNC,
+            None,
         );
         assert!(result.is_err());
         assert!(result.unwrap_err().to_string().contains("Price must be greater than 0"));
@@ -156,10 +401,13 @@ mod tests {
     fn test_validate_order_sl_without_trigger() {
         let result = validate_order(
             OrderType::SL,
+            TransactionType::Buy,
             10,
             1400.0,
             None,
+            None,
             Product::CNC,
+            None,
         );
         assert!(result.is_err());
         assert!(result.unwrap_err().to_string().contains("Stop Loss orders require a trigger price"));
@@ -169,15 +417,191 @@ mod tests {
     fn test_validate_order_slm_without_trigger() {
         let result = validate_order(
             OrderType::SLM,
+            TransactionType::Buy,
             10,
             1400.0,
             None,
+            None,
             Product::CNC,
+            None,
         );
         assert!(result.is_err());
         assert!(result.unwrap_err().to_string().contains("Stop Loss Market orders require a trigger price"));
     }
 
+    #[test]
+    fn test_validate_order_disclosed_quantity() {
+        let disclosed = |quantity, disclosed| {
+            validate_order(
+                OrderType::Limit,
+                TransactionType::Buy,
+                quantity,
+                1400.0,
+                None,
+                Some(disclosed),
+                Product::CNC,
+                None,
+            )
+        };
+        assert!(disclosed(100, 10).is_ok());
+        assert!(disclosed(100, 100).is_ok());
+        // 10% of 15 is 1.5, so 1 is too few
+        assert!(disclosed(15, 2).is_ok());
+        assert!(disclosed(15, 1).is_err());
+
+        let err = disclosed(100, 9).unwrap_err().to_string();
+        assert!(err.contains("at least 10%"), "{}", err);
+        let err = disclosed(100, 101).unwrap_err().to_string();
+        assert!(err.contains("can't exceed the order quantity 100"), "{}", err);
+    }
+
+    #[test]
+    fn test_validate_order_buy_trigger_side() {
+        let buy = |trigger| {
+            validate_order(
+                OrderType::SL,
+                TransactionType::Buy,
+                10,
+                1510.0,
+                Some(trigger),
+                None,
+                Product::CNC,
+                Some(1500.0),
+            )
+        };
+        assert!(buy(1505.0).is_ok());
+        let err = buy(1495.0).unwrap_err().to_string();
+        assert!(err.contains("BUY stop-loss must be above the last price"));
+    }
+
+    #[test]
+    fn test_validate_order_sell_trigger_side() {
+        let sell = |trigger| {
+            validate_order(
+                OrderType::SLM,
+                TransactionType::Sell,
+                10,
+                1490.0,
+                Some(trigger),
+                None,
+                Product::MIS,
+                Some(1500.0),
+            )
+        };
+        assert!(sell(1495.0).is_ok());
+        assert!(sell(1500.0).is_err());
+        let err = sell(1505.0).unwrap_err().to_string();
+        assert!(err.contains("SELL stop-loss must be below the last price"));
+    }
+
+    #[test]
+    fn test_validate_order_trigger_side_skipped_without_ltp() {
+        let result = validate_order(
+            OrderType::SL,
+            TransactionType::Sell,
+            10,
+            1400.0,
+            Some(1600.0),
+            None,
+            Product::NRML,
+            None,
+        );
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_validate_lot_size() {
+        assert!(validate_lot_size(75, 25).is_ok());
+        assert!(validate_lot_size(7, 1).is_ok());
+
+        let err = |q| validate_lot_size(q, 25).unwrap_err().to_string();
+        assert!(err(30).contains("nearest valid quantity is 25"));
+        assert!(err(40).contains("nearest valid quantity is 50"));
+        assert!(err(10).contains("nearest valid quantity is 25"));
+    }
+
+    #[test]
+    fn test_round_to_tick() {
+        assert_eq!(round_to_tick(1500.03, 0.05), 1500.05);
+        assert_eq!(round_to_tick(1500.02, 0.05), 1500.0);
+        assert_eq!(round_to_tick(1500.025, 0.05), 1500.05);
+        assert_eq!(round_to_tick(99.994, 0.01), 99.99);
+        assert_eq!(round_to_tick(0.1 + 0.2, 0.05), 0.3);
+        assert_eq!(round_to_tick(1500.03, 0.0), 1500.03);
+    }
+
+    #[test]
+    fn test_validate_tick_size() {
+        assert!(validate_tick_size(1500.05, 0.05).is_ok());
+        assert!(validate_tick_size(0.1 + 0.2, 0.05).is_ok());
+        assert!(validate_tick_size(99.99, 0.01).is_ok());
+
+        let err = validate_tick_size(1500.03, 0.05).unwrap_err().to_string();
+        assert!(err.contains("use 1500 or 1500.05"), "{}", err);
+    }
+
+    #[test]
+    fn test_validate_validity_ttl_valid() {
+        assert!(validate_validity_ttl(&Validity::TTL, Some(1)).is_ok());
+        assert!(validate_validity_ttl(&Validity::TTL, Some(365)).is_ok());
+        assert!(validate_validity_ttl(&Validity::Day, None).is_ok());
+    }
+
+    #[test]
+    fn test_validate_validity_ttl_out_of_range() {
+        let result = validate_validity_ttl(&Validity::TTL, Some(0));
+        assert!(result.unwrap_err().to_string().contains("between 1 and 365"));
+        assert!(validate_validity_ttl(&Validity::TTL, Some(366)).is_err());
+    }
+
+    #[test]
+    fn test_validate_validity_ttl_mismatch() {
+        let result = validate_validity_ttl(&Validity::TTL, None);
+        assert!(result.unwrap_err().to_string().contains("requires --ttl-minutes"));
+        let result = validate_validity_ttl(&Validity::Day, Some(30));
+        assert!(result.unwrap_err().to_string().contains("only allowed with TTL"));
+    }
+
+    #[test]
+    fn test_validate_two_leg_gtt_sell() {
+        assert!(validate_two_leg_gtt(&TransactionType::Sell, 1400.0, 1600.0, 1500.0).is_ok());
+
+        let result = validate_two_leg_gtt(&TransactionType::Sell, 1600.0, 1400.0, 1500.0);
+        assert!(result.unwrap_err().to_string().contains("stoploss trigger must be below"));
+    }
+
+    #[test]
+    fn test_validate_two_leg_gtt_buy() {
+        assert!(validate_two_leg_gtt(&TransactionType::Buy, 1600.0, 1400.0, 1500.0).is_ok());
+        assert!(validate_two_leg_gtt(&TransactionType::Buy, 1400.0, 1600.0, 1500.0).is_err());
+    }
+
+    #[test]
+    fn test_validate_two_leg_gtt_last_price_outside() {
+        let result = validate_two_leg_gtt(&TransactionType::Sell, 1400.0, 1600.0, 1700.0);
+        assert!(result.unwrap_err().to_string().contains("must lie between"));
+    }
+
+    #[test]
+    fn test_validate_tag_valid() {
+        assert!(validate_tag("mybot").is_ok());
+        assert!(validate_tag("a".repeat(MAX_TAG_LEN).as_str()).is_ok());
+    }
+
+    #[test]
+    fn test_validate_tag_too_long() {
+        let result = validate_tag(&"a".repeat(MAX_TAG_LEN + 1));
+        assert!(result.is_err());
+        assert!(result.unwrap_err().to_string().contains("at most 20 characters"));
+    }
+
+    #[test]
+    fn test_validate_tag_invalid_chars() {
+        let result = validate_tag("my-bot");
+        assert!(result.is_err());
+        assert!(result.unwrap_err().to_string().contains("alphanumeric"));
+    }
+
     #[test]
     fn test_validate_symbol_valid_nse() {
         let result = validate_symbol("NSE:INFY");
@@ -219,6 +643,32 @@ mod tests {
         assert!(result.unwrap_err().to_string().contains("Invalid exchange"));
     }
 
+    #[test]
+    fn test_normalize_bare_symbol_uses_default_exchange() {
+        let key = normalize_symbol("infy", "NSE").unwrap();
+        assert_eq!(key, InstrumentKey::new("NSE", "INFY"));
+        assert_eq!(key.tradingsymbol, "INFY");
+        assert_eq!(normalize_symbol(" Reliance ", "bse").unwrap().exchange, "BSE");
+    }
+
+    #[test]
+    fn test_normalize_mixed_case_and_explicit_exchange() {
+        for input in ["NSE:INFY", "nse:infy", "Nse:Infy", " nse : INFY "] {
+            let key = normalize_symbol(input, "BSE").unwrap();
+            assert_eq!((key.exchange.as_str(), key.tradingsymbol.as_str()), ("NSE", "INFY"), "{}", input);
+        }
+        assert_eq!(normalize_symbol("nfo:nifty24junfut", "NSE").unwrap().exchange, "NFO");
+    }
+
+    #[test]
+    fn test_normalize_rejects_bad_input() {
+        assert!(normalize_symbol("", "NSE").is_err());
+        assert!(normalize_symbol("NSE:", "NSE").is_err());
+        assert!(normalize_symbol("NSE:INFY:EXTRA", "NSE").is_err());
+        assert!(normalize_symbol("XYZ:INFY", "NSE").is_err());
+        assert!(normalize_symbol("INFY", "XYZ").is_err());
+    }
+
     #[test]
     fn test_validate_symbol_too_many_colons() {
         let result = validate_symbol("NSE:INFY:EXTRA");